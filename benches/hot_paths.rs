@@ -0,0 +1,116 @@
+//! Microbenchmarks for the per-utterance hot paths. Run with `cargo bench`;
+//! compare against a saved baseline with
+//! `cargo bench -- --save-baseline main` / `--baseline main`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use hyprwhspr_rs::audio::{resample_audio, FastVad, FastVadSettings};
+use hyprwhspr_rs::config::{
+    CodeDictationConfig, FastVadConfig, PostProcessConfig, ResampleQuality,
+};
+use hyprwhspr_rs::input::TextPipeline;
+use hyprwhspr_rs::transcription::encode_to_flac;
+use std::collections::{HashMap, HashSet};
+use std::f32::consts::PI;
+
+const SAMPLE_RATE: u32 = 16_000;
+
+const DICTATION: &str = "so the plan is open paren step one comma deploy to staging close paren period \
+    then email john dot doe at example dot com new line check h t t p s colon slash slash docs dot rs slash serde \
+    and say \"ship it\" period scratch that period we're done question mark";
+
+/// Speech-like fixture: alternating voiced bursts and near-silence, so VAD
+/// and the encoder see realistic structure rather than a pure tone.
+fn speech_fixture(seconds: f32, sample_rate: u32) -> Vec<f32> {
+    let len = (seconds * sample_rate as f32) as usize;
+    (0..len)
+        .map(|i| {
+            let t = i as f32 / sample_rate as f32;
+            let voiced = (t * 2.5).fract() < 0.6;
+            let envelope = if voiced { 0.4 } else { 0.01 };
+            let carrier = (2.0 * PI * 180.0 * t).sin() + 0.5 * (2.0 * PI * 720.0 * t).sin();
+            envelope * carrier
+        })
+        .collect()
+}
+
+fn text_pipeline(c: &mut Criterion) {
+    let pipeline = TextPipeline::new(
+        HashSet::new(),
+        HashMap::from([("hyper whisper".to_string(), "hyprwhspr".to_string())]),
+        Vec::new(),
+        PostProcessConfig::default(),
+        CodeDictationConfig::default(),
+    );
+
+    let mut group = c.benchmark_group("text_pipeline");
+    for repeat in [1, 8] {
+        let input = vec![DICTATION; repeat].join(" ");
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(repeat), &input, |b, input| {
+            b.iter(|| pipeline.process(black_box(input), Some("firefox")))
+        });
+    }
+    group.finish();
+}
+
+fn resampler(c: &mut Criterion) {
+    let mut group = c.benchmark_group("resample_to_16k");
+    for source_rate in [44_100, 48_000] {
+        let audio = speech_fixture(10.0, source_rate);
+        group.throughput(Throughput::Elements(audio.len() as u64));
+        for quality in [
+            ResampleQuality::Fast,
+            ResampleQuality::Balanced,
+            ResampleQuality::High,
+        ] {
+            group.bench_with_input(
+                BenchmarkId::new(format!("{quality:?}"), source_rate),
+                &audio,
+                |b, audio| {
+                    b.iter(|| resample_audio(black_box(audio), source_rate, SAMPLE_RATE, quality))
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+fn flac_encode(c: &mut Criterion) {
+    // Shells out to ffmpeg like the real backends do.
+    if std::process::Command::new("ffmpeg")
+        .arg("-version")
+        .output()
+        .is_err()
+    {
+        eprintln!("ffmpeg not found; skipping flac_encode");
+        return;
+    }
+
+    let runtime = tokio::runtime::Runtime::new().expect("tokio runtime");
+    let audio = speech_fixture(10.0, SAMPLE_RATE);
+    let mut group = c.benchmark_group("flac_encode");
+    group.sample_size(20);
+    group.throughput(Throughput::Elements(audio.len() as u64));
+    group.bench_function("10s", |b| {
+        b.iter(|| runtime.block_on(encode_to_flac(black_box(&audio))))
+    });
+    group.finish();
+}
+
+fn fast_vad(c: &mut Criterion) {
+    let settings = FastVadSettings::from_config(&FastVadConfig::default());
+    let audio = speech_fixture(10.0, SAMPLE_RATE);
+
+    let mut group = c.benchmark_group("fast_vad_trim");
+    group.throughput(Throughput::Elements(audio.len() as u64));
+    group.bench_function("10s", |b| {
+        b.iter(|| {
+            let mut vad = FastVad::with_settings(settings.clone(), SAMPLE_RATE);
+            vad.trim(black_box(&audio))
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, text_pipeline, resampler, flac_encode, fast_vad);
+criterion_main!(benches);
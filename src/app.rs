@@ -1,69 +1,160 @@
 use anyhow::{Context, Result};
+use std::path::PathBuf;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc,
 };
 use std::thread::{self, JoinHandle};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, Mutex};
 use tracing::{debug, error, info, warn};
 
 use crate::audio::{
-    capture::RecordingSession, AudioCapture, AudioFeedback, CapturedAudio, FastVad, FastVadOutcome,
+    capture::RecordingSession,
+    denoise,
+    echo_cancel::EchoCancel,
+    monitor, recording, resample_audio,
+    wakeword::{self, Heard, WakeWordListener},
+    AudioCapture, AudioFeedback, CapturedAudio, FastVad, FastVadOutcome, FeedbackEvent,
+    PendingUtterances, SegmentBreak,
 };
 use crate::benchmark::BenchmarkRecorder;
-use crate::config::{Config, ConfigManager, ShortcutsConfig, TranscriptionProvider};
-use crate::input::{GlobalShortcuts, ShortcutEvent, ShortcutKind, ShortcutPhase, TextInjector};
+use crate::calendar::MeetingCalendar;
+use crate::config::{
+    profile_for, Config, ConfigManager, DictationMode, MixMode, ResampleQuality, ShortcutAction,
+    ShortcutTrigger, ShortcutsConfig, StartSoundOverlap, TranscriptionProvider, VoiceAction,
+    WakeWordConfig,
+};
+use crate::dnd::DoNotDisturb;
+use crate::events::{AppEvent, EventBus};
+use crate::export::{ExportEntry, Exporter};
+use crate::history::History;
+use crate::input::focused_window_class;
+use crate::input::hyprland::{conflicting_binds, HyprlandIpc};
+use crate::input::{
+    copy_to_clipboard, DoublePress, GlobalShortcuts, PressBehavior, ShortcutEvent, ShortcutKind,
+    ShortcutPhase, Tap, TextInjector,
+};
+use crate::ipc::{self, ControlSocket, DbusService, IpcCommand};
+use crate::notify::Notifier;
+use crate::session::SessionStats;
+use crate::state::{AppState, ProcessingJob, RecordingDeadline, RecordingTrigger};
 use crate::status::StatusWriter;
-use crate::transcription::{TranscriptionBackend, TranscriptionResult};
+use crate::summary;
+use crate::transcription::{
+    item_starts, utterance_key, DurationEstimator, PromptBooster, PromptContext,
+    TranscriptionBackend, TranscriptionResult,
+};
+use crate::voice_commands::{Control, VoiceCommands};
 use crate::whisper::WhisperVadOptions;
+use crate::workers::WorkerPool;
+
+/// How often a live recording is checked for a stalled input stream.
+const CAPTURE_WATCHDOG_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How often the microphone level is published for VU meters while recording.
+const LEVEL_METER_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Playback speed of `replay_last fast`.
+const FAST_REPLAY_SPEED: f32 = 1.5;
 
 struct ShortcutListener {
     stop_flag: Arc<AtomicBool>,
     handle: Option<JoinHandle<()>>,
     shortcut: String,
     kind: ShortcutKind,
+    behavior: PressBehavior,
 }
 
-fn resample_audio(samples: &[f32], src_rate: u32, dst_rate: u32) -> Vec<f32> {
-    if samples.is_empty() || src_rate == 0 || dst_rate == 0 {
-        return Vec::new();
-    }
-    if src_rate == dst_rate {
-        return samples.to_vec();
+/// Resamples for and runs fast VAD when enabled. CPU-bound; runs on the
+/// worker pool.
+fn preprocess_audio(
+    denoise: Option<f32>,
+    quality: ResampleQuality,
+    fast_vad: Option<&mut FastVad>,
+    audio_data: CapturedAudio,
+) -> Result<Option<PreprocessedAudio>> {
+    let CapturedAudio {
+        mut samples,
+        mut sample_rate,
+    } = audio_data;
+
+    if let Some(strength) = denoise {
+        samples = denoise::denoise(&samples, sample_rate, strength, quality);
     }
 
-    let src_len = samples.len();
-    if src_len == 0 {
-        return Vec::new();
-    }
+    if let Some(vad) = fast_vad {
+        if !FastVad::supports_sample_rate(sample_rate) {
+            warn!(
+                "🎚️ Input sample rate {} Hz unsupported by fast VAD; resampling to 16 kHz",
+                sample_rate
+            );
+            samples = resample_audio(&samples, sample_rate, 16_000, quality);
+            sample_rate = 16_000;
+        }
 
-    let output_len = ((src_len as u64 * dst_rate as u64) + (src_rate as u64 / 2)) / src_rate as u64;
-    if output_len == 0 {
-        return Vec::new();
-    }
+        if vad.sample_rate_hz() != sample_rate {
+            vad.set_sample_rate(sample_rate)
+                .context("Failed to configure fast VAD sample rate")?;
+        }
 
-    let mut output = Vec::with_capacity(output_len as usize);
-    let rate_ratio = src_rate as f64 / dst_rate as f64;
-    let last_index = src_len.saturating_sub(1);
+        let outcome = vad.trim(&samples).context("Fast VAD trimming failed")?;
+        if outcome.trimmed_audio.is_empty() {
+            info!(
+                "🎧 Recording contained only silence after fast VAD trimming; skipping transcription"
+            );
+            return Ok(None);
+        }
 
-    for n in 0..output_len as usize {
-        let src_pos = n as f64 * rate_ratio;
-        let idx = src_pos.floor() as usize;
-        let frac = src_pos - idx as f64;
-        let left = samples[idx.min(last_index)];
-        let right = samples[(idx + 1).min(last_index)];
-        let value = left + (right - left) * frac as f32;
-        output.push(value);
+        let FastVadOutcome {
+            trimmed_audio,
+            segments,
+            profile_switches,
+            final_profile,
+            dropped_samples,
+            breaks,
+            ..
+        } = outcome;
+
+        let trimmed_len = trimmed_audio.len();
+
+        debug!(
+            "Earshot fast VAD kept {}/{} samples across {} segments (profile={}, switches={}, dropped={})",
+            trimmed_len,
+            samples.len(),
+            segments,
+            final_profile,
+            profile_switches,
+            dropped_samples
+        );
+
+        return Ok(Some(PreprocessedAudio {
+            audio: CapturedAudio {
+                samples: trimmed_audio,
+                sample_rate,
+            },
+            report: Some(FastVadSummary {
+                dropped_samples,
+                sample_rate,
+                breaks,
+            }),
+        }));
     }
 
-    output
+    Ok(Some(PreprocessedAudio {
+        audio: CapturedAudio {
+            samples,
+            sample_rate,
+        },
+        report: None,
+    }))
 }
 
 impl ShortcutListener {
     fn spawn(
         shortcut: String,
         kind: ShortcutKind,
+        behavior: PressBehavior,
         tx: mpsc::Sender<ShortcutEvent>,
     ) -> Result<Self> {
         let stop_flag = Arc::new(AtomicBool::new(false));
@@ -71,22 +162,25 @@ impl ShortcutListener {
         let runner_tx = tx.clone();
         let shortcut_name = shortcut.clone();
 
-        let handle = thread::spawn(move || match GlobalShortcuts::new(&shortcut, kind) {
-            Ok(shortcuts) => {
-                if let Err(e) = shortcuts.run(runner_tx, runner_flag) {
-                    error!("Global shortcuts error: {}", e);
+        let handle = thread::spawn(
+            move || match GlobalShortcuts::new(&shortcut, kind, behavior) {
+                Ok(shortcuts) => {
+                    if let Err(e) = shortcuts.run(runner_tx, runner_flag) {
+                        error!("Global shortcuts error: {}", e);
+                    }
                 }
-            }
-            Err(e) => {
-                error!("Failed to initialize global shortcuts: {}", e);
-            }
-        });
+                Err(e) => {
+                    error!("Failed to initialize global shortcuts: {}", e);
+                }
+            },
+        );
 
         Ok(Self {
             stop_flag,
             handle: Some(handle),
             shortcut: shortcut_name,
             kind,
+            behavior,
         })
     }
 
@@ -94,10 +188,11 @@ impl ShortcutListener {
         &mut self,
         shortcut: String,
         kind: ShortcutKind,
+        behavior: PressBehavior,
         tx: mpsc::Sender<ShortcutEvent>,
     ) -> Result<()> {
         self.stop();
-        *self = Self::spawn(shortcut, kind, tx)?;
+        *self = Self::spawn(shortcut, kind, behavior, tx)?;
         Ok(())
     }
 
@@ -110,8 +205,8 @@ impl ShortcutListener {
         }
     }
 
-    fn matches(&self, shortcut: &str, kind: ShortcutKind) -> bool {
-        self.shortcut == shortcut && self.kind == kind
+    fn matches(&self, shortcut: &str, kind: ShortcutKind, behavior: PressBehavior) -> bool {
+        self.shortcut == shortcut && self.kind == kind && self.behavior == behavior
     }
 }
 
@@ -121,16 +216,11 @@ impl Drop for ShortcutListener {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum RecordingTrigger {
-    HoldShortcut,
-    PressShortcut,
-}
-
 #[derive(Debug, Clone)]
 struct FastVadSummary {
     dropped_samples: usize,
     sample_rate: u32,
+    breaks: Vec<SegmentBreak>,
 }
 
 #[derive(Debug)]
@@ -139,18 +229,111 @@ struct PreprocessedAudio {
     report: Option<FastVadSummary>,
 }
 
-fn build_vad_options(config_manager: &ConfigManager, config: &Config) -> WhisperVadOptions {
-    let whisper_vad = &config.transcription.whisper_cpp.vad;
-    WhisperVadOptions {
-        enabled: whisper_vad.enabled,
-        model_path: config_manager.get_vad_model_path(config),
-        threshold: whisper_vad.threshold,
-        min_speech_ms: whisper_vad.min_speech_ms,
-        min_silence_ms: whisper_vad.min_silence_ms,
-        max_speech_s: whisper_vad.max_speech_s,
-        speech_pad_ms: whisper_vad.speech_pad_ms,
-        samples_overlap: whisper_vad.samples_overlap,
+/// The short-command backend; a failure only costs the fast path.
+fn build_short_transcriber(
+    config_manager: &ConfigManager,
+    config: &Config,
+) -> Option<Arc<TranscriptionBackend>> {
+    let vad = build_vad_options(config_manager, config);
+    TranscriptionBackend::build_short_command(config_manager, config, vad)
+        .inspect_err(|err| warn!("Short commands will use the main backend: {err:#}"))
+        .ok()
+        .flatten()
+        .map(Arc::new)
+}
+
+/// The backend for second tries at thin transcripts; a failure only costs
+/// the retry.
+fn build_retry_transcriber(
+    config_manager: &ConfigManager,
+    config: &Config,
+) -> Option<TranscriptionBackend> {
+    let vad = build_vad_options(config_manager, config);
+    TranscriptionBackend::build_retry(config_manager, config, vad)
+        .inspect_err(|err| warn!("Low-confidence retries are off: {err:#}"))
+        .ok()
+        .flatten()
+}
+
+/// The wake-word listener, when it is on; without one, recordings only
+/// start from shortcuts and IPC. Every burst of speech would go to
+/// `backend`, so it has to be local.
+fn start_wake_word(
+    capture: &AudioCapture,
+    config: &WakeWordConfig,
+    backend: &TranscriptionBackend,
+    heard: &mpsc::UnboundedSender<Heard>,
+) -> Option<WakeWordListener> {
+    if !config.enabled {
+        return None;
+    }
+    if !backend.is_local() {
+        warn!(
+            "Wake word needs a local backend (whisper_cpp without lan_offload, or short_command.provider set to it); {} would receive everything said near the mic",
+            backend.provider().label()
+        );
+        return None;
     }
+    WakeWordListener::start(capture, config, heard.clone())
+        .inspect_err(|err| warn!("Wake word unavailable: {:#}", err))
+        .ok()
+}
+
+pub(crate) fn build_vad_options(
+    config_manager: &ConfigManager,
+    config: &Config,
+) -> WhisperVadOptions {
+    WhisperVadOptions::from_config(
+        &config.transcription.whisper_cpp.vad,
+        config_manager.get_vad_model_path(config),
+    )
+}
+
+/// Warns when a configured shortcut is also bound in Hyprland, which would
+/// otherwise fire both actions on every press.
+fn spawn_shortcut_conflict_check(shortcuts: ShortcutsConfig) {
+    let Some(ipc) = HyprlandIpc::from_env() else {
+        return;
+    };
+
+    tokio::spawn(async move {
+        let binds = match ipc.binds().await {
+            Ok(binds) => binds,
+            Err(err) => {
+                debug!("Skipping shortcut conflict check: {:#}", err);
+                return;
+            }
+        };
+
+        let mut conflicts = Vec::new();
+        for shortcut in [
+            shortcuts.press.as_deref(),
+            shortcuts.hold.as_deref(),
+            shortcuts.cancel.as_deref(),
+            shortcuts.mode.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            for bind in conflicting_binds(shortcut, &binds) {
+                warn!(
+                    "⚠️  Shortcut {} is also bound in Hyprland ({}); both will fire",
+                    shortcut,
+                    bind.describe()
+                );
+                conflicts.push(format!("{shortcut}: {}", bind.describe()));
+            }
+        }
+
+        match StatusWriter::new() {
+            Ok(status) => {
+                if let Err(err) = status.set_shortcut_conflicts(&conflicts) {
+                    warn!("Failed to record shortcut conflicts: {:#}", err);
+                }
+            }
+            Err(err) => warn!("Failed to record shortcut conflicts: {:#}", err),
+        }
+    });
 }
 
 fn fast_vad_allowed(config: &Config) -> bool {
@@ -171,40 +354,104 @@ pub struct HyprwhsprApp {
     config_manager: ConfigManager,
     audio_capture: AudioCapture,
     audio_feedback: AudioFeedback,
-    transcriber: TranscriptionBackend,
+    transcriber: Arc<TranscriptionBackend>,
+    /// Lower-latency backend for short commands, when one is configured.
+    short_transcriber: Option<Arc<TranscriptionBackend>>,
+    /// Second opinion for long recordings that come back too thin.
+    retry_transcriber: Option<TranscriptionBackend>,
+    prompt_booster: PromptBooster,
+    last_transcript: Option<String>,
+    /// Totals for the `session_stats` command.
+    session: SessionStats,
+    voice_commands: VoiceCommands,
+    /// Text of the last injection, for the `reinject_last` action.
+    last_injection: Option<String>,
+    duration_estimator: DurationEstimator,
     fast_vad: Option<FastVad>,
     text_injector: Arc<Mutex<TextInjector>>,
-    status_writer: StatusWriter,
+    status_writer: Arc<StatusWriter>,
+    events: EventBus,
+    notifier: Notifier,
+    exporter: Exporter,
+    calendar: Option<MeetingCalendar>,
+    history: Option<History>,
+    pending: PendingUtterances,
+    workers: WorkerPool,
     shortcut_tx: mpsc::Sender<ShortcutEvent>,
     shortcut_rx: Option<mpsc::Receiver<ShortcutEvent>>,
+    wake_word: Option<WakeWordListener>,
+    /// The wake-word candidate being transcribed; others are dropped
+    /// until it finishes.
+    wake_check: Option<tokio::task::JoinHandle<()>>,
+    wake_tx: mpsc::UnboundedSender<Heard>,
+    wake_rx: Option<mpsc::UnboundedReceiver<Heard>>,
     press_listener: Option<ShortcutListener>,
     hold_listener: Option<ShortcutListener>,
+    cancel_listener: Option<ShortcutListener>,
+    mode_listener: Option<ShortcutListener>,
+    /// A press held back until `double_press_ms` tells it from a double press.
+    pending_press: DoublePress,
+    /// Starts as `dictation_mode` from the config; switched at runtime.
+    dictation_mode: DictationMode,
+    /// Transcripts are kept out of other windows and the clipboard.
+    read_only: bool,
     current_config: Config,
+    state: AppState,
     recording_session: Option<RecordingSession>,
-    recording_trigger: Option<RecordingTrigger>,
+    recording_deadline: RecordingDeadline,
     benchmark: Option<BenchmarkRecorder>,
-    is_processing: bool,
+    dbus: Option<DbusService>,
+    /// Unloads the echo-cancel module when the app goes away.
+    _echo_cancel: Option<EchoCancel>,
 }
 
 impl HyprwhsprApp {
     pub fn new(config_manager: ConfigManager) -> Result<Self> {
         let config = config_manager.get();
 
-        let audio_capture = AudioCapture::new().context("Failed to initialize audio capture")?;
+        let echo_cancel = if config.aec.enabled {
+            if config.audio_device.is_some() {
+                warn!("Echo cancellation only applies to the default input; audio_device is set");
+            }
+            EchoCancel::start(&config.aec)
+                .inspect_err(|err| warn!("Echo cancellation unavailable: {:#}", err))
+                .ok()
+        } else {
+            None
+        };
+
+        let mut audio_capture = AudioCapture::new(config.audio_device.clone())
+            .context("Failed to initialize audio capture")?;
+        audio_capture.set_source(echo_cancel.as_ref().map(|aec| aec.source().to_string()));
+        audio_capture.set_low_memory(config.performance.low_memory);
+        if config.device_probe.enabled && config.audio_device.is_none() {
+            if let Err(err) =
+                audio_capture.probe_best_device(Duration::from_millis(config.device_probe.probe_ms))
+            {
+                warn!("Device probe failed: {}", err);
+            }
+        }
+        // After the probe, so the pre-roll opens on the chosen device.
+        audio_capture.set_pre_roll(Duration::from_millis(config.pre_roll_ms));
 
         let assets_dir = config_manager.get_assets_dir();
-        let audio_feedback = AudioFeedback::new(
+        let mut audio_feedback = AudioFeedback::new(
             config.audio_feedback,
             assets_dir,
             config.start_sound_path.clone(),
             config.stop_sound_path.clone(),
             config.start_sound_volume,
             config.stop_sound_volume,
+            config.feedback_sounds.clone(),
         );
+        audio_feedback.set_do_not_disturb(DoNotDisturb::new(&config.do_not_disturb));
 
         let vad_options = build_vad_options(&config_manager, &config);
 
+        let short_transcriber = build_short_transcriber(&config_manager, &config);
+        let retry_transcriber = build_retry_transcriber(&config_manager, &config);
         let transcriber = TranscriptionBackend::build(&config_manager, &config, vad_options)
+            .map(Arc::new)
             .context("Failed to configure transcription backend")?;
 
         transcriber
@@ -216,21 +463,37 @@ impl HyprwhsprApp {
             transcriber.provider().label()
         );
 
-        let text_injector = TextInjector::new(
-            config.shift_paste,
-            config.paste_hints.shift.clone(),
-            config.word_overrides.clone(),
-            config.auto_copy_clipboard,
-        )?;
+        let text_injector = TextInjector::from_config(&config)?;
 
         let status_writer = StatusWriter::new()?;
+        status_writer.set_mode(config.dictation_mode);
+        status_writer.set_backend(transcriber.provider().label());
+        status_writer.set_fifo(config.status_fifo.as_ref().map(PathBuf::from));
+        status_writer.set_border_indicator(&config.border_indicator);
+        let history = History::maybe_open(&config_manager, &config);
+        let exporter = Exporter::new(config.export_targets(), config.profile_export_targets())?;
         status_writer.set_recording(false)?;
+        let status_writer = Arc::new(status_writer);
+        let events = EventBus::new();
+        status_writer.follow(&events);
+        let notifier = Notifier::new(&config.notifications, &config.do_not_disturb);
+        notifier.follow(&events);
 
         let (shortcut_tx, shortcut_rx) = mpsc::channel(10);
+        let (wake_tx, wake_rx) = mpsc::unbounded_channel();
+        let wake_word = start_wake_word(
+            &audio_capture,
+            &config.wake_word,
+            short_transcriber.as_ref().unwrap_or(&transcriber),
+            &wake_tx,
+        );
 
         let fast_vad = if fast_vad_allowed(&config) {
-            FastVad::maybe_new(&config.fast_vad, audio_capture.sample_rate_hint())
-                .context("Failed to initialize fast VAD pipeline")?
+            FastVad::maybe_new(
+                &config.fast_vad,
+                FastVad::rate_for_input(audio_capture.sample_rate_hint()),
+            )
+            .context("Failed to initialize fast VAD pipeline")?
         } else {
             if config.fast_vad.enabled
                 && config.transcription.provider == TranscriptionProvider::WhisperCpp
@@ -249,23 +512,53 @@ impl HyprwhsprApp {
             );
         }
 
+        let pending = PendingUtterances::new(config_manager.get_pending_dir());
+        let workers = WorkerPool::new(config.performance.worker_threads)?;
+        debug!("Worker pool ready with {} threads", workers.threads());
+
         Ok(Self {
             config_manager,
             audio_capture,
             audio_feedback,
             transcriber,
+            short_transcriber,
+            retry_transcriber,
+            prompt_booster: PromptBooster::new(&config.transcription.prompt_boost),
+            last_transcript: None,
+            session: SessionStats::new(),
+            voice_commands: VoiceCommands::new(&config.voice_commands),
+            last_injection: None,
+            duration_estimator: DurationEstimator::new(config.transcription.provider),
             fast_vad,
             text_injector: Arc::new(Mutex::new(text_injector)),
             status_writer,
+            events,
+            notifier,
+            exporter,
+            calendar: MeetingCalendar::maybe_new(&config.calendar),
+            history,
+            pending,
+            workers,
             shortcut_tx,
             shortcut_rx: Some(shortcut_rx),
+            wake_word,
+            wake_check: None,
+            wake_tx,
+            wake_rx: Some(wake_rx),
             press_listener: None,
             hold_listener: None,
+            cancel_listener: None,
+            mode_listener: None,
+            pending_press: DoublePress::default(),
+            dictation_mode: config.dictation_mode,
+            read_only: false,
             current_config: config,
+            state: AppState::Idle,
             recording_session: None,
-            recording_trigger: None,
+            recording_deadline: RecordingDeadline::default(),
             benchmark: None,
-            is_processing: false,
+            dbus: None,
+            _echo_cancel: echo_cancel,
         })
     }
 
@@ -278,16 +571,100 @@ impl HyprwhsprApp {
             .expect("shortcut receiver already consumed");
         self.ensure_shortcut_listeners(self.current_config.shortcuts.clone())?;
         self.log_shortcut_configuration(&self.current_config.shortcuts);
+        spawn_shortcut_conflict_check(self.current_config.shortcuts.clone());
 
+        let mut wake_rx = self
+            .wake_rx
+            .take()
+            .expect("wake word receiver already consumed");
         let mut config_rx = self.config_manager.subscribe();
 
+        // The sender stays alive here so the branch below never sees a closed
+        // channel when D-Bus, the control socket and signals are unavailable.
+        let (ipc_tx, mut ipc_rx) = mpsc::channel(16);
+        if self.current_config.ipc.dbus {
+            match DbusService::start(ipc_tx.clone()).await {
+                Ok(service) => {
+                    info!("🔌 D-Bus service {} ready", ipc::dbus::BUS_NAME);
+                    service.follow(&self.events, self.current_config.ipc.dbus_transcripts);
+                    self.dbus = Some(service);
+                }
+                Err(err) => warn!("D-Bus service unavailable: {:#}", err),
+            }
+        }
+        match ControlSocket::open(self.current_config.ipc.socket) {
+            Ok(Some(socket)) => {
+                if socket.is_activated() {
+                    info!("🔌 Control socket ready (socket-activated)");
+                } else {
+                    info!("🔌 Control socket ready");
+                }
+                socket.serve(ipc_tx.clone());
+            }
+            Ok(None) => {}
+            Err(err) => warn!("Control socket unavailable: {:#}", err),
+        }
+        if let Err(err) = ipc::signals::listen(&self.current_config.ipc.signals, ipc_tx.clone()) {
+            warn!("Signal controls unavailable: {:#}", err);
+        }
+
+        if let Err(err) = self.resume_pending_utterances().await {
+            error!("Failed to resume pending utterances: {:#}", err);
+        }
+
+        let mut capture_watchdog = tokio::time::interval(CAPTURE_WATCHDOG_INTERVAL);
+        capture_watchdog.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        let mut level_meter = tokio::time::interval(LEVEL_METER_INTERVAL);
+        level_meter.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
         loop {
+            let press_deadline = self.pending_press.deadline(self.double_press_window());
             tokio::select! {
+                _ = tokio::time::sleep_until(press_deadline.unwrap_or_else(Instant::now).into()), if press_deadline.is_some() => {
+                    if let Some(pressed) = self.pending_press.expire() {
+                        if let Err(e) = self.run_shortcut_action(ShortcutTrigger::Press, pressed).await {
+                            error!("Error handling shortcut: {}", e);
+                            self.publish_error(&e);
+                        }
+                    }
+                }
+                _ = self.recording_deadline.reached(), if self.recording_session.is_some() => {
+                    if let Err(e) = self.stop_at_recording_limit().await {
+                        error!("Error stopping recording at the limit: {}", e);
+                        self.publish_error(&e);
+                    }
+                }
+                _ = capture_watchdog.tick(), if self.recording_session.is_some() => {
+                    if let Some(session) = self.recording_session.as_mut() {
+                        if let Err(err) = session.ensure_streaming() {
+                            warn!("Audio capture watchdog: {:#}", err);
+                        }
+                    }
+                }
+                _ = level_meter.tick(), if self.recording_session.is_some() => {
+                    if let Some(session) = self.recording_session.as_ref() {
+                        let (rms, peak) = session.current_levels();
+                        self.status_writer.set_level(rms, peak);
+                    }
+                }
+                Some(command) = ipc_rx.recv() => {
+                    if let Err(e) = self.handle_ipc(command).await {
+                        error!("Error handling {:?} request: {}", command, e);
+                        self.publish_error(&e);
+                    }
+                }
+                Some(heard) = wake_rx.recv() => {
+                    if let Err(e) = self.handle_wake_word(heard).await {
+                        error!("Error handling the wake word: {}", e);
+                        self.publish_error(&e);
+                    }
+                }
                 event = shortcut_rx.recv() => {
                     match event {
                         Some(event) => {
                             if let Err(e) = self.handle_shortcut(event).await {
                                 error!("Error handling shortcut: {}", e);
+                                self.publish_error(&e);
                             }
                         }
                         None => {
@@ -317,26 +694,52 @@ impl HyprwhsprApp {
     }
 
     fn ensure_shortcut_listeners(&mut self, shortcuts: ShortcutsConfig) -> Result<()> {
-        self.ensure_listener(ShortcutKind::Press, shortcuts.press.clone())?;
-        self.ensure_listener(ShortcutKind::Hold, shortcuts.hold.clone())
+        let debounce = shortcuts.press_debounce();
+        if debounce.as_millis() < u128::from(shortcuts.press_debounce_ms) {
+            warn!(
+                "shortcuts.press_debounce_ms ({}) would swallow double presses; using {}ms (below double_press_ms {})",
+                shortcuts.press_debounce_ms,
+                debounce.as_millis(),
+                shortcuts.double_press_ms
+            );
+        }
+        let behavior = PressBehavior {
+            debounce,
+            suppress_repeat: shortcuts.suppress_key_repeat,
+        };
+        self.ensure_listener(ShortcutKind::Press, shortcuts.press.clone(), behavior)?;
+        self.ensure_listener(ShortcutKind::Hold, shortcuts.hold.clone(), behavior)?;
+        self.ensure_listener(ShortcutKind::Cancel, shortcuts.cancel.clone(), behavior)?;
+        self.ensure_listener(ShortcutKind::Mode, shortcuts.mode.clone(), behavior)
     }
 
-    fn ensure_listener(&mut self, kind: ShortcutKind, shortcut: Option<String>) -> Result<()> {
+    fn ensure_listener(
+        &mut self,
+        kind: ShortcutKind,
+        shortcut: Option<String>,
+        behavior: PressBehavior,
+    ) -> Result<()> {
         let slot = match kind {
             ShortcutKind::Press => &mut self.press_listener,
             ShortcutKind::Hold => &mut self.hold_listener,
+            ShortcutKind::Cancel => &mut self.cancel_listener,
+            ShortcutKind::Mode => &mut self.mode_listener,
         };
 
         match shortcut {
             Some(ref target) => {
                 if let Some(listener) = slot {
-                    if listener.matches(target, kind) {
+                    if listener.matches(target, kind, behavior) {
                         return Ok(());
                     }
-                    listener.restart(target.clone(), kind, self.shortcut_tx.clone())?;
+                    listener.restart(target.clone(), kind, behavior, self.shortcut_tx.clone())?;
                 } else {
-                    let listener =
-                        ShortcutListener::spawn(target.clone(), kind, self.shortcut_tx.clone())?;
+                    let listener = ShortcutListener::spawn(
+                        target.clone(),
+                        kind,
+                        behavior,
+                        self.shortcut_tx.clone(),
+                    )?;
                     *slot = Some(listener);
                 }
             }
@@ -352,34 +755,45 @@ impl HyprwhsprApp {
     }
 
     fn apply_config_update(&mut self, new_config: Config) -> Result<()> {
-        tracing::debug!(?new_config, "Apply config update requested");
+        tracing::debug!("Apply config update requested");
         if new_config == self.current_config {
             tracing::debug!("Config unchanged; ignoring update");
             return Ok(());
         }
 
-        if self.recording_session.is_some() || self.is_processing {
-            warn!("Skipping config refresh while busy");
+        if !self.state.is_idle() {
+            warn!("Skipping config refresh while {}", self.state);
             return Ok(());
         }
 
         let assets_dir = self.config_manager.get_assets_dir();
-        let audio_feedback = AudioFeedback::new(
+        let mut audio_feedback = AudioFeedback::new(
             new_config.audio_feedback,
             assets_dir,
             new_config.start_sound_path.clone(),
             new_config.stop_sound_path.clone(),
             new_config.start_sound_volume,
             new_config.stop_sound_volume,
+            new_config.feedback_sounds.clone(),
         );
+        audio_feedback.set_do_not_disturb(DoNotDisturb::new(&new_config.do_not_disturb));
 
-        let text_injector = TextInjector::new(
-            new_config.shift_paste,
-            new_config.paste_hints.shift.clone(),
-            new_config.word_overrides.clone(),
-            new_config.auto_copy_clipboard,
-        )?;
-
+        let mut text_injector = TextInjector::from_config(&new_config)?;
+        if new_config.dictation_mode != self.current_config.dictation_mode {
+            self.dictation_mode = new_config.dictation_mode;
+            self.status_writer.set_mode(self.dictation_mode);
+            info!("📝 Dictation mode: {}", self.dictation_mode);
+        }
+        text_injector.set_mode(self.dictation_mode);
+        text_injector.set_read_only(self.read_only);
+        self.voice_commands = VoiceCommands::new(&new_config.voice_commands);
+
+        self.audio_capture
+            .set_device(new_config.audio_device.clone());
+        self.audio_capture
+            .set_low_memory(new_config.performance.low_memory);
+        self.audio_capture
+            .set_pre_roll(Duration::from_millis(new_config.pre_roll_ms));
         let transcriber_changed =
             TranscriptionBackend::needs_refresh(&self.current_config, &new_config);
 
@@ -395,17 +809,50 @@ impl HyprwhsprApp {
                 "🎯 Active transcription backend: {}",
                 backend.provider().label()
             );
-            self.transcriber = backend;
+            self.transcriber = Arc::new(backend);
+            self.status_writer
+                .set_backend(self.transcriber.provider().label());
+        }
+        if new_config.transcription != self.current_config.transcription {
+            self.short_transcriber = build_short_transcriber(&self.config_manager, &new_config);
+            self.retry_transcriber = build_retry_transcriber(&self.config_manager, &new_config);
+        }
+        if new_config.wake_word != self.current_config.wake_word
+            || new_config.audio_device != self.current_config.audio_device
+            || new_config.transcription != self.current_config.transcription
+        {
+            self.wake_word = None;
+            self.wake_word = start_wake_word(
+                &self.audio_capture,
+                &new_config.wake_word,
+                self.wake_word_backend(),
+                &self.wake_tx,
+            );
+        }
+
+        if new_config.transcription.prompt_boost != self.current_config.transcription.prompt_boost {
+            self.prompt_booster = PromptBooster::new(&new_config.transcription.prompt_boost);
+        }
+
+        if new_config.performance != self.current_config.performance {
+            self.workers = WorkerPool::new(new_config.performance.worker_threads)?;
+            info!(
+                "🧵 Worker pool resized to {} threads",
+                self.workers.threads()
+            );
         }
 
         let shortcuts_changed = new_config.shortcuts != self.current_config.shortcuts
             || self.press_listener.is_none()
-            || (new_config.hold_shortcut().is_some() && self.hold_listener.is_none());
+            || (new_config.hold_shortcut().is_some() && self.hold_listener.is_none())
+            || (new_config.cancel_shortcut().is_some() && self.cancel_listener.is_none())
+            || (new_config.mode_shortcut().is_some() && self.mode_listener.is_none());
 
         if shortcuts_changed {
             self.ensure_shortcut_listeners(new_config.shortcuts.clone())?;
             self.log_shortcut_configuration(&new_config.shortcuts);
         }
+        spawn_shortcut_conflict_check(new_config.shortcuts.clone());
 
         let fast_vad_was_allowed = fast_vad_allowed(&self.current_config);
         let fast_vad_is_allowed = fast_vad_allowed(&new_config);
@@ -426,11 +873,14 @@ impl HyprwhsprApp {
             self.fast_vad = None;
         } else if !fast_vad_was_allowed
             || self.current_config.fast_vad != new_config.fast_vad
+            || self.current_config.audio_device != new_config.audio_device
             || self.fast_vad.is_none()
         {
-            self.fast_vad =
-                FastVad::maybe_new(&new_config.fast_vad, self.audio_capture.sample_rate_hint())
-                    .context("Failed to refresh fast VAD pipeline")?;
+            self.fast_vad = FastVad::maybe_new(
+                &new_config.fast_vad,
+                FastVad::rate_for_input(self.audio_capture.sample_rate_hint()),
+            )
+            .context("Failed to refresh fast VAD pipeline")?;
             if let Some(vad) = &self.fast_vad {
                 info!(
                     "⚡ Earshot fast VAD enabled (profile: {}, silence timeout: {} ms)",
@@ -440,8 +890,46 @@ impl HyprwhsprApp {
             }
         }
 
+        if new_config.status_fifo != self.current_config.status_fifo {
+            self.status_writer
+                .set_fifo(new_config.status_fifo.as_ref().map(PathBuf::from));
+        }
+
+        if new_config.border_indicator != self.current_config.border_indicator {
+            self.status_writer
+                .set_border_indicator(&new_config.border_indicator);
+        }
+
+        if new_config.calendar != self.current_config.calendar {
+            self.calendar = MeetingCalendar::maybe_new(&new_config.calendar);
+        }
+
+        if new_config.history != self.current_config.history {
+            self.history = History::maybe_open(&self.config_manager, &new_config);
+        }
+
+        if new_config.export_targets() != self.current_config.export_targets()
+            || new_config.profile_export_targets() != self.current_config.profile_export_targets()
+        {
+            self.exporter = Exporter::new(
+                new_config.export_targets(),
+                new_config.profile_export_targets(),
+            )?;
+        }
+
+        if new_config.notifications != self.current_config.notifications
+            || new_config.do_not_disturb != self.current_config.do_not_disturb
+        {
+            self.notifier
+                .update(&new_config.notifications, &new_config.do_not_disturb);
+        }
+
         self.text_injector = Arc::new(Mutex::new(text_injector));
         self.audio_feedback = audio_feedback;
+        let changes = new_config.changes_since(&self.current_config);
+        if let Err(err) = self.status_writer.set_config_changes(&changes) {
+            warn!("Failed to record config changes: {err:#}");
+        }
         self.current_config = new_config;
 
         info!("Configuration updated");
@@ -459,51 +947,426 @@ impl HyprwhsprApp {
             Some(value) => info!("Hold shortcut active: {}", value),
             None => info!("Hold shortcut disabled"),
         }
+
+        if let Some(value) = shortcuts.cancel.as_deref() {
+            info!("Cancel shortcut active: {}", value);
+        }
+
+        if let Some(value) = shortcuts.mode.as_deref() {
+            info!("Mode shortcut active: {}", value);
+        }
     }
 
     async fn handle_shortcut(&mut self, event: ShortcutEvent) -> Result<()> {
         match (event.kind, event.phase) {
             (ShortcutKind::Press, ShortcutPhase::Start) => {
-                if self.is_processing {
-                    warn!("Still processing previous recording, ignoring shortcut");
-                    return Ok(());
+                let double_press = self
+                    .current_config
+                    .shortcuts
+                    .action(ShortcutTrigger::DoublePress);
+                if double_press == ShortcutAction::None {
+                    return self
+                        .run_shortcut_action(ShortcutTrigger::Press, event.triggered_at)
+                        .await;
                 }
 
-                if self.recording_session.is_some() {
+                let window = self.double_press_window();
+                match self.pending_press.tap(event.triggered_at, window) {
+                    Some(Tap::Double) => {
+                        self.run_shortcut_action(ShortcutTrigger::DoublePress, event.triggered_at)
+                            .await?;
+                    }
+                    Some(Tap::Single(first)) => {
+                        self.run_shortcut_action(ShortcutTrigger::Press, first)
+                            .await?;
+                    }
+                    None => {}
+                }
+            }
+            (ShortcutKind::Hold, ShortcutPhase::Start) => {
+                self.run_shortcut_action(ShortcutTrigger::Hold, event.triggered_at)
+                    .await?;
+            }
+            (ShortcutKind::Hold, ShortcutPhase::End) => {
+                let push_to_talk = self.current_config.shortcuts.action(ShortcutTrigger::Hold)
+                    == ShortcutAction::PushToTalk;
+                if push_to_talk && self.state.trigger() == Some(RecordingTrigger::HoldShortcut) {
                     self.stop_recording(event.triggered_at).await?;
                 } else {
-                    self.start_recording(RecordingTrigger::PressShortcut, event.triggered_at)
-                        .await?;
+                    debug!("Hold release ignored (no active hold-triggered recording)");
                 }
             }
-            (ShortcutKind::Hold, ShortcutPhase::Start) => {
-                if self.is_processing {
-                    warn!("Still processing previous recording, ignoring hold shortcut");
+            (ShortcutKind::Cancel, ShortcutPhase::Start) => {
+                if self.state.is_recording() {
+                    self.cancel_recording()?;
+                } else {
+                    debug!("Cancel shortcut ignored (not recording)");
+                }
+            }
+            (ShortcutKind::Mode, ShortcutPhase::Start) => {
+                self.set_dictation_mode(self.dictation_mode.next()).await;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn double_press_window(&self) -> Duration {
+        Duration::from_millis(self.current_config.shortcuts.double_press_ms)
+    }
+
+    /// Runs what `shortcuts.actions` maps `trigger` to.
+    async fn run_shortcut_action(
+        &mut self,
+        trigger: ShortcutTrigger,
+        triggered_at: Instant,
+    ) -> Result<()> {
+        let action = self.current_config.shortcuts.action(trigger);
+        match action {
+            ShortcutAction::Toggle | ShortcutAction::PushToTalk => {
+                if self.state.is_busy() {
+                    warn!(
+                        "Still processing previous recording, ignoring {:?} shortcut",
+                        trigger
+                    );
                     return Ok(());
                 }
 
-                if self.recording_session.is_some() {
-                    debug!("Hold shortcut ignored because recording is already active");
-                } else {
-                    self.start_recording(RecordingTrigger::HoldShortcut, event.triggered_at)
+                if !self.state.is_recording() {
+                    let recording_trigger = match trigger {
+                        ShortcutTrigger::Hold => RecordingTrigger::HoldShortcut,
+                        ShortcutTrigger::Press | ShortcutTrigger::DoublePress => {
+                            RecordingTrigger::PressShortcut
+                        }
+                    };
+                    self.start_recording(recording_trigger, triggered_at)
                         .await?;
+                } else if action == ShortcutAction::Toggle {
+                    self.stop_recording(triggered_at).await?;
+                } else {
+                    debug!("Hold shortcut ignored because recording is already active");
                 }
             }
-            (ShortcutKind::Hold, ShortcutPhase::End) => {
-                if matches!(self.recording_trigger, Some(RecordingTrigger::HoldShortcut))
-                    && self.recording_session.is_some()
-                {
-                    self.stop_recording(event.triggered_at).await?;
+            ShortcutAction::ReinjectLast => self.reinject_last().await?,
+            ShortcutAction::ToggleMonitor => self.toggle_monitor(triggered_at).await?,
+            ShortcutAction::CycleMode => {
+                self.set_dictation_mode(self.dictation_mode.next()).await;
+            }
+            ShortcutAction::Cancel => {
+                if self.state.is_recording() {
+                    self.cancel_recording()?;
                 } else {
-                    debug!("Hold release ignored (no active hold-triggered recording)");
+                    debug!("{:?} cancel ignored (not recording)", trigger);
+                }
+            }
+            ShortcutAction::None => debug!("{:?} shortcut has no action", trigger),
+        }
+        Ok(())
+    }
+
+    /// Pastes the last injected text again, as typed the first time.
+    async fn reinject_last(&mut self) -> Result<()> {
+        if !self.state.is_idle() {
+            warn!("Not re-injecting while {}", self.state);
+            return Ok(());
+        }
+        let Some(text) = self.last_injection.clone() else {
+            debug!("Nothing injected yet; re-inject ignored");
+            return Ok(());
+        };
+
+        let mut injector = self.text_injector.lock().await;
+        if let Some(method) = injector.paste_verbatim(&text).await? {
+            info!("Re-injected last transcript via {}", method);
+        }
+        Ok(())
+    }
+
+    /// Types the `session_stats` template, filled in, at the cursor.
+    async fn insert_session_stats(&mut self) -> Result<()> {
+        let stats = self
+            .session
+            .render(&self.current_config.session_stats.template);
+        let mut injector = self.text_injector.lock().await;
+        if let Some(method) = injector.paste_verbatim(&stats).await? {
+            info!("📊 Inserted session statistics via {}: {}", method, stats);
+        }
+        Ok(())
+    }
+
+    async fn handle_ipc(&mut self, command: IpcCommand) -> Result<()> {
+        let now = Instant::now();
+        match command {
+            IpcCommand::CycleMode => {
+                self.set_dictation_mode(self.dictation_mode.next()).await;
+                return Ok(());
+            }
+            IpcCommand::SetMode(mode) => {
+                self.set_dictation_mode(mode).await;
+                return Ok(());
+            }
+            IpcCommand::ToggleMonitor => return self.toggle_monitor(now).await,
+            IpcCommand::ToggleReadOnly => {
+                self.set_read_only(!self.read_only).await;
+                return Ok(());
+            }
+            IpcCommand::ToggleRequestLog => {
+                // Applied through the reload path once the config is saved.
+                let enabled = !self.current_config.transcription.request_log.enabled;
+                info!(
+                    "📜 Provider request log {}",
+                    if enabled { "on" } else { "off" }
+                );
+                return self.config_manager.update(|config| {
+                    config.transcription.request_log.enabled = enabled;
+                });
+            }
+            IpcCommand::ReplayLast { fast } => {
+                self.replay_last_recording(fast);
+                return Ok(());
+            }
+            IpcCommand::InsertStats => {
+                if !self.state.is_idle() {
+                    warn!("Not inserting session statistics while {}", self.state);
+                    return Ok(());
                 }
+                return self.insert_session_stats().await;
+            }
+            IpcCommand::SetProvider(provider) => {
+                // Applied through the reload path once the config is saved.
+                info!(
+                    "🔀 Switching transcription provider to {}",
+                    provider.label()
+                );
+                return self
+                    .config_manager
+                    .update(|config| config.transcription.provider = provider);
             }
             _ => {}
         }
 
+        if self.state.is_busy() && command != IpcCommand::Cancel {
+            warn!(
+                "Still processing previous recording, ignoring {:?}",
+                command
+            );
+            return Ok(());
+        }
+
+        match (command, self.state.is_recording()) {
+            (IpcCommand::Toggle | IpcCommand::Start, false) => {
+                self.start_recording(RecordingTrigger::Ipc, now).await?
+            }
+            (IpcCommand::Toggle | IpcCommand::Stop, true) => self.stop_recording(now).await?,
+            (IpcCommand::Cancel, true) => self.cancel_recording()?,
+            _ => debug!("{:?} ignored in the current state", command),
+        }
+
         Ok(())
     }
 
+    /// Applies to the next transcript, including one still being recorded.
+    async fn set_dictation_mode(&mut self, mode: DictationMode) {
+        self.dictation_mode = mode;
+        self.text_injector.lock().await.set_mode(mode);
+        self.status_writer.set_mode(mode);
+        info!("📝 Dictation mode: {}", mode);
+    }
+
+    /// Runs a `voice_commands` phrase said on its own instead of typing it.
+    async fn run_voice_command(&mut self, action: VoiceAction) -> Result<()> {
+        info!("🗣️  Voice command: {:?}", action);
+        match action {
+            VoiceAction::Undo => {
+                self.text_injector
+                    .lock()
+                    .await
+                    .erase_previous_injection()
+                    .await?;
+                self.last_injection = None;
+            }
+            VoiceAction::StopDictation => {
+                self.wake_word = None;
+                if self.state.trigger() == Some(RecordingTrigger::WakeWord) {
+                    self.cancel_recording()?;
+                }
+                info!("👂 Stopped listening for the wake word until the next recording");
+            }
+            VoiceAction::CycleMode => self.set_dictation_mode(self.dictation_mode.next()).await,
+            _ => {
+                if let Some(mode) = action.mode() {
+                    self.set_dictation_mode(mode).await;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Keeps transcripts from being typed, pasted or copied (screen
+    /// sharing); they still reach history, exports and notifications.
+    pub async fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+        self.text_injector.lock().await.set_read_only(read_only);
+        self.status_writer.set_read_only(read_only);
+        if read_only {
+            info!("🔒 Read-only mode: transcripts will not be pasted or copied");
+        } else {
+            info!("🔓 Read-only mode off");
+        }
+    }
+
+    /// The backend wake-word candidates go to.
+    fn wake_word_backend(&self) -> &Arc<TranscriptionBackend> {
+        self.short_transcriber.as_ref().unwrap_or(&self.transcriber)
+    }
+
+    /// Checks a burst of speech for the wake phrase off the event loop and
+    /// starts recording once it matches; ends a recording the phrase started
+    /// at the next pause after speech.
+    async fn handle_wake_word(&mut self, heard: Heard) -> Result<()> {
+        match heard {
+            Heard::Silence => {
+                // The pause after the phrase itself can land once recording
+                // has started.
+                let stop_after = Duration::from_millis(
+                    self.current_config.wake_word.stop_after_silence_ms.into(),
+                );
+                let past_phrase = self
+                    .recording_session
+                    .as_ref()
+                    .is_some_and(|session| session.elapsed() > stop_after);
+                if self.state.trigger() == Some(RecordingTrigger::WakeWord) && past_phrase {
+                    info!("🤫 Pause after wake-word dictation, stopping");
+                    return self.stop_recording(Instant::now()).await;
+                }
+            }
+            Heard::Candidate(audio) => {
+                let checking = self
+                    .wake_check
+                    .as_ref()
+                    .is_some_and(|check| !check.is_finished());
+                if !self.state.is_idle() || checking {
+                    return Ok(());
+                }
+                self.wake_check = Some(self.spawn_wake_check(audio));
+            }
+            Heard::Phrase(heard_at) => {
+                // A shortcut may have started a recording, or "stop
+                // dictation" ended listening, while the check ran.
+                if self.state.is_idle() && self.wake_word.is_some() {
+                    return self
+                        .start_recording(RecordingTrigger::WakeWord, heard_at)
+                        .await;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Transcribes a wake-word candidate and sends [`Heard::Phrase`] back
+    /// when it matches. A failed check only costs that candidate.
+    fn spawn_wake_check(&self, audio: CapturedAudio) -> tokio::task::JoinHandle<()> {
+        let heard_at = Instant::now();
+        let config = self.current_config.wake_word.clone();
+        let quality = self.current_config.performance.resample_quality;
+        let workers = self.workers.clone();
+        let transcriber = Arc::clone(self.wake_word_backend());
+        let heard = self.wake_tx.clone();
+        tokio::spawn(async move {
+            let CapturedAudio {
+                samples,
+                sample_rate,
+            } = audio;
+            let context = PromptContext {
+                vocabulary: None,
+                app_class: None,
+                prev_transcript: None,
+                prompt_override: None,
+                append_vocabulary: false,
+                utterance_key: None,
+                short_command: true,
+            };
+            let transcript = match workers
+                .run(move || resample_audio(&samples, sample_rate, 16_000, quality))
+                .await
+            {
+                Ok(samples) => transcriber.transcribe(samples, &context).await,
+                Err(err) => Err(err),
+            };
+            match transcript {
+                Ok(result) => {
+                    let text = result.text;
+                    if wakeword::matches_phrase(&text, &config.phrase, config.sensitivity) {
+                        info!("👂 Heard \"{}\", recording", text.trim());
+                        let _ = heard.send(Heard::Phrase(heard_at));
+                    } else {
+                        debug!("Not the wake phrase: {:?}", text);
+                    }
+                }
+                Err(err) => debug!("Wake-word check failed: {:#}", err),
+            }
+        })
+    }
+
+    /// Ends the recording without transcribing it.
+    fn cancel_recording(&mut self) -> Result<()> {
+        let Some(session) = self.recording_session.take() else {
+            return Ok(());
+        };
+
+        info!("🚫 Recording cancelled");
+        drop(session.stop());
+        self.audio_feedback.play_cancel_sound()?;
+        self.events
+            .publish(AppEvent::RecordingStopped { cancelled: true });
+        self.state.transition(AppState::Idle)?;
+        self.benchmark = None;
+        Ok(())
+    }
+
+    /// Plays the kept last recording without holding up the main loop; not
+    /// while recording, where the microphone would pick it up.
+    fn replay_last_recording(&self, fast: bool) {
+        if !self.state.is_idle() {
+            warn!("Not replaying the last recording while {}", self.state);
+            return;
+        }
+        let path = self.config_manager.get_last_recording_path();
+        if !path.exists() {
+            warn!(
+                "No recording found at {:?}; is keep_last_recording enabled?",
+                path
+            );
+            return;
+        }
+
+        let speed = if fast { FAST_REPLAY_SPEED } else { 1.0 };
+        info!("🔁 Replaying the last recording at {}x", speed);
+        tokio::task::spawn_blocking(move || {
+            let played = recording::load_recording(&path)
+                .and_then(|audio| AudioFeedback::play_captured_blocking(&audio, speed));
+            if let Err(err) = played {
+                warn!("Failed to replay the last recording: {:#}", err);
+            }
+        });
+    }
+
+    /// Records system output, or stops the running recording whatever it
+    /// captures.
+    async fn toggle_monitor(&mut self, triggered_at: Instant) -> Result<()> {
+        if self.state.is_busy() {
+            warn!("Still processing previous recording, ignoring monitor toggle");
+            return Ok(());
+        }
+        if self.state.is_recording() {
+            self.stop_recording(triggered_at).await
+        } else {
+            self.start_recording(RecordingTrigger::Monitor, triggered_at)
+                .await
+        }
+    }
+
     async fn start_recording(
         &mut self,
         trigger: RecordingTrigger,
@@ -511,15 +1374,53 @@ impl HyprwhsprApp {
     ) -> Result<()> {
         info!("🎤 Starting recording...");
 
+        // Back from "stop dictation".
+        if trigger != RecordingTrigger::WakeWord && self.wake_word.is_none() {
+            self.wake_word = start_wake_word(
+                &self.audio_capture,
+                &self.current_config.wake_word,
+                self.wake_word_backend(),
+                &self.wake_tx,
+            );
+        }
+
         self.audio_feedback.play_start_sound()?;
 
-        let session = self
-            .audio_capture
-            .start_recording()
-            .context("Failed to start recording")?;
+        let start_sound = self.audio_feedback.start_sound_window();
+        let mut overlap = self.current_config.start_sound_overlap;
+        if let (StartSoundOverlap::Delay, Some(window)) = (overlap, start_sound) {
+            tokio::time::sleep(window).await;
+        }
+
+        let mixed = self.current_config.capture_mix.enabled;
+        let mut session = if trigger == RecordingTrigger::Monitor || mixed {
+            // The monitor hears the start sound straight from the speakers.
+            if overlap == StartSoundOverlap::Keep {
+                overlap = StartSoundOverlap::Trim;
+            }
+            let source = monitor::resolve_source(self.current_config.monitor_source.as_deref())?;
+            if trigger == RecordingTrigger::Monitor {
+                info!("🔊 Recording system output from {}", source);
+                self.audio_capture.start_monitor_recording(&source)
+            } else {
+                info!("🔊 Recording the microphone mixed with {}", source);
+                self.audio_capture.start_mixed_recording(&source)
+            }
+        } else {
+            // Pre-roll would hold the wake phrase.
+            let pre_roll = trigger != RecordingTrigger::WakeWord
+                && (overlap == StartSoundOverlap::Keep || start_sound.is_none());
+            self.audio_capture.start_recording(pre_roll)
+        }
+        .context("Failed to start recording")?;
+        if let (StartSoundOverlap::Trim, Some(window)) = (overlap, start_sound) {
+            session.discard_leading(window);
+        }
 
+        self.state.transition(AppState::Recording { trigger })?;
         self.recording_session = Some(session);
-        self.recording_trigger = Some(trigger);
+        self.recording_deadline =
+            RecordingDeadline::new(self.current_config.max_recording(), Instant::now());
 
         let recording_started_at = Instant::now();
         self.benchmark = Some(BenchmarkRecorder::new(
@@ -528,7 +1429,7 @@ impl HyprwhsprApp {
             recording_started_at,
         ));
 
-        self.status_writer.set_recording(true)?;
+        self.events.publish(AppEvent::RecordingStarted);
 
         Ok(())
     }
@@ -541,118 +1442,232 @@ impl HyprwhsprApp {
             .take()
             .context("No active recording session")?;
 
+        let result = self.finish_recording(session, triggered_at, false).await;
+        self.state.transition(AppState::Idle)?;
+        result
+    }
+
+    /// Ends a recording that ran for `max_recording_secs` and transcribes
+    /// what it captured.
+    async fn stop_at_recording_limit(&mut self) -> Result<()> {
+        self.recording_deadline = RecordingDeadline::default();
+        let Some(session) = self.recording_session.take() else {
+            return Ok(());
+        };
+        warn!(
+            "⏱️  Recording reached max_recording_secs ({}s), stopping",
+            self.current_config.max_recording_secs
+        );
+
+        let result = self.finish_recording(session, Instant::now(), true).await;
+        self.state.transition(AppState::Idle)?;
+        result
+    }
+
+    /// Transcribes and injects a stopped recording, playing the limit sound
+    /// instead of the stop sound when `limit_reached`. The caller returns to
+    /// idle however this ends.
+    async fn finish_recording(
+        &mut self,
+        session: RecordingSession,
+        triggered_at: Instant,
+        limit_reached: bool,
+    ) -> Result<()> {
         if let Some(benchmark) = self.benchmark.as_mut() {
             benchmark.mark_keybind_stop(triggered_at);
         }
 
-        self.audio_feedback.play_stop_sound()?;
+        // Close the input stream before the stop sound starts so speakers
+        // near the mic can't bleed it into the tail of the capture.
+        let (captured_audio, monitor_audio) = session
+            .stop_with_companion()
+            .context("Failed to stop recording")?;
+
+        if limit_reached {
+            self.audio_feedback.play_limit_sound()?;
+        } else {
+            self.audio_feedback.play_stop_sound()?;
+        }
 
-        self.status_writer.set_recording(false)?;
+        self.events
+            .publish(AppEvent::RecordingStopped { cancelled: false });
+
+        let mix = &self.current_config.capture_mix;
+        let (captured_audio, monitor_track) = match monitor_audio {
+            Some(monitor_audio) if mix.mode == MixMode::Tracks => (
+                captured_audio,
+                Some((monitor_audio, mix.monitor_label.clone())),
+            ),
+            Some(monitor_audio) => {
+                let gain = mix.monitor_gain;
+                let quality = self.current_config.performance.resample_quality;
+                let mixed = self
+                    .workers
+                    .run(move || monitor::mix(captured_audio, monitor_audio, gain, quality))
+                    .await?;
+                (mixed, None)
+            }
+            None => (captured_audio, None),
+        };
+        let mic_label = monitor_track.as_ref().map(|_| mix.mic_label.clone());
 
-        let captured_audio = session.stop().context("Failed to stop recording")?;
+        if self.current_config.keep_last_recording && !captured_audio.is_empty() {
+            let path = self.config_manager.get_last_recording_path();
+            if let Err(err) = recording::save_last_recording(&path, &captured_audio) {
+                warn!("Failed to keep last recording at {:?}: {:#}", path, err);
+            }
+        }
         let stop_timestamp = Instant::now();
-        self.recording_trigger = None;
 
         if let Some(benchmark) = self.benchmark.as_mut() {
             benchmark.mark_recording_stop(stop_timestamp);
             benchmark.record_original_audio(captured_audio.len(), captured_audio.sample_rate);
         }
 
-        if !captured_audio.is_empty() {
-            self.is_processing = true;
-            if let Err(e) = self.process_audio(captured_audio).await {
-                error!("❌ Error processing audio: {:#}", e);
-                // Show user-friendly error notification
-                warn!("Failed to process recording. Check logs for details.");
+        let tracks = std::iter::once((captured_audio, mic_label))
+            .chain(monitor_track.map(|(audio, label)| (audio, Some(label))));
+        let processing = AppState::Processing {
+            job: ProcessingJob::Recording,
+        };
+        for (captured_audio, label) in tracks {
+            if captured_audio.is_empty() {
+                warn!("No audio data captured");
+                self.benchmark = None;
+                continue;
+            }
+            if self.state != processing {
+                self.state.transition(processing)?;
+            }
+            self.events.publish(AppEvent::ProcessingStarted);
+            let spooled = if self.current_config.resume_pending_utterances {
+                self.pending
+                    .enqueue(&captured_audio)
+                    .inspect_err(|err| warn!("Failed to spool utterance for resume: {:#}", err))
+                    .ok()
+            } else {
+                None
+            };
+            match self
+                .process_audio(captured_audio, label.as_deref(), false)
+                .await
+            {
+                Ok(()) => {
+                    if let Some(path) = spooled {
+                        self.pending.complete(&path);
+                    }
+                }
+                Err(e) => {
+                    error!("❌ Error processing audio: {:#}", e);
+                    self.audio_feedback
+                        .play_event(FeedbackEvent::TranscriptionFailed);
+                    self.publish_error(&e);
+                    warn!("Failed to process recording. Check logs for details.");
+                }
             }
             self.benchmark = None;
-            self.is_processing = false;
-        } else {
-            warn!("No audio data captured");
-            self.benchmark = None;
+            self.events.publish(AppEvent::ProcessingFinished);
         }
 
         Ok(())
     }
 
-    fn preprocess_audio(&mut self, audio_data: CapturedAudio) -> Result<Option<PreprocessedAudio>> {
-        let CapturedAudio {
-            mut samples,
-            mut sample_rate,
-        } = audio_data;
-
-        if let Some(vad) = self.fast_vad.as_mut() {
-            if !FastVad::supports_sample_rate(sample_rate) {
-                warn!(
-                    "🎚️ Input sample rate {} Hz unsupported by fast VAD; resampling to 16 kHz",
-                    sample_rate
-                );
-                samples = resample_audio(&samples, sample_rate, 16_000);
-                sample_rate = 16_000;
-            }
+    /// Transcribes utterances that were captured but not yet handled when the
+    /// previous run stopped. Focus has moved on since, so the transcripts are
+    /// copied instead of pasted; one that fails again stays spooled.
+    async fn resume_pending_utterances(&mut self) -> Result<()> {
+        let spooled = self.pending.spooled();
+        if spooled.is_empty() {
+            return Ok(());
+        }
 
-            if vad.sample_rate_hz() != sample_rate {
-                vad.set_sample_rate(sample_rate)
-                    .context("Failed to configure fast VAD sample rate")?;
-            }
+        if !self.current_config.resume_pending_utterances {
+            info!("Discarding {} pending utterance(s)", spooled.len());
+            spooled.iter().for_each(|path| self.pending.complete(path));
+            return Ok(());
+        }
 
-            let outcome = vad.trim(&samples).context("Fast VAD trimming failed")?;
-            if outcome.trimmed_audio.is_empty() {
-                info!(
-                    "🎧 Recording contained only silence after fast VAD trimming; skipping transcription"
-                );
-                return Ok(None);
+        info!(
+            "♻️  Resuming {} utterance(s) captured before the last shutdown",
+            spooled.len()
+        );
+        for path in spooled {
+            match recording::load_recording(&path) {
+                Ok(audio) if !audio.is_empty() => {
+                    self.state.transition(AppState::Processing {
+                        job: ProcessingJob::Resumed,
+                    })?;
+                    self.events.publish(AppEvent::ProcessingStarted);
+                    let result = self.process_audio(audio, None, true).await;
+                    self.state.transition(AppState::Idle)?;
+                    self.events.publish(AppEvent::ProcessingFinished);
+                    match result {
+                        Ok(()) => self.pending.complete(&path),
+                        Err(e) => {
+                            error!("❌ Error processing resumed utterance: {:#}", e);
+                            self.audio_feedback
+                                .play_event(FeedbackEvent::TranscriptionFailed);
+                            self.publish_error(&e);
+                            warn!("Keeping {:?} for the next start", path);
+                        }
+                    }
+                }
+                Ok(_) => self.pending.complete(&path),
+                Err(err) => {
+                    warn!("Failed to load pending utterance {:?}: {:#}", path, err);
+                    self.pending.complete(&path);
+                }
             }
-
-            let FastVadOutcome {
-                trimmed_audio,
-                segments,
-                profile_switches,
-                final_profile,
-                dropped_samples,
-                ..
-            } = outcome;
-
-            let trimmed_len = trimmed_audio.len();
-
-            debug!(
-                "Earshot fast VAD kept {}/{} samples across {} segments (profile={}, switches={}, dropped={})",
-                trimmed_len,
-                samples.len(),
-                segments,
-                final_profile,
-                profile_switches,
-                dropped_samples
-            );
-
-            return Ok(Some(PreprocessedAudio {
-                audio: CapturedAudio {
-                    samples: trimmed_audio,
-                    sample_rate,
-                },
-                report: Some(FastVadSummary {
-                    dropped_samples,
-                    sample_rate,
-                }),
-            }));
         }
-
-        Ok(Some(PreprocessedAudio {
-            audio: CapturedAudio {
-                samples,
-                sample_rate,
-            },
-            report: None,
-        }))
+        Ok(())
     }
 
-    async fn process_audio(&mut self, audio_data: CapturedAudio) -> Result<()> {
+    /// Transcribes and injects `audio_data`, prefixed with `label` when it
+    /// is one side of a recording transcribed track by track. `resumed`
+    /// audio, spooled before a restart, is copied instead of injected and
+    /// never runs a voice command.
+    async fn process_audio(
+        &mut self,
+        audio_data: CapturedAudio,
+        label: Option<&str>,
+        resumed: bool,
+    ) -> Result<()> {
         if let Some(benchmark) = self.benchmark.as_mut() {
             benchmark.mark_processing_start(Instant::now());
         }
 
         let preprocess_start = Instant::now();
-        let maybe_audio = self.preprocess_audio(audio_data)?;
+        let mut fast_vad = self.fast_vad.take();
+        let had_fast_vad = fast_vad.is_some();
+        let denoise = self.current_config.denoise.strength();
+        let quality = self.current_config.performance.resample_quality;
+        let preprocessed = self
+            .workers
+            .run(move || {
+                let result = preprocess_audio(denoise, quality, fast_vad.as_mut(), audio_data);
+                (fast_vad, result)
+            })
+            .await;
+        let (fast_vad, maybe_audio) = match preprocessed {
+            Ok(done) => done,
+            Err(err) => {
+                // The VAD went down with the worker; a fresh one keeps the
+                // next recordings trimmed.
+                if had_fast_vad {
+                    self.fast_vad = FastVad::maybe_new(
+                        &self.current_config.fast_vad,
+                        FastVad::rate_for_input(self.audio_capture.sample_rate_hint()),
+                    )
+                    .inspect_err(|vad_err| {
+                        warn!("Fast VAD disabled until the next config reload: {vad_err:#}")
+                    })
+                    .ok()
+                    .flatten();
+                }
+                return Err(err);
+            }
+        };
+        self.fast_vad = fast_vad;
+        let maybe_audio = maybe_audio?;
         let preprocess_duration = preprocess_start.elapsed();
 
         if let Some(benchmark) = self.benchmark.as_mut() {
@@ -686,6 +1701,13 @@ impl HyprwhsprApp {
             .map(|summary| summary.sample_rate)
             .unwrap_or(audio.sample_rate);
         let dropped_samples = report.as_ref().map(|summary| summary.dropped_samples);
+        let lists = &self.current_config.list_segmentation;
+        let list_starts = match &report {
+            Some(summary) if lists.enabled => {
+                item_starts(&summary.breaks, summary.sample_rate, lists.min_pause_ms)
+            }
+            _ => Vec::new(),
+        };
 
         if let Some(benchmark) = self.benchmark.as_mut() {
             benchmark.record_trimmed_audio(audio.len(), trimmed_rate, dropped_samples);
@@ -703,15 +1725,151 @@ impl HyprwhsprApp {
                 "Resampling processed audio from {} Hz to 16 kHz for transcription backend",
                 sample_rate
             );
-            resample_audio(&samples, sample_rate, 16_000)
+            let quality = self.current_config.performance.resample_quality;
+            self.workers
+                .run(move || resample_audio(&samples, sample_rate, 16_000, quality))
+                .await?
         };
 
         if let Some(benchmark) = self.benchmark.as_mut() {
             benchmark.record_audio_sent(audio_for_transcription.len(), 16_000);
         }
 
-        let TranscriptionResult { text, metrics } =
-            self.transcriber.transcribe(audio_for_transcription).await?;
+        let meeting = self
+            .calendar
+            .as_ref()
+            .and_then(MeetingCalendar::current_meeting);
+        if let Some(title) = &meeting {
+            info!("📅 Dictating during \"{}\"", title);
+        }
+        self.status_writer.set_meeting(meeting.clone());
+        let meeting_profile = self
+            .calendar
+            .as_ref()
+            .zip(meeting.as_deref())
+            .and_then(|(calendar, title)| calendar.profile_for(title))
+            .map(str::to_string);
+        if let Some(name) = &meeting_profile {
+            info!("📅 Using the \"{}\" profile for this meeting", name);
+        }
+        self.text_injector
+            .lock()
+            .await
+            .set_profile_override(meeting_profile.clone());
+        let app_class = focused_window_class().await;
+        let profile_prompt = profile_for(
+            &self.current_config.profiles,
+            meeting_profile.as_deref().or(app_class.as_deref()),
+        )
+        .and_then(|profile| profile.whisper_prompt.clone());
+        let utterance = utterance_key();
+        let audio_duration =
+            Duration::from_secs_f32(audio_for_transcription.len() as f32 / 16_000.0);
+        let short_command = self
+            .current_config
+            .transcription
+            .short_command
+            .applies_to(audio_duration);
+        let transcriber = match &self.short_transcriber {
+            Some(backend) if short_command => backend,
+            _ => &self.transcriber,
+        };
+        // The meeting title tends to name the people and topics being said,
+        // but it only leaves the machine when allowed to.
+        let hinted_meeting = meeting
+            .clone()
+            .filter(|_| transcriber.is_local() || self.current_config.calendar.send_to_remote);
+        let vocabulary = match (self.prompt_booster.vocabulary(), hinted_meeting) {
+            (Some(terms), Some(title)) => Some(format!("{terms}, {title}")),
+            (terms, title) => terms.or(title),
+        };
+        let context = PromptContext {
+            vocabulary: vocabulary.as_deref(),
+            app_class: app_class.as_deref(),
+            prev_transcript: self.last_transcript.as_deref(),
+            prompt_override: profile_prompt.as_deref(),
+            append_vocabulary: true,
+            utterance_key: Some(&utterance),
+            short_command,
+        };
+        if short_command {
+            debug!(
+                "⚡ Short command ({:.1}s), taking the fast path",
+                audio_duration.as_secs_f32()
+            );
+        }
+        let list_starts = if short_command {
+            Vec::new()
+        } else {
+            list_starts
+        };
+        if !list_starts.is_empty() {
+            info!("📋 Dictated list of {} items", list_starts.len() + 1);
+        }
+        let mut provider = transcriber.provider();
+        let request_timeout = self
+            .current_config
+            .transcription
+            .request_timeout_secs
+            .max(5);
+        let timeout = (provider != TranscriptionProvider::WhisperCpp)
+            .then(|| Duration::from_secs(request_timeout));
+        let progress = self.status_writer.start_progress(
+            provider.label(),
+            audio_duration,
+            self.duration_estimator.expected(&provider, audio_duration),
+            timeout,
+        );
+        let transcribe_start = Instant::now();
+        let retry_audio = self
+            .retry_transcriber
+            .as_ref()
+            .map(|_| audio_for_transcription.clone());
+        let result = transcriber
+            .transcribe_list(
+                audio_for_transcription,
+                &list_starts,
+                self.current_config.list_segmentation.style,
+                &context,
+            )
+            .await;
+        drop(progress);
+        let mut result = result?;
+        self.duration_estimator
+            .observe(&provider, audio_duration, transcribe_start.elapsed());
+        if let (Some(retry), Some(audio)) = (&self.retry_transcriber, retry_audio) {
+            if let Some(second) = retry
+                .second_opinion(
+                    &result,
+                    audio,
+                    &list_starts,
+                    self.current_config.list_segmentation.style,
+                    &context,
+                    &self.current_config.transcription.retry_low_confidence,
+                )
+                .await
+            {
+                result = second;
+                provider = retry.provider();
+            }
+        }
+        let TranscriptionResult {
+            text,
+            segments,
+            metrics,
+        } = result;
+        if metrics.fallback_used {
+            self.audio_feedback
+                .play_event(FeedbackEvent::ProviderFallback);
+        }
+        self.prompt_booster.observe(&text);
+        if !text.trim().is_empty() {
+            self.last_transcript = Some(text.clone());
+            self.events.publish(AppEvent::TranscriptReady {
+                text: text.clone(),
+                backend: provider.label(),
+            });
+        }
 
         if let Some(benchmark) = self.benchmark.as_mut() {
             benchmark.record_backend_metrics(metrics);
@@ -719,6 +1877,10 @@ impl HyprwhsprApp {
 
         if text.trim().is_empty() {
             warn!("Empty transcription, nothing to inject");
+            self.audio_feedback.play_event(FeedbackEvent::EmptyResult);
+            self.events.publish(AppEvent::EmptyTranscript {
+                backend: provider.label(),
+            });
             if let Some(mut benchmark) = self.benchmark.take() {
                 benchmark.mark_injection_skipped(Instant::now());
                 if let Some(summary) = benchmark.finalize() {
@@ -730,16 +1892,75 @@ impl HyprwhsprApp {
 
         info!("📝 Transcription: \"{}\"", text);
 
+        // Labelled tracks include the other side of a call.
+        let live = label.is_none() && !resumed;
+        match self
+            .voice_commands
+            .control(&text, &self.current_config.session_stats.phrase, live)
+        {
+            Some(Control::InsertStats) => return self.insert_session_stats().await,
+            // "stop dictation" is plain text without hands-free listening
+            // to stop.
+            Some(Control::Voice(action))
+                if action != VoiceAction::StopDictation || self.wake_word.is_some() =>
+            {
+                return self.run_voice_command(action).await;
+            }
+            _ => {}
+        }
+
+        let text = match label {
+            Some(label) => format!("{label}: {text}"),
+            None => text,
+        };
+        // With dual output the exporter archives `text` and only a summary
+        // is pasted.
+        let dual_output = &self.current_config.dual_output;
+        let pasted = if dual_output.enabled && !short_command {
+            summary::summarize(&text, dual_output).await
+        } else {
+            text.clone()
+        };
         let text_injector = Arc::clone(&self.text_injector);
         let mut injector = text_injector.lock().await;
 
+        self.state.transition(AppState::Injecting)?;
         let injection_start = Instant::now();
         if let Some(benchmark) = self.benchmark.as_mut() {
             benchmark.mark_injection_start(injection_start);
         }
 
-        debug!("⌨️  Injecting text into active application...");
-        injector.inject_text(&text).await?;
+        let report = if resumed {
+            self.deliver_recovered(&pasted);
+            None
+        } else {
+            debug!("⌨️  Injecting text into active application...");
+            injector.inject_text(&pasted).await?
+        };
+        self.session.record(&text, audio_duration);
+        self.audio_feedback
+            .play_event(FeedbackEvent::TranscriptionComplete);
+        if let Some(history) = &self.history {
+            let injected = report
+                .as_ref()
+                .map_or(pasted.as_str(), |report| &report.text);
+            history.record(
+                &text,
+                injected,
+                provider.label(),
+                audio_duration,
+                meeting.clone(),
+                &segments,
+            );
+        }
+        self.exporter.export(
+            ExportEntry::new(&text, provider.label(), audio_duration, meeting, segments),
+            meeting_profile.or(app_class),
+        );
+        if let Some(report) = report {
+            self.last_injection = Some(report.text.clone());
+            self.events.publish(AppEvent::InjectionDone(report));
+        }
 
         let injection_end = Instant::now();
         if let Some(benchmark) = self.benchmark.as_mut() {
@@ -755,6 +1976,29 @@ impl HyprwhsprApp {
         Ok(())
     }
 
+    /// Copies a transcript recovered from the spool, unless read-only, and
+    /// says so in a notification.
+    fn deliver_recovered(&self, text: &str) {
+        let copied = !self.read_only
+            && copy_to_clipboard(text)
+                .inspect_err(|err| warn!("Failed to copy recovered transcript: {:#}", err))
+                .is_ok();
+        info!(
+            "♻️  Recovered transcript {}",
+            if copied { "copied" } else { "kept in history" }
+        );
+        self.events.publish(AppEvent::Recovered {
+            text: text.to_string(),
+            copied,
+        });
+    }
+
+    fn publish_error(&self, err: &anyhow::Error) {
+        self.events.publish(AppEvent::Error {
+            message: format!("{err:#}"),
+        });
+    }
+
     pub async fn cleanup(&mut self) -> Result<()> {
         info!("🧹 Cleaning up...");
 
@@ -772,7 +2016,19 @@ impl HyprwhsprApp {
             listener.stop();
         }
         self.hold_listener = None;
-        self.recording_trigger = None;
+
+        if let Some(listener) = &mut self.cancel_listener {
+            listener.stop();
+        }
+        self.cancel_listener = None;
+
+        if let Some(listener) = &mut self.mode_listener {
+            listener.stop();
+        }
+        self.mode_listener = None;
+        if !self.state.is_idle() {
+            self.state.transition(AppState::Idle)?;
+        }
 
         info!("✅ Cleanup completed");
         Ok(())
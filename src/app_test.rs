@@ -1,15 +1,34 @@
 use anyhow::{Context, Result};
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 use tracing::{debug, error, info, warn};
 
 use crate::audio::{
-    capture::RecordingSession, AudioCapture, AudioFeedback, CapturedAudio, FastVad, FastVadOutcome,
+    capture::RecordingSession, denoise, echo_cancel::EchoCancel, recording, resample_audio,
+    AudioCapture, AudioFeedback, CapturedAudio, FastVad, FastVadOutcome, FeedbackEvent,
+    SegmentBreak,
 };
-use crate::config::{Config, ConfigManager, TranscriptionProvider};
+use crate::calendar::MeetingCalendar;
+use crate::config::{
+    profile_for, Config, ConfigManager, DictationMode, StartSoundOverlap, TranscriptionProvider,
+    VoiceAction,
+};
+use crate::dnd::DoNotDisturb;
+use crate::export::{ExportEntry, Exporter};
+use crate::history::History;
+use crate::input::focused_window_class;
 use crate::input::TextInjector;
+use crate::session::{self, SessionStats};
+use crate::state::{AppState, ProcessingJob, RecordingDeadline, RecordingTrigger};
 use crate::status::StatusWriter;
-use crate::transcription::{TranscriptionBackend, TranscriptionResult};
+use crate::summary;
+use crate::transcription::{
+    item_starts, utterance_key, DurationEstimator, PromptBooster, PromptContext,
+    TranscriptionBackend, TranscriptionResult,
+};
+use crate::voice_commands::VoiceCommands;
 use crate::whisper::WhisperVadOptions;
 
 /// Test version of the app that doesn't use global shortcuts
@@ -18,32 +37,73 @@ pub struct HyprwhsprAppTest {
     audio_capture: AudioCapture,
     audio_feedback: AudioFeedback,
     transcriber: TranscriptionBackend,
+    short_transcriber: Option<TranscriptionBackend>,
+    retry_transcriber: Option<TranscriptionBackend>,
+    prompt_booster: PromptBooster,
+    last_transcript: Option<String>,
+    session: SessionStats,
+    voice_commands: VoiceCommands,
+    dictation_mode: DictationMode,
+    duration_estimator: DurationEstimator,
     fast_vad: Option<FastVad>,
     text_injector: Arc<Mutex<TextInjector>>,
     status_writer: StatusWriter,
+    exporter: Exporter,
+    calendar: Option<MeetingCalendar>,
+    history: Option<History>,
     current_config: Config,
+    state: AppState,
     recording_session: Option<RecordingSession>,
-    is_processing: bool,
+    recording_deadline: RecordingDeadline,
+    /// Unloads the echo-cancel module when the app goes away.
+    _echo_cancel: Option<EchoCancel>,
 }
 
 impl HyprwhsprAppTest {
     pub fn new(config_manager: ConfigManager) -> Result<Self> {
         let config = config_manager.get();
 
-        let audio_capture = AudioCapture::new().context("Failed to initialize audio capture")?;
+        let echo_cancel = if config.aec.enabled {
+            if config.audio_device.is_some() {
+                warn!("Echo cancellation only applies to the default input; audio_device is set");
+            }
+            EchoCancel::start(&config.aec)
+                .inspect_err(|err| warn!("Echo cancellation unavailable: {:#}", err))
+                .ok()
+        } else {
+            None
+        };
+
+        let mut audio_capture = AudioCapture::new(config.audio_device.clone())
+            .context("Failed to initialize audio capture")?;
+        audio_capture.set_source(echo_cancel.as_ref().map(|aec| aec.source().to_string()));
+        audio_capture.set_low_memory(config.performance.low_memory);
+        if config.device_probe.enabled && config.audio_device.is_none() {
+            if let Err(err) =
+                audio_capture.probe_best_device(Duration::from_millis(config.device_probe.probe_ms))
+            {
+                warn!("Device probe failed: {}", err);
+            }
+        }
+        // After the probe, so the pre-roll opens on the chosen device.
+        audio_capture.set_pre_roll(Duration::from_millis(config.pre_roll_ms));
 
         let assets_dir = config_manager.get_assets_dir();
-        let audio_feedback = AudioFeedback::new(
+        let mut audio_feedback = AudioFeedback::new(
             config.audio_feedback,
             assets_dir,
             config.start_sound_path.clone(),
             config.stop_sound_path.clone(),
             config.start_sound_volume,
             config.stop_sound_volume,
+            config.feedback_sounds.clone(),
         );
+        audio_feedback.set_do_not_disturb(DoNotDisturb::new(&config.do_not_disturb));
 
         let vad_options = build_vad_options(&config_manager, &config);
 
+        let short_transcriber = build_short_transcriber(&config_manager, &config);
+        let retry_transcriber = build_retry_transcriber(&config_manager, &config);
         let transcriber = TranscriptionBackend::build(&config_manager, &config, vad_options)
             .context("Failed to configure transcription backend")?;
 
@@ -56,19 +116,23 @@ impl HyprwhsprAppTest {
             transcriber.provider().label()
         );
 
-        let text_injector = TextInjector::new(
-            config.shift_paste,
-            config.paste_hints.shift.clone(),
-            config.word_overrides.clone(),
-            config.auto_copy_clipboard,
-        )?;
+        let text_injector = TextInjector::from_config(&config)?;
 
         let status_writer = StatusWriter::new()?;
+        status_writer.set_backend(transcriber.provider().label());
+        status_writer.set_fifo(config.status_fifo.as_ref().map(PathBuf::from));
+        status_writer.set_mode(config.dictation_mode);
+        status_writer.set_border_indicator(&config.border_indicator);
+        let history = History::maybe_open(&config_manager, &config);
+        let exporter = Exporter::new(config.export_targets(), config.profile_export_targets())?;
         status_writer.set_recording(false)?;
 
         let fast_vad = if fast_vad_allowed(&config) {
-            FastVad::maybe_new(&config.fast_vad, audio_capture.sample_rate_hint())
-                .context("Failed to initialize fast VAD pipeline")?
+            FastVad::maybe_new(
+                &config.fast_vad,
+                FastVad::rate_for_input(audio_capture.sample_rate_hint()),
+            )
+            .context("Failed to initialize fast VAD pipeline")?
         } else {
             if config.fast_vad.enabled
                 && config.transcription.provider == TranscriptionProvider::WhisperCpp
@@ -84,43 +148,63 @@ impl HyprwhsprAppTest {
             audio_capture,
             audio_feedback,
             transcriber,
+            short_transcriber,
+            retry_transcriber,
+            prompt_booster: PromptBooster::new(&config.transcription.prompt_boost),
+            last_transcript: None,
+            session: SessionStats::new(),
+            voice_commands: VoiceCommands::new(&config.voice_commands),
+            dictation_mode: config.dictation_mode,
+            duration_estimator: DurationEstimator::new(config.transcription.provider),
             fast_vad,
             text_injector: Arc::new(Mutex::new(text_injector)),
             status_writer,
+            exporter,
+            calendar: MeetingCalendar::maybe_new(&config.calendar),
+            history,
             current_config: config,
+            state: AppState::Idle,
             recording_session: None,
-            is_processing: false,
+            recording_deadline: RecordingDeadline::default(),
+            _echo_cancel: echo_cancel,
         })
     }
 
     pub fn apply_config_update(&mut self, new_config: Config) -> Result<()> {
-        tracing::debug!(?new_config, "Apply config update requested (test mode)");
+        tracing::debug!("Apply config update requested (test mode)");
         if new_config == self.current_config {
             tracing::debug!("Config unchanged; ignoring update (test mode)");
             return Ok(());
         }
 
-        if self.recording_session.is_some() || self.is_processing {
-            warn!("Skipping config refresh while busy");
+        if !self.state.is_idle() {
+            warn!("Skipping config refresh while {}", self.state);
             return Ok(());
         }
 
         let assets_dir = self.config_manager.get_assets_dir();
-        let audio_feedback = AudioFeedback::new(
+        let mut audio_feedback = AudioFeedback::new(
             new_config.audio_feedback,
             assets_dir,
             new_config.start_sound_path.clone(),
             new_config.stop_sound_path.clone(),
             new_config.start_sound_volume,
             new_config.stop_sound_volume,
+            new_config.feedback_sounds.clone(),
         );
+        audio_feedback.set_do_not_disturb(DoNotDisturb::new(&new_config.do_not_disturb));
+
+        let text_injector = TextInjector::from_config(&new_config)?;
+        self.status_writer.set_mode(new_config.dictation_mode);
+        self.dictation_mode = new_config.dictation_mode;
+        self.voice_commands = VoiceCommands::new(&new_config.voice_commands);
 
-        let text_injector = TextInjector::new(
-            new_config.shift_paste,
-            new_config.paste_hints.shift.clone(),
-            new_config.word_overrides.clone(),
-            new_config.auto_copy_clipboard,
-        )?;
+        self.audio_capture
+            .set_device(new_config.audio_device.clone());
+        self.audio_capture
+            .set_low_memory(new_config.performance.low_memory);
+        self.audio_capture
+            .set_pre_roll(Duration::from_millis(new_config.pre_roll_ms));
 
         let transcriber_changed =
             TranscriptionBackend::needs_refresh(&self.current_config, &new_config);
@@ -138,6 +222,16 @@ impl HyprwhsprAppTest {
                 backend.provider().label()
             );
             self.transcriber = backend;
+            self.status_writer
+                .set_backend(self.transcriber.provider().label());
+        }
+        if new_config.transcription != self.current_config.transcription {
+            self.short_transcriber = build_short_transcriber(&self.config_manager, &new_config);
+            self.retry_transcriber = build_retry_transcriber(&self.config_manager, &new_config);
+        }
+
+        if new_config.transcription.prompt_boost != self.current_config.transcription.prompt_boost {
+            self.prompt_booster = PromptBooster::new(&new_config.transcription.prompt_boost);
         }
 
         let fast_vad_was_allowed = fast_vad_allowed(&self.current_config);
@@ -159,11 +253,14 @@ impl HyprwhsprAppTest {
             self.fast_vad = None;
         } else if !fast_vad_was_allowed
             || self.current_config.fast_vad != new_config.fast_vad
+            || self.current_config.audio_device != new_config.audio_device
             || self.fast_vad.is_none()
         {
-            self.fast_vad =
-                FastVad::maybe_new(&new_config.fast_vad, self.audio_capture.sample_rate_hint())
-                    .context("Failed to refresh fast VAD pipeline")?;
+            self.fast_vad = FastVad::maybe_new(
+                &new_config.fast_vad,
+                FastVad::rate_for_input(self.audio_capture.sample_rate_hint()),
+            )
+            .context("Failed to refresh fast VAD pipeline")?;
             if let Some(vad) = &self.fast_vad {
                 info!(
                     "⚡ Earshot fast VAD enabled (profile: {}, silence timeout: {} ms)",
@@ -173,8 +270,39 @@ impl HyprwhsprAppTest {
             }
         }
 
+        if new_config.status_fifo != self.current_config.status_fifo {
+            self.status_writer
+                .set_fifo(new_config.status_fifo.as_ref().map(PathBuf::from));
+        }
+
+        if new_config.border_indicator != self.current_config.border_indicator {
+            self.status_writer
+                .set_border_indicator(&new_config.border_indicator);
+        }
+
+        if new_config.calendar != self.current_config.calendar {
+            self.calendar = MeetingCalendar::maybe_new(&new_config.calendar);
+        }
+
+        if new_config.history != self.current_config.history {
+            self.history = History::maybe_open(&self.config_manager, &new_config);
+        }
+
+        if new_config.export_targets() != self.current_config.export_targets()
+            || new_config.profile_export_targets() != self.current_config.profile_export_targets()
+        {
+            self.exporter = Exporter::new(
+                new_config.export_targets(),
+                new_config.profile_export_targets(),
+            )?;
+        }
+
         self.text_injector = Arc::new(Mutex::new(text_injector));
         self.audio_feedback = audio_feedback;
+        let changes = new_config.changes_since(&self.current_config);
+        if let Err(err) = self.status_writer.set_config_changes(&changes) {
+            warn!("Failed to record config changes: {err:#}");
+        }
         self.current_config = new_config;
 
         info!("Configuration updated");
@@ -183,12 +311,12 @@ impl HyprwhsprAppTest {
     }
 
     pub async fn toggle_recording(&mut self) -> Result<()> {
-        if self.is_processing {
+        if self.state.is_busy() {
             warn!("Still processing previous recording, please wait");
             return Ok(());
         }
 
-        if self.recording_session.is_some() {
+        if self.state.is_recording() {
             self.stop_recording().await?;
         } else {
             self.start_recording().await?;
@@ -202,12 +330,26 @@ impl HyprwhsprAppTest {
 
         self.audio_feedback.play_start_sound()?;
 
-        let session = self
+        let start_sound = self.audio_feedback.start_sound_window();
+        let overlap = self.current_config.start_sound_overlap;
+        if let (StartSoundOverlap::Delay, Some(window)) = (overlap, start_sound) {
+            tokio::time::sleep(window).await;
+        }
+
+        let mut session = self
             .audio_capture
-            .start_recording()
+            .start_recording(overlap == StartSoundOverlap::Keep || start_sound.is_none())
             .context("Failed to start recording")?;
+        if let (StartSoundOverlap::Trim, Some(window)) = (overlap, start_sound) {
+            session.discard_leading(window);
+        }
 
+        self.state.transition(AppState::Recording {
+            trigger: RecordingTrigger::Stdin,
+        })?;
         self.recording_session = Some(session);
+        self.recording_deadline =
+            RecordingDeadline::new(self.current_config.max_recording(), Instant::now());
 
         self.status_writer.set_recording(true)?;
 
@@ -224,19 +366,73 @@ impl HyprwhsprAppTest {
             .take()
             .context("No active recording session")?;
 
-        self.audio_feedback.play_stop_sound()?;
+        let result = self.finish_recording(session, false).await;
+        self.state.transition(AppState::Idle)?;
+        result
+    }
 
-        self.status_writer.set_recording(false)?;
+    /// When the current recording should be stopped by the limit, if any.
+    pub fn recording_deadline(&self) -> RecordingDeadline {
+        if self.recording_session.is_some() {
+            self.recording_deadline
+        } else {
+            RecordingDeadline::default()
+        }
+    }
+
+    /// Ends a recording that ran for `max_recording_secs` and transcribes
+    /// what it captured.
+    pub async fn stop_at_recording_limit(&mut self) -> Result<()> {
+        self.recording_deadline = RecordingDeadline::default();
+        let Some(session) = self.recording_session.take() else {
+            return Ok(());
+        };
+        warn!(
+            "⏱️  Recording reached max_recording_secs ({}s), stopping",
+            self.current_config.max_recording_secs
+        );
+
+        let result = self.finish_recording(session, true).await;
+        self.state.transition(AppState::Idle)?;
+        result
+    }
 
+    async fn finish_recording(
+        &mut self,
+        session: RecordingSession,
+        limit_reached: bool,
+    ) -> Result<()> {
+        // Close the input stream before the stop sound starts so speakers
+        // near the mic can't bleed it into the tail of the capture.
         let captured_audio = session.stop().context("Failed to stop recording")?;
 
+        if limit_reached {
+            self.audio_feedback.play_limit_sound()?;
+        } else {
+            self.audio_feedback.play_stop_sound()?;
+        }
+
+        self.status_writer.set_recording(false)?;
+
+        if self.current_config.keep_last_recording && !captured_audio.is_empty() {
+            let path = self.config_manager.get_last_recording_path();
+            if let Err(err) = recording::save_last_recording(&path, &captured_audio) {
+                warn!("Failed to keep last recording at {:?}: {:#}", path, err);
+            }
+        }
+
         if !captured_audio.is_empty() {
-            self.is_processing = true;
+            self.state.transition(AppState::Processing {
+                job: ProcessingJob::Recording,
+            })?;
+            self.status_writer.set_processing(true);
             info!("🧠 Processing audio...");
             if let Err(e) = self.process_audio(captured_audio).await {
                 error!("Error processing audio: {}", e);
+                self.audio_feedback
+                    .play_event(FeedbackEvent::TranscriptionFailed);
             }
-            self.is_processing = false;
+            self.status_writer.set_processing(false);
             info!("");
             info!("✅ Ready for next recording (press Enter)");
         } else {
@@ -246,19 +442,28 @@ impl HyprwhsprAppTest {
         Ok(())
     }
 
-    fn preprocess_audio(&mut self, audio_data: CapturedAudio) -> Result<Option<CapturedAudio>> {
+    /// The audio to transcribe, with fast VAD's segment breaks in it.
+    fn preprocess_audio(
+        &mut self,
+        audio_data: CapturedAudio,
+    ) -> Result<Option<(CapturedAudio, Vec<SegmentBreak>)>> {
         let CapturedAudio {
             mut samples,
             mut sample_rate,
         } = audio_data;
 
+        let quality = self.current_config.performance.resample_quality;
+        if let Some(strength) = self.current_config.denoise.strength() {
+            samples = denoise::denoise(&samples, sample_rate, strength, quality);
+        }
+
         if let Some(vad) = self.fast_vad.as_mut() {
             if !FastVad::supports_sample_rate(sample_rate) {
                 warn!(
                     "🎚️ Input sample rate {} Hz unsupported by fast VAD; resampling to 16 kHz (test mode)",
                     sample_rate
                 );
-                samples = resample_audio(&samples, sample_rate, 16_000);
+                samples = resample_audio(&samples, sample_rate, 16_000, quality);
                 sample_rate = 16_000;
             }
 
@@ -275,24 +480,34 @@ impl HyprwhsprAppTest {
                 return Ok(None);
             }
 
-            let FastVadOutcome { trimmed_audio, .. } = outcome;
-
-            return Ok(Some(CapturedAudio {
-                samples: trimmed_audio,
-                sample_rate,
-            }));
+            let FastVadOutcome {
+                trimmed_audio,
+                breaks,
+                ..
+            } = outcome;
+
+            return Ok(Some((
+                CapturedAudio {
+                    samples: trimmed_audio,
+                    sample_rate,
+                },
+                breaks,
+            )));
         }
 
-        Ok(Some(CapturedAudio {
-            samples,
-            sample_rate,
-        }))
+        Ok(Some((
+            CapturedAudio {
+                samples,
+                sample_rate,
+            },
+            Vec::new(),
+        )))
     }
 
     async fn process_audio(&mut self, audio_data: CapturedAudio) -> Result<()> {
         let maybe_audio = self.preprocess_audio(audio_data)?;
 
-        let Some(processed_audio) = maybe_audio else {
+        let Some((processed_audio, breaks)) = maybe_audio else {
             return Ok(());
         };
 
@@ -305,6 +520,12 @@ impl HyprwhsprAppTest {
             samples,
             sample_rate,
         } = processed_audio;
+        let lists = &self.current_config.list_segmentation;
+        let list_starts = if lists.enabled {
+            item_starts(&breaks, sample_rate, lists.min_pause_ms)
+        } else {
+            Vec::new()
+        };
 
         let audio_for_transcription = if sample_rate == 16_000 {
             samples
@@ -313,16 +534,150 @@ impl HyprwhsprAppTest {
                 "Resampling processed audio from {} Hz to 16 kHz for transcription backend (test mode)",
                 sample_rate
             );
-            resample_audio(&samples, sample_rate, 16_000)
+            resample_audio(
+                &samples,
+                sample_rate,
+                16_000,
+                self.current_config.performance.resample_quality,
+            )
         };
 
+        let meeting = self
+            .calendar
+            .as_ref()
+            .and_then(MeetingCalendar::current_meeting);
+        if let Some(title) = &meeting {
+            info!("📅 Dictating during \"{}\"", title);
+        }
+        self.status_writer.set_meeting(meeting.clone());
+        let meeting_profile = self
+            .calendar
+            .as_ref()
+            .zip(meeting.as_deref())
+            .and_then(|(calendar, title)| calendar.profile_for(title))
+            .map(str::to_string);
+        if let Some(name) = &meeting_profile {
+            info!("📅 Using the \"{}\" profile for this meeting", name);
+        }
+        self.text_injector
+            .lock()
+            .await
+            .set_profile_override(meeting_profile.clone());
+        let app_class = focused_window_class().await;
+        let profile_prompt = profile_for(
+            &self.current_config.profiles,
+            meeting_profile.as_deref().or(app_class.as_deref()),
+        )
+        .and_then(|profile| profile.whisper_prompt.clone());
+        let utterance = utterance_key();
+        let audio_duration =
+            Duration::from_secs_f32(audio_for_transcription.len() as f32 / 16_000.0);
+        let short_command = self
+            .current_config
+            .transcription
+            .short_command
+            .applies_to(audio_duration);
+        let transcriber = match &self.short_transcriber {
+            Some(backend) if short_command => backend,
+            _ => &self.transcriber,
+        };
+        // The meeting title tends to name the people and topics being said,
+        // but it only leaves the machine when allowed to.
+        let hinted_meeting = meeting
+            .clone()
+            .filter(|_| transcriber.is_local() || self.current_config.calendar.send_to_remote);
+        let vocabulary = match (self.prompt_booster.vocabulary(), hinted_meeting) {
+            (Some(terms), Some(title)) => Some(format!("{terms}, {title}")),
+            (terms, title) => terms.or(title),
+        };
+        let context = PromptContext {
+            vocabulary: vocabulary.as_deref(),
+            app_class: app_class.as_deref(),
+            prev_transcript: self.last_transcript.as_deref(),
+            prompt_override: profile_prompt.as_deref(),
+            append_vocabulary: true,
+            utterance_key: Some(&utterance),
+            short_command,
+        };
+        if short_command {
+            debug!(
+                "⚡ Short command ({:.1}s), taking the fast path",
+                audio_duration.as_secs_f32()
+            );
+        }
+        let list_starts = if short_command {
+            Vec::new()
+        } else {
+            list_starts
+        };
+        if !list_starts.is_empty() {
+            info!("📋 Dictated list of {} items", list_starts.len() + 1);
+        }
+        let mut provider = transcriber.provider();
+        let request_timeout = self
+            .current_config
+            .transcription
+            .request_timeout_secs
+            .max(5);
+        let timeout = (provider != TranscriptionProvider::WhisperCpp)
+            .then(|| Duration::from_secs(request_timeout));
+        let progress = self.status_writer.start_progress(
+            provider.label(),
+            audio_duration,
+            self.duration_estimator.expected(&provider, audio_duration),
+            timeout,
+        );
+        let transcribe_start = Instant::now();
+        let retry_audio = self
+            .retry_transcriber
+            .as_ref()
+            .map(|_| audio_for_transcription.clone());
+        let result = transcriber
+            .transcribe_list(
+                audio_for_transcription,
+                &list_starts,
+                self.current_config.list_segmentation.style,
+                &context,
+            )
+            .await;
+        drop(progress);
+        let mut result = result?;
+        self.duration_estimator
+            .observe(&provider, audio_duration, transcribe_start.elapsed());
+        if let (Some(retry), Some(audio)) = (&self.retry_transcriber, retry_audio) {
+            if let Some(second) = retry
+                .second_opinion(
+                    &result,
+                    audio,
+                    &list_starts,
+                    self.current_config.list_segmentation.style,
+                    &context,
+                    &self.current_config.transcription.retry_low_confidence,
+                )
+                .await
+            {
+                result = second;
+                provider = retry.provider();
+            }
+        }
         let TranscriptionResult {
             text: transcription,
-            ..
-        } = self.transcriber.transcribe(audio_for_transcription).await?;
+            segments,
+            metrics,
+        } = result;
+        if metrics.fallback_used {
+            self.audio_feedback
+                .play_event(FeedbackEvent::ProviderFallback);
+        }
+        self.prompt_booster.observe(&transcription);
+        if !transcription.trim().is_empty() {
+            self.last_transcript = Some(transcription.clone());
+            self.status_writer.record_transcript(&transcription);
+        }
 
         if transcription.trim().is_empty() {
             warn!("Empty transcription - Whisper couldn't understand the audio");
+            self.audio_feedback.play_event(FeedbackEvent::EmptyResult);
             return Ok(());
         }
 
@@ -331,8 +686,78 @@ impl HyprwhsprAppTest {
         let text_injector = Arc::clone(&self.text_injector);
         let mut injector = text_injector.lock().await;
 
+        if session::is_stats_phrase(&transcription, &self.current_config.session_stats.phrase) {
+            let stats = self
+                .session
+                .render(&self.current_config.session_stats.template);
+            if let Some(method) = injector.paste_verbatim(&stats).await? {
+                info!("📊 Inserted session statistics via {}: {}", method, stats);
+            }
+            return Ok(());
+        }
+
+        // Test mode has no wake word, so "stop dictation" is typed.
+        if let Some(action) = self
+            .voice_commands
+            .parse(&transcription)
+            .filter(|action| *action != VoiceAction::StopDictation)
+        {
+            info!("🗣️  Voice command: {:?}", action);
+            let mode = match action {
+                VoiceAction::Undo => return injector.erase_previous_injection().await,
+                VoiceAction::CycleMode => Some(self.dictation_mode.next()),
+                _ => action.mode(),
+            };
+            if let Some(mode) = mode {
+                self.dictation_mode = mode;
+                injector.set_mode(mode);
+                self.status_writer.set_mode(mode);
+                info!("📝 Dictation mode: {}", mode);
+            }
+            return Ok(());
+        }
+
+        let dual_output = &self.current_config.dual_output;
+        let pasted = if dual_output.enabled && !short_command {
+            summary::summarize(&transcription, dual_output).await
+        } else {
+            transcription.clone()
+        };
+
+        self.state.transition(AppState::Injecting)?;
         info!("⌨️  Injecting text into active application...");
-        injector.inject_text(&transcription).await?;
+        let report = injector.inject_text(&pasted).await?;
+        self.session.record(&transcription, audio_duration);
+        self.audio_feedback
+            .play_event(FeedbackEvent::TranscriptionComplete);
+        if let Some(history) = &self.history {
+            let injected = report
+                .as_ref()
+                .map_or(pasted.as_str(), |report| &report.text);
+            history.record(
+                &transcription,
+                injected,
+                provider.label(),
+                audio_duration,
+                meeting.clone(),
+                &segments,
+            );
+        }
+        self.exporter.export(
+            ExportEntry::new(
+                &transcription,
+                provider.label(),
+                audio_duration,
+                meeting,
+                segments,
+            ),
+            meeting_profile.or(app_class),
+        );
+        if let Some(report) = report {
+            if let Err(err) = self.status_writer.record_injection(&report) {
+                warn!("Failed to publish injection status: {err:#}");
+            }
+        }
         info!("✅ Text injected successfully!");
 
         Ok(())
@@ -345,59 +770,42 @@ impl HyprwhsprAppTest {
             self.status_writer.set_recording(false)?;
             self.recording_session = None;
         }
+        if !self.state.is_idle() {
+            self.state.transition(AppState::Idle)?;
+        }
 
         info!("✅ Cleanup completed");
         Ok(())
     }
 }
 
-fn resample_audio(samples: &[f32], src_rate: u32, dst_rate: u32) -> Vec<f32> {
-    if samples.is_empty() || src_rate == 0 || dst_rate == 0 {
-        return Vec::new();
-    }
-    if src_rate == dst_rate {
-        return samples.to_vec();
-    }
-
-    let src_len = samples.len();
-    if src_len == 0 {
-        return Vec::new();
-    }
-
-    let output_len = ((src_len as u64 * dst_rate as u64) + (src_rate as u64 / 2)) / src_rate as u64;
-    if output_len == 0 {
-        return Vec::new();
-    }
-
-    let mut output = Vec::with_capacity(output_len as usize);
-    let rate_ratio = src_rate as f64 / dst_rate as f64;
-    let last_index = src_len.saturating_sub(1);
-
-    for n in 0..output_len as usize {
-        let src_pos = n as f64 * rate_ratio;
-        let idx = src_pos.floor() as usize;
-        let frac = src_pos - idx as f64;
-        let left = samples[idx.min(last_index)];
-        let right = samples[(idx + 1).min(last_index)];
-        let value = left + (right - left) * frac as f32;
-        output.push(value);
-    }
+fn build_short_transcriber(
+    config_manager: &ConfigManager,
+    config: &Config,
+) -> Option<TranscriptionBackend> {
+    let vad = build_vad_options(config_manager, config);
+    TranscriptionBackend::build_short_command(config_manager, config, vad)
+        .inspect_err(|err| warn!("Short commands will use the main backend: {err:#}"))
+        .ok()
+        .flatten()
+}
 
-    output
+fn build_retry_transcriber(
+    config_manager: &ConfigManager,
+    config: &Config,
+) -> Option<TranscriptionBackend> {
+    let vad = build_vad_options(config_manager, config);
+    TranscriptionBackend::build_retry(config_manager, config, vad)
+        .inspect_err(|err| warn!("Low-confidence retries are off: {err:#}"))
+        .ok()
+        .flatten()
 }
 
 fn build_vad_options(config_manager: &ConfigManager, config: &Config) -> WhisperVadOptions {
-    let whisper_vad = &config.transcription.whisper_cpp.vad;
-    WhisperVadOptions {
-        enabled: whisper_vad.enabled,
-        model_path: config_manager.get_vad_model_path(config),
-        threshold: whisper_vad.threshold,
-        min_speech_ms: whisper_vad.min_speech_ms,
-        min_silence_ms: whisper_vad.min_silence_ms,
-        max_speech_s: whisper_vad.max_speech_s,
-        speech_pad_ms: whisper_vad.speech_pad_ms,
-        samples_overlap: whisper_vad.samples_overlap,
-    }
+    WhisperVadOptions::from_config(
+        &config.transcription.whisper_cpp.vad,
+        config_manager.get_vad_model_path(config),
+    )
 }
 
 fn fast_vad_allowed(config: &Config) -> bool {
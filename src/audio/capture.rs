@@ -1,19 +1,113 @@
+use crate::audio::probe;
+use crate::audio::source::SourceStream;
+use crate::config::AudioDeviceSelector;
 use anyhow::{Context, Result};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{BufferSize, InputCallbackInfo, SampleRate, StreamConfig};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tracing::{debug, error, info, warn};
 
+/// Silence from the stream (no callbacks at all) for this long means the
+/// device went away or the audio server restarted.
+const STALL_TIMEOUT: Duration = Duration::from_millis(1500);
+/// Rebuild attempts per recording before giving up on the watchdog.
+const MAX_STREAM_RESTARTS: u32 = 5;
+/// whisper.cpp's native rate; requested whenever the device offers it.
+const PREFERRED_SAMPLE_RATE: u32 = 16_000;
+
+/// Seconds of audio the capture buffer grows by in low-memory mode.
+const LOW_MEMORY_GROWTH_SECS: usize = 5;
+
 pub struct AudioCapture {
+    /// Rate streams are opened at for the selected device.
     sample_rate: u32,
+    /// Configured input device; `None` follows the system default.
+    device: Option<AudioDeviceSelector>,
+    /// Winner of the startup probe, used while `device` is unset.
+    probed: Option<AudioDeviceSelector>,
+    /// Echo-cancelled source the microphone is recorded from while `device`
+    /// is unset.
+    source: Option<String>,
+    low_memory: bool,
+    pre_roll_duration: Duration,
+    pre_roll: Option<PreRoll>,
+}
+
+/// The last moments of input, kept by an always-open stream so speech that
+/// starts with the shortcut press isn't clipped.
+struct PreRoll {
+    _stream: InputStream,
+    ring: Arc<Mutex<VecDeque<f32>>>,
+}
+
+/// An open input, kept for as long as it should capture.
+pub enum InputStream {
+    /// An ALSA device through cpal.
+    Device(cpal::Stream),
+    /// A named sound-server source (see [`crate::audio::source`]).
+    Source(SourceStream),
 }
 
 pub struct RecordingSession {
-    stream: cpal::Stream,
+    started: Instant,
+    stream: InputStream,
+    config: StreamConfig,
+    device: Option<AudioDeviceSelector>,
     audio_data: Arc<Mutex<Vec<f32>>>,
     sample_rate_tracker: Arc<Mutex<SampleRateTracker>>,
     requested_sample_rate: u32,
+    health: Arc<StreamHealth>,
+    restarts: u32,
+    /// Leading audio dropped on stop (the start sound's overlap).
+    discard_leading: Duration,
+    /// Samples to grow the buffer by at a time; `None` lets `Vec` double.
+    growth_step: Option<usize>,
+    /// Source the stream (and any rebuild of it) records instead of the
+    /// input device: a monitor, or the echo-cancelled microphone.
+    source: Option<String>,
+    /// A second recording running alongside this one.
+    companion: Option<Box<RecordingSession>>,
+}
+
+/// Liveness signals written from the cpal callbacks.
+struct StreamHealth {
+    started: Instant,
+    /// Milliseconds after `started` of the latest data callback.
+    last_callback_ms: AtomicU64,
+    failed: AtomicBool,
+}
+
+impl StreamHealth {
+    fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            last_callback_ms: AtomicU64::new(0),
+            failed: AtomicBool::new(false),
+        }
+    }
+
+    fn mark_callback(&self) {
+        let elapsed = self.started.elapsed().as_millis() as u64;
+        self.last_callback_ms.store(elapsed, Ordering::Relaxed);
+    }
+
+    fn stalled_for(&self) -> Duration {
+        let last = Duration::from_millis(self.last_callback_ms.load(Ordering::Relaxed));
+        self.started.elapsed().saturating_sub(last)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InputDeviceInfo {
+    pub index: usize,
+    pub name: String,
+    /// Native rate and channel count, when the device reports them.
+    pub sample_rate: Option<u32>,
+    pub channels: Option<u16>,
+    pub is_default: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -32,149 +126,679 @@ impl CapturedAudio {
     }
 }
 
+/// Measures the device's true sample rate from callback timestamps over the
+/// whole recording. Cheap USB mics run a little off their nominal rate;
+/// treating the capture as exactly nominal time-stretches multi-minute
+/// dictations, so the measured rate is reported and resampled from instead.
 #[derive(Debug)]
 struct SampleRateTracker {
     requested: u32,
     channels: u16,
-    last_capture: Option<cpal::StreamInstant>,
-    accumulated_frames: u64,
-    accumulated_duration: Duration,
-    measured: Option<u32>,
+    first_capture: Option<cpal::StreamInstant>,
+    /// Frames delivered since `first_capture`, excluding the latest callback.
+    frames_before_latest: u64,
+    elapsed: Duration,
+    /// Totals from streams replaced by the watchdog.
+    settled_frames: u64,
+    settled_elapsed: Duration,
+    pending_frames: u64,
 }
 
+/// Measurements shorter than this are too noisy to trust.
+const MIN_DRIFT_WINDOW: Duration = Duration::from_secs(1);
+/// Larger deviations mean broken timestamps rather than clock drift.
+const MAX_DRIFT_RATIO: f64 = 0.02;
+
 impl SampleRateTracker {
     fn new(requested: u32, channels: u16) -> Self {
         Self {
             requested,
             channels,
-            last_capture: None,
-            accumulated_frames: 0,
-            accumulated_duration: Duration::ZERO,
-            measured: None,
+            first_capture: None,
+            frames_before_latest: 0,
+            elapsed: Duration::ZERO,
+            settled_frames: 0,
+            settled_elapsed: Duration::ZERO,
+            pending_frames: 0,
         }
     }
 
     fn update(&mut self, data_len: usize, info: &InputCallbackInfo) {
         let capture = info.timestamp().capture;
-
-        if let Some(prev) = self.last_capture {
-            if let Some(delta) = capture.duration_since(&prev) {
-                let channel_count = self.channels.max(1) as usize;
-                let frames = data_len / channel_count;
-                self.accumulated_frames += frames as u64;
-                self.accumulated_duration += delta;
-
-                if self.accumulated_duration.as_secs_f64() >= 0.05 {
-                    let secs = self.accumulated_duration.as_secs_f64();
-                    if secs > 0.0 && self.accumulated_frames > 0 {
-                        let rate = (self.accumulated_frames as f64 / secs).round() as u32;
-                        self.measured = Some(rate);
-                    }
-                    self.accumulated_frames = 0;
-                    self.accumulated_duration = Duration::ZERO;
+        let frames = (data_len / self.channels.max(1) as usize) as u64;
+
+        match self.first_capture {
+            None => self.first_capture = Some(capture),
+            Some(first) => {
+                if let Some(elapsed) = capture.duration_since(&first) {
+                    // Everything delivered before this buffer was captured
+                    // within `elapsed`.
+                    self.frames_before_latest += self.pending_frames;
+                    self.elapsed = elapsed;
+                    self.pending_frames = 0;
                 }
             }
         }
+        self.pending_frames += frames;
+    }
 
-        self.last_capture = Some(capture);
+    /// Folds the current stream's measurement into the totals; a rebuilt
+    /// stream's clock isn't comparable with the old one.
+    fn reset_timing(&mut self) {
+        self.settled_frames += self.frames_before_latest;
+        self.settled_elapsed += self.elapsed;
+        self.first_capture = None;
+        self.frames_before_latest = 0;
+        self.elapsed = Duration::ZERO;
+        self.pending_frames = 0;
     }
 
     fn sample_rate(&self) -> u32 {
-        self.measured.unwrap_or(self.requested)
+        let frames = self.settled_frames + self.frames_before_latest;
+        let elapsed = self.settled_elapsed + self.elapsed;
+        if elapsed < MIN_DRIFT_WINDOW || frames == 0 {
+            return self.requested;
+        }
+
+        let measured = frames as f64 / elapsed.as_secs_f64();
+        let nominal = f64::from(self.requested);
+        if ((measured - nominal) / nominal).abs() > MAX_DRIFT_RATIO {
+            debug!(
+                "Ignoring implausible measured sample rate {:.1} Hz (nominal {} Hz)",
+                measured, self.requested
+            );
+            return self.requested;
+        }
+
+        measured.round() as u32
     }
 }
 
 impl AudioCapture {
-    pub fn new() -> Result<Self> {
-        let host = cpal::default_host();
-        let device = host
-            .default_input_device()
-            .context("No input device available")?;
+    pub fn new(device: Option<AudioDeviceSelector>) -> Result<Self> {
+        let input = resolve_input_device(device.as_ref())?;
+        let device_name = input.name().unwrap_or_else(|_| "Unknown".to_string());
+        let sample_rate = stream_sample_rate(&input);
 
-        let device_name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+        info!(
+            "Using audio input device: {} ({} Hz)",
+            device_name, sample_rate
+        );
 
-        info!("Using audio input device: {}", device_name);
+        Ok(Self {
+            sample_rate,
+            device,
+            probed: None,
+            source: None,
+            low_memory: false,
+            pre_roll_duration: Duration::ZERO,
+            pre_roll: None,
+        })
+    }
 
-        Ok(Self { sample_rate: 16000 })
+    /// Keeps the input open with the last `duration` of it buffered, for
+    /// recordings to start with; zero closes it.
+    pub fn set_pre_roll(&mut self, duration: Duration) {
+        if self.pre_roll_duration == duration {
+            return;
+        }
+        self.pre_roll_duration = duration;
+        self.reopen_pre_roll();
     }
 
-    pub fn sample_rate_hint(&self) -> u32 {
-        self.sample_rate
+    fn reopen_pre_roll(&mut self) {
+        self.pre_roll = None;
+        if self.pre_roll_duration.is_zero() {
+            return;
+        }
+        match open_pre_roll(
+            &self.stream_config(),
+            self.selected_device(),
+            self.mic_source(),
+            self.pre_roll_duration,
+        ) {
+            Ok(pre_roll) => {
+                debug!(
+                    "Buffering {} ms of pre-roll audio",
+                    self.pre_roll_duration.as_millis()
+                );
+                self.pre_roll = Some(pre_roll);
+            }
+            Err(err) => warn!("Recording without pre-roll: {:#}", err),
+        }
     }
 
-    pub fn start_recording(&self) -> Result<RecordingSession> {
+    /// Records the microphone from `source` (echo cancellation's) instead of
+    /// the default input device; a configured `audio_device` still wins.
+    pub fn set_source(&mut self, source: Option<String>) {
+        if self.source == source {
+            return;
+        }
+        self.source = source;
+        self.reopen_pre_roll();
+    }
+
+    /// Grows recordings a few seconds at a time rather than doubling, so a
+    /// long dictation doesn't briefly hold twice its size.
+    pub fn set_low_memory(&mut self, low_memory: bool) {
+        self.low_memory = low_memory;
+    }
+
+    /// Re-reads the stream rate after the selected device changed.
+    fn refresh_sample_rate(&mut self) {
+        match resolve_input_device(self.selected_device()) {
+            Ok(input) => {
+                self.sample_rate = stream_sample_rate(&input);
+                debug!("Audio input runs at {} Hz", self.sample_rate);
+            }
+            Err(err) => warn!("Keeping {} Hz input rate: {:#}", self.sample_rate, err),
+        }
+        self.reopen_pre_roll();
+    }
+
+    /// Switches the input device used by the next recording; a recording in
+    /// progress keeps its stream.
+    pub fn set_device(&mut self, device: Option<AudioDeviceSelector>) {
+        if self.device == device {
+            return;
+        }
+
+        match &device {
+            Some(selector) => info!("🎙️ Audio input device changed to {}", selector),
+            None => info!("🎙️ Audio input device changed to system default"),
+        }
+        self.device = device;
+        self.refresh_sample_rate();
+    }
+
+    /// Records `duration` from every input device and remembers the one with
+    /// the best signal-to-noise ratio. Blocks for the length of all probes.
+    pub fn probe_best_device(&mut self, duration: Duration) -> Result<()> {
         let host = cpal::default_host();
-        let device = host
-            .default_input_device()
-            .context("No input device available")?;
+        let devices: Vec<cpal::Device> = host
+            .input_devices()
+            .context("Failed to enumerate input devices")?
+            .collect();
+        if devices.len() < 2 {
+            return Ok(());
+        }
 
-        // Configure for 16kHz mono (whisper.cpp prefers this)
         let config = StreamConfig {
             channels: 1,
             sample_rate: SampleRate(self.sample_rate),
             buffer_size: BufferSize::Default,
         };
 
+        let mut names = Vec::with_capacity(devices.len());
+        let mut scores = Vec::with_capacity(devices.len());
+        for device in &devices {
+            let name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+            let score = record_probe(device, &config, duration)
+                .inspect_err(|err| debug!("Skipping {} in device probe: {}", name, err))
+                .ok()
+                .and_then(|samples| probe::snr_db(&samples));
+            match score {
+                Some(snr) => debug!("Device probe: {} scored {:.1} dB", name, snr),
+                None => debug!("Device probe: {} delivered no usable audio", name),
+            }
+            names.push(name);
+            scores.push(score);
+        }
+
+        match probe::best(&scores) {
+            Some(best) => {
+                info!("🎙️ Device probe picked {}", names[best]);
+                self.probed = Some(AudioDeviceSelector::Name(names.swap_remove(best)));
+                self.refresh_sample_rate();
+            }
+            None => warn!("Device probe found no usable input; keeping the system default"),
+        }
+        Ok(())
+    }
+
+    fn selected_device(&self) -> Option<&AudioDeviceSelector> {
+        self.device.as_ref().or(self.probed.as_ref())
+    }
+
+    fn mic_source(&self) -> Option<&str> {
+        self.source.as_deref().filter(|_| self.device.is_none())
+    }
+
+    /// Rate the next recording will be captured at: 16 kHz when the device
+    /// supports it, otherwise its default input rate.
+    pub fn sample_rate_hint(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Mono, at 16kHz when the device allows (whisper.cpp prefers this).
+    fn stream_config(&self) -> StreamConfig {
+        StreamConfig {
+            channels: 1,
+            sample_rate: SampleRate(self.sample_rate),
+            buffer_size: BufferSize::Default,
+        }
+    }
+
+    /// Opens a recording. With `pre_roll`, it starts with the buffered audio
+    /// from just before this call (see [`Self::set_pre_roll`]).
+    pub fn start_recording(&self, pre_roll: bool) -> Result<RecordingSession> {
+        let pre_roll = self
+            .pre_roll
+            .as_ref()
+            .filter(|_| pre_roll)
+            .map(|pre_roll| Arc::clone(&pre_roll.ring));
+        let source = self.mic_source().map(str::to_string);
+        self.open_session(self.selected_device().cloned(), pre_roll, source)
+    }
+
+    /// Opens a recording of `source`, a monitor of system output (see
+    /// [`crate::audio::monitor`]), through `parec`. Pre-roll and a
+    /// configured `audio_device` belong to the microphone, so neither
+    /// applies.
+    pub fn start_monitor_recording(&self, source: &str) -> Result<RecordingSession> {
+        self.open_session(None, None, Some(source.to_string()))
+    }
+
+    /// Opens a microphone recording with a recording of `source` running
+    /// alongside it, collected by [`RecordingSession::stop_with_companion`].
+    /// Pre-roll would put the microphone ahead of the monitor, so it is
+    /// left out.
+    pub fn start_mixed_recording(&self, source: &str) -> Result<RecordingSession> {
+        let mic_source = self.mic_source().map(str::to_string);
+        let mut session = self.open_session(self.selected_device().cloned(), None, mic_source)?;
+        let companion = self
+            .start_monitor_recording(source)
+            .context("Failed to record the monitor source")?;
+        session.companion = Some(Box::new(companion));
+        Ok(session)
+    }
+
+    fn open_session(
+        &self,
+        device: Option<AudioDeviceSelector>,
+        pre_roll: Option<Arc<Mutex<VecDeque<f32>>>>,
+        source: Option<String>,
+    ) -> Result<RecordingSession> {
+        let config = self.stream_config();
+
         debug!("Starting audio capture at {}Hz mono", self.sample_rate);
 
         // Shared buffer for audio data
         let audio_data = Arc::new(Mutex::new(Vec::new()));
-        let audio_data_clone = Arc::clone(&audio_data);
         let sample_rate_tracker = Arc::new(Mutex::new(SampleRateTracker::new(
             config.sample_rate.0,
             config.channels,
         )));
-        let tracker_clone = Arc::clone(&sample_rate_tracker);
-
-        // Build input stream
-        let stream = device
-            .build_input_stream(
-                &config,
-                move |data: &[f32], info: &InputCallbackInfo| {
-                    if let Ok(mut tracker) = tracker_clone.lock() {
-                        tracker.update(data.len(), info);
-                    }
-                    // Store audio samples
-                    if let Ok(mut buffer) = audio_data_clone.lock() {
-                        buffer.extend_from_slice(data);
-                    }
-                },
-                move |err| {
-                    error!("Audio stream error: {}", err);
-                },
-                None,
-            )
-            .context("Failed to build input stream")?;
-
-        // Start the stream
-        stream.play().context("Failed to start audio stream")?;
-
-        let device_name = device.name().unwrap_or_else(|_| "Unknown".to_string());
-        info!("✅ Audio recording started on {}", device_name);
+        let health = Arc::new(StreamHealth::new());
+        let growth_step = self
+            .low_memory
+            .then(|| self.sample_rate as usize * LOW_MEMORY_GROWTH_SECS);
+
+        let stream = build_stream(
+            &config,
+            Input::new(device.as_ref(), source.as_deref()),
+            &audio_data,
+            &sample_rate_tracker,
+            &health,
+            growth_step,
+            pre_roll,
+        )?;
 
         Ok(RecordingSession {
+            started: Instant::now(),
             stream,
+            config,
+            device,
             audio_data,
             sample_rate_tracker,
-            requested_sample_rate: config.sample_rate.0,
+            requested_sample_rate: self.sample_rate,
+            health,
+            restarts: 0,
+            discard_leading: Duration::ZERO,
+            growth_step,
+            source,
+            companion: None,
         })
     }
 
-    pub fn get_available_devices() -> Result<Vec<String>> {
+    /// Opens another stream on the microphone that hands every buffer to
+    /// `sink` until it is dropped.
+    pub fn listen(&self, mut sink: impl FnMut(&[f32]) + Send + 'static) -> Result<InputStream> {
+        let (stream, _) = open_input(
+            &self.stream_config(),
+            Input::new(self.selected_device(), self.mic_source()),
+            "listening stream",
+            move |data, _| sink(data),
+            || {},
+        )?;
+        Ok(stream)
+    }
+
+    /// Input devices in the order `audio_device` indices refer to.
+    pub fn get_available_devices() -> Result<Vec<InputDeviceInfo>> {
         let host = cpal::default_host();
+        let default_name = host
+            .default_input_device()
+            .and_then(|device| device.name().ok());
         let mut devices = Vec::new();
 
-        for device in host.input_devices()? {
-            if let Ok(name) = device.name() {
-                devices.push(name);
-            }
+        for (index, device) in host.input_devices()?.enumerate() {
+            let name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+            let default_config = device.default_input_config().ok();
+            devices.push(InputDeviceInfo {
+                index,
+                is_default: default_name.as_deref() == Some(name.as_str()),
+                name,
+                sample_rate: default_config.as_ref().map(|config| config.sample_rate().0),
+                channels: default_config.as_ref().map(|config| config.channels()),
+            });
         }
 
         Ok(devices)
     }
 }
 
+fn open_pre_roll(
+    config: &StreamConfig,
+    device: Option<&AudioDeviceSelector>,
+    source: Option<&str>,
+    duration: Duration,
+) -> Result<PreRoll> {
+    let capacity = (duration.as_secs_f64() * f64::from(config.sample_rate.0)) as usize;
+    let ring = Arc::new(Mutex::new(VecDeque::with_capacity(capacity)));
+    let sink = Arc::clone(&ring);
+    let (stream, _) = open_input(
+        config,
+        Input::new(device, source),
+        "pre-roll stream",
+        move |data, _| {
+            if let Ok(mut ring) = sink.lock() {
+                ring.extend(data);
+                let excess = ring.len().saturating_sub(capacity);
+                ring.drain(..excess);
+            }
+        },
+        || {},
+    )?;
+    Ok(PreRoll {
+        _stream: stream,
+        ring,
+    })
+}
+
+fn record_probe(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    duration: Duration,
+) -> Result<Vec<f32>> {
+    let samples = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&samples);
+    let stream = device.build_input_stream(
+        config,
+        move |data: &[f32], _: &InputCallbackInfo| {
+            if let Ok(mut buffer) = sink.lock() {
+                buffer.extend_from_slice(data);
+            }
+        },
+        |err| debug!("Device probe stream error: {}", err),
+        None,
+    )?;
+    stream.play()?;
+    std::thread::sleep(duration);
+    drop(stream);
+
+    let samples = samples
+        .lock()
+        .map(|buffer| buffer.clone())
+        .unwrap_or_default();
+    Ok(samples)
+}
+
+/// Finds the configured input device, falling back to the system default
+/// when it isn't connected.
+fn resolve_input_device(selector: Option<&AudioDeviceSelector>) -> Result<cpal::Device> {
+    let host = cpal::default_host();
+
+    if let Some(selector) = selector {
+        let devices: Vec<cpal::Device> = host
+            .input_devices()
+            .map(|devices| devices.collect())
+            .unwrap_or_default();
+
+        let found = match selector {
+            AudioDeviceSelector::Index(index) => devices.into_iter().nth(*index),
+            AudioDeviceSelector::Name(pattern) => {
+                let pattern = pattern.to_lowercase();
+                devices.into_iter().find(|device| {
+                    device
+                        .name()
+                        .is_ok_and(|name| name.to_lowercase().contains(&pattern))
+                })
+            }
+        };
+
+        match found {
+            Some(device) => return Ok(device),
+            None => warn!(
+                "Configured audio device {} not found; falling back to system default",
+                selector
+            ),
+        }
+    }
+
+    host.default_input_device()
+        .context("No input device available")
+}
+
+/// 16 kHz if any supported input config covers it, else the device's
+/// default input rate.
+fn stream_sample_rate(device: &cpal::Device) -> u32 {
+    let supports_preferred = device.supported_input_configs().is_ok_and(|mut configs| {
+        configs.any(|config| {
+            (config.min_sample_rate().0..=config.max_sample_rate().0)
+                .contains(&PREFERRED_SAMPLE_RATE)
+        })
+    });
+    if supports_preferred {
+        return PREFERRED_SAMPLE_RATE;
+    }
+
+    device
+        .default_input_config()
+        .map(|config| config.sample_rate().0)
+        .unwrap_or(PREFERRED_SAMPLE_RATE)
+}
+
+/// What a stream records: a sound-server source when one is set, else an
+/// input device (`None` for the system default).
+#[derive(Clone, Copy)]
+enum Input<'a> {
+    Device(Option<&'a AudioDeviceSelector>),
+    Source(&'a str),
+}
+
+impl<'a> Input<'a> {
+    fn new(device: Option<&'a AudioDeviceSelector>, source: Option<&'a str>) -> Self {
+        source.map_or(Self::Device(device), Self::Source)
+    }
+}
+
+/// Opens `input`, feeding `sink` until the stream is dropped. `on_lost`
+/// runs when the device disappears or `parec` exits. Also returns what was
+/// opened, for logging.
+fn open_input(
+    config: &StreamConfig,
+    input: Input<'_>,
+    label: &'static str,
+    mut sink: impl FnMut(&[f32], Option<&InputCallbackInfo>) + Send + 'static,
+    mut on_lost: impl FnMut() + Send + 'static,
+) -> Result<(InputStream, String)> {
+    let device = match input {
+        Input::Source(source) => {
+            let stream = SourceStream::open(
+                source,
+                config.sample_rate.0,
+                move |data| sink(data, None),
+                on_lost,
+            )
+            .with_context(|| format!("Failed to open {label} on {source}"))?;
+            return Ok((InputStream::Source(stream), source.to_string()));
+        }
+        Input::Device(device) => resolve_input_device(device)?,
+    };
+    let stream = device
+        .build_input_stream(
+            config,
+            move |data: &[f32], info: &InputCallbackInfo| sink(data, Some(info)),
+            move |err| {
+                error!("Audio {label} error: {}", err);
+                if matches!(err, cpal::StreamError::DeviceNotAvailable) {
+                    on_lost();
+                }
+            },
+            None,
+        )
+        .with_context(|| format!("Failed to build {label}"))?;
+    stream
+        .play()
+        .with_context(|| format!("Failed to start {label}"))?;
+    let name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+    Ok((InputStream::Device(stream), name))
+}
+
+/// Opens the input and streams into `audio_data`. Samples from a rebuilt
+/// stream are appended to the same buffer, so a restart splices into the
+/// capture rather than starting over. The first callback moves the
+/// `pre_roll` ring in ahead of its own samples, so the two join up to
+/// within one device buffer.
+fn build_stream(
+    config: &StreamConfig,
+    input: Input<'_>,
+    audio_data: &Arc<Mutex<Vec<f32>>>,
+    sample_rate_tracker: &Arc<Mutex<SampleRateTracker>>,
+    health: &Arc<StreamHealth>,
+    growth_step: Option<usize>,
+    mut pre_roll: Option<Arc<Mutex<VecDeque<f32>>>>,
+) -> Result<InputStream> {
+    let audio_data = Arc::clone(audio_data);
+    let tracker = Arc::clone(sample_rate_tracker);
+    let data_health = Arc::clone(health);
+    let lost_health = Arc::clone(health);
+
+    let (stream, name) = open_input(
+        config,
+        input,
+        "input stream",
+        move |data, info| {
+            data_health.mark_callback();
+            // `parec` is resampled by the sound server, so only device
+            // streams drift.
+            if let (Some(info), Ok(mut tracker)) = (info, tracker.lock()) {
+                tracker.update(data.len(), info);
+            }
+            // Store audio samples
+            if let Ok(mut buffer) = audio_data.lock() {
+                if let Some(ring) = pre_roll.take() {
+                    if let Ok(mut ring) = ring.lock() {
+                        buffer.extend(ring.drain(..));
+                    }
+                }
+                if let Some(step) = growth_step {
+                    if buffer.capacity() - buffer.len() < data.len() {
+                        buffer.reserve_exact(step.max(data.len()));
+                    }
+                }
+                buffer.extend_from_slice(data);
+            }
+        },
+        move || lost_health.failed.store(true, Ordering::Relaxed),
+    )?;
+
+    info!("✅ Audio recording started on {}", name);
+    Ok(stream)
+}
+
 impl RecordingSession {
+    /// Watchdog check, called periodically while recording. Rebuilds the
+    /// input stream when the device reported itself gone or callbacks
+    /// stopped arriving (unplugged mic, PipeWire restart).
+    pub fn ensure_streaming(&mut self) -> Result<()> {
+        if let Some(companion) = self.companion.as_mut() {
+            companion.ensure_streaming()?;
+        }
+
+        let failed = self.health.failed.load(Ordering::Relaxed);
+        let stalled_for = self.health.stalled_for();
+        if !failed && stalled_for < STALL_TIMEOUT {
+            return Ok(());
+        }
+
+        if self.restarts >= MAX_STREAM_RESTARTS {
+            if self.restarts == MAX_STREAM_RESTARTS {
+                warn!("Audio stream restarted {MAX_STREAM_RESTARTS} times; giving up for this recording");
+                self.restarts += 1;
+            }
+            return Ok(());
+        }
+        self.restarts += 1;
+
+        if failed {
+            warn!("🎙️ Audio device became unavailable; rebuilding input stream");
+        } else {
+            warn!(
+                "🎙️ Audio stream delivered nothing for {:.1}s; rebuilding input stream",
+                stalled_for.as_secs_f32()
+            );
+        }
+
+        let health = Arc::new(StreamHealth::new());
+        let result = build_stream(
+            &self.config,
+            Input::new(self.device.as_ref(), self.source.as_deref()),
+            &self.audio_data,
+            &self.sample_rate_tracker,
+            &health,
+            self.growth_step,
+            None,
+        );
+        // Restart the stall clock either way so failures retry on a cadence.
+        self.health = health;
+        let stream = result.with_context(|| {
+            format!(
+                "Failed to rebuild audio stream (attempt {}/{})",
+                self.restarts, MAX_STREAM_RESTARTS
+            )
+        })?;
+
+        self.stream = stream;
+        if let Ok(mut tracker) = self.sample_rate_tracker.lock() {
+            tracker.reset_timing();
+        }
+        Ok(())
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Drops the first `duration` of the capture when the recording stops.
+    pub fn discard_leading(&mut self, duration: Duration) {
+        self.discard_leading = duration;
+        if let Some(companion) = self.companion.as_mut() {
+            companion.discard_leading(duration);
+        }
+    }
+
+    /// Stops this recording and its companion, if it has one.
+    pub fn stop_with_companion(mut self) -> Result<(CapturedAudio, Option<CapturedAudio>)> {
+        let companion = self
+            .companion
+            .take()
+            .map(|companion| companion.stop())
+            .transpose()?;
+        Ok((self.stop()?, companion))
+    }
+
     pub fn stop(self) -> Result<CapturedAudio> {
         // Drop the stream (stops recording)
         drop(self.stream);
@@ -184,13 +808,30 @@ impl RecordingSession {
             .lock()
             .map(|tracker| tracker.sample_rate())
             .unwrap_or(self.requested_sample_rate);
+        if measured_sample_rate != self.requested_sample_rate {
+            // Reported as-is so processing resamples to exactly 16 kHz.
+            info!(
+                "🕰️ Input clock runs at {} Hz (nominal {} Hz); compensating for drift",
+                measured_sample_rate, self.requested_sample_rate
+            );
+        }
 
         // Extract the recorded audio
-        let audio_data = Arc::try_unwrap(self.audio_data)
+        let mut audio_data = Arc::try_unwrap(self.audio_data)
             .map_err(|_| anyhow::anyhow!("Failed to unwrap audio data"))?
             .into_inner()
             .map_err(|_| anyhow::anyhow!("Failed to lock audio data"))?;
 
+        if !self.discard_leading.is_zero() {
+            let leading =
+                (self.discard_leading.as_secs_f64() * f64::from(measured_sample_rate)) as usize;
+            audio_data.drain(..leading.min(audio_data.len()));
+            debug!(
+                "Dropped {:.0} ms of start-sound overlap",
+                self.discard_leading.as_secs_f64() * 1000.0
+            );
+        }
+
         let duration_secs = if measured_sample_rate > 0 {
             audio_data.len() as f32 / measured_sample_rate as f32
         } else {
@@ -206,6 +847,9 @@ impl RecordingSession {
         if audio_data.is_empty() {
             warn!("No audio data captured");
         }
+        if self.growth_step.is_some() {
+            audio_data.shrink_to_fit();
+        }
 
         Ok(CapturedAudio {
             samples: audio_data,
@@ -214,28 +858,33 @@ impl RecordingSession {
     }
 
     pub fn get_current_level(&self) -> f32 {
-        if let Ok(data) = self.audio_data.lock() {
-            if data.is_empty() {
-                return 0.0;
-            }
+        self.current_levels().0
+    }
+
+    /// RMS and peak of the latest 1024 samples, both scaled to 0.0..=1.0
+    /// for meters.
+    pub fn current_levels(&self) -> (f32, f32) {
+        let Ok(data) = self.audio_data.lock() else {
+            return (0.0, 0.0);
+        };
+        if data.is_empty() {
+            return (0.0, 0.0);
+        }
 
-            // Calculate RMS level for last 1024 samples
-            let start = data.len().saturating_sub(1024);
-            let samples = &data[start..];
+        let start = data.len().saturating_sub(1024);
+        let samples = &data[start..];
 
-            let sum_squares: f32 = samples.iter().map(|s| s * s).sum();
-            let rms = (sum_squares / samples.len() as f32).sqrt();
+        let sum_squares: f32 = samples.iter().map(|s| s * s).sum();
+        let rms = (sum_squares / samples.len() as f32).sqrt();
+        let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
 
-            // Scale for better visualization (0.0 to 1.0)
-            (rms * 10.0).min(1.0)
-        } else {
-            0.0
-        }
+        // Scale RMS for better visualization (0.0 to 1.0)
+        ((rms * 10.0).min(1.0), peak.min(1.0))
     }
 }
 
 impl Default for AudioCapture {
     fn default() -> Self {
-        Self::new().expect("Failed to create AudioCapture")
+        Self::new(None).expect("Failed to create AudioCapture")
     }
 }
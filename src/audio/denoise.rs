@@ -0,0 +1,115 @@
+//! RNNoise noise suppression (through `nnnoiseless`) for finished
+//! recordings, run before fast VAD and transcription so steady noise like
+//! a laptop fan neither passes for speech nor reaches the model. Builds
+//! without the `denoise` feature hand the audio back unchanged.
+
+use crate::config::ResampleQuality;
+
+/// Denoises `samples`, mixing `strength` (0.0..=1.0) of the cleaned signal
+/// with the rest of the original so speech keeps some of its body.
+pub fn denoise(
+    samples: &[f32],
+    sample_rate: u32,
+    strength: f32,
+    quality: ResampleQuality,
+) -> Vec<f32> {
+    let strength = strength.clamp(0.0, 1.0);
+    if samples.is_empty() || strength == 0.0 {
+        return samples.to_vec();
+    }
+    match engine::suppress(samples, sample_rate, quality) {
+        Some(cleaned) => blend(samples, &cleaned, strength),
+        None => samples.to_vec(),
+    }
+}
+
+/// `original` with `strength` of it swapped for `cleaned`; a shorter
+/// `cleaned` leaves the tail untouched.
+fn blend(original: &[f32], cleaned: &[f32], strength: f32) -> Vec<f32> {
+    original
+        .iter()
+        .enumerate()
+        .map(|(index, &dry)| match cleaned.get(index) {
+            Some(&wet) => dry + (wet - dry) * strength,
+            None => dry,
+        })
+        .collect()
+}
+
+#[cfg(feature = "denoise")]
+mod engine {
+    use crate::audio::resample_audio;
+    use crate::config::ResampleQuality;
+    use nnnoiseless::DenoiseState;
+
+    /// RNNoise only runs at 48 kHz.
+    const MODEL_RATE: u32 = 48_000;
+    const FRAME_SIZE: usize = DenoiseState::FRAME_SIZE;
+
+    /// Suppresses noise in `samples`, returning audio at the same rate and
+    /// length.
+    pub fn suppress(
+        samples: &[f32],
+        sample_rate: u32,
+        quality: ResampleQuality,
+    ) -> Option<Vec<f32>> {
+        let input = resample_audio(samples, sample_rate, MODEL_RATE, quality);
+        let mut state = DenoiseState::new();
+        let mut output = Vec::with_capacity(input.len() + FRAME_SIZE);
+        let mut frame_in = [0.0f32; FRAME_SIZE];
+        let mut frame_out = [0.0f32; FRAME_SIZE];
+
+        // One extra silent frame flushes the last of the audio out.
+        for index in 0..=input.len().div_ceil(FRAME_SIZE) {
+            // RNNoise works on 16-bit sample values.
+            frame_in.fill(0.0);
+            let chunk = input.get(index * FRAME_SIZE..).unwrap_or_default();
+            for (slot, &sample) in frame_in.iter_mut().zip(chunk) {
+                *slot = sample * i16::MAX as f32;
+            }
+            state.process_frame(&mut frame_out, &frame_in);
+            // Output lags a frame behind; the first one only holds the
+            // model's fade-in.
+            if index > 0 {
+                output.extend(frame_out.iter().map(|&sample| sample / i16::MAX as f32));
+            }
+        }
+        output.truncate(input.len());
+
+        Some(resample_audio(&output, MODEL_RATE, sample_rate, quality))
+    }
+}
+
+#[cfg(not(feature = "denoise"))]
+mod engine {
+    use crate::config::ResampleQuality;
+    use std::sync::Once;
+    use tracing::warn;
+
+    static WARNED: Once = Once::new();
+
+    pub fn suppress(
+        _samples: &[f32],
+        _sample_rate: u32,
+        _quality: ResampleQuality,
+    ) -> Option<Vec<f32>> {
+        WARNED.call_once(|| {
+            warn!("Noise suppression needs the `denoise` feature; leaving audio as recorded")
+        });
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blends_by_strength() {
+        assert_eq!(blend(&[1.0, 0.5, 0.2], &[0.0, 0.5], 0.75), [0.25, 0.5, 0.2]);
+        assert_eq!(
+            denoise(&[0.3, -0.3], 16_000, 0.0, ResampleQuality::Fast),
+            [0.3, -0.3]
+        );
+    }
+}
@@ -0,0 +1,160 @@
+//! Acoustic echo cancellation through the sound server's
+//! `module-echo-cancel` (WebRTC AEC, loaded with `pactl` on PipeWire or
+//! PulseAudio), so a call playing on speakers isn't transcribed along with
+//! the user. The microphone is then recorded from the cancelled source (see
+//! [`crate::audio::source`]); a fixed `audio_device` bypasses it.
+
+use crate::config::AecConfig;
+use anyhow::{bail, Context, Result};
+use std::process::Command;
+use tracing::{debug, info, warn};
+
+const SOURCE_NAME: &str = "hyprwhspr_aec_source";
+const SINK_NAME: &str = "hyprwhspr_aec_sink";
+
+/// Echo cancellation for the daemon's lifetime; dropping it unloads the
+/// module and gives the previous default output back.
+pub struct EchoCancel {
+    source: String,
+    module: Option<u32>,
+    restore_sink: Option<String>,
+}
+
+impl EchoCancel {
+    /// Loads the module, unless `config.source` names an echo-cancelled
+    /// source set up elsewhere. Capture picks the source up from
+    /// [`Self::source`].
+    pub fn start(config: &AecConfig) -> Result<Self> {
+        if let Some(source) = &config.source {
+            info!("🔇 Echo cancellation: capturing from {}", source);
+            return Ok(Self {
+                source: source.clone(),
+                module: None,
+                restore_sink: None,
+            });
+        }
+
+        unload_stale_modules();
+
+        let output = pactl(&[
+            "load-module",
+            "module-echo-cancel",
+            "aec_method=webrtc",
+            &format!("source_name={SOURCE_NAME}"),
+            &format!("sink_name={SINK_NAME}"),
+        ])
+        .context("Failed to load module-echo-cancel")?;
+        let module = parse_module_index(&output)?;
+        let mut echo_cancel = Self {
+            source: SOURCE_NAME.to_string(),
+            module: Some(module),
+            restore_sink: None,
+        };
+
+        // The module only cancels what plays through its sink.
+        if config.route_playback {
+            match pactl(&["get-default-sink"]) {
+                Ok(previous) => {
+                    pactl(&["set-default-sink", SINK_NAME])
+                        .context("Failed to route playback through the echo-cancel sink")?;
+                    echo_cancel.restore_sink = Some(previous.trim().to_string());
+                }
+                Err(err) => warn!("Leaving playback routing alone: {:#}", err),
+            }
+        }
+
+        info!("🔇 Echo cancellation on (module #{module})");
+        Ok(echo_cancel)
+    }
+
+    /// The echo-cancelled source to record the microphone from.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+}
+
+impl Drop for EchoCancel {
+    fn drop(&mut self) {
+        if let Some(sink) = self.restore_sink.take() {
+            if let Err(err) = pactl(&["set-default-sink", &sink]) {
+                warn!("Failed to restore default output {}: {:#}", sink, err);
+            }
+        }
+        if let Some(module) = self.module.take() {
+            match pactl(&["unload-module", &module.to_string()]) {
+                Ok(_) => debug!("Unloaded echo-cancel module #{module}"),
+                Err(err) => warn!("Failed to unload echo-cancel module #{module}: {:#}", err),
+            }
+        }
+    }
+}
+
+/// Unloads modules a killed daemon left behind. The sound server moves the
+/// default output off their sink as they go.
+fn unload_stale_modules() {
+    let Ok(modules) = pactl(&["list", "short", "modules"]) else {
+        return;
+    };
+    for module in stale_modules(&modules) {
+        match pactl(&["unload-module", &module.to_string()]) {
+            Ok(_) => info!("Unloaded echo-cancel module #{module} left by a previous run"),
+            Err(err) => warn!(
+                "Failed to unload stale echo-cancel module #{module}: {:#}",
+                err
+            ),
+        }
+    }
+}
+
+/// Our module-echo-cancel instances in `pactl list short modules` output.
+fn stale_modules(listing: &str) -> Vec<u32> {
+    listing
+        .lines()
+        .filter(|line| line.contains("module-echo-cancel"))
+        .filter(|line| line.contains(&format!("source_name={SOURCE_NAME}")))
+        .filter_map(|line| line.split_whitespace().next()?.parse().ok())
+        .collect()
+}
+
+pub(super) fn pactl(args: &[&str]) -> Result<String> {
+    let output = Command::new("pactl")
+        .args(args)
+        .output()
+        .context("Failed to run pactl")?;
+    if !output.status.success() {
+        bail!(
+            "pactl {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn parse_module_index(output: &str) -> Result<u32> {
+    output
+        .trim()
+        .parse()
+        .with_context(|| format!("Unexpected load-module output {:?}", output.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_module_index() {
+        assert_eq!(parse_module_index("536870913\n").unwrap(), 536_870_913);
+        assert!(parse_module_index("Failure: Module initialization failed").is_err());
+    }
+
+    #[test]
+    fn finds_modules_left_by_a_previous_run() {
+        let listing = "\
+536870912\tmodule-null-sink\tsink_name=other
+536870913\tmodule-echo-cancel\taec_method=webrtc source_name=hyprwhspr_aec_source sink_name=hyprwhspr_aec_sink
+536870914\tmodule-echo-cancel\tsource_name=someone_else
+";
+        assert_eq!(stale_modules(listing), [536_870_913]);
+    }
+}
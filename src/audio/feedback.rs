@@ -1,16 +1,101 @@
-use anyhow::{Context, Result};
-use rodio::{Decoder, OutputStream, Sink};
+#[cfg(feature = "feedback")]
+use crate::audio::recording::time_stretch;
+use crate::audio::CapturedAudio;
+use crate::config::{FeedbackSound, FeedbackSoundsConfig};
+use crate::dnd::DoNotDisturb;
+#[cfg(not(feature = "feedback"))]
+use anyhow::bail;
+#[cfg(feature = "feedback")]
+use anyhow::Context;
+use anyhow::Result;
+#[cfg(feature = "feedback")]
+use rodio::buffer::SamplesBuffer;
+#[cfg(feature = "feedback")]
+use rodio::{Decoder, OutputStream, Sink, Source};
+use std::collections::HashMap;
+#[cfg(feature = "feedback")]
 use std::fs::File;
+#[cfg(feature = "feedback")]
 use std::io::BufReader;
-use std::path::PathBuf;
-use tracing::{debug, error, warn};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Time from queuing a sound on the open output to hearing it.
+const PLAYBACK_STARTUP: Duration = Duration::from_millis(20);
+
+/// Without a configured cancel sound, the stop sound is played slowed down
+/// (and so lower) to tell a discarded recording apart from a finished one.
+const CANCEL_FALLBACK_SPEED: f32 = 0.7;
+/// Likewise sped up (and so higher) for a recording ended by the limit.
+const LIMIT_FALLBACK_SPEED: f32 = 1.5;
+
+/// Transcription outcomes that can have their own sound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedbackEvent {
+    TranscriptionComplete,
+    TranscriptionFailed,
+    EmptyResult,
+    ProviderFallback,
+    RecordingCancelled,
+    RecordingLimit,
+}
+
+/// A configured outcome sound; a disabled one still replaces the default.
+struct EventSound {
+    event: FeedbackEvent,
+    path: PathBuf,
+    volume: f32,
+    enabled: bool,
+}
+
+/// A sound decoded up front, so playing it touches neither disk nor decoder.
+#[derive(Clone)]
+struct Clip {
+    channels: u16,
+    sample_rate: u32,
+    samples: Arc<Vec<f32>>,
+}
+
+impl Clip {
+    #[cfg(feature = "feedback")]
+    fn load(path: &Path) -> Result<Self> {
+        let file =
+            File::open(path).with_context(|| format!("Failed to open audio file: {:?}", path))?;
+        let source = Decoder::new(BufReader::new(file)).context("Failed to decode audio file")?;
+        let channels = source.channels().max(1);
+        let sample_rate = source.sample_rate().max(1);
+        Ok(Self {
+            channels,
+            sample_rate,
+            samples: Arc::new(source.convert_samples().collect()),
+        })
+    }
+
+    #[cfg(not(feature = "feedback"))]
+    fn load(_path: &Path) -> Result<Self> {
+        bail!("Decoding sounds needs a build with the `feedback` feature")
+    }
+
+    fn duration(&self) -> Duration {
+        let frames = self.samples.len() as u64 / u64::from(self.channels);
+        Duration::from_millis(frames * 1000 / u64::from(self.sample_rate))
+    }
+}
 
 pub struct AudioFeedback {
     enabled: bool,
     start_sound: PathBuf,
+    start_sound_length: Option<Duration>,
     stop_sound: PathBuf,
     start_volume: f32,
     stop_volume: f32,
+    event_sounds: Vec<EventSound>,
+    clips: HashMap<PathBuf, Clip>,
+    /// Opened while feedback is enabled and kept for the app's lifetime.
+    output: Option<output::Output>,
+    do_not_disturb: Option<DoNotDisturb>,
 }
 
 impl AudioFeedback {
@@ -21,6 +106,7 @@ impl AudioFeedback {
         stop_sound_path: Option<String>,
         start_volume: f32,
         stop_volume: f32,
+        event_sounds: FeedbackSoundsConfig,
     ) -> Self {
         // Resolve start sound path
         let start_sound = if let Some(ref path) = start_sound_path {
@@ -56,6 +142,13 @@ impl AudioFeedback {
             assets_dir.join("ping-down.ogg")
         };
 
+        #[cfg(not(feature = "feedback"))]
+        if enabled {
+            warn!("Audio feedback needs a build with the `feedback` feature; sounds stay off");
+        }
+        #[cfg(not(feature = "feedback"))]
+        let enabled = false;
+
         // Validate volumes
         let start_volume = start_volume.clamp(0.1, 1.0);
         let stop_volume = stop_volume.clamp(0.1, 1.0);
@@ -73,17 +166,94 @@ impl AudioFeedback {
             enabled, start_sound, stop_sound
         );
 
+        let event_sounds = [
+            (
+                FeedbackEvent::TranscriptionComplete,
+                event_sounds.transcription_complete,
+            ),
+            (
+                FeedbackEvent::TranscriptionFailed,
+                event_sounds.transcription_failed,
+            ),
+            (FeedbackEvent::EmptyResult, event_sounds.empty_result),
+            (
+                FeedbackEvent::ProviderFallback,
+                event_sounds.provider_fallback,
+            ),
+            (
+                FeedbackEvent::RecordingCancelled,
+                event_sounds.recording_cancelled,
+            ),
+            (FeedbackEvent::RecordingLimit, event_sounds.recording_limit),
+        ]
+        .into_iter()
+        .filter_map(|(event, sound)| {
+            let FeedbackSound {
+                path,
+                volume,
+                enabled,
+            } = sound?;
+            let custom_path = PathBuf::from(&path);
+            let resolved = if custom_path.exists() {
+                custom_path
+            } else {
+                assets_dir.join(&path)
+            };
+            if enabled && !resolved.exists() {
+                warn!("Sound for {:?} not found: {:?}", event, resolved);
+            }
+            Some(EventSound {
+                event,
+                path: resolved,
+                volume: volume.clamp(0.1, 1.0),
+                enabled,
+            })
+        })
+        .collect::<Vec<_>>();
+
+        let clips: HashMap<PathBuf, Clip> = [&start_sound, &stop_sound]
+            .into_iter()
+            .chain(event_sounds.iter().map(|sound| &sound.path))
+            .filter(|path| cfg!(feature = "feedback") && path.exists())
+            .filter_map(|path| match Clip::load(path) {
+                Ok(clip) => Some((path.clone(), clip)),
+                Err(err) => {
+                    warn!("Failed to load sound {:?}: {:#}", path, err);
+                    None
+                }
+            })
+            .collect();
+        let start_sound_length = clips.get(&start_sound).map(Clip::duration);
+
         Self {
             enabled,
             start_sound,
+            start_sound_length,
             stop_sound,
             start_volume,
             stop_volume,
+            event_sounds,
+            clips,
+            output: enabled.then(output::Output::open),
+            do_not_disturb: None,
         }
     }
 
+    /// Silences every sound while `dnd` is active.
+    pub fn set_do_not_disturb(&mut self, dnd: DoNotDisturb) {
+        self.do_not_disturb = Some(dnd);
+    }
+
+    fn muted(&self) -> bool {
+        !self.enabled
+            || self
+                .do_not_disturb
+                .as_ref()
+                .is_some_and(DoNotDisturb::active)
+    }
+
     pub fn play_start_sound(&self) -> Result<()> {
-        if !self.enabled {
+        if self.muted() {
             return Ok(());
         }
 
@@ -91,8 +261,18 @@ impl AudioFeedback {
         self.play_sound(&self.start_sound, self.start_volume)
     }
 
+    /// How long after [`Self::play_start_sound`] the start sound may still
+    /// be audible; `None` when feedback is off or the sound can't be read.
+    pub fn start_sound_window(&self) -> Option<Duration> {
+        if self.muted() {
+            return None;
+        }
+        self.start_sound_length
+            .map(|length| length + PLAYBACK_STARTUP)
+    }
+
     pub fn play_stop_sound(&self) -> Result<()> {
-        if !self.enabled {
+        if self.muted() {
             return Ok(());
         }
 
@@ -100,48 +280,241 @@ impl AudioFeedback {
         self.play_sound(&self.stop_sound, self.stop_volume)
     }
 
-    fn play_sound(&self, path: &PathBuf, volume: f32) -> Result<()> {
-        if !path.exists() {
-            warn!("Sound file not found: {:?}", path);
+    /// Plays the configured cancel sound, or a lowered stop sound.
+    pub fn play_cancel_sound(&self) -> Result<()> {
+        if self.muted() {
             return Ok(());
         }
 
-        // Spawn in a separate thread to avoid blocking
-        let path = path.clone();
-        std::thread::spawn(move || {
-            if let Err(e) = Self::play_sound_blocking(&path, volume) {
-                error!("Failed to play sound {:?}: {}", path, e);
-            }
-        });
+        if self.has_event_sound(FeedbackEvent::RecordingCancelled) {
+            self.play_event(FeedbackEvent::RecordingCancelled);
+            return Ok(());
+        }
+        debug!("Playing cancel sound: {:?} (slowed)", self.stop_sound);
+        self.play_sound_at(&self.stop_sound, self.stop_volume, CANCEL_FALLBACK_SPEED)
+    }
+
+    /// Plays the configured limit sound, or a raised stop sound.
+    pub fn play_limit_sound(&self) -> Result<()> {
+        if self.muted() {
+            return Ok(());
+        }
+
+        if self.has_event_sound(FeedbackEvent::RecordingLimit) {
+            self.play_event(FeedbackEvent::RecordingLimit);
+            return Ok(());
+        }
+        debug!("Playing limit sound: {:?} (sped up)", self.stop_sound);
+        self.play_sound_at(&self.stop_sound, self.stop_volume, LIMIT_FALLBACK_SPEED)
+    }
+
+    fn has_event_sound(&self, event: FeedbackEvent) -> bool {
+        self.event_sounds.iter().any(|sound| sound.event == event)
+    }
+
+    /// Plays the sound configured for `event`, if any.
+    pub fn play_event(&self, event: FeedbackEvent) {
+        if self.muted() {
+            return;
+        }
+
+        let Some(sound) = self
+            .event_sounds
+            .iter()
+            .find(|sound| sound.event == event && sound.enabled)
+        else {
+            return;
+        };
+
+        debug!("Playing {:?} sound: {:?}", event, sound.path);
+        if let Err(err) = self.play_sound(&sound.path, sound.volume) {
+            warn!("Failed to play {:?} sound: {}", event, err);
+        }
+    }
+
+    fn play_sound(&self, path: &PathBuf, volume: f32) -> Result<()> {
+        self.play_sound_at(path, volume, 1.0)
+    }
 
+    fn play_sound_at(&self, path: &PathBuf, volume: f32, speed: f32) -> Result<()> {
+        let Some(clip) = self.clips.get(path) else {
+            warn!("Sound file not found: {:?}", path);
+            return Ok(());
+        };
+        let Some(output) = &self.output else {
+            return Ok(());
+        };
+
+        // Queued on the output thread; returns immediately
+        output.play(clip.clone(), volume, speed);
         Ok(())
     }
 
-    fn play_sound_blocking(path: &PathBuf, volume: f32) -> Result<()> {
-        // Create output stream
+    /// Plays a captured recording on the default output and blocks until it
+    /// finishes. `speed` above 1.0 speeds speech up without raising its pitch.
+    #[cfg(feature = "feedback")]
+    pub fn play_captured_blocking(audio: &CapturedAudio, speed: f32) -> Result<()> {
+        let samples = time_stretch(&audio.samples, audio.sample_rate, speed);
+
         let (_stream, stream_handle) =
             OutputStream::try_default().context("Failed to open audio output")?;
-
-        // Create sink
         let sink = Sink::try_new(&stream_handle).context("Failed to create audio sink")?;
 
-        // Load and decode audio file
-        let file =
-            File::open(path).with_context(|| format!("Failed to open audio file: {:?}", path))?;
-        let source = Decoder::new(BufReader::new(file)).context("Failed to decode audio file")?;
-
-        // Set volume and play
-        sink.set_volume(volume);
-        sink.append(source);
-
-        // Wait for playback to complete
+        sink.append(SamplesBuffer::new(1, audio.sample_rate, samples));
         sink.sleep_until_end();
 
         Ok(())
     }
 
+    #[cfg(not(feature = "feedback"))]
+    pub fn play_captured_blocking(_audio: &CapturedAudio, _speed: f32) -> Result<()> {
+        bail!("Playback needs a build with the `feedback` feature")
+    }
+
     pub fn set_enabled(&mut self, enabled: bool) {
         self.enabled = enabled;
+        if enabled && self.output.is_none() {
+            self.output = Some(output::Output::open());
+        }
         debug!("Audio feedback enabled: {}", enabled);
     }
 }
+
+#[cfg(feature = "feedback")]
+mod output {
+    use super::Clip;
+    use rodio::{OutputStream, OutputStreamHandle, Source};
+    use std::sync::mpsc::{self, Sender};
+    use std::thread;
+    use std::time::Duration;
+    use tracing::{debug, error};
+
+    struct Playback {
+        clip: Clip,
+        volume: f32,
+        speed: f32,
+    }
+
+    /// The default output device, held open by a dedicated thread (rodio's
+    /// stream can't leave the thread that opened it) so a sound starts as
+    /// soon as it is queued. The thread ends when this is dropped.
+    pub struct Output {
+        playbacks: Sender<Playback>,
+    }
+
+    impl Output {
+        pub fn open() -> Self {
+            let (playbacks, queued) = mpsc::channel::<Playback>();
+            let spawned = thread::Builder::new()
+                .name("feedback-output".to_string())
+                .spawn(move || {
+                    let mut stream = open_stream();
+                    for playback in queued {
+                        if stream.is_none() {
+                            stream = open_stream();
+                        }
+                        let Some((_, handle)) = &stream else {
+                            continue;
+                        };
+                        let Playback {
+                            clip,
+                            volume,
+                            speed,
+                        } = playback;
+                        let source = ClipSource::new(clip).amplify(volume).speed(speed);
+                        if let Err(err) = handle.play_raw(source) {
+                            // The device may have gone away; reopen for the next sound.
+                            error!("Failed to play feedback sound: {err}");
+                            stream = None;
+                        }
+                    }
+                    debug!("Feedback output closed");
+                });
+            if let Err(err) = spawned {
+                error!("Failed to start the feedback output thread: {err}");
+            }
+            Self { playbacks }
+        }
+
+        pub fn play(&self, clip: Clip, volume: f32, speed: f32) {
+            let _ = self.playbacks.send(Playback {
+                clip,
+                volume,
+                speed,
+            });
+        }
+    }
+
+    /// Reads a clip's shared samples in place, so playing a sound doesn't
+    /// copy them.
+    struct ClipSource {
+        clip: Clip,
+        position: usize,
+    }
+
+    impl ClipSource {
+        fn new(clip: Clip) -> Self {
+            Self { clip, position: 0 }
+        }
+    }
+
+    impl Iterator for ClipSource {
+        type Item = f32;
+
+        fn next(&mut self) -> Option<f32> {
+            let sample = self.clip.samples.get(self.position).copied()?;
+            self.position += 1;
+            Some(sample)
+        }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            let left = self.clip.samples.len() - self.position;
+            (left, Some(left))
+        }
+    }
+
+    impl Source for ClipSource {
+        fn current_frame_len(&self) -> Option<usize> {
+            None
+        }
+
+        fn channels(&self) -> u16 {
+            self.clip.channels
+        }
+
+        fn sample_rate(&self) -> u32 {
+            self.clip.sample_rate
+        }
+
+        fn total_duration(&self) -> Option<Duration> {
+            let frames = self.clip.samples.len() / usize::from(self.clip.channels.max(1));
+            Some(Duration::from_secs_f64(
+                frames as f64 / f64::from(self.clip.sample_rate.max(1)),
+            ))
+        }
+    }
+
+    fn open_stream() -> Option<(OutputStream, OutputStreamHandle)> {
+        OutputStream::try_default()
+            .inspect_err(|err| error!("Failed to open audio output: {err}"))
+            .ok()
+    }
+}
+
+#[cfg(not(feature = "feedback"))]
+mod output {
+    use super::Clip;
+    use tracing::warn;
+
+    pub struct Output;
+
+    impl Output {
+        pub fn open() -> Self {
+            Self
+        }
+
+        pub fn play(&self, _clip: Clip, _volume: f32, _speed: f32) {
+            warn!("Feedback sounds need a build with the `feedback` feature");
+        }
+    }
+}
@@ -0,0 +1,62 @@
+//! Recycles the short (30 ms) frame buffers the VAD holds on to for pre-roll
+//! and trailing silence, so long listening sessions don't allocate and free
+//! one `Vec` per frame.
+
+#[derive(Debug, Default)]
+pub struct FramePool {
+    free: Vec<Vec<f32>>,
+    max_idle: usize,
+}
+
+impl FramePool {
+    /// Keeps at most `max_idle` spare buffers; extras are freed on return.
+    pub fn new(max_idle: usize) -> Self {
+        Self {
+            free: Vec::with_capacity(max_idle),
+            max_idle,
+        }
+    }
+
+    /// Returns a pooled buffer holding a copy of `samples`.
+    pub fn take_from(&mut self, samples: &[f32]) -> Vec<f32> {
+        let mut frame = self.free.pop().unwrap_or_default();
+        frame.clear();
+        frame.extend_from_slice(samples);
+        frame
+    }
+
+    pub fn give(&mut self, frame: Vec<f32>) {
+        if self.free.len() < self.max_idle {
+            self.free.push(frame);
+        }
+    }
+
+    pub fn idle(&self) -> usize {
+        self.free.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reuses_returned_buffers() {
+        let mut pool = FramePool::new(2);
+        let frame = pool.take_from(&[0.1; 480]);
+        let address = frame.as_ptr();
+        pool.give(frame);
+
+        let reused = pool.take_from(&[0.2; 480]);
+        assert_eq!(reused.as_ptr(), address);
+        assert_eq!(reused, vec![0.2; 480]);
+    }
+
+    #[test]
+    fn caps_idle_buffers() {
+        let mut pool = FramePool::new(1);
+        pool.give(vec![0.0; 480]);
+        pool.give(vec![0.0; 480]);
+        assert_eq!(pool.idle(), 1);
+    }
+}
@@ -1,7 +1,20 @@
 pub mod capture;
+pub mod denoise;
+pub mod echo_cancel;
 pub mod feedback;
+pub mod frame_pool;
+pub mod monitor;
+pub mod pcm;
+pub mod pending;
+pub mod probe;
+pub mod recording;
+pub mod resample;
+pub mod source;
 pub mod vad;
+pub mod wakeword;
 
-pub use capture::{AudioCapture, CapturedAudio};
-pub use feedback::AudioFeedback;
-pub use vad::{FastVad, FastVadOutcome, FastVadProfile, FastVadSettings};
+pub use capture::{AudioCapture, CapturedAudio, InputDeviceInfo};
+pub use feedback::{AudioFeedback, FeedbackEvent};
+pub use pending::PendingUtterances;
+pub use resample::resample_audio;
+pub use vad::{FastVad, FastVadOutcome, FastVadProfile, FastVadSettings, SegmentBreak};
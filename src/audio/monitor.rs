@@ -0,0 +1,73 @@
+//! Recording what plays on the speakers (a call, a video) instead of the
+//! microphone, through the sound server's monitor source. The monitor is
+//! recorded with `parec` (see [`crate::audio::source`]), so it needs
+//! PulseAudio or PipeWire's pulse server, as on most desktops.
+
+use super::echo_cancel::pactl;
+use super::{resample_audio, CapturedAudio};
+use crate::config::ResampleQuality;
+use anyhow::{Context, Result};
+
+/// The source to record: `configured`, or the monitor of the current
+/// default output.
+pub fn resolve_source(configured: Option<&str>) -> Result<String> {
+    if let Some(source) = configured.filter(|source| !source.trim().is_empty()) {
+        return Ok(source.trim().to_string());
+    }
+    let sink = pactl(&["get-default-sink"]).context("Failed to find the default output")?;
+    Ok(format!("{}.monitor", sink.trim()))
+}
+
+/// Sums a monitor capture into a microphone capture of the same moment,
+/// at the microphone's rate. The streams run on separate clocks, so the
+/// monitor is resampled from its measured rate first.
+pub fn mix(
+    mic: CapturedAudio,
+    monitor: CapturedAudio,
+    monitor_gain: f32,
+    quality: ResampleQuality,
+) -> CapturedAudio {
+    let monitor = resample_audio(
+        &monitor.samples,
+        monitor.sample_rate,
+        mic.sample_rate,
+        quality,
+    );
+    let mut samples = mic.samples;
+    if samples.len() < monitor.len() {
+        samples.resize(monitor.len(), 0.0);
+    }
+    for (sample, other) in samples.iter_mut().zip(&monitor) {
+        *sample = (*sample + other * monitor_gain).clamp(-1.0, 1.0);
+    }
+    CapturedAudio {
+        samples,
+        sample_rate: mic.sample_rate,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mixes_onto_the_mic_clock() {
+        let mic = CapturedAudio {
+            samples: vec![0.5, -0.25, 0.75],
+            sample_rate: 16_000,
+        };
+        let monitor = CapturedAudio {
+            samples: vec![0.25, 0.25, 0.5, 0.5],
+            sample_rate: 16_000,
+        };
+        let mixed = mix(mic.clone(), monitor, 1.0, ResampleQuality::Fast);
+        assert_eq!(mixed.samples, [0.75, 0.0, 1.0, 0.5]);
+
+        let fast_clock = CapturedAudio {
+            samples: vec![0.0; 16_160],
+            sample_rate: 16_160,
+        };
+        let mixed = mix(mic, fast_clock, 0.5, ResampleQuality::Fast);
+        assert_eq!((mixed.len(), mixed.sample_rate), (16_000, 16_000));
+    }
+}
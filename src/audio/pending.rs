@@ -0,0 +1,113 @@
+//! Captured utterances that haven't been transcribed yet, spooled to disk so
+//! a crash or restart mid-transcription doesn't lose them.
+
+use crate::audio::recording::write_wav;
+use crate::audio::CapturedAudio;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+pub struct PendingUtterances {
+    dir: PathBuf,
+}
+
+impl PendingUtterances {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Spools `audio`; pass the returned path to [`Self::complete`] once the
+    /// utterance has been handled.
+    pub fn enqueue(&self, audio: &CapturedAudio) -> Result<PathBuf> {
+        static COUNTER: AtomicU32 = AtomicU32::new(0);
+
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create pending directory: {:?}", self.dir))?;
+
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis())
+            .unwrap_or_default();
+        // Zero-padded so lexical order is capture order.
+        let name = format!(
+            "{millis:015}-{:04}.wav",
+            COUNTER.fetch_add(1, Ordering::Relaxed) % 10_000
+        );
+        let path = self.dir.join(name);
+
+        // Written under a temporary name so a crash mid-write never leaves a
+        // truncated file that looks resumable.
+        let tmp = path.with_extension("wav.tmp");
+        write_wav(&tmp, &audio.samples, audio.sample_rate)?;
+        fs::rename(&tmp, &path)
+            .with_context(|| format!("Failed to spool pending utterance: {:?}", path))?;
+        Ok(path)
+    }
+
+    pub fn complete(&self, path: &Path) {
+        if let Err(err) = fs::remove_file(path) {
+            warn!("Failed to remove pending utterance {:?}: {}", path, err);
+        }
+    }
+
+    /// Spooled utterances, oldest first.
+    pub fn spooled(&self) -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "wav"))
+            .collect();
+        paths.sort();
+        paths
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::recording::load_recording;
+
+    #[test]
+    fn spooled_utterances_round_trip_until_completed() {
+        let dir = std::env::temp_dir().join(format!("hyprwhspr-pending-{}", std::process::id()));
+        let pending = PendingUtterances::new(dir.clone());
+        assert!(pending.spooled().is_empty());
+
+        let first = CapturedAudio {
+            samples: vec![0.0, 0.5, -0.5, 0.25],
+            sample_rate: 16_000,
+        };
+        let second = CapturedAudio {
+            samples: vec![0.1; 8],
+            sample_rate: 48_000,
+        };
+        let first_path = pending.enqueue(&first).unwrap();
+        let second_path = pending.enqueue(&second).unwrap();
+        // A write cut short by a crash, and something that isn't audio.
+        fs::write(dir.join("999999999999999-0000.wav.tmp"), b"RIFF").unwrap();
+        fs::write(dir.join("notes.txt"), b"hello").unwrap();
+
+        assert_eq!(pending.spooled(), [first_path.clone(), second_path.clone()]);
+        let loaded = load_recording(&first_path).unwrap();
+        assert_eq!(loaded.sample_rate, 16_000);
+        assert_eq!(loaded.samples.len(), first.samples.len());
+        for (loaded, saved) in loaded.samples.iter().zip(&first.samples) {
+            assert!((loaded - saved).abs() < 1e-3, "{loaded} vs {saved}");
+        }
+
+        // A corrupt spool file fails to load, so resuming drops it.
+        fs::write(&second_path, b"not a wav").unwrap();
+        assert!(load_recording(&second_path).is_err());
+
+        pending.complete(&first_path);
+        pending.complete(&second_path);
+        assert!(pending.spooled().is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,70 @@
+//! Scores short recordings from candidate microphones so the one with the
+//! cleanest signal can be picked automatically (e.g. a headset over a
+//! laptop's webcam mic).
+
+/// 20 ms at 16 kHz.
+const FRAME_LEN: usize = 320;
+
+/// Frames quieter than this are digital silence: a muted or dead device.
+const DEAD_RMS: f32 = 1e-5;
+
+/// Signal-to-noise ratio of `samples` in dB, comparing the loud and quiet
+/// ends of the per-frame energy distribution. `None` when the capture is too
+/// short or the device delivered nothing but silence.
+pub fn snr_db(samples: &[f32]) -> Option<f32> {
+    let mut levels: Vec<f32> = samples
+        .chunks_exact(FRAME_LEN)
+        .map(|frame| (frame.iter().map(|s| s * s).sum::<f32>() / FRAME_LEN as f32).sqrt())
+        .collect();
+    if levels.len() < 4 {
+        return None;
+    }
+
+    levels.sort_by(f32::total_cmp);
+    let noise = levels[levels.len() / 10];
+    let signal = levels[levels.len() * 9 / 10];
+    if signal < DEAD_RMS {
+        return None;
+    }
+
+    Some(20.0 * (signal / noise.max(DEAD_RMS)).log10())
+}
+
+/// Index of the best-scoring candidate; unusable probes never win.
+pub fn best(scores: &[Option<f32>]) -> Option<usize> {
+    scores
+        .iter()
+        .enumerate()
+        .filter_map(|(index, score)| score.map(|score| (index, score)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(index, _)| index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probe(noise: f32, speech: f32) -> Vec<f32> {
+        (0..FRAME_LEN * 20)
+            .map(|i| {
+                let amplitude = if (i / FRAME_LEN).is_multiple_of(2) {
+                    speech
+                } else {
+                    noise
+                };
+                amplitude * if i.is_multiple_of(2) { 1.0 } else { -1.0 }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn prefers_the_cleaner_device() {
+        let webcam = snr_db(&probe(0.05, 0.2));
+        let headset = snr_db(&probe(0.001, 0.2));
+        let muted = snr_db(&vec![0.0; FRAME_LEN * 20]);
+
+        assert!(muted.is_none());
+        assert_eq!(best(&[webcam, muted, headset]), Some(2));
+        assert_eq!(best(&[muted]), None);
+    }
+}
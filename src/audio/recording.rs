@@ -0,0 +1,276 @@
+use crate::audio::{pcm, CapturedAudio};
+#[cfg(not(feature = "feedback"))]
+use anyhow::anyhow;
+use anyhow::{Context, Result};
+#[cfg(feature = "feedback")]
+use rodio::{Decoder, Source};
+use std::f32::consts::PI;
+use std::fs::{self, File};
+#[cfg(feature = "feedback")]
+use std::io::BufReader;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Writes mono f32 samples as a 16-bit PCM WAV file.
+pub fn write_wav(path: &Path, samples: &[f32], sample_rate: u32) -> Result<()> {
+    let mut file = BufWriter::new(
+        File::create(path).with_context(|| format!("Failed to create WAV file: {:?}", path))?,
+    );
+    encode_wav(&mut file, samples, sample_rate)?;
+    file.flush()?;
+    Ok(())
+}
+
+/// Streams mono f32 samples into `out` as a 16-bit PCM WAV.
+pub fn encode_wav(out: &mut impl Write, samples: &[f32], sample_rate: u32) -> io::Result<()> {
+    let channels: u16 = 1;
+    let bits_per_sample: u16 = 16;
+    let byte_rate = sample_rate * channels as u32 * bits_per_sample as u32 / 8;
+    let block_align = channels * bits_per_sample / 8;
+    let data_size = (samples.len() * 2) as u32;
+
+    // RIFF header
+    out.write_all(b"RIFF")?;
+    out.write_all(&(36 + data_size).to_le_bytes())?;
+    out.write_all(b"WAVE")?;
+
+    // fmt chunk
+    out.write_all(b"fmt ")?;
+    out.write_all(&16u32.to_le_bytes())?; // Chunk size
+    out.write_all(&1u16.to_le_bytes())?; // Audio format (PCM)
+    out.write_all(&channels.to_le_bytes())?;
+    out.write_all(&sample_rate.to_le_bytes())?;
+    out.write_all(&byte_rate.to_le_bytes())?;
+    out.write_all(&block_align.to_le_bytes())?;
+    out.write_all(&bits_per_sample.to_le_bytes())?;
+
+    // data chunk
+    out.write_all(b"data")?;
+    out.write_all(&data_size.to_le_bytes())?;
+
+    let mut converted = [0i16; pcm::CHUNK_SAMPLES];
+    let mut bytes = Vec::with_capacity(pcm::CHUNK_SAMPLES * 2);
+    for chunk in samples.chunks(pcm::CHUNK_SAMPLES) {
+        let converted = &mut converted[..chunk.len()];
+        pcm::f32_to_i16(chunk, converted);
+        bytes.clear();
+        bytes.extend(converted.iter().flat_map(|sample| sample.to_le_bytes()));
+        out.write_all(&bytes)?;
+    }
+    Ok(())
+}
+
+/// Stores the raw capture so it can be replayed later with `--replay`.
+pub fn save_last_recording(path: &Path, audio: &CapturedAudio) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).ok();
+    }
+    write_wav(path, &audio.samples, audio.sample_rate)
+}
+
+/// Decodes a recording written by [`save_last_recording`], downmixing to mono.
+#[cfg(feature = "feedback")]
+pub fn load_recording(path: &Path) -> Result<CapturedAudio> {
+    let file = File::open(path).with_context(|| format!("Failed to open recording: {:?}", path))?;
+    let decoder = Decoder::new(BufReader::new(file)).context("Failed to decode recording")?;
+
+    let sample_rate = decoder.sample_rate();
+    let channels = usize::from(decoder.channels().max(1));
+    let interleaved: Vec<f32> = decoder.convert_samples::<f32>().collect();
+    Ok(downmix(interleaved, channels, sample_rate))
+}
+
+/// Reads a 16-bit PCM WAV such as [`save_last_recording`] writes,
+/// downmixing to mono; other formats need the `feedback` feature's decoders.
+#[cfg(not(feature = "feedback"))]
+pub fn load_recording(path: &Path) -> Result<CapturedAudio> {
+    let bytes = fs::read(path).with_context(|| format!("Failed to open recording: {:?}", path))?;
+    let (channels, sample_rate, data) = pcm16_wav(&bytes).ok_or_else(|| {
+        anyhow!(
+            "{:?} is not a 16-bit PCM WAV; other formats need a build with the `feedback` feature",
+            path
+        )
+    })?;
+    let interleaved = data
+        .chunks_exact(2)
+        .map(|sample| f32::from(i16::from_le_bytes([sample[0], sample[1]])) / 32768.0)
+        .collect();
+    Ok(downmix(interleaved, channels, sample_rate))
+}
+
+/// Channel count, sample rate and sample bytes of a 16-bit PCM WAV.
+#[cfg(not(feature = "feedback"))]
+fn pcm16_wav(bytes: &[u8]) -> Option<(usize, u32, &[u8])> {
+    if bytes.get(..4)? != b"RIFF" || bytes.get(8..12)? != b"WAVE" {
+        return None;
+    }
+    let u16_at =
+        |chunk: &[u8], at: usize| Some(u16::from_le_bytes(chunk.get(at..at + 2)?.try_into().ok()?));
+    let u32_at =
+        |chunk: &[u8], at: usize| Some(u32::from_le_bytes(chunk.get(at..at + 4)?.try_into().ok()?));
+
+    let mut format = None;
+    let mut rest = &bytes[12..];
+    while rest.len() >= 8 {
+        let len = u32_at(rest, 4)? as usize;
+        // A recording cut short by a crash can claim more data than it holds.
+        let body = rest.get(8..8 + len).unwrap_or(&rest[8..]);
+        match &rest[..4] {
+            b"fmt " => {
+                if u16_at(body, 0)? != 1 || u16_at(body, 14)? != 16 {
+                    return None;
+                }
+                format = Some((usize::from(u16_at(body, 2)?.max(1)), u32_at(body, 4)?));
+            }
+            b"data" => {
+                let (channels, sample_rate) = format?;
+                return Some((channels, sample_rate, body));
+            }
+            _ => {}
+        }
+        // Chunks are padded to an even length.
+        rest = rest.get(8 + len + len % 2..).unwrap_or_default();
+    }
+    None
+}
+
+fn downmix(interleaved: Vec<f32>, channels: usize, sample_rate: u32) -> CapturedAudio {
+    let samples = if channels == 1 {
+        interleaved
+    } else {
+        interleaved
+            .chunks(channels)
+            .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+            .collect()
+    };
+
+    CapturedAudio {
+        samples,
+        sample_rate,
+    }
+}
+
+/// Changes playback speed without shifting pitch using WSOLA (waveform
+/// similarity overlap-add). Each 40 ms Hann-windowed frame is taken near its
+/// nominal position, nudged to line up with the previous frame's natural
+/// continuation so voiced speech doesn't phase-smear.
+pub fn time_stretch(samples: &[f32], sample_rate: u32, speed: f32) -> Vec<f32> {
+    if !speed.is_finite() || speed <= 0.0 || (speed - 1.0).abs() < 0.01 {
+        return samples.to_vec();
+    }
+
+    let frame = (sample_rate as usize * 40 / 1000).max(16);
+    if samples.len() <= frame {
+        return samples.to_vec();
+    }
+
+    let synthesis_hop = frame / 2;
+    let analysis_hop = ((synthesis_hop as f32) * speed).round().max(1.0) as usize;
+    let tolerance = frame / 4;
+    let last_start = samples.len() - frame;
+
+    let window: Vec<f32> = (0..frame)
+        .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / frame as f32).cos())
+        .collect();
+
+    let frames = last_start / analysis_hop + 1;
+    let output_len = (frames - 1) * synthesis_hop + frame;
+    let mut output = vec![0.0f32; output_len];
+    let mut weights = vec![0.0f32; output_len];
+    let mut previous: Option<usize> = None;
+
+    for index in 0..frames {
+        let nominal = index * analysis_hop;
+        let start = match previous {
+            None => nominal,
+            Some(prev) => {
+                let natural = (prev + synthesis_hop).min(last_start);
+                let low = nominal.saturating_sub(tolerance);
+                let high = (nominal + tolerance).min(last_start);
+                best_alignment(samples, natural, low, high, synthesis_hop)
+            }
+        };
+
+        let offset = index * synthesis_hop;
+        for (i, weight) in window.iter().enumerate() {
+            output[offset + i] += samples[start + i] * weight;
+            weights[offset + i] += weight;
+        }
+        previous = Some(start);
+    }
+
+    for (sample, weight) in output.iter_mut().zip(weights) {
+        if weight > 1e-3 {
+            *sample /= weight;
+        }
+    }
+
+    output
+}
+
+/// Picks the start in `low..=high` whose first `overlap` samples correlate
+/// best with the segment beginning at `reference`.
+fn best_alignment(
+    samples: &[f32],
+    reference: usize,
+    low: usize,
+    high: usize,
+    overlap: usize,
+) -> usize {
+    let target = &samples[reference..reference + overlap];
+    let mut best = low;
+    let mut best_score = f32::MIN;
+
+    for candidate in low..=high {
+        let score: f32 = target
+            .iter()
+            .zip(&samples[candidate..candidate + overlap])
+            .map(|(a, b)| a * b)
+            .sum();
+        if score > best_score {
+            best_score = score;
+            best = candidate;
+        }
+    }
+
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_recordings_load_back() {
+        let path =
+            std::env::temp_dir().join(format!("hyprwhspr-recording-{}.wav", std::process::id()));
+        let audio = CapturedAudio {
+            samples: vec![0.0, 0.5, -0.5, 0.25],
+            sample_rate: 16_000,
+        };
+        save_last_recording(&path, &audio).unwrap();
+        let loaded = load_recording(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.sample_rate, 16_000);
+        assert_eq!(loaded.samples.len(), 4);
+        for (loaded, saved) in loaded.samples.iter().zip(&audio.samples) {
+            assert!((loaded - saved).abs() < 1e-3, "{loaded} vs {saved}");
+        }
+    }
+
+    #[test]
+    fn time_stretch_shortens_by_speed_factor() {
+        let sample_rate = 16_000;
+        let samples: Vec<f32> = (0..sample_rate * 3)
+            .map(|i| (2.0 * PI * 220.0 * i as f32 / sample_rate as f32).sin())
+            .collect();
+
+        let stretched = time_stretch(&samples, sample_rate as u32, 1.5);
+
+        let expected = samples.len() as f32 / 1.5;
+        let ratio = stretched.len() as f32 / expected;
+        assert!((0.95..1.05).contains(&ratio), "ratio was {ratio}");
+        assert!(stretched.iter().all(|sample| sample.abs() <= 1.01));
+    }
+}
@@ -0,0 +1,122 @@
+//! Capture from a named sound-server source (the echo-cancelled microphone,
+//! a monitor of system output) through `parec`, which PulseAudio and
+//! PipeWire's pulse server both provide alongside `pactl`. cpal only sees
+//! ALSA devices, and pointing ALSA's default at a source would take
+//! process-wide environment variables that every thread and child process
+//! shares.
+
+use anyhow::{Context, Result};
+use std::io::{ErrorKind, Read};
+use std::process::{Child, Command, Stdio};
+use std::thread::{self, JoinHandle};
+use tracing::{debug, warn};
+
+/// Bytes read from `parec` at a time: 20 ms of mono f32 at 48 kHz.
+const READ_CHUNK: usize = 3840;
+
+/// A running `parec` feeding samples to a callback; dropping it stops the
+/// process.
+pub struct SourceStream {
+    child: Child,
+    reader: Option<JoinHandle<()>>,
+}
+
+impl SourceStream {
+    /// Records `source` as mono f32 at `sample_rate`, handing every read to
+    /// `sink`. `on_end` runs once the process exits, which includes this
+    /// stream being dropped.
+    pub fn open(
+        source: &str,
+        sample_rate: u32,
+        mut sink: impl FnMut(&[f32]) + Send + 'static,
+        on_end: impl FnOnce() + Send + 'static,
+    ) -> Result<Self> {
+        let mut child = Command::new("parec")
+            .arg(format!("--device={source}"))
+            .args(["--raw", "--format=float32le", "--channels=1"])
+            .arg(format!("--rate={sample_rate}"))
+            .arg("--latency-msec=20")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("Failed to start parec")?;
+        let mut stdout = child.stdout.take().context("parec has no stdout")?;
+        debug!("Capturing from {} through parec", source);
+
+        let reader = thread::Builder::new()
+            .name("source-capture".to_string())
+            .spawn(move || {
+                let mut bytes = [0u8; READ_CHUNK];
+                let mut decoder = SampleDecoder::default();
+                loop {
+                    match stdout.read(&mut bytes) {
+                        Ok(0) => break,
+                        Ok(read) => sink(decoder.push(&bytes[..read])),
+                        Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                        Err(err) => {
+                            warn!("Reading from parec failed: {}", err);
+                            break;
+                        }
+                    }
+                }
+                on_end();
+            })
+            .context("Failed to start the source capture thread")?;
+
+        Ok(Self {
+            child,
+            reader: Some(reader),
+        })
+    }
+}
+
+impl Drop for SourceStream {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        if let Some(reader) = self.reader.take() {
+            let _ = reader.join();
+        }
+    }
+}
+
+/// Turns a byte stream of little-endian f32 into samples, carrying a
+/// partial sample over to the next read.
+#[derive(Default)]
+struct SampleDecoder {
+    partial: Vec<u8>,
+    samples: Vec<f32>,
+}
+
+impl SampleDecoder {
+    fn push(&mut self, bytes: &[u8]) -> &[f32] {
+        self.partial.extend_from_slice(bytes);
+        let whole = self.partial.len() / 4 * 4;
+        self.samples.clear();
+        self.samples.extend(
+            self.partial[..whole]
+                .chunks_exact(4)
+                .map(|sample| f32::from_le_bytes([sample[0], sample[1], sample[2], sample[3]])),
+        );
+        self.partial.drain(..whole);
+        &self.samples
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_samples_split_across_reads() {
+        let bytes: Vec<u8> = [0.5f32, -0.25, 1.0]
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect();
+        let mut decoder = SampleDecoder::default();
+        assert_eq!(decoder.push(&bytes[..6]), [0.5]);
+        assert_eq!(decoder.push(&bytes[6..7]), [] as [f32; 0]);
+        assert_eq!(decoder.push(&bytes[7..]), [-0.25, 1.0]);
+    }
+}
@@ -3,9 +3,11 @@ use std::fmt;
 #[cfg(test)]
 use std::time::Duration;
 
-use anyhow::{bail, Context, Result};
-use earshot::{VoiceActivityDetector, VoiceActivityProfile};
+use anyhow::{bail, Result};
+use tracing::warn;
 
+use crate::audio::frame_pool::FramePool;
+use crate::audio::pcm;
 use crate::config::{FastVadConfig, FastVadProfileConfig};
 
 const FRAME_MS: u32 = 30;
@@ -73,17 +75,76 @@ impl From<FastVadProfileConfig> for FastVadProfile {
     }
 }
 
-impl From<FastVadProfile> for VoiceActivityProfile {
-    fn from(value: FastVadProfile) -> Self {
-        match value {
-            FastVadProfile::Quality => VoiceActivityProfile::QUALITY,
-            FastVadProfile::LowBitrate => VoiceActivityProfile::LBR,
-            FastVadProfile::Aggressive => VoiceActivityProfile::AGGRESSIVE,
-            FastVadProfile::VeryAggressive => VoiceActivityProfile::VERY_AGGRESSIVE,
+#[cfg(feature = "vad")]
+mod detector {
+    use super::FastVadProfile;
+    use anyhow::{bail, Context, Result};
+    use earshot::{VoiceActivityDetector, VoiceActivityProfile};
+
+    impl From<FastVadProfile> for VoiceActivityProfile {
+        fn from(value: FastVadProfile) -> Self {
+            match value {
+                FastVadProfile::Quality => VoiceActivityProfile::QUALITY,
+                FastVadProfile::LowBitrate => VoiceActivityProfile::LBR,
+                FastVadProfile::Aggressive => VoiceActivityProfile::AGGRESSIVE,
+                FastVadProfile::VeryAggressive => VoiceActivityProfile::VERY_AGGRESSIVE,
+            }
+        }
+    }
+
+    pub(super) struct Detector(VoiceActivityDetector);
+
+    impl Detector {
+        pub(super) fn new(profile: FastVadProfile) -> Self {
+            Self(VoiceActivityDetector::new(profile.into()))
+        }
+
+        pub(super) fn predict(&mut self, sample_rate_hz: u32, frame: &[i16]) -> Result<bool> {
+            match sample_rate_hz {
+                8_000 => self
+                    .0
+                    .predict_8khz(frame)
+                    .context("Earshot VAD failed to evaluate 8 kHz frame"),
+                16_000 => self
+                    .0
+                    .predict_16khz(frame)
+                    .context("Earshot VAD failed to evaluate 16 kHz frame"),
+                32_000 => self
+                    .0
+                    .predict_32khz(frame)
+                    .context("Earshot VAD failed to evaluate 32 kHz frame"),
+                48_000 => self
+                    .0
+                    .predict_48khz(frame)
+                    .context("Earshot VAD failed to evaluate 48 kHz frame"),
+                rate => bail!("Unsupported sample rate {} Hz for fast VAD", rate),
+            }
         }
     }
 }
 
+/// Stand-in for builds without the `vad` feature; [`FastVad::maybe_new`]
+/// never gets this far there, so only hand-built detectors reach it.
+#[cfg(not(feature = "vad"))]
+mod detector {
+    use super::FastVadProfile;
+    use anyhow::{bail, Result};
+
+    pub(super) struct Detector;
+
+    impl Detector {
+        pub(super) fn new(_profile: FastVadProfile) -> Self {
+            Self
+        }
+
+        pub(super) fn predict(&mut self, _sample_rate_hz: u32, _frame: &[i16]) -> Result<bool> {
+            bail!("this build lacks the `vad` feature")
+        }
+    }
+}
+
+use detector::Detector;
+
 #[derive(Debug, Clone)]
 pub struct FastVadSettings {
     pub base_profile: FastVadProfile,
@@ -98,6 +159,9 @@ pub struct FastVadSettings {
 
 impl FastVadSettings {
     pub fn from_config(config: &FastVadConfig) -> Self {
+        // Already logged when the config was loaded.
+        let mut config = config.clone();
+        let _ = config.sanitize();
         let ms_to_frames = |ms: u32| -> usize {
             if ms == 0 {
                 return 0;
@@ -145,12 +209,16 @@ impl FastVadSettings {
 
 pub struct FastVad {
     settings: FastVadSettings,
-    detector: VoiceActivityDetector,
+    detector: Detector,
     current_profile: FastVadProfile,
     decision_history: VecDeque<bool>,
     profile_switches: usize,
     frame_samples: usize,
     sample_rate_hz: u32,
+    /// Buffers for frames held in pre-roll or pending silence, kept across
+    /// calls so repeated trims stop churning the allocator.
+    frame_pool: FramePool,
+    pcm_scratch: Vec<i16>,
 }
 
 impl FastVad {
@@ -158,6 +226,10 @@ impl FastVad {
         if !config.enabled {
             return Ok(None);
         }
+        if cfg!(not(feature = "vad")) {
+            warn!("fast_vad needs a build with the `vad` feature; recordings are not trimmed");
+            return Ok(None);
+        }
 
         Self::validate_sample_rate(sample_rate_hz)?;
         let settings = FastVadSettings::from_config(config);
@@ -167,7 +239,10 @@ impl FastVad {
     pub fn with_settings(settings: FastVadSettings, sample_rate_hz: u32) -> Self {
         let frame_samples = Self::frame_samples(sample_rate_hz);
         let base_profile = settings.base_profile;
-        let detector = VoiceActivityDetector::new(base_profile.into());
+        let detector = Detector::new(base_profile);
+        // Upper bound on frames held at once: the pre-roll plus the silence
+        // awaiting a timeout decision.
+        let frame_pool = FramePool::new(settings.pre_roll_frames + settings.silence_timeout_frames);
 
         Self {
             settings,
@@ -177,6 +252,8 @@ impl FastVad {
             profile_switches: 0,
             frame_samples,
             sample_rate_hz,
+            frame_pool,
+            pcm_scratch: Vec::with_capacity(frame_samples),
         }
     }
 
@@ -184,6 +261,16 @@ impl FastVad {
         SUPPORTED_SAMPLE_RATES.contains(&sample_rate_hz)
     }
 
+    /// Rate the VAD runs at for audio captured at `input_hz`; unsupported
+    /// rates are resampled to 16 kHz before trimming.
+    pub fn rate_for_input(input_hz: u32) -> u32 {
+        if Self::supports_sample_rate(input_hz) {
+            input_hz
+        } else {
+            16_000
+        }
+    }
+
     pub fn sample_rate_hz(&self) -> u32 {
         self.sample_rate_hz
     }
@@ -220,12 +307,12 @@ impl FastVad {
                 profile_switches: 0,
                 final_profile: self.settings.base_profile,
                 dropped_samples: 0,
+                breaks: Vec::new(),
             });
         }
 
         self.current_profile = self.settings.base_profile;
-        self.detector = VoiceActivityDetector::new(self.current_profile.into());
-        self.detector.reset();
+        self.detector = Detector::new(self.current_profile);
         self.decision_history.clear();
         self.profile_switches = 0;
 
@@ -238,66 +325,65 @@ impl FastVad {
         let mut silence_frames = 0usize;
         let mut evaluated_frames = 0usize;
         let mut segments = 0usize;
-
-        for chunk in audio.chunks(self.frame_samples) {
-            let frame: Vec<f32> = chunk.to_vec();
-            let pcm_frame = Self::convert_frame(&frame, self.frame_samples);
-            let is_speech = self.predict_frame(&pcm_frame)?;
+        let mut breaks = Vec::new();
+        // Non-speech frames since the last speech frame, and that count when
+        // the current segment started.
+        let mut silent_run = 0usize;
+        let mut pause_before = 0usize;
+
+        for frame in audio.chunks(self.frame_samples) {
+            let is_speech = self.predict_chunk(frame)?;
             evaluated_frames += 1;
             let volatility = self.push_decision(is_speech);
             self.adjust_profile(volatility);
+            if is_speech {
+                if !in_speech {
+                    pause_before = silent_run;
+                }
+                silent_run = 0;
+            } else {
+                silent_run += 1;
+            }
 
             if !in_speech {
                 if is_speech {
                     in_speech = true;
                     self.flush_pre_roll(&mut pre_roll, &mut active_segment);
-                    if !pending_silence.is_empty() {
-                        for (silence_frame, appended) in pending_silence.drain(..) {
-                            if !appended {
-                                active_segment.extend_from_slice(&silence_frame);
-                            }
-                        }
-                    }
-                    active_segment.extend_from_slice(&frame);
+                    self.drain_pending_silence(&mut pending_silence, &mut active_segment);
+                    active_segment.extend_from_slice(frame);
                     silence_frames = 0;
                 } else {
-                    self.push_pre_roll(&mut pre_roll, &frame);
+                    self.push_pre_roll(&mut pre_roll, frame);
                 }
                 continue;
             }
 
             if is_speech {
-                if !pending_silence.is_empty() {
-                    for (silence_frame, appended) in pending_silence.drain(..) {
-                        if !appended {
-                            active_segment.extend_from_slice(&silence_frame);
-                        }
-                    }
-                }
-                active_segment.extend_from_slice(&frame);
+                self.drain_pending_silence(&mut pending_silence, &mut active_segment);
+                active_segment.extend_from_slice(frame);
                 silence_frames = 0;
                 continue;
             }
 
             silence_frames += 1;
             let appended = if silence_frames <= self.settings.post_roll_frames {
-                active_segment.extend_from_slice(&frame);
+                active_segment.extend_from_slice(frame);
                 true
             } else {
                 false
             };
-            pending_silence.push_back((frame.clone(), appended));
+            pending_silence.push_back((self.frame_pool.take_from(frame), appended));
 
             if silence_frames >= self.settings.silence_timeout_frames {
                 if !active_segment.is_empty() && active_segment.len() >= self.min_speech_samples() {
+                    push_break(&mut breaks, segments, trimmed.len(), pause_before);
                     trimmed.extend_from_slice(&active_segment);
                     segments += 1;
                 }
                 active_segment.clear();
 
                 if !pending_silence.is_empty() {
-                    self.reseed_pre_roll(&mut pre_roll, &pending_silence);
-                    pending_silence.clear();
+                    self.reseed_pre_roll(&mut pre_roll, &mut pending_silence);
                 }
 
                 in_speech = false;
@@ -306,19 +392,21 @@ impl FastVad {
         }
 
         if in_speech {
-            if !pending_silence.is_empty() {
-                for (silence_frame, appended) in pending_silence.drain(..) {
-                    if !appended {
-                        active_segment.extend_from_slice(&silence_frame);
-                    }
-                }
-            }
+            self.drain_pending_silence(&mut pending_silence, &mut active_segment);
             if !active_segment.is_empty() && active_segment.len() >= self.min_speech_samples() {
+                push_break(&mut breaks, segments, trimmed.len(), pause_before);
                 trimmed.extend_from_slice(&active_segment);
                 segments += 1;
             }
         }
 
+        for frame in pre_roll.drain(..) {
+            self.frame_pool.give(frame);
+        }
+        for (frame, _) in pending_silence.drain(..) {
+            self.frame_pool.give(frame);
+        }
+
         let dropped_samples = audio.len().saturating_sub(trimmed.len());
 
         Ok(FastVadOutcome {
@@ -328,6 +416,7 @@ impl FastVad {
             profile_switches: self.profile_switches,
             final_profile: self.current_profile,
             dropped_samples,
+            breaks,
         })
     }
 
@@ -335,35 +424,56 @@ impl FastVad {
         &self.settings
     }
 
-    fn push_pre_roll(&self, pre_roll: &mut VecDeque<Vec<f32>>, frame: &[f32]) {
+    fn push_pre_roll(&mut self, pre_roll: &mut VecDeque<Vec<f32>>, frame: &[f32]) {
         if self.settings.pre_roll_frames == 0 {
             return;
         }
         if pre_roll.len() == self.settings.pre_roll_frames {
-            pre_roll.pop_front();
+            if let Some(oldest) = pre_roll.pop_front() {
+                self.frame_pool.give(oldest);
+            }
         }
-        pre_roll.push_back(frame.to_vec());
+        pre_roll.push_back(self.frame_pool.take_from(frame));
     }
 
-    fn flush_pre_roll(&self, pre_roll: &mut VecDeque<Vec<f32>>, active_segment: &mut Vec<f32>) {
+    fn flush_pre_roll(&mut self, pre_roll: &mut VecDeque<Vec<f32>>, active_segment: &mut Vec<f32>) {
         while let Some(frame) = pre_roll.pop_front() {
             active_segment.extend_from_slice(&frame);
+            self.frame_pool.give(frame);
         }
     }
 
+    /// Appends silence frames not already covered by post-roll.
+    fn drain_pending_silence(
+        &mut self,
+        pending: &mut VecDeque<(Vec<f32>, bool)>,
+        active_segment: &mut Vec<f32>,
+    ) {
+        for (frame, appended) in pending.drain(..) {
+            if !appended {
+                active_segment.extend_from_slice(&frame);
+            }
+            self.frame_pool.give(frame);
+        }
+    }
+
+    /// Moves the tail of `pending` into the pre-roll, recycling the rest.
     fn reseed_pre_roll(
-        &self,
+        &mut self,
         pre_roll: &mut VecDeque<Vec<f32>>,
-        pending: &VecDeque<(Vec<f32>, bool)>,
+        pending: &mut VecDeque<(Vec<f32>, bool)>,
     ) {
-        pre_roll.clear();
-        if self.settings.pre_roll_frames == 0 || pending.is_empty() {
-            return;
+        for frame in pre_roll.drain(..) {
+            self.frame_pool.give(frame);
         }
-        let count = pending.len().min(self.settings.pre_roll_frames);
-        let skip = pending.len().saturating_sub(count);
-        for (frame, _) in pending.iter().skip(skip) {
-            pre_roll.push_back(frame.clone());
+        let keep = pending.len().min(self.settings.pre_roll_frames);
+        let skip = pending.len() - keep;
+        for (index, (frame, _)) in pending.drain(..).enumerate() {
+            if index >= skip {
+                pre_roll.push_back(frame);
+            } else {
+                self.frame_pool.give(frame);
+            }
         }
     }
 
@@ -406,8 +516,7 @@ impl FastVad {
             return;
         }
         self.current_profile = profile;
-        self.detector = VoiceActivityDetector::new(profile.into());
-        self.detector.reset();
+        self.detector = Detector::new(profile);
         self.decision_history.clear();
         self.profile_switches += 1;
     }
@@ -416,39 +525,18 @@ impl FastVad {
         self.settings.min_speech_frames * self.frame_samples
     }
 
-    fn predict_frame(&mut self, frame: &[i16]) -> Result<bool> {
-        match self.sample_rate_hz {
-            8_000 => self
-                .detector
-                .predict_8khz(frame)
-                .context("Earshot VAD failed to evaluate 8 kHz frame"),
-            16_000 => self
-                .detector
-                .predict_16khz(frame)
-                .context("Earshot VAD failed to evaluate 16 kHz frame"),
-            32_000 => self
-                .detector
-                .predict_32khz(frame)
-                .context("Earshot VAD failed to evaluate 32 kHz frame"),
-            48_000 => self
-                .detector
-                .predict_48khz(frame)
-                .context("Earshot VAD failed to evaluate 48 kHz frame"),
-            rate => bail!("Unsupported sample rate {} Hz for fast VAD", rate),
-        }
-    }
-
-    fn convert_frame(frame: &[f32], target_len: usize) -> Vec<i16> {
-        let mut pcm = Vec::with_capacity(target_len);
-        for &sample in frame.iter() {
-            let scaled = (sample * i16::MAX as f32).round();
-            let clamped = scaled.clamp(i16::MIN as f32, i16::MAX as f32);
-            pcm.push(clamped as i16);
-        }
-        while pcm.len() < target_len {
-            pcm.push(0);
-        }
-        pcm
+    /// Converts `frame` to zero-padded 16-bit PCM in the reusable scratch
+    /// buffer and runs the detector on it.
+    fn predict_chunk(&mut self, frame: &[f32]) -> Result<bool> {
+        let mut scratch = std::mem::take(&mut self.pcm_scratch);
+        scratch.clear();
+        scratch.resize(self.frame_samples.max(frame.len()), 0);
+        pcm::f32_to_i16(frame, &mut scratch[..frame.len()]);
+        scratch.truncate(self.frame_samples);
+
+        let result = self.detector.predict(self.sample_rate_hz, &scratch);
+        self.pcm_scratch = scratch;
+        result
     }
 }
 
@@ -464,6 +552,16 @@ impl fmt::Debug for FastVad {
     }
 }
 
+/// Records where a kept segment after the first starts in the trimmed audio.
+fn push_break(breaks: &mut Vec<SegmentBreak>, kept: usize, offset: usize, pause_frames: usize) {
+    if kept > 0 {
+        breaks.push(SegmentBreak {
+            offset,
+            pause_ms: (pause_frames as u32).saturating_mul(FRAME_MS),
+        });
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct FastVadOutcome {
     pub trimmed_audio: Vec<f32>,
@@ -472,6 +570,16 @@ pub struct FastVadOutcome {
     pub profile_switches: usize,
     pub final_profile: FastVadProfile,
     pub dropped_samples: usize,
+    /// Where each segment after the first begins, and the pause before it.
+    pub breaks: Vec<SegmentBreak>,
+}
+
+/// The start of a kept segment in `trimmed_audio`, in samples, and how
+/// much non-speech came before it in the recording.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SegmentBreak {
+    pub offset: usize,
+    pub pause_ms: u32,
 }
 
 impl FastVadOutcome {
@@ -517,7 +625,7 @@ pub fn benchmark_against_passthrough(
     })
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "vad"))]
 mod tests {
     use super::*;
     use crate::config::FastVadConfig;
@@ -620,4 +728,30 @@ mod tests {
         assert!(metrics.trimmed_samples <= metrics.original_samples);
         Ok(())
     }
+
+    #[test]
+    fn nonsensical_settings_are_repaired() {
+        let settings = FastVadSettings::from_config(&FastVadConfig {
+            silence_timeout_ms: 0,
+            pre_roll_ms: 900,
+            volatility_window: 0,
+            volatility_increase_threshold: 3.0,
+            volatility_decrease_threshold: f32::NAN,
+            ..Default::default()
+        });
+
+        // 500 ms default timeout in 30 ms frames.
+        assert_eq!(settings.silence_timeout_frames, 17);
+        assert_eq!(settings.pre_roll_frames, 17);
+        assert_eq!(settings.volatility_window, 2);
+        assert_eq!(settings.volatility_increase_threshold, 0.35);
+        assert_eq!(settings.volatility_decrease_threshold, 0.12);
+
+        let inverted = FastVadSettings::from_config(&FastVadConfig {
+            volatility_increase_threshold: 0.1,
+            volatility_decrease_threshold: 0.4,
+            ..Default::default()
+        });
+        assert!(inverted.volatility_increase_threshold > inverted.volatility_decrease_threshold);
+    }
 }
@@ -0,0 +1,223 @@
+//! Hands-free start. An always-open input stream is cut into bursts of
+//! speech by energy; a burst short enough to be the wake phrase goes to the
+//! app, which transcribes it with a local backend and starts recording when
+//! it matches `wake_word.phrase`. A long pause after speech ends a recording the wake
+//! word started.
+
+use super::capture::InputStream;
+use super::{AudioCapture, CapturedAudio};
+use crate::config::WakeWordConfig;
+use anyhow::Result;
+use std::mem;
+use std::time::Instant;
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::info;
+
+const FRAME_MS: u32 = 30;
+/// Silence that closes a burst of speech.
+const GAP_MS: u32 = 300;
+/// Shorter bursts are clicks and coughs.
+const MIN_PHRASE_MS: u32 = 300;
+
+/// What the listener picked out of the input.
+#[derive(Debug)]
+pub enum Heard {
+    /// A burst of speech short enough to be the wake phrase.
+    Candidate(CapturedAudio),
+    /// Speech stopped `stop_after_silence_ms` ago.
+    Silence,
+    /// A candidate heard at this instant was the phrase; sent by the app's
+    /// check once transcription finishes.
+    Phrase(Instant),
+}
+
+pub struct WakeWordListener {
+    _stream: InputStream,
+}
+
+impl WakeWordListener {
+    pub fn start(
+        capture: &AudioCapture,
+        config: &WakeWordConfig,
+        heard: UnboundedSender<Heard>,
+    ) -> Result<Self> {
+        let mut segmenter = Segmenter::new(config, capture.sample_rate_hint());
+        let stream = capture.listen(move |data| {
+            for event in segmenter.push(data) {
+                let _ = heard.send(event);
+            }
+        })?;
+        info!("👂 Listening for \"{}\"", config.phrase);
+        Ok(Self { _stream: stream })
+    }
+}
+
+/// Whether `transcript` is the wake `phrase`, give or take a few letters;
+/// higher `sensitivity` (0.0–1.0) forgives more.
+pub fn matches_phrase(transcript: &str, phrase: &str, sensitivity: f32) -> bool {
+    let phrase = normalize(phrase);
+    if phrase.is_empty() {
+        return false;
+    }
+    let heard = normalize(transcript);
+    if format!(" {heard} ").contains(&format!(" {phrase} ")) {
+        return true;
+    }
+    let allowed = phrase.chars().count() as f32 * 0.4 * sensitivity.clamp(0.0, 1.0);
+    edit_distance(&heard, &phrase) as f32 <= allowed.round()
+}
+
+fn normalize(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b) in b.iter().enumerate() {
+            let substituted = diagonal + usize::from(a != *b);
+            diagonal = row[j + 1];
+            row[j + 1] = substituted.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Splits a live stream into bursts of speech by frame energy.
+struct Segmenter {
+    sample_rate: u32,
+    frame_len: usize,
+    threshold: f32,
+    min_len: usize,
+    max_len: usize,
+    gap_frames: usize,
+    /// Zero never reports silence.
+    stop_frames: usize,
+    frame: Vec<f32>,
+    /// The last quiet frame, put in front of a burst for the onset.
+    previous: Vec<f32>,
+    burst: Vec<f32>,
+    /// The current burst outgrew `max_len` and is skipped.
+    too_long: bool,
+    silent_frames: usize,
+    /// Speech since silence was last reported.
+    spoke: bool,
+}
+
+impl Segmenter {
+    fn new(config: &WakeWordConfig, sample_rate: u32) -> Self {
+        let samples = |ms: u32| (u64::from(sample_rate) * u64::from(ms) / 1000) as usize;
+        let sensitivity = config.sensitivity.clamp(0.0, 1.0);
+        Self {
+            sample_rate,
+            frame_len: samples(FRAME_MS).max(1),
+            threshold: 0.004 + 0.04 * (1.0 - sensitivity),
+            min_len: samples(MIN_PHRASE_MS),
+            max_len: samples(config.max_phrase_ms),
+            gap_frames: (GAP_MS / FRAME_MS) as usize,
+            stop_frames: (config.stop_after_silence_ms / FRAME_MS) as usize,
+            frame: Vec::new(),
+            previous: Vec::new(),
+            burst: Vec::new(),
+            too_long: false,
+            silent_frames: 0,
+            spoke: false,
+        }
+    }
+
+    fn push(&mut self, data: &[f32]) -> Vec<Heard> {
+        let mut events = Vec::new();
+        for &sample in data {
+            self.frame.push(sample);
+            if self.frame.len() == self.frame_len {
+                let frame = mem::take(&mut self.frame);
+                self.on_frame(frame, &mut events);
+            }
+        }
+        events
+    }
+
+    fn on_frame(&mut self, frame: Vec<f32>, events: &mut Vec<Heard>) {
+        let rms = (frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32).sqrt();
+        if rms >= self.threshold {
+            self.silent_frames = 0;
+            self.spoke = true;
+            if !self.too_long {
+                if self.burst.is_empty() {
+                    self.burst.append(&mut self.previous);
+                }
+                self.burst.extend_from_slice(&frame);
+                if self.burst.len() > self.max_len {
+                    self.too_long = true;
+                    self.burst.clear();
+                }
+            }
+            return;
+        }
+
+        self.silent_frames += 1;
+        if self.silent_frames == self.gap_frames {
+            let burst = mem::take(&mut self.burst);
+            if !self.too_long && burst.len() >= self.min_len {
+                events.push(Heard::Candidate(CapturedAudio {
+                    samples: burst,
+                    sample_rate: self.sample_rate,
+                }));
+            }
+            self.too_long = false;
+        } else if !self.burst.is_empty() {
+            // Short pauses between the words of the phrase.
+            self.burst.extend_from_slice(&frame);
+        }
+        if self.spoke && self.silent_frames == self.stop_frames {
+            events.push(Heard::Silence);
+            self.spoke = false;
+        }
+        self.previous = frame;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_the_phrase_loosely() {
+        assert!(matches_phrase("Hey, Whisper!", "hey whisper", 0.5));
+        assert!(matches_phrase("Hey whisperer.", "hey whisper", 0.5));
+        assert!(!matches_phrase("Hey whisperer.", "hey whisper", 0.0));
+        assert!(matches_phrase("um hey whisper", "Hey Whisper", 0.0));
+        assert!(!matches_phrase("a whisper", "hey whisper", 0.5));
+        assert!(!matches_phrase("hey whisper", "", 1.0));
+    }
+
+    #[test]
+    fn reports_short_bursts_and_the_pause_after_speech() {
+        let config = WakeWordConfig {
+            stop_after_silence_ms: 900,
+            ..WakeWordConfig::default()
+        };
+        let mut segmenter = Segmenter::new(&config, 1_000);
+        let speech = |ms: usize| vec![0.2; ms];
+        let silence = |ms: usize| vec![0.0; ms];
+
+        let events = segmenter.push(&[silence(300), speech(600), silence(600)].concat());
+        let [Heard::Candidate(burst), ..] = events.as_slice() else {
+            panic!("no candidate in {events:?}");
+        };
+        // The quiet frame before, the speech, and the gap but its last frame.
+        assert_eq!(burst.len(), 30 + 600 + 270);
+
+        // Dictation is too long to be the phrase; its pause still counts.
+        let events = segmenter.push(&[speech(3_000), silence(1_000)].concat());
+        assert!(matches!(events.as_slice(), [Heard::Silence]), "{events:?}");
+        assert!(segmenter.push(&silence(2_000)).is_empty());
+    }
+}
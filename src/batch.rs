@@ -0,0 +1,94 @@
+//! Transcribes audio files outside a live recording (`hyprwhspr-rs
+//! transcribe`), using the configured backend and text pipeline.
+
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::path::Path;
+use tracing::{debug, info};
+
+use crate::app::build_vad_options;
+use crate::audio::{recording, resample_audio};
+use crate::config::{ConfigManager, ResampleQuality};
+use crate::input::TextPipeline;
+use crate::transcription::{utterance_key, PromptContext, TranscriptionBackend};
+
+pub struct FileTranscriber {
+    backend: TranscriptionBackend,
+    pipeline: TextPipeline,
+    resample_quality: ResampleQuality,
+}
+
+impl FileTranscriber {
+    pub fn new(config_manager: &ConfigManager) -> Result<Self> {
+        let config = config_manager.get();
+        let vad_options = build_vad_options(config_manager, &config);
+
+        let backend = TranscriptionBackend::build(config_manager, &config, vad_options)
+            .context("Failed to configure transcription backend")?;
+        backend
+            .initialize()
+            .context("Failed to initialize transcription backend")?;
+        info!(
+            "🎯 Active transcription backend: {}",
+            backend.provider().label()
+        );
+
+        // No target window, so per-class paste hints don't apply.
+        let mut pipeline = TextPipeline::new(
+            HashSet::new(),
+            config.word_overrides.clone(),
+            config.plugins.clone(),
+            config.postprocess.clone(),
+            config.code_dictation.clone(),
+        );
+        pipeline.set_speech_commands(&config.speech_commands);
+
+        Ok(Self {
+            backend,
+            pipeline,
+            resample_quality: config.performance.resample_quality,
+        })
+    }
+
+    /// Decodes `path` (WAV, FLAC, MP3, Ogg Vorbis), transcribes it and runs
+    /// the result through the text pipeline.
+    pub async fn transcribe(&self, path: &Path) -> Result<String> {
+        let audio = recording::load_recording(path)?;
+        if audio.is_empty() {
+            return Ok(String::new());
+        }
+
+        let samples = if audio.sample_rate == 16_000 {
+            audio.samples
+        } else {
+            debug!(
+                "Resampling {:?} from {} Hz to 16 kHz",
+                path, audio.sample_rate
+            );
+            resample_audio(
+                &audio.samples,
+                audio.sample_rate,
+                16_000,
+                self.resample_quality,
+            )
+        };
+
+        let utterance = utterance_key();
+        let context = PromptContext {
+            vocabulary: None,
+            app_class: None,
+            prev_transcript: None,
+            prompt_override: None,
+            append_vocabulary: true,
+            utterance_key: Some(&utterance),
+            short_command: false,
+        };
+        let result = self
+            .backend
+            .transcribe(samples, &context)
+            .await
+            .with_context(|| format!("Failed to transcribe {:?}", path))?;
+
+        Ok(self.pipeline.process(&result.text, None))
+    }
+}
@@ -0,0 +1,195 @@
+//! One-shot client for a running daemon, so scripts and keybindings don't
+//! have to speak the control socket protocol by hand. Commands go over the
+//! control socket, or D-Bus when the socket is off.
+
+use anyhow::{anyhow, Context, Result};
+use clap::{Parser, Subcommand};
+use hyprwhspr_rs::{
+    config::{DictationMode, TranscriptionProvider},
+    history,
+    ipc::{dbus, socket},
+    status::LIVE_STATUS_FILE,
+    ConfigManager,
+};
+use serde_json::Value;
+use std::fs;
+
+#[derive(Parser)]
+#[command(
+    name = "hyprwhsprctl",
+    version,
+    about = "Control a running hyprwhspr-rs daemon"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Start recording, or stop and transcribe
+    Toggle,
+    Start,
+    Stop,
+    /// Stop recording and throw the audio away
+    Cancel,
+    /// Start recording system output (a call, a video), or stop
+    ToggleMonitor,
+    /// Switch to the next dictation mode
+    CycleMode,
+    /// Switch to a dictation mode (raw, prose or code)
+    Mode {
+        mode: String,
+    },
+    /// Show whether the daemon is idle, recording or processing
+    Status {
+        /// Print the raw status object instead of one line
+        #[arg(long)]
+        json: bool,
+    },
+    /// Change the transcription provider
+    Provider {
+        #[command(subcommand)]
+        action: ProviderCommand,
+    },
+    /// Type the session's word count and duration at the cursor
+    InsertStats,
+    /// Stop or resume pasting transcripts (for screen sharing)
+    ToggleReadOnly,
+    /// Turn the Groq/Gemini request log on or off (saved to the config)
+    ToggleRequestLog,
+    /// Play what the microphone heard last (needs `keep_last_recording`)
+    ReplayLast {
+        /// At 1.5× speed
+        #[arg(long)]
+        fast: bool,
+    },
+    /// Read past transcriptions (needs `history.enabled`)
+    History {
+        #[command(subcommand)]
+        action: HistoryCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum ProviderCommand {
+    /// Switch to local, groq, gemini, ssh or echo and save it to the config
+    Set { provider: String },
+}
+
+#[derive(Subcommand)]
+enum HistoryCommand {
+    /// Print the latest transcript
+    Last,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    match Cli::parse().command {
+        Command::Toggle => send("toggle", "Toggle", None).await,
+        Command::Start => send("start", "Start", None).await,
+        Command::Stop => send("stop", "Stop", None).await,
+        Command::Cancel => send("cancel", "Cancel", None).await,
+        Command::ToggleMonitor => send("toggle_monitor", "ToggleMonitor", None).await,
+        Command::CycleMode => send("cycle_mode", "CycleMode", None).await,
+        Command::Mode { mode } => {
+            let mode = DictationMode::from_label(&mode)
+                .ok_or_else(|| anyhow!("unknown dictation mode {mode:?}"))?;
+            send(&format!("mode {mode}"), "SetMode", Some(mode.label())).await
+        }
+        Command::InsertStats => send("insert_stats", "InsertStats", None).await,
+        Command::ToggleReadOnly => send("toggle_read_only", "ToggleReadOnly", None).await,
+        Command::ToggleRequestLog => send("toggle_request_log", "ToggleRequestLog", None).await,
+        Command::ReplayLast { fast: false } => {
+            send("replay_last", "ReplayLast", Some("normal")).await
+        }
+        Command::ReplayLast { fast: true } => {
+            send("replay_last fast", "ReplayLast", Some("fast")).await
+        }
+        Command::Status { json } => print_status(json),
+        Command::Provider {
+            action: ProviderCommand::Set { provider },
+        } => {
+            let provider = TranscriptionProvider::from_label(&provider)
+                .ok_or_else(|| anyhow!("unknown provider {provider:?}"))?;
+            let label = provider.label().to_ascii_lowercase();
+            send(&format!("provider {label}"), "SetProvider", Some(&label)).await
+        }
+        Command::History {
+            action: HistoryCommand::Last,
+        } => print_last_transcript(),
+    }
+}
+
+/// Sends `line` over the control socket when it exists, otherwise calls
+/// `method` on the D-Bus service.
+async fn send(line: &str, method: &str, argument: Option<&str>) -> Result<()> {
+    if socket::socket_path().is_ok_and(|path| path.exists()) {
+        return socket::request(line).await;
+    }
+
+    let connection = zbus::Connection::session()
+        .await
+        .context("No control socket, and the session bus is unavailable")?;
+    let call = match argument {
+        Some(argument) => {
+            connection
+                .call_method(
+                    Some(dbus::BUS_NAME),
+                    dbus::OBJECT_PATH,
+                    Some(dbus::BUS_NAME),
+                    method,
+                    &(argument,),
+                )
+                .await
+        }
+        None => {
+            connection
+                .call_method(
+                    Some(dbus::BUS_NAME),
+                    dbus::OBJECT_PATH,
+                    Some(dbus::BUS_NAME),
+                    method,
+                    &(),
+                )
+                .await
+        }
+    };
+    call.map(drop)
+        .with_context(|| format!("{method} over D-Bus failed; is the daemon running?"))
+}
+
+fn print_status(json: bool) -> Result<()> {
+    let path = directories::ProjectDirs::from("", "", "hyprwhspr-rs")
+        .context("Failed to get config directory")?
+        .config_dir()
+        .join(LIVE_STATUS_FILE);
+    let contents = fs::read_to_string(&path)
+        .with_context(|| format!("No status at {}; is the daemon running?", path.display()))?;
+    if json {
+        println!("{}", contents.trim());
+        return Ok(());
+    }
+
+    let status: Value = serde_json::from_str(&contents).context("Unreadable status file")?;
+    let field = |name: &str| status[name].as_str().unwrap_or_default().to_string();
+    let mut line = field("state");
+    if line == "recording" {
+        let elapsed = status["elapsed_secs"].as_u64().unwrap_or_default();
+        line.push_str(&format!(" {}:{:02}", elapsed / 60, elapsed % 60));
+    }
+    if status["read_only"].as_bool().unwrap_or_default() {
+        line.push_str(", read-only");
+    }
+    println!("{line} ({}, {} mode)", field("backend"), field("mode"));
+    Ok(())
+}
+
+fn print_last_transcript() -> Result<()> {
+    let config_manager = ConfigManager::load()?;
+    let config = config_manager.get();
+    let entries = history::read_entries(&config_manager.get_history_path(&config))?;
+    let entry = entries.last().ok_or_else(|| anyhow!("History is empty"))?;
+    println!("{}", entry.text);
+    Ok(())
+}
@@ -0,0 +1,97 @@
+//! Recording cue without extra software: tints the focused window's border
+//! through Hyprland's IPC and puts the previous colors back afterwards.
+//!
+//! The previous value is also written to disk before tinting, so a daemon
+//! that dies mid-recording restores it on its next start.
+
+use crate::config::BorderIndicatorConfig;
+use crate::input::hyprland::HyprlandIpc;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+pub struct BorderIndicator {
+    tx: mpsc::UnboundedSender<bool>,
+}
+
+impl BorderIndicator {
+    /// `None` when disabled or Hyprland isn't reachable. A border left
+    /// tinted by a crashed run is restored either way.
+    pub fn maybe_new(config: &BorderIndicatorConfig, restore_file: PathBuf) -> Option<Self> {
+        let ipc = HyprlandIpc::from_env()?;
+        if !config.enabled {
+            if let Ok(previous) = fs::read_to_string(&restore_file) {
+                tokio::spawn(async move {
+                    restore(&ipc, previous.trim(), &restore_file).await;
+                });
+            }
+            return None;
+        }
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(run(ipc, config.color.clone(), restore_file, rx));
+        Some(Self { tx })
+    }
+
+    pub fn set_recording(&self, recording: bool) {
+        let _ = self.tx.send(recording);
+    }
+}
+
+/// Applies changes in order; dropping the indicator restores the border.
+async fn run(
+    ipc: HyprlandIpc,
+    color: String,
+    restore_file: PathBuf,
+    mut rx: mpsc::UnboundedReceiver<bool>,
+) {
+    if let Ok(previous) = fs::read_to_string(&restore_file) {
+        restore(&ipc, previous.trim(), &restore_file).await;
+    }
+
+    let mut saved: Option<String> = None;
+    while let Some(recording) = rx.recv().await {
+        match (recording, saved.take()) {
+            (true, None) => saved = tint(&ipc, &color, &restore_file).await,
+            (false, Some(previous)) => restore(&ipc, &previous, &restore_file).await,
+            (_, unchanged) => saved = unchanged,
+        }
+    }
+
+    if let Some(previous) = saved {
+        restore(&ipc, &previous, &restore_file).await;
+    }
+}
+
+/// Returns the value to restore once the tint is applied.
+async fn tint(ipc: &HyprlandIpc, color: &str, restore_file: &Path) -> Option<String> {
+    let previous = match ipc.active_border().await {
+        Ok(previous) => previous,
+        Err(err) => {
+            warn!("Not tinting the window border: {err:#}");
+            return None;
+        }
+    };
+    if let Err(err) = fs::write(restore_file, &previous) {
+        warn!("Not tinting the window border; can't save the current one: {err}");
+        return None;
+    }
+    if let Err(err) = ipc.set_active_border(color).await {
+        warn!("Failed to tint the window border: {err:#}");
+        let _ = fs::remove_file(restore_file);
+        return None;
+    }
+    debug!("Tinted window border (was {previous})");
+    Some(previous)
+}
+
+async fn restore(ipc: &HyprlandIpc, previous: &str, restore_file: &Path) {
+    match ipc.set_active_border(previous).await {
+        Ok(()) => {
+            debug!("Restored window border {previous}");
+            let _ = fs::remove_file(restore_file);
+        }
+        Err(err) => warn!("Failed to restore the window border: {err:#}"),
+    }
+}
@@ -0,0 +1,223 @@
+//! Looks up the calendar event happening right now (via khal or an iCalendar
+//! file) so utterances dictated during a meeting can be tagged with it.
+
+use crate::config::{CalendarConfig, CalendarSource};
+use std::collections::HashMap;
+use std::fs;
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use time::macros::format_description;
+use time::{OffsetDateTime, PrimitiveDateTime, UtcOffset};
+use tracing::{debug, warn};
+
+/// Calendars change rarely; avoid re-reading them for every utterance.
+const CACHE_TTL: Duration = Duration::from_secs(60);
+
+pub struct MeetingCalendar {
+    source: CalendarSource,
+    ics_path: Option<String>,
+    profiles: HashMap<String, String>,
+    cached: Mutex<Option<(Instant, Option<String>)>>,
+}
+
+impl MeetingCalendar {
+    pub fn maybe_new(config: &CalendarConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        if config.source == CalendarSource::Ics && config.ics_path.is_none() {
+            warn!("Calendar source is \"ics\" but no ics_path is set; meeting tagging disabled");
+            return None;
+        }
+
+        Some(Self {
+            source: config.source,
+            ics_path: config.ics_path.clone(),
+            profiles: config.profiles.clone(),
+            cached: Mutex::new(None),
+        })
+    }
+
+    /// Title of the event in progress, if any.
+    pub fn current_meeting(&self) -> Option<String> {
+        let mut cached = self.cached.lock().ok()?;
+        if let Some((checked_at, title)) = cached.as_ref() {
+            if checked_at.elapsed() < CACHE_TTL {
+                return title.clone();
+            }
+        }
+
+        let title = match self.source {
+            CalendarSource::Khal => khal_current_event(),
+            CalendarSource::Ics => self.ics_path.as_deref().and_then(ics_current_event),
+        };
+        *cached = Some((Instant::now(), title.clone()));
+        title
+    }
+
+    /// The profile `calendar.profiles` picks for a meeting called `title`.
+    pub fn profile_for(&self, title: &str) -> Option<&str> {
+        meeting_profile(&self.profiles, title)
+    }
+}
+
+/// The profile whose key appears in `title` (case-insensitive); the
+/// longest key wins, so "team standup" beats "standup".
+fn meeting_profile<'a>(profiles: &'a HashMap<String, String>, title: &str) -> Option<&'a str> {
+    let title = title.to_lowercase();
+    profiles
+        .iter()
+        .filter(|(key, _)| {
+            let key = key.trim();
+            !key.is_empty() && title.contains(&key.to_lowercase())
+        })
+        .max_by_key(|(key, _)| key.trim().len())
+        .map(|(_, profile)| profile.as_str())
+}
+
+fn khal_current_event() -> Option<String> {
+    let output = Command::new("khal")
+        .args([
+            "list",
+            "--day-format",
+            "",
+            "--format",
+            "{title}",
+            "now",
+            "1m",
+        ])
+        .output()
+        .inspect_err(|err| debug!("khal unavailable: {}", err))
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+}
+
+fn ics_current_event(path: &str) -> Option<String> {
+    let contents = fs::read_to_string(path)
+        .inspect_err(|err| debug!("Failed to read calendar {:?}: {}", path, err))
+        .ok()?;
+    let now = OffsetDateTime::now_utc();
+    let local = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+    event_at(&contents, now, local)
+}
+
+/// Summary of the first timed `VEVENT` spanning `now`. Floating and `TZID`
+/// times are read as `local`; all-day and recurring events are ignored.
+fn event_at(ics: &str, now: OffsetDateTime, local: UtcOffset) -> Option<String> {
+    // Long lines are folded with a leading space or tab.
+    let unfolded = ics
+        .replace("\r\n", "\n")
+        .replace("\n ", "")
+        .replace("\n\t", "");
+
+    let mut in_event = false;
+    let mut start = None;
+    let mut end = None;
+    let mut summary = None;
+    let mut recurring = false;
+
+    for line in unfolded.lines() {
+        match line {
+            "BEGIN:VEVENT" => {
+                in_event = true;
+                (start, end, summary, recurring) = (None, None, None, false);
+            }
+            "END:VEVENT" => {
+                in_event = false;
+                if let (Some(start), Some(end), Some(summary)) = (start, end, summary.take()) {
+                    if !recurring && start <= now && now < end {
+                        return Some(summary);
+                    }
+                }
+            }
+            _ if in_event => {
+                let Some((name, value)) = line.split_once(':') else {
+                    continue;
+                };
+                match name.split(';').next() {
+                    Some("DTSTART") => start = parse_ics_time(value, local),
+                    Some("DTEND") => end = parse_ics_time(value, local),
+                    Some("SUMMARY") => summary = Some(value.replace("\\,", ",")),
+                    Some("RRULE") => recurring = true,
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn parse_ics_time(value: &str, local: UtcOffset) -> Option<OffsetDateTime> {
+    let format = format_description!("[year][month][day]T[hour][minute][second]");
+    match value.strip_suffix('Z') {
+        Some(utc) => PrimitiveDateTime::parse(utc, format)
+            .ok()
+            .map(PrimitiveDateTime::assume_utc),
+        None => PrimitiveDateTime::parse(value, format)
+            .ok()
+            .map(|time| time.assume_offset(local)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    #[test]
+    fn finds_the_event_in_progress() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+            BEGIN:VEVENT\r\n\
+            DTSTART:20261016T090000Z\r\n\
+            DTEND:20261016T093000Z\r\n\
+            SUMMARY:Standup\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            DTSTART;TZID=Europe/Berlin:20261016T140000\r\n\
+            DTEND;TZID=Europe/Berlin:20261016T150000\r\n\
+            SUMMARY:Design review\\, search\r\n  ranking\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            DTSTART;VALUE=DATE:20261016\r\n\
+            DTEND;VALUE=DATE:20261017\r\n\
+            SUMMARY:Holiday\r\n\
+            END:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+        let berlin = UtcOffset::from_hms(2, 0, 0).unwrap();
+
+        assert_eq!(
+            event_at(ics, datetime!(2026-10-16 09:15 UTC), berlin).as_deref(),
+            Some("Standup")
+        );
+        assert_eq!(
+            event_at(ics, datetime!(2026-10-16 12:30 UTC), berlin).as_deref(),
+            Some("Design review, search ranking")
+        );
+        assert_eq!(event_at(ics, datetime!(2026-10-16 10:00 UTC), berlin), None);
+    }
+
+    #[test]
+    fn picks_the_most_specific_meeting_profile() {
+        let profiles = HashMap::from([
+            ("standup".to_string(), "notes".to_string()),
+            ("Team Standup".to_string(), "team-notes".to_string()),
+        ]);
+        assert_eq!(
+            meeting_profile(&profiles, "Weekly team standup"),
+            Some("team-notes")
+        );
+        assert_eq!(meeting_profile(&profiles, "Standup"), Some("notes"));
+        assert_eq!(meeting_profile(&profiles, "1:1"), None);
+    }
+}
@@ -1,17 +1,56 @@
 use crate::transcription::DEFAULT_PROMPT;
 use anyhow::{anyhow, Context, Result};
 use jsonc_parser::{parse_to_serde_value, ParseOptions};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::collections::HashMap;
 use std::env;
+use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::{Duration, SystemTime};
 use tokio::sync::watch;
 use tokio::time;
 
+mod patch;
+
+use patch::Change;
+
+/// Top-level keys that older configs used, with the paths they migrate to.
+const LEGACY_CONFIG_KEYS: &[(&str, &[&[&str]])] = &[
+    ("primary_shortcut", &[&["shortcuts", "press"]]),
+    ("model", &[&["transcription", "whisper_cpp", "model"]]),
+    ("threads", &[&["transcription", "whisper_cpp", "threads"]]),
+    (
+        "gpu_layers",
+        &[&["transcription", "whisper_cpp", "gpu_layers"]],
+    ),
+    (
+        "whisper_prompt",
+        &[
+            &["transcription", "whisper_cpp", "prompt"],
+            &["transcription", "groq", "prompt"],
+            &["transcription", "gemini", "prompt"],
+        ],
+    ),
+    (
+        "models_dirs",
+        &[&["transcription", "whisper_cpp", "models_dirs"]],
+    ),
+    (
+        "no_speech_threshold",
+        &[&["transcription", "whisper_cpp", "no_speech_threshold"]],
+    ),
+    (
+        "fallback_cli",
+        &[&["transcription", "whisper_cpp", "fallback_cli"]],
+    ),
+    ("vad", &[&["transcription", "whisper_cpp", "vad"]]),
+];
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(default)]
 pub struct ShortcutsConfig {
@@ -20,6 +59,84 @@ pub struct ShortcutsConfig {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub press: Option<String>,
+
+    /// Discards the active recording without transcribing it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cancel: Option<String>,
+
+    /// Cycles the dictation mode (raw → prose → code).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+
+    /// Minimum time between two press-shortcut toggles.
+    pub press_debounce_ms: u64,
+
+    /// Ignore re-presses that arrive right after a release, as emitted by
+    /// keyboards and remappers that implement key repeat with press/release pairs.
+    pub suppress_key_repeat: bool,
+
+    /// What each trigger does; see [`ShortcutsConfig::action`] for defaults.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub actions: HashMap<ShortcutTrigger, ShortcutAction>,
+
+    /// Two press-shortcut taps this close together count as a double press.
+    pub double_press_ms: u64,
+}
+
+impl ShortcutsConfig {
+    /// `press_debounce_ms`, halved to fit inside `double_press_ms` when a
+    /// double press is configured, since a longer one would swallow the
+    /// second tap.
+    pub fn press_debounce(&self) -> Duration {
+        let debounce = if self.action(ShortcutTrigger::DoublePress) != ShortcutAction::None
+            && self.press_debounce_ms >= self.double_press_ms
+        {
+            self.double_press_ms / 2
+        } else {
+            self.press_debounce_ms
+        };
+        Duration::from_millis(debounce)
+    }
+
+    /// Press toggles and hold is push-to-talk unless configured otherwise;
+    /// double press does nothing by default.
+    pub fn action(&self, trigger: ShortcutTrigger) -> ShortcutAction {
+        self.actions
+            .get(&trigger)
+            .copied()
+            .unwrap_or(match trigger {
+                ShortcutTrigger::Press => ShortcutAction::Toggle,
+                ShortcutTrigger::Hold => ShortcutAction::PushToTalk,
+                ShortcutTrigger::DoublePress => ShortcutAction::None,
+            })
+    }
+}
+
+/// Ways the press and hold shortcuts can fire.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ShortcutTrigger {
+    Press,
+    Hold,
+    /// Two taps of the press shortcut.
+    DoublePress,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ShortcutAction {
+    /// Start recording, or stop and transcribe the running one.
+    Toggle,
+    /// Record while the shortcut is held; only the hold trigger sees releases.
+    PushToTalk,
+    /// Paste the last injected text again.
+    ReinjectLast,
+    /// Record system output (a call, a video) instead of the microphone,
+    /// or stop the running recording.
+    ToggleMonitor,
+    CycleMode,
+    Cancel,
+    None,
 }
 
 impl Default for ShortcutsConfig {
@@ -27,390 +144,2051 @@ impl Default for ShortcutsConfig {
         Self {
             hold: None,
             press: Some(default_primary_shortcut()),
+            cancel: None,
+            mode: None,
+            press_debounce_ms: default_press_debounce_ms(),
+            suppress_key_repeat: true,
+            actions: HashMap::new(),
+            double_press_ms: default_double_press_ms(),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Paste key combination per window class (case-insensitive), e.g.
+/// `{ "emacs": "ctrl+y", "default": "ctrl+v" }`. Classes without an entry
+/// use Ctrl+Shift+V in known terminals and `default` elsewhere.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 #[serde(default)]
 pub struct PasteHintsConfig {
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    /// Unset follows `shift_paste`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<PasteKeys>,
+    /// Extra terminal-like classes (also matched per `.`/`-`/`_` component)
+    /// that paste with Ctrl+Shift+V.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub shift: Vec<String>,
+    #[serde(flatten)]
+    pub per_class: HashMap<String, PasteKeys>,
 }
 
-impl Default for PasteHintsConfig {
-    fn default() -> Self {
-        Self { shift: Vec::new() }
+impl PasteHintsConfig {
+    pub fn keys_for(&self, window_class: Option<&str>) -> Option<&PasteKeys> {
+        let class = window_class?;
+        self.per_class
+            .iter()
+            .find(|(key, _)| key.trim().eq_ignore_ascii_case(class))
+            .map(|(_, keys)| keys)
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub struct Config {
-    #[serde(default = "default_primary_shortcut", skip_serializing)]
-    pub primary_shortcut: String,
-
-    #[serde(default)]
-    pub shortcuts: ShortcutsConfig,
-
-    #[serde(default)]
-    pub word_overrides: HashMap<String, String>,
-
-    #[serde(default)]
-    pub audio_feedback: bool,
-
-    #[serde(default = "default_volume")]
-    pub start_sound_volume: f32,
-
-    #[serde(default = "default_volume")]
-    pub stop_sound_volume: f32,
-
-    #[serde(default)]
-    pub start_sound_path: Option<String>,
-
-    #[serde(default)]
-    pub stop_sound_path: Option<String>,
-
-    #[serde(default = "default_auto_copy_clipboard")]
-    pub auto_copy_clipboard: bool,
-
-    #[serde(default = "default_shift_paste")]
-    pub shift_paste: bool,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasteModifier {
+    Ctrl,
+    Shift,
+    Alt,
+    Super,
+}
 
-    #[serde(default)]
-    pub paste_hints: PasteHintsConfig,
+impl PasteModifier {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Ctrl => "ctrl",
+            Self::Shift => "shift",
+            Self::Alt => "alt",
+            Self::Super => "super",
+        }
+    }
+}
 
-    #[serde(default)]
-    pub audio_device: Option<usize>,
+/// A key combination written as `ctrl+shift+v`: modifiers, then one key
+/// (a character or a name such as `insert`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct PasteKeys {
+    pub modifiers: Vec<PasteModifier>,
+    pub key: String,
+}
 
-    #[serde(default)]
-    pub fast_vad: FastVadConfig,
+impl PasteKeys {
+    pub fn ctrl_v() -> Self {
+        Self {
+            modifiers: vec![PasteModifier::Ctrl],
+            key: "v".to_string(),
+        }
+    }
 
-    #[serde(default)]
-    pub transcription: TranscriptionConfig,
+    pub fn ctrl_shift_v() -> Self {
+        Self {
+            modifiers: vec![PasteModifier::Ctrl, PasteModifier::Shift],
+            key: "v".to_string(),
+        }
+    }
 
-    #[serde(default, rename = "model", skip_serializing)]
-    legacy_model: Option<String>,
+    pub fn parse(value: &str) -> Result<Self> {
+        let mut parts: Vec<String> = value
+            .split('+')
+            .map(|part| part.trim().to_ascii_lowercase())
+            .collect();
+        let key = parts.pop().filter(|key| !key.is_empty());
+        let Some(key) = key else {
+            return Err(anyhow!("key combination {value:?} has no key"));
+        };
 
-    #[serde(default, rename = "threads", skip_serializing)]
-    legacy_threads: Option<usize>,
+        let mut modifiers = Vec::new();
+        for part in parts {
+            let modifier = match part.as_str() {
+                "ctrl" | "control" => PasteModifier::Ctrl,
+                "shift" => PasteModifier::Shift,
+                "alt" => PasteModifier::Alt,
+                "super" | "logo" | "meta" | "win" => PasteModifier::Super,
+                other => return Err(anyhow!("unknown modifier {other:?} in {value:?}")),
+            };
+            if !modifiers.contains(&modifier) {
+                modifiers.push(modifier);
+            }
+        }
+        Ok(Self { modifiers, key })
+    }
+}
 
-    #[serde(default, rename = "gpu_layers", skip_serializing)]
-    legacy_gpu_layers: Option<i32>,
+impl TryFrom<String> for PasteKeys {
+    type Error = anyhow::Error;
 
-    #[serde(default, rename = "whisper_prompt", skip_serializing)]
-    legacy_whisper_prompt: Option<String>,
+    fn try_from(value: String) -> Result<Self> {
+        Self::parse(&value)
+    }
+}
 
-    #[serde(default, rename = "models_dirs", skip_serializing)]
-    legacy_models_dirs: Option<Vec<String>>,
+impl From<PasteKeys> for String {
+    fn from(keys: PasteKeys) -> Self {
+        keys.to_string()
+    }
+}
 
-    #[serde(default, rename = "no_speech_threshold", skip_serializing)]
-    legacy_no_speech_threshold: Option<f32>,
+impl fmt::Display for PasteKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for modifier in &self.modifiers {
+            write!(f, "{}+", modifier.label())?;
+        }
+        f.write_str(&self.key)
+    }
+}
 
-    #[serde(default, rename = "fallback_cli", skip_serializing)]
-    legacy_fallback_cli: Option<bool>,
+/// How text reaches the focused window. `per_class` entries (keyed by window
+/// class, case-insensitive) override `method`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct InjectionConfig {
+    pub method: InjectionMethod,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub per_class: HashMap<String, InjectionMethod>,
+    /// Profiles can override this per application.
+    pub clipboard_history: ClipboardHistory,
+    pub post_check: PostCheckConfig,
+}
 
-    #[serde(default, rename = "vad", skip_serializing)]
-    legacy_vad: Option<VadConfig>,
+/// Rules the final text must pass before it is typed or pasted. Text that
+/// fails one is only copied to the clipboard, with a warning. Every rule is
+/// off by default.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct PostCheckConfig {
+    /// Longest text injected as-is; 0 = no limit.
+    pub max_chars: usize,
+    /// Regexes the text must not match, e.g. `(?i)^sure[,!]`.
+    pub deny_patterns: Vec<String>,
+    /// Writing systems the text must be in; empty skips the check.
+    pub scripts: Vec<WritingScript>,
 }
 
-fn default_gpu_layers() -> i32 {
-    999 // Offload all layers to GPU by default
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum InjectionMethod {
+    /// Copy to the clipboard and send the paste shortcut.
+    #[default]
+    Paste,
+    /// Type character by character; works where paste is blocked and
+    /// leaves the clipboard alone.
+    Type,
 }
 
-fn default_primary_shortcut() -> String {
-    "SUPER+ALT+R".to_string() // R for Rust version (Python uses D)
+/// What clipboard managers get to see of a copied transcript.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipboardHistory {
+    /// Whatever the running clipboard manager does on its own.
+    #[default]
+    Default,
+    /// Also hand the text to `cliphist store`.
+    Store,
+    /// Offer `x-kde-passwordManagerHint: secret` so managers skip it.
+    Exclude,
 }
 
-fn default_model() -> String {
-    "base".to_string()
+impl InjectionConfig {
+    pub fn method_for(&self, window_class: Option<&str>) -> InjectionMethod {
+        window_class
+            .and_then(|class| {
+                self.per_class
+                    .iter()
+                    .find(|(key, _)| key.trim().eq_ignore_ascii_case(class))
+                    .map(|(_, method)| *method)
+            })
+            .unwrap_or(self.method)
+    }
 }
 
-fn default_threads() -> usize {
-    4
+/// Changes how text is delivered while a Hyprland workspace or monitor is
+/// focused, e.g. never paste where a screen recorder is capturing. The first
+/// matching rule wins; a rule without `workspace` or `monitor` never matches.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InjectionRule {
+    /// Workspace name or numeric id.
+    #[serde(default)]
+    pub workspace: Option<String>,
+    /// Monitor connector name (e.g. `DP-2`).
+    #[serde(default)]
+    pub monitor: Option<String>,
+    pub action: InjectionAction,
 }
 
-fn default_whisper_prompt() -> String {
-    DEFAULT_PROMPT.to_string()
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InjectionAction {
+    /// Neither paste nor touch the clipboard.
+    Skip,
+    /// Copy to the clipboard without pasting.
+    ClipboardOnly,
 }
 
-fn default_volume() -> f32 {
-    0.3
+impl InjectionRule {
+    pub fn matches(&self, workspace_id: i64, workspace_name: &str, monitor: &str) -> bool {
+        if self.workspace.is_none() && self.monitor.is_none() {
+            return false;
+        }
+        let workspace_matches = self.workspace.as_deref().is_none_or(|wanted| {
+            let wanted = wanted.trim();
+            wanted == workspace_name || wanted.parse() == Ok(workspace_id)
+        });
+        let monitor_matches = self
+            .monitor
+            .as_deref()
+            .is_none_or(|wanted| wanted.trim().eq_ignore_ascii_case(monitor));
+        workspace_matches && monitor_matches
+    }
 }
 
-fn default_auto_copy_clipboard() -> bool {
-    true
+/// Code dictation grammar ("fat arrow", "snake case alpha beta"). Always on
+/// when `enabled`, otherwise only while one of `window_classes` is focused.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct CodeDictationConfig {
+    pub enabled: bool,
+    pub window_classes: Vec<String>,
 }
 
-fn default_shift_paste() -> bool {
-    true
+impl CodeDictationConfig {
+    pub fn active_for(&self, window_class: Option<&str>) -> bool {
+        self.enabled
+            || window_class.is_some_and(|class| {
+                self.window_classes
+                    .iter()
+                    .any(|entry| entry.trim().eq_ignore_ascii_case(class))
+            })
+    }
 }
 
-fn default_no_speech_threshold() -> f32 {
-    0.60
+/// Optional text pipeline stages. `script` is an inline Rhai transformation
+/// applied just before injection (requires the `scripting` feature).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PostProcessConfig {
+    pub normalize_addresses: bool,
+    pub typography: TypographyConfig,
+    pub whitespace: WhitespaceConfig,
+    pub script: Option<String>,
+    /// Words kept exactly as written here, whatever capitalization or word
+    /// overrides did to them.
+    pub case_exceptions: Vec<String>,
 }
 
-fn default_vad_model() -> String {
-    "ggml-silero-v5.1.2.bin".to_string()
+impl Default for PostProcessConfig {
+    fn default() -> Self {
+        Self {
+            normalize_addresses: true,
+            typography: TypographyConfig::default(),
+            whitespace: WhitespaceConfig::default(),
+            script: None,
+            case_exceptions: Vec::new(),
+        }
+    }
 }
 
-fn default_vad_threshold() -> f32 {
-    0.50
+/// Which text pipeline layers run over a transcript.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DictationMode {
+    /// The transcript exactly as the backend returned it.
+    Raw,
+    /// The full cleanup pipeline.
+    #[default]
+    Prose,
+    /// Code dictation and symbol merging, without auto-capitalization or
+    /// typography.
+    Code,
 }
 
-fn default_vad_min_speech_ms() -> u32 {
-    250
+impl DictationMode {
+    pub const ALL: [DictationMode; 3] = [Self::Raw, Self::Prose, Self::Code];
+
+    /// The mode after this one in the shortcut's cycle.
+    pub fn next(self) -> Self {
+        match self {
+            Self::Raw => Self::Prose,
+            Self::Prose => Self::Code,
+            Self::Code => Self::Raw,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Raw => "raw",
+            Self::Prose => "prose",
+            Self::Code => "code",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.label().eq_ignore_ascii_case(label.trim()))
+    }
 }
 
-fn default_vad_min_silence_ms() -> u32 {
-    100
+impl fmt::Display for DictationMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
 }
 
-fn default_vad_max_speech_s() -> f32 {
-    f32::INFINITY
+/// Spoken punctuation ("comma", "new line"). `commands` is merged over the
+/// built-in table, keyed by phrase (case-insensitive).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SpeechCommandsConfig {
+    pub enabled: bool,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub commands: HashMap<String, SpeechCommand>,
 }
 
-fn default_vad_speech_pad_ms() -> u32 {
-    30
+impl Default for SpeechCommandsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            commands: HashMap::new(),
+        }
+    }
 }
 
-fn default_vad_samples_overlap() -> f32 {
-    0.10
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum SpeechCommand {
+    /// `false` turns a built-in phrase off.
+    Enabled(bool),
+    /// Inserted text; a built-in phrase keeps its punctuation handling.
+    Replacement(String),
+    Custom {
+        replacement: String,
+        /// Joins the preceding word, dropping punctuation before it (like
+        /// "comma").
+        #[serde(default)]
+        attach: bool,
+    },
 }
 
-fn default_transcription_request_timeout_secs() -> u64 {
-    45
+/// What saying `phrase` (or the `insert_stats` IPC command) inserts
+/// instead of a transcript; see [`crate::session::SessionStats`] for the
+/// placeholders.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SessionStatsConfig {
+    /// Empty turns the spoken command off.
+    pub phrase: String,
+    pub template: String,
 }
 
-fn default_transcription_max_retries() -> u32 {
-    2
+impl Default for SessionStatsConfig {
+    fn default() -> Self {
+        Self {
+            phrase: "insert word count".to_string(),
+            template: "{words} words in {dictations} dictations over {duration} ({date} {time})"
+                .to_string(),
+        }
+    }
 }
 
-fn default_groq_model() -> String {
-    "whisper-large-v3-turbo".to_string()
+/// Utterances that control the daemon instead of being typed; see
+/// [`crate::voice_commands`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct VoiceCommandsConfig {
+    /// Off by default: a lone "undo" would otherwise erase text someone
+    /// meant to type.
+    pub enabled: bool,
+    /// Merged over the built-in phrases; `off` drops one.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub commands: HashMap<String, VoiceAction>,
 }
 
-fn default_groq_endpoint() -> String {
-    "https://api.groq.com/openai/v1/audio/transcriptions".to_string()
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VoiceAction {
+    /// Erases the previous injection, like "scratch that" said alone.
+    Undo,
+    /// Stops listening for the wake word, and drops a recording it
+    /// started, until a recording is started another way. Typed as text
+    /// when there is no wake-word listening to stop.
+    StopDictation,
+    CycleMode,
+    RawMode,
+    ProseMode,
+    CodeMode,
+    /// Turns a built-in phrase off.
+    Off,
 }
 
-fn default_gemini_model() -> String {
-    "gemini-2.5-pro-exp-0827".to_string()
+impl VoiceAction {
+    pub fn for_mode(mode: DictationMode) -> Self {
+        match mode {
+            DictationMode::Raw => Self::RawMode,
+            DictationMode::Prose => Self::ProseMode,
+            DictationMode::Code => Self::CodeMode,
+        }
+    }
+
+    pub fn mode(self) -> Option<DictationMode> {
+        match self {
+            Self::RawMode => Some(DictationMode::Raw),
+            Self::ProseMode => Some(DictationMode::Prose),
+            Self::CodeMode => Some(DictationMode::Code),
+            _ => None,
+        }
+    }
 }
 
-fn default_gemini_endpoint() -> String {
-    "https://generativelanguage.googleapis.com/v1beta/models".to_string()
+/// Injecting a one-line summary of each transcript while the full text is
+/// appended to `archive`; see [`crate::summary`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct DualOutputConfig {
+    pub enabled: bool,
+    /// Markdown file for the full transcripts, with the placeholders of a
+    /// file export.
+    pub archive: String,
+    pub header: String,
+    pub summary: SummaryMethod,
+    /// Run with the transcript on stdin when `summary` is `command`; the
+    /// first line it prints is injected.
+    pub command: Vec<String>,
+    pub timeout_ms: u64,
 }
 
-fn default_gemini_temperature() -> f32 {
-    0.0
+impl Default for DualOutputConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            archive: "~/Documents/hyprwhspr/{date}.md".to_string(),
+            header: default_file_header(),
+            summary: SummaryMethod::default(),
+            command: Vec::new(),
+            timeout_ms: 10_000,
+        }
+    }
 }
 
-fn default_gemini_max_output_tokens() -> u32 {
-    1024
+impl DualOutputConfig {
+    /// The archive as a file export, while dual output is on.
+    pub fn archive_target(&self) -> Option<ExportTarget> {
+        (self.enabled && !self.archive.trim().is_empty()).then(|| ExportTarget::File {
+            path: self.archive.clone(),
+            header: self.header.clone(),
+            notes: false,
+            timestamps: false,
+        })
+    }
 }
 
-fn default_fast_vad_min_speech_ms() -> u32 {
-    120
+/// How dual output shortens a transcript.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SummaryMethod {
+    #[default]
+    FirstSentence,
+    /// An external summarizer such as an LLM CLI; falls back to the first
+    /// sentence when it fails.
+    Command,
 }
 
-fn default_fast_vad_silence_timeout_ms() -> u32 {
-    500
+/// Prompt and cleanup settings used while one application is focused.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct AppProfile {
+    /// Replaces the provider's prompt (template variables still apply).
+    pub whisper_prompt: Option<String>,
+    /// Applied before the global `word_overrides`, so they win on overlap.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub word_overrides: HashMap<String, String>,
+    /// Spoken punctuation ("period", "new line"); unset follows
+    /// `speech_commands.enabled`.
+    pub speech_replacements_enabled: Option<bool>,
+    /// Overrides `injection.clipboard_history`.
+    pub clipboard_history: Option<ClipboardHistory>,
+    /// Applied over `postprocess.whitespace` (including its `per_class`).
+    pub whitespace: Option<WhitespaceOverride>,
+    /// Replaces `exports`; an empty list exports nothing.
+    pub exports: Option<Vec<ExportTarget>>,
 }
 
-fn default_fast_vad_pre_roll_ms() -> u32 {
-    120
+/// Looks up the profile for `window_class` (case-insensitive).
+pub fn profile_for<'a>(
+    profiles: &'a HashMap<String, AppProfile>,
+    window_class: Option<&str>,
+) -> Option<&'a AppProfile> {
+    let class = window_class?;
+    profiles
+        .iter()
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case(class))
+        .map(|(_, profile)| profile)
 }
 
-fn default_fast_vad_post_roll_ms() -> u32 {
-    150
+/// Whitespace added around the trimmed transcript. `per_class` entries
+/// (keyed by Hyprland window class, case-insensitive) override the defaults.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct WhitespaceConfig {
+    pub leading_space: bool,
+    pub trailing: TrailingWhitespace,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub per_class: HashMap<String, WhitespaceOverride>,
 }
 
-fn default_fast_vad_volatility_window() -> u32 {
-    24
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct WhitespaceOverride {
+    pub leading_space: Option<bool>,
+    pub trailing: Option<TrailingWhitespace>,
 }
 
-fn default_fast_vad_volatility_increase_threshold() -> f32 {
-    0.35
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TrailingWhitespace {
+    #[default]
+    None,
+    Space,
+    Newline,
 }
 
-fn default_fast_vad_volatility_decrease_threshold() -> f32 {
-    0.12
+impl WhitespaceConfig {
+    /// Resolves `(leading_space, trailing)` for the focused window; the
+    /// active profile's override, if any, wins over `per_class`.
+    pub fn resolve(
+        &self,
+        window_class: Option<&str>,
+        profile: Option<&WhitespaceOverride>,
+    ) -> (bool, TrailingWhitespace) {
+        let mut leading = self.leading_space;
+        let mut trailing = self.trailing;
+
+        let entry = window_class.and_then(|class| {
+            self.per_class
+                .iter()
+                .find(|(key, _)| key.trim().eq_ignore_ascii_case(class))
+                .map(|(_, entry)| entry)
+        });
+        for entry in entry.into_iter().chain(profile) {
+            leading = entry.leading_space.unwrap_or(leading);
+            trailing = entry.trailing.unwrap_or(trailing);
+        }
+
+        (leading, trailing)
+    }
 }
 
+/// Locale typography (curly quotes, French spacing). Off by default since
+/// terminals and code editors want plain ASCII.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(default)]
-pub struct VadConfig {
+pub struct TypographyConfig {
     pub enabled: bool,
-    pub model: String,
-    pub threshold: f32,
-    pub min_speech_ms: u32,
-    pub min_silence_ms: u32,
-    pub max_speech_s: f32,
-    pub speech_pad_ms: u32,
+    pub language: String,
+    pub smart_quotes: bool,
+    pub skip_terminals: bool,
+}
+
+impl Default for TypographyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            language: "en".to_string(),
+            smart_quotes: true,
+            skip_terminals: true,
+        }
+    }
+}
+
+/// External text post-processor run as a subprocess speaking line-delimited
+/// JSON (see `input::plugins`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PluginConfig {
+    pub name: String,
+    pub enabled: bool,
+    pub command: Vec<String>,
+    pub position: PluginPosition,
+    pub timeout_ms: u64,
+}
+
+impl Default for PluginConfig {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            enabled: true,
+            command: Vec::new(),
+            position: PluginPosition::default(),
+            timeout_ms: default_plugin_timeout_ms(),
+        }
+    }
+}
+
+fn default_plugin_timeout_ms() -> u64 {
+    1000
+}
+
+/// Where a plugin runs in the text pipeline: on the raw transcript before
+/// the built-in cleanup, or on the cleaned text just before injection.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginPosition {
+    BeforeCleanup,
+    #[default]
+    AfterCleanup,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Config {
+    #[serde(default = "default_primary_shortcut", skip_serializing)]
+    pub primary_shortcut: String,
+
+    #[serde(default)]
+    pub shortcuts: ShortcutsConfig,
+
+    #[serde(default)]
+    pub word_overrides: HashMap<String, String>,
+
+    #[serde(default)]
+    pub speech_commands: SpeechCommandsConfig,
+
+    #[serde(default)]
+    pub session_stats: SessionStatsConfig,
+
+    #[serde(default)]
+    pub voice_commands: VoiceCommandsConfig,
+
+    #[serde(default)]
+    pub dual_output: DualOutputConfig,
+
+    /// Mode at startup; shortcuts and IPC switch it at runtime.
+    #[serde(default)]
+    pub dictation_mode: DictationMode,
+
+    /// Per-application tweaks keyed by Hyprland window class.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub profiles: HashMap<String, AppProfile>,
+
+    #[serde(default)]
+    pub audio_feedback: bool,
+
+    #[serde(default = "default_volume")]
+    pub start_sound_volume: f32,
+
+    #[serde(default = "default_volume")]
+    pub stop_sound_volume: f32,
+
+    #[serde(default)]
+    pub start_sound_path: Option<String>,
+
+    #[serde(default)]
+    pub stop_sound_path: Option<String>,
+
+    #[serde(default)]
+    pub start_sound_overlap: StartSoundOverlap,
+
+    /// Audio from before the shortcut press each recording starts with;
+    /// nonzero keeps the microphone open between recordings.
+    #[serde(default)]
+    pub pre_roll_ms: u64,
+
+    /// Stops and transcribes a recording that runs this long, e.g. when a
+    /// hold shortcut gets stuck. 0 leaves recordings unbounded.
+    #[serde(default = "default_max_recording_secs")]
+    pub max_recording_secs: u64,
+
+    #[serde(default)]
+    pub feedback_sounds: FeedbackSoundsConfig,
+
+    #[serde(default)]
+    pub do_not_disturb: DoNotDisturbConfig,
+
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+
+    #[serde(default = "default_auto_copy_clipboard")]
+    pub auto_copy_clipboard: bool,
+
+    #[serde(default = "default_shift_paste")]
+    pub shift_paste: bool,
+
+    #[serde(default)]
+    pub paste_hints: PasteHintsConfig,
+
+    #[serde(default)]
+    pub audio_device: Option<AudioDeviceSelector>,
+
+    #[serde(default)]
+    pub device_probe: DeviceProbeConfig,
+
+    /// Source the `toggle_monitor` action and `capture_mix` record, e.g.
+    /// "alsa_output.pci-0000_00_1f.3.analog-stereo.monitor"; unset follows
+    /// the default output.
+    #[serde(default)]
+    pub monitor_source: Option<String>,
+
+    #[serde(default)]
+    pub capture_mix: CaptureMixConfig,
+
+    #[serde(default)]
+    pub wake_word: WakeWordConfig,
+
+    #[serde(default)]
+    pub aec: AecConfig,
+
+    /// Writes each capture to disk for `--replay` and `replay_last`; off
+    /// unless asked for, as it keeps what the microphone heard.
+    #[serde(default = "default_keep_last_recording")]
+    pub keep_last_recording: bool,
+
+    /// Spool captured audio until it has been transcribed and resume it on
+    /// the next start if the daemon died first; resumed transcripts are
+    /// copied, not pasted.
+    #[serde(default = "default_resume_pending_utterances")]
+    pub resume_pending_utterances: bool,
+
+    #[serde(default)]
+    pub denoise: DenoiseConfig,
+
+    #[serde(default)]
+    pub fast_vad: FastVadConfig,
+
+    #[serde(default)]
+    pub list_segmentation: ListSegmentationConfig,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub plugins: Vec<PluginConfig>,
+
+    #[serde(default)]
+    pub code_dictation: CodeDictationConfig,
+
+    #[serde(default)]
+    pub postprocess: PostProcessConfig,
+
+    /// Namespaced settings for hooks and plugins, kept as-is across saves;
+    /// see [`Config::extension`].
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub extra: Map<String, Value>,
+
+    #[serde(default)]
+    pub transcription: TranscriptionConfig,
+
+    #[serde(default)]
+    pub performance: PerformanceConfig,
+
+    /// FIFO that receives every live status update as a JSON line.
+    #[serde(default)]
+    pub status_fifo: Option<String>,
+
+    #[serde(default)]
+    pub ipc: IpcConfig,
+
+    #[serde(default)]
+    pub calendar: CalendarConfig,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exports: Vec<ExportTarget>,
+
+    #[serde(default)]
+    pub history: HistoryConfig,
+
+    #[serde(default)]
+    pub injection: InjectionConfig,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub injection_rules: Vec<InjectionRule>,
+
+    #[serde(default)]
+    pub border_indicator: BorderIndicatorConfig,
+
+    #[serde(default)]
+    pub logging: LoggingConfig,
+
+    #[serde(default, rename = "model", skip_serializing)]
+    legacy_model: Option<String>,
+
+    #[serde(default, rename = "threads", skip_serializing)]
+    legacy_threads: Option<usize>,
+
+    #[serde(default, rename = "gpu_layers", skip_serializing)]
+    legacy_gpu_layers: Option<i32>,
+
+    #[serde(default, rename = "whisper_prompt", skip_serializing)]
+    legacy_whisper_prompt: Option<String>,
+
+    #[serde(default, rename = "models_dirs", skip_serializing)]
+    legacy_models_dirs: Option<Vec<String>>,
+
+    #[serde(default, rename = "no_speech_threshold", skip_serializing)]
+    legacy_no_speech_threshold: Option<f32>,
+
+    #[serde(default, rename = "fallback_cli", skip_serializing)]
+    legacy_fallback_cli: Option<bool>,
+
+    #[serde(default, rename = "vad", skip_serializing)]
+    legacy_vad: Option<VadConfig>,
+}
+
+fn default_gpu_layers() -> i32 {
+    999 // Offload all layers to GPU by default
+}
+
+pub(crate) fn is_secret_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    key == "key"
+        || key.ends_with("api_key")
+        || key.contains("token")
+        || key.contains("password")
+        || key.contains("secret")
+}
+
+fn default_primary_shortcut() -> String {
+    "SUPER+ALT+R".to_string() // R for Rust version (Python uses D)
+}
+
+fn default_press_debounce_ms() -> u64 {
+    300
+}
+
+fn default_double_press_ms() -> u64 {
+    400
+}
+
+fn default_model() -> String {
+    "base".to_string()
+}
+
+/// `whisper_cpp.model` under `performance.low_memory` when none is set.
+const LOW_MEMORY_MODEL: &str = "tiny";
+
+fn default_threads() -> usize {
+    4
+}
+
+fn default_whisper_prompt() -> String {
+    DEFAULT_PROMPT.to_string()
+}
+
+fn default_volume() -> f32 {
+    0.3
+}
+
+fn default_sound_enabled() -> bool {
+    true
+}
+
+fn default_max_recording_secs() -> u64 {
+    300
+}
+
+fn default_auto_copy_clipboard() -> bool {
+    true
+}
+
+fn default_shift_paste() -> bool {
+    true
+}
+
+fn default_keep_last_recording() -> bool {
+    false
+}
+
+fn default_resume_pending_utterances() -> bool {
+    false
+}
+
+fn default_no_speech_threshold() -> f32 {
+    0.60
+}
+
+fn default_vad_model() -> String {
+    "ggml-silero-v5.1.2.bin".to_string()
+}
+
+fn default_vad_threshold() -> f32 {
+    0.50
+}
+
+fn default_vad_min_speech_ms() -> u32 {
+    250
+}
+
+fn default_vad_min_silence_ms() -> u32 {
+    100
+}
+
+fn default_vad_max_speech_s() -> f32 {
+    f32::INFINITY
+}
+
+fn default_vad_speech_pad_ms() -> u32 {
+    30
+}
+
+fn default_vad_samples_overlap() -> f32 {
+    0.10
+}
+
+fn default_transcription_request_timeout_secs() -> u64 {
+    45
+}
+
+fn default_transcription_max_retries() -> u32 {
+    2
+}
+
+fn default_groq_model() -> String {
+    "whisper-large-v3-turbo".to_string()
+}
+
+fn default_groq_endpoint() -> String {
+    "https://api.groq.com/openai/v1/audio/transcriptions".to_string()
+}
+
+fn default_gemini_model() -> String {
+    "gemini-2.5-pro-exp-0827".to_string()
+}
+
+fn default_gemini_endpoint() -> String {
+    "https://generativelanguage.googleapis.com/v1beta/models".to_string()
+}
+
+fn default_gemini_upload_endpoint() -> String {
+    "https://generativelanguage.googleapis.com/upload/v1beta/files".to_string()
+}
+
+fn default_gemini_temperature() -> f32 {
+    0.0
+}
+
+fn default_gemini_max_output_tokens() -> u32 {
+    1024
+}
+
+fn default_ssh_command() -> String {
+    "whisper-cli -m ~/.local/share/hyprwhspr/models/ggml-large-v3-turbo-q8_0.bin -f - --no-timestamps --no-prints --prompt {prompt}".to_string()
+}
+
+fn default_prompt_boost_sticky_utterances() -> u32 {
+    3
+}
+
+fn default_lan_service_type() -> String {
+    "_hyprwhspr-stt._tcp".to_string()
+}
+
+fn default_lan_path() -> String {
+    "/inference".to_string()
+}
+
+fn default_lan_discovery_timeout_ms() -> u64 {
+    1500
+}
+
+fn default_fast_vad_min_speech_ms() -> u32 {
+    120
+}
+
+fn default_fast_vad_silence_timeout_ms() -> u32 {
+    500
+}
+
+fn default_fast_vad_pre_roll_ms() -> u32 {
+    120
+}
+
+fn default_fast_vad_post_roll_ms() -> u32 {
+    150
+}
+
+fn default_fast_vad_volatility_window() -> u32 {
+    24
+}
+
+fn default_fast_vad_volatility_increase_threshold() -> f32 {
+    0.35
+}
+
+fn default_fast_vad_volatility_decrease_threshold() -> f32 {
+    0.12
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct VadConfig {
+    pub enabled: bool,
+    pub model: String,
+    pub threshold: f32,
+    pub min_speech_ms: u32,
+    pub min_silence_ms: u32,
+    pub max_speech_s: f32,
+    pub speech_pad_ms: u32,
     pub samples_overlap: f32,
 }
 
-impl Default for VadConfig {
+impl Default for VadConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            model: default_vad_model(),
+            threshold: default_vad_threshold(),
+            min_speech_ms: default_vad_min_speech_ms(),
+            min_silence_ms: default_vad_min_silence_ms(),
+            max_speech_s: default_vad_max_speech_s(),
+            speech_pad_ms: default_vad_speech_pad_ms(),
+            samples_overlap: default_vad_samples_overlap(),
+        }
+    }
+}
+
+/// Longest `speech_pad_ms` kept; wider pads swallow the silence that ends
+/// a segment.
+const MAX_VAD_SPEECH_PAD_MS: u32 = 1000;
+
+impl VadConfig {
+    /// Replaces values whisper-cli would accept but that leave nothing to
+    /// transcribe, returning what was changed.
+    pub fn sanitize(&mut self) -> Vec<String> {
+        let mut fixes = Vec::new();
+
+        if !(0.0..=1.0).contains(&self.threshold) {
+            fixes.push(format!(
+                "threshold {} is outside 0-1; using {}",
+                self.threshold,
+                default_vad_threshold()
+            ));
+            self.threshold = default_vad_threshold();
+        }
+        if !self.samples_overlap.is_finite() || self.samples_overlap < 0.0 {
+            fixes.push(format!(
+                "samples_overlap {} is invalid; using {}",
+                self.samples_overlap,
+                default_vad_samples_overlap()
+            ));
+            self.samples_overlap = default_vad_samples_overlap();
+        }
+        if self.max_speech_s.is_nan() || self.max_speech_s <= 0.0 {
+            fixes.push(format!(
+                "max_speech_s {} is not positive; leaving speech unlimited",
+                self.max_speech_s
+            ));
+            self.max_speech_s = f32::INFINITY;
+        }
+
+        if self.max_speech_s.is_finite() {
+            let max_speech_ms = self.max_speech_s * 1000.0;
+            if self.min_silence_ms as f32 >= max_speech_ms {
+                fixes.push(format!(
+                    "min_silence_ms {} is not shorter than max_speech_s {}; leaving speech unlimited",
+                    self.min_silence_ms, self.max_speech_s
+                ));
+                self.max_speech_s = f32::INFINITY;
+            } else if self.speech_pad_ms.saturating_mul(2) as f32 >= max_speech_ms {
+                let pad = (max_speech_ms / 4.0) as u32;
+                fixes.push(format!(
+                    "speech_pad_ms {} would pad past max_speech_s {}; using {}",
+                    self.speech_pad_ms, self.max_speech_s, pad
+                ));
+                self.speech_pad_ms = pad;
+            }
+        }
+        if self.speech_pad_ms > MAX_VAD_SPEECH_PAD_MS {
+            fixes.push(format!(
+                "speech_pad_ms {} merges every segment; using {}",
+                self.speech_pad_ms, MAX_VAD_SPEECH_PAD_MS
+            ));
+            self.speech_pad_ms = MAX_VAD_SPEECH_PAD_MS;
+        }
+
+        fixes
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FastVadProfileConfig {
+    Quality,
+    LowBitrate,
+    Aggressive,
+    VeryAggressive,
+}
+
+impl Default for FastVadProfileConfig {
+    fn default() -> Self {
+        Self::Aggressive
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct FastVadConfig {
+    pub enabled: bool,
+    pub profile: FastVadProfileConfig,
+    pub min_speech_ms: u32,
+    pub silence_timeout_ms: u32,
+    pub pre_roll_ms: u32,
+    pub post_roll_ms: u32,
+    pub volatility_window: u32,
+    pub volatility_increase_threshold: f32,
+    pub volatility_decrease_threshold: f32,
+}
+
+impl Default for FastVadConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            profile: FastVadProfileConfig::default(),
+            min_speech_ms: default_fast_vad_min_speech_ms(),
+            silence_timeout_ms: default_fast_vad_silence_timeout_ms(),
+            pre_roll_ms: default_fast_vad_pre_roll_ms(),
+            post_roll_ms: default_fast_vad_post_roll_ms(),
+            volatility_window: default_fast_vad_volatility_window(),
+            volatility_increase_threshold: default_fast_vad_volatility_increase_threshold(),
+            volatility_decrease_threshold: default_fast_vad_volatility_decrease_threshold(),
+        }
+    }
+}
+
+impl FastVadConfig {
+    /// Same contract as [`VadConfig::sanitize`].
+    pub fn sanitize(&mut self) -> Vec<String> {
+        let mut fixes = Vec::new();
+
+        for (name, value, default) in [
+            (
+                "volatility_increase_threshold",
+                &mut self.volatility_increase_threshold,
+                default_fast_vad_volatility_increase_threshold(),
+            ),
+            (
+                "volatility_decrease_threshold",
+                &mut self.volatility_decrease_threshold,
+                default_fast_vad_volatility_decrease_threshold(),
+            ),
+        ] {
+            if !(0.0..=1.0).contains(&*value) {
+                fixes.push(format!("{name} {value} is outside 0-1; using {default}"));
+                *value = default;
+            }
+        }
+        if self.volatility_increase_threshold <= self.volatility_decrease_threshold {
+            fixes.push(format!(
+                "volatility_increase_threshold {} does not exceed volatility_decrease_threshold {}; they will be spread apart",
+                self.volatility_increase_threshold, self.volatility_decrease_threshold
+            ));
+        }
+
+        if self.silence_timeout_ms == 0 {
+            fixes.push(format!(
+                "silence_timeout_ms 0 trims between every word; using {}",
+                default_fast_vad_silence_timeout_ms()
+            ));
+            self.silence_timeout_ms = default_fast_vad_silence_timeout_ms();
+        }
+        for (name, value) in [
+            ("pre_roll_ms", &mut self.pre_roll_ms),
+            ("post_roll_ms", &mut self.post_roll_ms),
+        ] {
+            if *value > self.silence_timeout_ms {
+                fixes.push(format!(
+                    "{name} {value} exceeds silence_timeout_ms {}; using {}",
+                    self.silence_timeout_ms, self.silence_timeout_ms
+                ));
+                *value = self.silence_timeout_ms;
+            }
+        }
+
+        fixes
+    }
+}
+
+/// Optional sounds for transcription outcomes, played while
+/// `audio_feedback` is on. Unset events stay silent.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct FeedbackSoundsConfig {
+    /// After the transcript has been injected.
+    pub transcription_complete: Option<FeedbackSound>,
+    /// Any failure while processing a recording, backend errors included.
+    pub transcription_failed: Option<FeedbackSound>,
+    pub empty_result: Option<FeedbackSound>,
+    pub provider_fallback: Option<FeedbackSound>,
+    /// Defaults to the stop sound, slowed down.
+    pub recording_cancelled: Option<FeedbackSound>,
+    /// Played instead of the stop sound when `max_recording_secs` ends a
+    /// recording. Defaults to the stop sound, sped up.
+    pub recording_limit: Option<FeedbackSound>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FeedbackSound {
+    /// Absolute, or relative to the assets directory.
+    pub path: String,
+    #[serde(default = "default_volume")]
+    pub volume: f32,
+    /// Mutes this sound without dropping its settings.
+    #[serde(default = "default_sound_enabled")]
+    pub enabled: bool,
+}
+
+/// When feedback sounds (and notifications) stay silent.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct DoNotDisturbConfig {
+    /// Follow the notification daemon's DND switch (mako, swaync, dunst).
+    pub follow_desktop: bool,
+    pub quiet_hours: Option<QuietHours>,
+}
+
+impl Default for DoNotDisturbConfig {
+    fn default() -> Self {
+        Self {
+            follow_desktop: true,
+            quiet_hours: None,
+        }
+    }
+}
+
+/// Desktop notifications per transcription outcome.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    /// Shows the start of each transcript.
+    pub transcription_complete: bool,
+    pub empty_result: bool,
+    /// Backend and processing errors, which otherwise only reach the log.
+    pub transcription_failed: bool,
+    pub preview_chars: usize,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            transcription_complete: false,
+            empty_result: false,
+            transcription_failed: true,
+            preview_chars: 80,
+        }
+    }
+}
+
+/// Local `HH:MM` times; `end` before `start` wraps past midnight.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QuietHours {
+    pub start: String,
+    pub end: String,
+}
+
+/// RNNoise noise suppression applied to each recording before fast VAD and
+/// transcription.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct DenoiseConfig {
+    pub enabled: bool,
+    /// Share of the cleaned signal in the result, 0.0..=1.0.
+    pub strength: f32,
+}
+
+impl Default for DenoiseConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            strength: 1.0,
+        }
+    }
+}
+
+impl DenoiseConfig {
+    /// The strength to apply, or `None` when suppression is off.
+    pub fn strength(&self) -> Option<f32> {
+        (self.enabled && self.strength > 0.0).then(|| self.strength.min(1.0))
+    }
+}
+
+/// Turns long pauses in one recording into list items, for dictating
+/// shopping lists and TODOs. Needs `fast_vad`, whose segments mark the
+/// pauses; each item is transcribed on its own.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ListSegmentationConfig {
+    pub enabled: bool,
+    /// Shorter pauses stay inside an item.
+    pub min_pause_ms: u32,
+    pub style: ListStyle,
+}
+
+impl Default for ListSegmentationConfig {
     fn default() -> Self {
         Self {
             enabled: false,
-            model: default_vad_model(),
-            threshold: default_vad_threshold(),
-            min_speech_ms: default_vad_min_speech_ms(),
-            min_silence_ms: default_vad_min_silence_ms(),
-            max_speech_s: default_vad_max_speech_s(),
-            speech_pad_ms: default_vad_speech_pad_ms(),
-            samples_overlap: default_vad_samples_overlap(),
+            min_pause_ms: 1_200,
+            style: ListStyle::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ListStyle {
+    /// One item per line.
+    #[default]
+    Newline,
+    /// `- item` lines.
+    Bullet,
+    /// `1. item` lines.
+    Numbered,
+}
+
+/// How capture deals with the start sound, which can mask or distort the
+/// first syllables when the mic picks it up.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StartSoundOverlap {
+    /// Record from the moment the shortcut fires.
+    #[default]
+    Keep,
+    /// Start capturing once the sound has finished playing.
+    Delay,
+    /// Capture right away but drop the part the sound played over.
+    Trim,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptionProvider {
+    WhisperCpp,
+    Groq,
+    Gemini,
+    Ssh,
+    /// Returns `echo.template` instead of transcribing; for demos and tests.
+    Echo,
+}
+
+impl Default for TranscriptionProvider {
+    fn default() -> Self {
+        TranscriptionProvider::WhisperCpp
+    }
+}
+
+impl TranscriptionProvider {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TranscriptionProvider::WhisperCpp => "Local",
+            TranscriptionProvider::Groq => "Groq",
+            TranscriptionProvider::Gemini => "Gemini",
+            TranscriptionProvider::Ssh => "SSH",
+            TranscriptionProvider::Echo => "Echo",
+        }
+    }
+
+    /// Matches the label or the config name, ignoring case ("local" and
+    /// "whisper_cpp" both work).
+    pub fn from_label(label: &str) -> Option<Self> {
+        let label = label.trim();
+        if label.eq_ignore_ascii_case("whisper_cpp") {
+            return Some(Self::WhisperCpp);
+        }
+        [
+            Self::WhisperCpp,
+            Self::Groq,
+            Self::Gemini,
+            Self::Ssh,
+            Self::Echo,
+        ]
+        .into_iter()
+        .find(|provider| provider.label().eq_ignore_ascii_case(label))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct WhisperCppConfig {
+    pub prompt: String,
+    pub model: String,
+    pub threads: usize,
+    pub gpu_layers: i32,
+    pub fallback_cli: bool,
+    pub no_speech_threshold: f32,
+    pub models_dirs: Vec<String>,
+    /// Download `model` at startup when it isn't installed, instead of
+    /// falling back to another installed model.
+    pub auto_download: bool,
+    pub vad: VadConfig,
+}
+
+impl Default for WhisperCppConfig {
+    fn default() -> Self {
+        Self {
+            prompt: default_whisper_prompt(),
+            model: default_model(),
+            threads: default_threads(),
+            gpu_layers: default_gpu_layers(),
+            fallback_cli: false,
+            no_speech_threshold: default_no_speech_threshold(),
+            models_dirs: Vec::new(),
+            auto_download: false,
+            vad: VadConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct GroqConfig {
+    pub model: String,
+    pub endpoint: String,
+    pub prompt: String,
+    pub no_speech_threshold: f32,
+    /// Additional multipart fields sent verbatim with every request
+    /// (e.g. `"timestamp_granularities": ["segment", "word"]`).
+    #[serde(skip_serializing_if = "Map::is_empty")]
+    pub extra_body: Map<String, Value>,
+}
+
+impl Default for GroqConfig {
+    fn default() -> Self {
+        Self {
+            model: default_groq_model(),
+            endpoint: default_groq_endpoint(),
+            prompt: default_whisper_prompt(),
+            no_speech_threshold: default_no_speech_threshold(),
+            extra_body: Map::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct GeminiConfig {
+    pub model: String,
+    pub endpoint: String,
+    pub upload_endpoint: String,
+    pub temperature: f32,
+    pub max_output_tokens: u32,
+    pub prompt: String,
+    /// JSON deep-merged into the `generateContent` body
+    /// (e.g. `"safetySettings"` or extra `"generationConfig"` keys).
+    #[serde(skip_serializing_if = "Map::is_empty")]
+    pub extra_body: Map<String, Value>,
+    pub response_filter: ResponseFilterConfig,
+}
+
+impl Default for GeminiConfig {
+    fn default() -> Self {
+        Self {
+            model: default_gemini_model(),
+            endpoint: default_gemini_endpoint(),
+            upload_endpoint: default_gemini_upload_endpoint(),
+            temperature: default_gemini_temperature(),
+            max_output_tokens: default_gemini_max_output_tokens(),
+            prompt: default_whisper_prompt(),
+            extra_body: Map::new(),
+            response_filter: ResponseFilterConfig::default(),
+        }
+    }
+}
+
+/// Cuts assistant preambles off replies and rejects those that aren't a
+/// transcript (commentary about the audio, or text in a writing system the
+/// user doesn't dictate in). A rejected reply is retried once with a
+/// stricter instruction, then reported as a failure.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ResponseFilterConfig {
+    pub enabled: bool,
+    /// Writing systems a transcript may use; empty allows any.
+    pub scripts: Vec<WritingScript>,
+}
+
+impl Default for ResponseFilterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            scripts: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WritingScript {
+    Latin,
+    Cyrillic,
+    Greek,
+    Arabic,
+    Hebrew,
+    Devanagari,
+    Thai,
+    Hangul,
+    /// Hiragana, katakana, and kanji.
+    Japanese,
+    /// Han ideographs.
+    Chinese,
+}
+
+/// Runs a whisper command on another machine via `ssh`. The FLAC recording is
+/// written to the command's stdin and its stdout is used as the transcript.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SshConfig {
+    /// Anything `ssh` accepts as a destination, including `~/.ssh/config` aliases.
+    pub host: String,
+    /// Remote shell command; `{prompt}` is replaced with the quoted prompt.
+    pub command: String,
+    pub ssh_args: Vec<String>,
+    pub prompt: String,
+}
+
+impl Default for SshConfig {
+    fn default() -> Self {
+        Self {
+            host: String::new(),
+            command: default_ssh_command(),
+            ssh_args: Vec::new(),
+            prompt: default_whisper_prompt(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct EchoConfig {
+    /// `{duration}` is replaced with the seconds of audio recorded and
+    /// `{count}` with the number of recordings so far.
+    pub template: String,
+    /// Pretend transcription takes this long.
+    pub delay_ms: u64,
+}
+
+impl Default for EchoConfig {
+    fn default() -> Self {
+        Self {
+            template: "Echo {count}: {duration} seconds of audio.".to_string(),
+            delay_ms: 0,
+        }
+    }
+}
+
+/// Input device chosen by its index in the device list or by a
+/// case-insensitive substring of its name (`"USB"`, `"Blue Yeti"`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum AudioDeviceSelector {
+    Index(usize),
+    Name(String),
+}
+
+impl fmt::Display for AudioDeviceSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Index(index) => write!(f, "#{index}"),
+            Self::Name(name) => write!(f, "\"{name}\""),
+        }
+    }
+}
+
+/// Acoustic echo cancellation for dictating while a call plays on
+/// speakers. Read at startup.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AecConfig {
+    pub enabled: bool,
+    /// An echo-cancelled source already set up in the sound server's
+    /// config; when unset, `module-echo-cancel` is loaded while running.
+    pub source: Option<String>,
+    /// Make the loaded module's sink the default output, since only audio
+    /// played through it is cancelled.
+    pub route_playback: bool,
+}
+
+impl Default for AecConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            source: None,
+            route_playback: true,
+        }
+    }
+}
+
+/// Startup probe that records briefly from every input device and picks
+/// the one with the best signal-to-noise ratio. Only used while
+/// `audio_device` is unset.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct DeviceProbeConfig {
+    pub enabled: bool,
+    pub probe_ms: u64,
+}
+
+impl Default for DeviceProbeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            probe_ms: 500,
+        }
+    }
+}
+
+/// Recording `monitor_source` alongside the microphone, so both sides of a
+/// call end up in the transcript.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct CaptureMixConfig {
+    pub enabled: bool,
+    pub mode: MixMode,
+    /// Volume of the monitor relative to the microphone when mixing.
+    pub monitor_gain: f32,
+    /// Prefixes of each side's transcript in `tracks` mode.
+    pub mic_label: String,
+    pub monitor_label: String,
+}
+
+impl Default for CaptureMixConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mode: MixMode::default(),
+            monitor_gain: 1.0,
+            mic_label: "Me".to_string(),
+            monitor_label: "Them".to_string(),
+        }
+    }
+}
+
+/// Starting a recording by saying `phrase`; see
+/// [`crate::audio::wakeword`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct WakeWordConfig {
+    pub enabled: bool,
+    pub phrase: String,
+    /// 0.0–1.0: quieter speech and looser matches count as the phrase.
+    pub sensitivity: f32,
+    /// Longer bursts of speech aren't transcribed to look for the phrase.
+    pub max_phrase_ms: u32,
+    /// Pause that ends a recording the phrase started; 0 waits for a stop.
+    pub stop_after_silence_ms: u32,
+}
+
+impl Default for WakeWordConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            phrase: "hey whisper".to_string(),
+            sensitivity: 0.5,
+            max_phrase_ms: 2_000,
+            stop_after_silence_ms: 1_500,
+        }
+    }
+}
+
+/// How the two captures of a mixed recording reach the backend.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MixMode {
+    /// Summed into one recording and transcribed once.
+    #[default]
+    Mix,
+    /// Transcribed separately and inserted one after the other, labelled.
+    Tracks,
+}
+
+/// A note app each transcript is also appended to, through its local API.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExportTarget {
+    /// Today's daily note, via the Local REST API plugin.
+    Obsidian {
+        #[serde(default = "default_obsidian_url")]
+        url: String,
+        api_key: String,
+    },
+    /// A page (today's journal by default), via the HTTP API server.
+    Logseq {
+        #[serde(default = "default_logseq_url")]
+        url: String,
+        token: String,
+        #[serde(default)]
+        page: Option<String>,
+    },
+    /// An existing note, via the Web Clipper data API.
+    Joplin {
+        #[serde(default = "default_joplin_url")]
+        url: String,
+        token: String,
+        note_id: String,
+    },
+    /// A Markdown file, one headed entry per transcript. `path` may use the
+    /// same placeholders as `header` (e.g. `{date}`) to rotate files daily.
+    File {
+        path: String,
+        #[serde(default = "default_file_header")]
+        header: String,
+        /// Combine transcripts into notes split by spoken "new note" and
+        /// "note title …"; `{title}` in `path` gives each note its own file.
+        #[serde(default)]
+        notes: bool,
+        /// Outside notes mode, write one `[m:ss]` line per segment instead
+        /// of the cleaned-up text when the backend reports segments.
+        #[serde(default)]
+        timestamps: bool,
+    },
+}
+
+fn default_file_header() -> String {
+    "## {date} {time} ({backend}, {duration}s)".to_string()
+}
+
+fn default_obsidian_url() -> String {
+    "https://127.0.0.1:27124".to_string()
+}
+
+fn default_logseq_url() -> String {
+    "http://127.0.0.1:12315".to_string()
+}
+
+fn default_joplin_url() -> String {
+    "http://127.0.0.1:41184".to_string()
+}
+
+/// Tags utterances dictated during a calendar event with its title.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct CalendarConfig {
+    pub enabled: bool,
+    pub source: CalendarSource,
+    /// iCalendar file read when `source` is `ics`.
+    pub ics_path: Option<String>,
+    /// Hint the title to remote backends too; otherwise only local ones
+    /// see it.
+    pub send_to_remote: bool,
+    /// Profile (a key of `profiles`) to use instead of the focused window's
+    /// while a meeting whose title contains the key is in progress.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub profiles: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CalendarSource {
+    #[default]
+    Khal,
+    Ics,
+}
+
+/// Keeps every final transcription for `hyprwhspr-rs history`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct HistoryConfig {
+    pub enabled: bool,
+    /// Oldest entries are dropped beyond this; 0 keeps everything.
+    pub max_entries: usize,
+    /// Entries older than this many days are erased, along with recordings
+    /// and request log entries as old; 0 keeps them.
+    pub retention_days: u32,
+    /// Defaults to `history.jsonl` in the data directory when unset.
+    pub path: Option<String>,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_entries: 5000,
+            retention_days: 0,
+            path: None,
+        }
+    }
+}
+
+/// Tints the focused window's border in Hyprland while recording.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct BorderIndicatorConfig {
+    pub enabled: bool,
+    /// Any `general:col.active_border` value, gradients included.
+    pub color: String,
+}
+
+impl Default for BorderIndicatorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            color: "rgb(ff4444)".to_string(),
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+/// Daemon log output. Read at startup.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct LoggingConfig {
+    pub startup_summary: StartupSummary,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "snake_case")]
-pub enum FastVadProfileConfig {
-    Quality,
-    LowBitrate,
-    Aggressive,
-    VeryAggressive,
+pub enum StartupSummary {
+    /// The banner listing backend, model, shortcuts and modes.
+    #[default]
+    Full,
+    /// One startup line, then only warnings and errors; for running as a
+    /// service where the journal should stay quiet.
+    Minimal,
+    /// No banner; regular logging otherwise.
+    Off,
 }
 
-impl Default for FastVadProfileConfig {
+/// Interfaces other processes use to control the daemon. Read at startup.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct IpcConfig {
+    /// Serve `org.hyprwhspr.Daemon` on the session bus.
+    pub dbus: bool,
+    /// Put the transcript itself in the `TranscriptionReady` signal, where
+    /// any process on the session bus can read it; otherwise the signal
+    /// only carries its length and backend.
+    pub dbus_transcripts: bool,
+    /// Listen on `$XDG_RUNTIME_DIR/hyprwhspr-rs.sock`. A socket passed by
+    /// systemd is used either way.
+    pub socket: bool,
+    pub signals: SignalsConfig,
+}
+
+impl Default for IpcConfig {
     fn default() -> Self {
-        Self::Aggressive
+        Self {
+            dbus: true,
+            dbus_transcripts: false,
+            socket: true,
+            signals: SignalsConfig::default(),
+        }
     }
 }
 
+/// Control socket commands run on `SIGUSR1`/`SIGUSR2`, for keybinding
+/// setups that can only run `pkill`. An empty string ignores the signal.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(default)]
-pub struct FastVadConfig {
-    pub enabled: bool,
-    pub profile: FastVadProfileConfig,
-    pub min_speech_ms: u32,
-    pub silence_timeout_ms: u32,
-    pub pre_roll_ms: u32,
-    pub post_roll_ms: u32,
-    pub volatility_window: u32,
-    pub volatility_increase_threshold: f32,
-    pub volatility_decrease_threshold: f32,
+pub struct SignalsConfig {
+    pub usr1: String,
+    pub usr2: String,
 }
 
-impl Default for FastVadConfig {
+impl Default for SignalsConfig {
     fn default() -> Self {
         Self {
-            enabled: false,
-            profile: FastVadProfileConfig::default(),
-            min_speech_ms: default_fast_vad_min_speech_ms(),
-            silence_timeout_ms: default_fast_vad_silence_timeout_ms(),
-            pre_roll_ms: default_fast_vad_pre_roll_ms(),
-            post_roll_ms: default_fast_vad_post_roll_ms(),
-            volatility_window: default_fast_vad_volatility_window(),
-            volatility_increase_threshold: default_fast_vad_volatility_increase_threshold(),
-            volatility_decrease_threshold: default_fast_vad_volatility_decrease_threshold(),
+            usr1: "toggle".to_string(),
+            usr2: "cancel".to_string(),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+/// Threads for CPU-heavy audio work (resampling, VAD trimming), kept off
+/// the async runtime.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct PerformanceConfig {
+    /// 0 picks one per core, leaving a core for the runtime.
+    pub worker_threads: usize,
+    /// For ARM handhelds and SBCs: recordings grow in fixed steps instead
+    /// of doubling, and defaults left unset shrink (see
+    /// [`Config::apply_low_memory_defaults`]).
+    pub low_memory: bool,
+    pub resample_quality: ResampleQuality,
+}
+
+/// Trade-off for converting captures to 16 kHz, where most sound cards
+/// record at 44.1 or 48 kHz.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "snake_case")]
-pub enum TranscriptionProvider {
-    WhisperCpp,
-    Groq,
-    Gemini,
+pub enum ResampleQuality {
+    /// Cubic interpolation: cheapest, but lets some hiss alias into the
+    /// speech band.
+    Fast,
+    /// Short windowed-sinc filter.
+    #[default]
+    Balanced,
+    /// Long windowed-sinc filter, for slow recognizers where the extra
+    /// milliseconds don't show.
+    High,
 }
 
-impl Default for TranscriptionProvider {
-    fn default() -> Self {
-        TranscriptionProvider::WhisperCpp
-    }
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct TranscriptionConfig {
+    pub provider: TranscriptionProvider,
+    pub request_timeout_secs: u64,
+    pub max_retries: u32,
+    pub whisper_cpp: WhisperCppConfig,
+    pub groq: GroqConfig,
+    pub gemini: GeminiConfig,
+    pub ssh: SshConfig,
+    pub echo: EchoConfig,
+    pub request_log: RequestLogConfig,
+    pub lan_offload: LanOffloadConfig,
+    pub prompt_boost: PromptBoostConfig,
+    pub short_command: ShortCommandConfig,
+    pub retry_low_confidence: LowConfidenceRetryConfig,
 }
 
-impl TranscriptionProvider {
-    pub fn label(&self) -> &'static str {
-        match self {
-            TranscriptionProvider::WhisperCpp => "Local",
-            TranscriptionProvider::Groq => "Groq",
-            TranscriptionProvider::Gemini => "Gemini",
+/// A second try for long recordings that come back suspiciously thin
+/// (30 s of audio, 3 words), made once with another provider or a larger
+/// local model before anything is injected.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct LowConfidenceRetryConfig {
+    pub enabled: bool,
+    /// Shorter recordings are never retried.
+    pub min_audio_secs: f32,
+    /// Fewer words per second of audio than this looks wrong.
+    pub min_words_per_sec: f32,
+    /// So does a lower average segment log-probability, for providers that
+    /// report one (Groq).
+    pub min_avg_logprob: f32,
+    /// Retry with this provider; unset keeps `transcription.provider`.
+    pub provider: Option<TranscriptionProvider>,
+    /// whisper.cpp model for a local retry, e.g. "large-v3-turbo".
+    pub whisper_model: Option<String>,
+}
+
+impl Default for LowConfidenceRetryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_audio_secs: 10.0,
+            min_words_per_sec: 0.5,
+            min_avg_logprob: -1.0,
+            provider: None,
+            whisper_model: None,
         }
     }
 }
 
+/// A fast path for quick utterances ("thanks, sounds good"): they are
+/// uploaded as WAV instead of FLAC, skip text plugins and the postprocess
+/// script, and can go to a lower-latency provider.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(default)]
-pub struct WhisperCppConfig {
-    pub prompt: String,
-    pub model: String,
-    pub threads: usize,
-    pub gpu_layers: i32,
-    pub fallback_cli: bool,
-    pub no_speech_threshold: f32,
-    pub models_dirs: Vec<String>,
-    pub vad: VadConfig,
+pub struct ShortCommandConfig {
+    /// Speech up to this long is uploaded as WAV and goes to `provider`;
+    /// 0 turns it off. Text cleanup, plugins included, runs as usual.
+    pub max_secs: f32,
+    /// Used instead of `transcription.provider` for short commands.
+    pub provider: Option<TranscriptionProvider>,
 }
 
-impl Default for WhisperCppConfig {
+impl Default for ShortCommandConfig {
     fn default() -> Self {
         Self {
-            prompt: default_whisper_prompt(),
-            model: default_model(),
-            threads: default_threads(),
-            gpu_layers: default_gpu_layers(),
-            fallback_cli: false,
-            no_speech_threshold: default_no_speech_threshold(),
-            models_dirs: Vec::new(),
-            vad: VadConfig::default(),
+            max_secs: 2.0,
+            provider: None,
         }
     }
 }
 
+impl ShortCommandConfig {
+    pub fn applies_to(&self, speech: Duration) -> bool {
+        self.max_secs > 0.0 && speech.as_secs_f32() <= self.max_secs
+    }
+}
+
+/// Domain vocabularies appended to remote prompts after one of their keywords
+/// is heard, for the next `sticky_utterances` recordings.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(default)]
-pub struct GroqConfig {
-    pub model: String,
-    pub endpoint: String,
-    pub prompt: String,
+pub struct PromptBoostConfig {
+    pub sticky_utterances: u32,
+    pub domains: Vec<PromptBoostDomain>,
 }
 
-impl Default for GroqConfig {
+impl Default for PromptBoostConfig {
     fn default() -> Self {
         Self {
-            model: default_groq_model(),
-            endpoint: default_groq_endpoint(),
-            prompt: default_whisper_prompt(),
+            sticky_utterances: default_prompt_boost_sticky_utterances(),
+            domains: Vec::new(),
         }
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct PromptBoostDomain {
+    pub name: String,
+    pub keywords: Vec<String>,
+    pub vocabulary: Vec<String>,
+}
+
+/// Offloads whisper_cpp transcription to a whisper server advertised over mDNS,
+/// falling back to the local model when none is found or a request fails.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(default)]
-pub struct GeminiConfig {
-    pub model: String,
-    pub endpoint: String,
-    pub temperature: f32,
-    pub max_output_tokens: u32,
-    pub prompt: String,
+pub struct LanOffloadConfig {
+    pub enabled: bool,
+    pub service_type: String,
+    /// Request path used when the advertisement has no `path=` TXT record.
+    pub default_path: String,
+    pub model: Option<String>,
+    pub discovery_timeout_ms: u64,
 }
 
-impl Default for GeminiConfig {
+impl Default for LanOffloadConfig {
     fn default() -> Self {
         Self {
-            model: default_gemini_model(),
-            endpoint: default_gemini_endpoint(),
-            temperature: default_gemini_temperature(),
-            max_output_tokens: default_gemini_max_output_tokens(),
-            prompt: default_whisper_prompt(),
+            enabled: false,
+            service_type: default_lan_service_type(),
+            default_path: default_lan_path(),
+            model: None,
+            discovery_timeout_ms: default_lan_discovery_timeout_ms(),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Dumps redacted provider request/response bodies to a JSON Lines file.
+/// Audio payloads and API keys are never written.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 #[serde(default)]
-pub struct TranscriptionConfig {
-    pub provider: TranscriptionProvider,
-    pub request_timeout_secs: u64,
-    pub max_retries: u32,
-    pub whisper_cpp: WhisperCppConfig,
-    pub groq: GroqConfig,
-    pub gemini: GeminiConfig,
+pub struct RequestLogConfig {
+    pub enabled: bool,
+    /// Defaults to `request-log.jsonl` in the data directory when unset.
+    pub path: Option<String>,
 }
 
 impl Default for TranscriptionConfig {
@@ -422,6 +2200,13 @@ impl Default for TranscriptionConfig {
             whisper_cpp: WhisperCppConfig::default(),
             groq: GroqConfig::default(),
             gemini: GeminiConfig::default(),
+            ssh: SshConfig::default(),
+            echo: EchoConfig::default(),
+            request_log: RequestLogConfig::default(),
+            lan_offload: LanOffloadConfig::default(),
+            prompt_boost: PromptBoostConfig::default(),
+            short_command: ShortCommandConfig::default(),
+            retry_low_confidence: LowConfidenceRetryConfig::default(),
         }
     }
 }
@@ -432,17 +2217,52 @@ impl Default for Config {
             primary_shortcut: default_primary_shortcut(),
             shortcuts: ShortcutsConfig::default(),
             word_overrides: HashMap::new(),
+            speech_commands: SpeechCommandsConfig::default(),
+            session_stats: SessionStatsConfig::default(),
+            voice_commands: VoiceCommandsConfig::default(),
+            dual_output: DualOutputConfig::default(),
+            dictation_mode: DictationMode::default(),
+            profiles: HashMap::new(),
             audio_feedback: false,
             start_sound_volume: default_volume(),
             stop_sound_volume: default_volume(),
             start_sound_path: None,
             stop_sound_path: None,
+            start_sound_overlap: StartSoundOverlap::default(),
+            pre_roll_ms: 0,
+            max_recording_secs: default_max_recording_secs(),
+            feedback_sounds: FeedbackSoundsConfig::default(),
+            do_not_disturb: DoNotDisturbConfig::default(),
+            notifications: NotificationsConfig::default(),
             auto_copy_clipboard: default_auto_copy_clipboard(),
             shift_paste: default_shift_paste(),
             paste_hints: PasteHintsConfig::default(),
             audio_device: None,
+            device_probe: DeviceProbeConfig::default(),
+            monitor_source: None,
+            capture_mix: CaptureMixConfig::default(),
+            wake_word: WakeWordConfig::default(),
+            aec: AecConfig::default(),
+            keep_last_recording: default_keep_last_recording(),
+            resume_pending_utterances: default_resume_pending_utterances(),
+            denoise: DenoiseConfig::default(),
             fast_vad: FastVadConfig::default(),
+            list_segmentation: ListSegmentationConfig::default(),
+            plugins: Vec::new(),
+            code_dictation: CodeDictationConfig::default(),
+            postprocess: PostProcessConfig::default(),
+            extra: Map::new(),
             transcription: TranscriptionConfig::default(),
+            performance: PerformanceConfig::default(),
+            status_fifo: None,
+            ipc: IpcConfig::default(),
+            calendar: CalendarConfig::default(),
+            exports: Vec::new(),
+            history: HistoryConfig::default(),
+            injection: InjectionConfig::default(),
+            injection_rules: Vec::new(),
+            border_indicator: BorderIndicatorConfig::default(),
+            logging: LoggingConfig::default(),
             legacy_model: None,
             legacy_threads: None,
             legacy_gpu_layers: None,
@@ -471,6 +2291,16 @@ impl Config {
             .hold
             .as_ref()
             .and_then(|value| Self::sanitize_shortcut(value));
+        self.shortcuts.cancel = self
+            .shortcuts
+            .cancel
+            .as_ref()
+            .and_then(|value| Self::sanitize_shortcut(value));
+        self.shortcuts.mode = self
+            .shortcuts
+            .mode
+            .as_ref()
+            .and_then(|value| Self::sanitize_shortcut(value));
 
         if let (Some(current), Some(legacy)) = (&self.shortcuts.press, &legacy_primary) {
             if current != legacy {
@@ -482,6 +2312,18 @@ impl Config {
             }
         }
 
+        for trigger in [ShortcutTrigger::Press, ShortcutTrigger::DoublePress] {
+            if self.shortcuts.actions.get(&trigger) == Some(&ShortcutAction::PushToTalk) {
+                tracing::warn!(
+                    "shortcuts.actions: the press shortcut can't report releases; {:?} toggles instead of push_to_talk",
+                    trigger
+                );
+                self.shortcuts
+                    .actions
+                    .insert(trigger, ShortcutAction::Toggle);
+            }
+        }
+
         if let Some(press) = self.shortcuts.press.clone() {
             self.primary_shortcut = press;
         } else {
@@ -491,6 +2333,30 @@ impl Config {
         }
     }
 
+    /// With `performance.low_memory`, swaps defaults the config leaves unset
+    /// for lighter ones: the tiny whisper.cpp model and one worker thread.
+    pub fn apply_low_memory_defaults(&mut self, model_set: bool) {
+        if !self.performance.low_memory {
+            return;
+        }
+        if !model_set {
+            self.transcription.whisper_cpp.model = LOW_MEMORY_MODEL.to_string();
+        }
+        if self.performance.worker_threads == 0 {
+            self.performance.worker_threads = 1;
+        }
+    }
+
+    /// Logs and repairs VAD settings that would trim away every utterance.
+    pub fn sanitize_vad(&mut self) {
+        for fix in self.transcription.whisper_cpp.vad.sanitize() {
+            tracing::warn!("transcription.whisper_cpp.vad: {fix}");
+        }
+        for fix in self.fast_vad.sanitize() {
+            tracing::warn!("fast_vad: {fix}");
+        }
+    }
+
     pub fn migrate_legacy_transcription_settings(&mut self) {
         if let Some(model) = self.legacy_model.take() {
             self.transcription.whisper_cpp.model = model;
@@ -527,6 +2393,88 @@ impl Config {
         }
     }
 
+    /// Human-readable summary of the keys that differ from `previous`, one
+    /// entry per changed setting. Secret values are never included.
+    pub fn changes_since(&self, previous: &Config) -> Vec<String> {
+        let (Ok(old), Ok(new)) = (serde_json::to_value(previous), serde_json::to_value(self))
+        else {
+            return Vec::new();
+        };
+
+        patch::diff(&old, &new)
+            .into_iter()
+            .map(|change| match change {
+                Change::Remove(path) => format!("{} removed", path.join(".")),
+                Change::Set(path, value) => {
+                    let key = path.join(".");
+                    let before = path.iter().try_fold(&old, |node, part| node.get(part));
+                    match before {
+                        None => format!("{key} added"),
+                        Some(_) if path.iter().any(|part| is_secret_key(part)) => {
+                            format!("{key} changed")
+                        }
+                        Some(before)
+                            if !before.is_object() && !value.is_object() && !value.is_array() =>
+                        {
+                            format!("{key}: {before} → {value}")
+                        }
+                        Some(_) => format!("{key} updated"),
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Deserializes the `extra.<namespace>` block. Returns `Ok(None)` when the
+    /// namespace is absent and an error when it doesn't match `T`.
+    pub fn extension<T: DeserializeOwned>(&self, namespace: &str) -> Result<Option<T>> {
+        self.extra
+            .get(namespace)
+            .map(|value| {
+                serde_json::from_value(value.clone())
+                    .with_context(|| format!("Invalid settings in extra.{namespace}"))
+            })
+            .transpose()
+    }
+
+    pub fn set_extension<T: Serialize>(&mut self, namespace: &str, settings: &T) -> Result<()> {
+        let value = serde_json::to_value(settings)
+            .with_context(|| format!("Failed to serialize extra.{namespace}"))?;
+        self.extra.insert(namespace.to_string(), value);
+        Ok(())
+    }
+
+    /// `exports`, plus the dual-output archive while that is on.
+    pub fn export_targets(&self) -> Vec<ExportTarget> {
+        self.exports
+            .iter()
+            .cloned()
+            .chain(self.dual_output.archive_target())
+            .collect()
+    }
+
+    /// Per-profile replacements for [`Self::export_targets`], each with the
+    /// dual-output archive added.
+    pub fn profile_export_targets(&self) -> HashMap<String, Vec<ExportTarget>> {
+        self.profiles
+            .iter()
+            .filter_map(|(class, profile)| {
+                let exports = profile.exports.as_ref()?;
+                let targets = exports
+                    .iter()
+                    .cloned()
+                    .chain(self.dual_output.archive_target())
+                    .collect();
+                Some((class.clone(), targets))
+            })
+            .collect()
+    }
+
+    /// `max_recording_secs`, unless it is 0.
+    pub fn max_recording(&self) -> Option<Duration> {
+        (self.max_recording_secs > 0).then(|| Duration::from_secs(self.max_recording_secs))
+    }
+
     pub fn press_shortcut(&self) -> Option<&str> {
         self.shortcuts.press.as_deref()
     }
@@ -535,6 +2483,14 @@ impl Config {
         self.shortcuts.hold.as_deref()
     }
 
+    pub fn cancel_shortcut(&self) -> Option<&str> {
+        self.shortcuts.cancel.as_deref()
+    }
+
+    pub fn mode_shortcut(&self) -> Option<&str> {
+        self.shortcuts.mode.as_deref()
+    }
+
     fn sanitize_shortcut(value: &str) -> Option<String> {
         let trimmed = value.trim();
         if trimmed.is_empty() {
@@ -555,6 +2511,8 @@ struct ConfigManagerInner {
     config_path: PathBuf,
     change_tx: watch::Sender<Config>,
     watcher_active: AtomicBool,
+    /// The missing configured model and the installed one standing in.
+    model_fallback: Mutex<Option<(PathBuf, PathBuf)>>,
 }
 
 impl ConfigManager {
@@ -598,6 +2556,7 @@ impl ConfigManager {
                 config_path,
                 change_tx,
                 watcher_active: AtomicBool::new(false),
+                model_fallback: Mutex::new(None),
             }),
         })
     }
@@ -631,13 +2590,12 @@ impl ConfigManager {
                             *guard = new_config.clone();
                             drop(guard);
 
-                            if inner.change_tx.send(new_config.clone()).is_ok() {
+                            let changes = new_config.changes_since(&old_config);
+                            if inner.change_tx.send(new_config).is_ok() {
                                 tracing::info!("Reloaded config from: {:?}", inner.config_path);
-                                tracing::debug!(
-                                    ?old_config,
-                                    ?new_config,
-                                    "Config watcher applied update"
-                                );
+                                for change in &changes {
+                                    tracing::info!("  • {change}");
+                                }
                             }
                         }
                     }
@@ -661,6 +2619,13 @@ impl ConfigManager {
             .clone()
     }
 
+    /// Applies `edit` to the current config, then saves and broadcasts it
+    /// like a reload.
+    pub fn update(&self, edit: impl FnOnce(&mut Config)) -> Result<()> {
+        edit(&mut self.inner.config.write().expect("config lock poisoned"));
+        self.save()
+    }
+
     pub fn save(&self) -> Result<()> {
         let config = self.get();
         Self::write_config_file(&self.inner.config_path, &config)?;
@@ -676,9 +2641,82 @@ impl ConfigManager {
         Ok(())
     }
 
+    /// The configured model, or the closest installed one when it's
+    /// missing. The fallback is looked up, and warned about, once per
+    /// missing model.
     pub fn get_model_path(&self) -> PathBuf {
         let config = self.get();
-        Self::resolve_model_path(&config)
+        let configured = Self::configured_model_path(&config);
+        if configured.exists() {
+            return configured;
+        }
+
+        let mut cached = self
+            .inner
+            .model_fallback
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some((missing, fallback)) = cached.as_ref() {
+            if *missing == configured && fallback.exists() {
+                return fallback.clone();
+            }
+        }
+        let fallback = Self::fallback_model_path(&config, &configured);
+        *cached = Some((configured, fallback.clone()));
+        fallback
+    }
+
+    /// The configured whisper.cpp model's name when it isn't installed.
+    pub fn missing_model(&self) -> Option<String> {
+        let config = self.get();
+        (!Self::configured_model_path(&config).exists())
+            .then(|| config.transcription.whisper_cpp.model.clone())
+    }
+
+    /// Whisper ggml models found in the model search directories, in search
+    /// order. VAD models are skipped.
+    pub fn installed_models(&self) -> Vec<PathBuf> {
+        Self::find_models(&self.get())
+    }
+
+    fn find_models(config: &Config) -> Vec<PathBuf> {
+        let mut models = Vec::new();
+
+        for dir in Self::model_search_dirs(config) {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+
+            let mut found: Vec<PathBuf> = entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| {
+                    path.file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| {
+                            name.starts_with("ggml-")
+                                && name.ends_with(".bin")
+                                && !name.contains("silero")
+                        })
+                })
+                .collect();
+            found.sort();
+            models.extend(found);
+        }
+
+        models
+    }
+
+    /// Where `model pull` saves models: the first `models_dirs` entry, or
+    /// the legacy hyprwhspr directory when none is configured.
+    pub fn model_download_dir(&self) -> PathBuf {
+        let config = self.get();
+        match config.transcription.whisper_cpp.models_dirs.first() {
+            Some(dir) => expand_models_dir(dir),
+            None => {
+                let home = env::var("HOME").expect("HOME not set");
+                PathBuf::from(home).join(".local/share/hyprwhspr/whisper.cpp/models")
+            }
+        }
     }
 
     pub fn get_vad_model_path(&self, config: &Config) -> Option<PathBuf> {
@@ -726,6 +2764,54 @@ impl ConfigManager {
         temp_dir
     }
 
+    pub fn get_request_log_path(&self, config: &Config) -> PathBuf {
+        if let Some(path) = config.transcription.request_log.path.as_deref() {
+            let trimmed = path.trim();
+            if !trimmed.is_empty() {
+                if let (Some(rest), Ok(home)) = (trimmed.strip_prefix("~/"), env::var("HOME")) {
+                    return PathBuf::from(home).join(rest);
+                }
+                return PathBuf::from(trimmed);
+            }
+        }
+
+        directories::ProjectDirs::from("", "", "hyprwhspr-rs")
+            .expect("Failed to get data directory")
+            .data_dir()
+            .join("request-log.jsonl")
+    }
+
+    pub fn get_history_path(&self, config: &Config) -> PathBuf {
+        if let Some(path) = config.history.path.as_deref() {
+            let trimmed = path.trim();
+            if !trimmed.is_empty() {
+                if let (Some(rest), Ok(home)) = (trimmed.strip_prefix("~/"), env::var("HOME")) {
+                    return PathBuf::from(home).join(rest);
+                }
+                return PathBuf::from(trimmed);
+            }
+        }
+
+        directories::ProjectDirs::from("", "", "hyprwhspr-rs")
+            .expect("Failed to get data directory")
+            .data_dir()
+            .join("history.jsonl")
+    }
+
+    pub fn get_last_recording_path(&self) -> PathBuf {
+        directories::ProjectDirs::from("", "", "hyprwhspr-rs")
+            .expect("Failed to get data directory")
+            .data_dir()
+            .join("last_recording.wav")
+    }
+
+    pub fn get_pending_dir(&self) -> PathBuf {
+        directories::ProjectDirs::from("", "", "hyprwhspr-rs")
+            .expect("Failed to get data directory")
+            .data_dir()
+            .join("pending")
+    }
+
     pub fn get_assets_dir(&self) -> PathBuf {
         let install_path = PathBuf::from("/usr/lib/hyprwhspr-rs/share/assets");
         if install_path.exists() {
@@ -741,21 +2827,80 @@ impl ConfigManager {
         Self::parse_config(&content)
     }
 
+    /// Writes `config` to `path`. When the file already exists only the keys
+    /// whose effective value changed are edited, so comments and formatting
+    /// survive; a full rewrite is the fallback when patching fails.
     fn write_config_file(path: &Path, config: &Config) -> Result<()> {
         let mut config = config.clone();
         config.normalize_shortcuts();
-        let json = serde_json::to_string_pretty(&config).context("Failed to serialize config")?;
+        let value = serde_json::to_value(&config).context("Failed to serialize config")?;
+
+        if let Ok(existing) = fs::read_to_string(path) {
+            match Self::patch_config_text(&existing, &value) {
+                Ok(patched) if patched == existing => return Ok(()),
+                Ok(patched) => {
+                    return fs::write(path, patched)
+                        .with_context(|| format!("Failed to write config file at {:?}", path));
+                }
+                Err(err) => tracing::warn!(
+                    "Could not preserve formatting of {:?} ({err:#}); rewriting it",
+                    path
+                ),
+            }
+        }
+
+        let json = serde_json::to_string_pretty(&value).context("Failed to serialize config")?;
         fs::write(path, json).with_context(|| format!("Failed to write config file at {:?}", path))
     }
 
+    fn patch_config_text(existing: &str, value: &Value) -> Result<String> {
+        let document = parse_to_serde_value(existing, &ParseOptions::default())
+            .context("Failed to parse config as JSONC")?
+            .ok_or_else(|| anyhow!("Config file did not contain a JSON value"))?;
+        let previous = serde_json::to_value(Self::parse_config(existing)?)
+            .context("Failed to serialize previous config")?;
+
+        // Legacy keys override their replacements on every load, so they
+        // have to go once the replacements are written out explicitly.
+        let mut changes = Vec::new();
+        let mut migrated = Vec::new();
+        for (key, targets) in LEGACY_CONFIG_KEYS {
+            if document.get(*key).is_none() {
+                continue;
+            }
+            changes.push(Change::Remove(vec![key.to_string()]));
+            for target in *targets {
+                let path: Vec<String> = target.iter().map(|part| part.to_string()).collect();
+                let current = path.iter().try_fold(value, |node, part| node.get(part));
+                if let Some(current) = current {
+                    migrated.push(Change::Set(path, current.clone()));
+                }
+            }
+        }
+        changes.extend(patch::diff(&previous, value));
+        changes.extend(migrated);
+
+        let patched = patch::apply(existing, &changes)?;
+        let reparsed = serde_json::to_value(Self::parse_config(&patched)?)
+            .context("Failed to serialize patched config")?;
+        if reparsed != *value {
+            return Err(anyhow!("patched config does not match the saved settings"));
+        }
+        Ok(patched)
+    }
+
     fn parse_config(content: &str) -> Result<Config> {
         let value = parse_to_serde_value(content, &ParseOptions::default())
             .context("Failed to parse config as JSONC")?
             .ok_or_else(|| anyhow!("Config file did not contain a JSON value"))?;
+        let model_set = value.get("model").is_some()
+            || value.pointer("/transcription/whisper_cpp/model").is_some();
         let mut config: Config =
             serde_json::from_value(value).context("Failed to deserialize config")?;
         config.migrate_legacy_transcription_settings();
         config.normalize_shortcuts();
+        config.sanitize_vad();
+        config.apply_low_memory_defaults(model_set);
         Ok(config)
     }
 
@@ -765,7 +2910,32 @@ impl ConfigManager {
         Some((modified, metadata.len()))
     }
 
-    fn resolve_model_path(config: &Config) -> PathBuf {
+    /// The installed model closest to the missing `configured` one, or
+    /// `configured` itself when there is none.
+    fn fallback_model_path(config: &Config, configured: &Path) -> PathBuf {
+        let installed: Vec<(PathBuf, u64)> = Self::find_models(config)
+            .into_iter()
+            .map(|path| {
+                let size = fs::metadata(&path)
+                    .map(|meta| meta.len())
+                    .unwrap_or(u64::MAX);
+                (path, size)
+            })
+            .collect();
+        match crate::models::closest_model(&config.transcription.whisper_cpp.model, &installed) {
+            Some(fallback) => {
+                tracing::warn!(
+                    "Whisper model {:?} not found; using {:?} instead",
+                    configured,
+                    fallback
+                );
+                fallback.to_path_buf()
+            }
+            None => configured.to_path_buf(),
+        }
+    }
+
+    fn configured_model_path(config: &Config) -> PathBuf {
         let models_dir = Self::model_search_dirs(config)
             .into_iter()
             .next()
@@ -832,16 +3002,7 @@ impl ConfigManager {
 
         // Add custom models directories from config (with path expansion)
         for dir_str in &config.transcription.whisper_cpp.models_dirs {
-            let expanded = if dir_str.starts_with("~/") {
-                if let Ok(home) = env::var("HOME") {
-                    PathBuf::from(home).join(&dir_str[2..])
-                } else {
-                    PathBuf::from(dir_str)
-                }
-            } else {
-                PathBuf::from(dir_str)
-            };
-
+            let expanded = expand_models_dir(dir_str);
             if expanded.exists() {
                 dirs.push(expanded);
             }
@@ -862,3 +3023,61 @@ impl ConfigManager {
         dirs
     }
 }
+
+fn expand_models_dir(dir: &str) -> PathBuf {
+    match (dir.strip_prefix("~/"), env::var("HOME")) {
+        (Some(rest), Ok(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(dir),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn press_debounce_leaves_room_for_double_presses() {
+        let mut shortcuts = ShortcutsConfig {
+            press_debounce_ms: 500,
+            ..ShortcutsConfig::default()
+        };
+        assert_eq!(shortcuts.press_debounce(), Duration::from_millis(500));
+
+        shortcuts
+            .actions
+            .insert(ShortcutTrigger::DoublePress, ShortcutAction::ReinjectLast);
+        assert_eq!(shortcuts.press_debounce(), Duration::from_millis(200));
+
+        shortcuts.press_debounce_ms = ShortcutsConfig::default().press_debounce_ms;
+        assert!(shortcuts.press_debounce() < Duration::from_millis(shortcuts.double_press_ms));
+    }
+
+    #[test]
+    fn extra_namespaces_survive_load_and_save() {
+        let path = env::temp_dir().join(format!("hyprwhspr-extra-{}.jsonc", std::process::id()));
+        fs::write(
+            &path,
+            r#"{
+  // hook settings
+  "extra": { "my-hook": { "endpoint": "http://localhost:9000", "retries": 3 } },
+  "audio_feedback": false
+}"#,
+        )
+        .unwrap();
+
+        let mut config = ConfigManager::read_config_from_disk(&path).unwrap();
+        config.audio_feedback = true;
+        ConfigManager::write_config_file(&path, &config).unwrap();
+        let saved = fs::read_to_string(&path).unwrap();
+        let reloaded = ConfigManager::read_config_from_disk(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(saved.contains("// hook settings"));
+        assert_eq!(
+            reloaded.extension::<Value>("my-hook").unwrap(),
+            Some(serde_json::json!({ "endpoint": "http://localhost:9000", "retries": 3 }))
+        );
+        assert!(reloaded.audio_feedback);
+        assert_eq!(reloaded.extension::<Value>("other").unwrap(), None);
+    }
+}
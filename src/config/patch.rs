@@ -0,0 +1,481 @@
+//! Targeted edits of a JSONC document so programmatic saves keep the user's
+//! comments, ordering, and formatting. Only keys whose effective value
+//! changed are touched; everything else is left byte-for-byte intact.
+
+use anyhow::{anyhow, bail, Result};
+use serde_json::{Map, Value};
+use std::cmp::Reverse;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Set(Vec<String>, Value),
+    Remove(Vec<String>),
+}
+
+/// Computes the key-level changes needed to turn `old` into `new`.
+/// Objects are compared member by member; anything else is replaced wholesale.
+pub fn diff(old: &Value, new: &Value) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_into(&mut Vec::new(), old, new, &mut changes);
+    changes
+}
+
+fn diff_into(path: &mut Vec<String>, old: &Value, new: &Value, out: &mut Vec<Change>) {
+    match (old, new) {
+        (Value::Object(old_map), Value::Object(new_map)) => {
+            for (key, new_value) in new_map {
+                path.push(key.clone());
+                match old_map.get(key) {
+                    Some(old_value) => diff_into(path, old_value, new_value, out),
+                    None => out.push(Change::Set(path.clone(), new_value.clone())),
+                }
+                path.pop();
+            }
+            for key in old_map.keys().filter(|key| !new_map.contains_key(*key)) {
+                path.push(key.clone());
+                out.push(Change::Remove(path.clone()));
+                path.pop();
+            }
+        }
+        _ if old != new => out.push(Change::Set(path.clone(), new.clone())),
+        _ => {}
+    }
+}
+
+/// Applies `changes` to `source` one at a time, re-scanning between edits so
+/// offsets never go stale.
+pub fn apply(source: &str, changes: &[Change]) -> Result<String> {
+    let mut text = source.to_string();
+    for change in changes {
+        let root = Scanner::new(&text).parse_document()?;
+        text = match change {
+            Change::Set(path, value) => set(&text, &root, path, value)?,
+            Change::Remove(path) => remove(&text, &root, path),
+        };
+    }
+    Ok(text)
+}
+
+#[derive(Debug)]
+struct Node {
+    start: usize,
+    end: usize,
+    kind: NodeKind,
+}
+
+#[derive(Debug)]
+enum NodeKind {
+    Object(Vec<Member>),
+    Other,
+}
+
+#[derive(Debug)]
+struct Member {
+    key: String,
+    key_start: usize,
+    value: Node,
+}
+
+fn set(text: &str, root: &Node, path: &[String], value: &Value) -> Result<String> {
+    if path.is_empty() {
+        return Ok(replace(text, root, value));
+    }
+
+    let mut node = root;
+    for (index, key) in path.iter().enumerate() {
+        let NodeKind::Object(members) = &node.kind else {
+            return Ok(replace(text, node, &nest(&path[index..], value)));
+        };
+        match members.iter().find(|member| member.key == *key) {
+            Some(member) => node = &member.value,
+            None => return insert_member(text, node, key, &nest(&path[index + 1..], value)),
+        }
+    }
+
+    Ok(replace(text, node, value))
+}
+
+fn remove(text: &str, root: &Node, path: &[String]) -> String {
+    let Some((last, parents)) = path.split_last() else {
+        return text.to_string();
+    };
+
+    let mut node = root;
+    for key in parents {
+        let NodeKind::Object(members) = &node.kind else {
+            return text.to_string();
+        };
+        match members.iter().find(|member| member.key == *key) {
+            Some(member) => node = &member.value,
+            None => return text.to_string(),
+        }
+    }
+
+    let NodeKind::Object(members) = &node.kind else {
+        return text.to_string();
+    };
+    let Some(index) = members.iter().position(|member| member.key == *last) else {
+        return text.to_string();
+    };
+    let member = &members[index];
+    let bytes = text.as_bytes();
+
+    let mut edits: Vec<(usize, usize, String)> = Vec::new();
+    let mut start = member.key_start;
+    let line_start = line_start(text, start);
+    if text[line_start..start].trim().is_empty() {
+        start = line_start;
+    }
+
+    let mut end = member.value.end;
+    let after = skip_trivia(bytes, end);
+    if bytes.get(after) == Some(&b',') {
+        end = after + 1;
+    } else if index > 0 {
+        // Removing the final member: drop the separator after its predecessor.
+        let previous = &members[index - 1];
+        let comma = skip_trivia(bytes, previous.value.end);
+        if bytes.get(comma) == Some(&b',') {
+            edits.push((comma, comma + 1, String::new()));
+        }
+    }
+
+    if start == line_start {
+        let line_end = text[end..].find('\n').map(|offset| end + offset);
+        if let Some(line_end) = line_end {
+            let rest = text[end..line_end].trim();
+            if rest.is_empty() || rest.starts_with("//") {
+                end = line_end + 1;
+            }
+        }
+    }
+
+    edits.push((start, end, String::new()));
+    apply_edits(text, edits)
+}
+
+fn insert_member(text: &str, object: &Node, key: &str, value: &Value) -> Result<String> {
+    let NodeKind::Object(members) = &object.kind else {
+        bail!("Cannot insert `{key}` into a non-object value");
+    };
+
+    let close = object.end - 1;
+    let close_line = line_start(text, close);
+    if !text[close_line..close].trim().is_empty() {
+        // Single-line object (`{}` or `{ "a": 1 }`): rewrite just this object.
+        let mut merged = parse_value(&text[object.start..object.end])?;
+        if let Value::Object(map) = &mut merged {
+            map.insert(key.to_string(), value.clone());
+        }
+        return Ok(replace(text, object, &merged));
+    }
+
+    let indent = match members.first() {
+        Some(first) => line_indent(text, first.key_start).to_string(),
+        None => format!("{}  ", line_indent(text, object.start)),
+    };
+    let key_json = serde_json::to_string(key)?;
+    let entry = format!("{indent}{key_json}: {}\n", render(value, &indent));
+
+    let mut edits = vec![(close_line, close_line, entry)];
+    if let Some(last) = members.last() {
+        let after = skip_trivia(text.as_bytes(), last.value.end);
+        if text.as_bytes().get(after) != Some(&b',') {
+            edits.push((last.value.end, last.value.end, ",".to_string()));
+        }
+    }
+
+    Ok(apply_edits(text, edits))
+}
+
+fn replace(text: &str, node: &Node, value: &Value) -> String {
+    let indent = line_indent(text, node.start);
+    apply_edits(text, vec![(node.start, node.end, render(value, indent))])
+}
+
+/// Applies non-overlapping `(start, end, replacement)` edits back to front.
+fn apply_edits(text: &str, mut edits: Vec<(usize, usize, String)>) -> String {
+    edits.sort_by_key(|edit| Reverse(edit.0));
+    let mut result = text.to_string();
+    for (start, end, replacement) in edits {
+        result.replace_range(start..end, &replacement);
+    }
+    result
+}
+
+fn nest(path: &[String], value: &Value) -> Value {
+    path.iter().rev().fold(value.clone(), |inner, key| {
+        let mut map = Map::new();
+        map.insert(key.clone(), inner);
+        Value::Object(map)
+    })
+}
+
+fn render(value: &Value, indent: &str) -> String {
+    let pretty = serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string());
+    pretty.replace('\n', &format!("\n{indent}"))
+}
+
+fn parse_value(text: &str) -> Result<Value> {
+    jsonc_parser::parse_to_serde_value(text, &jsonc_parser::ParseOptions::default())
+        .map_err(|err| anyhow!("{err}"))?
+        .ok_or_else(|| anyhow!("empty JSONC value"))
+}
+
+fn line_start(text: &str, pos: usize) -> usize {
+    text[..pos].rfind('\n').map(|index| index + 1).unwrap_or(0)
+}
+
+fn line_indent(text: &str, pos: usize) -> &str {
+    let start = line_start(text, pos);
+    let line = &text[start..];
+    let width = line.len() - line.trim_start_matches([' ', '\t']).len();
+    &line[..width]
+}
+
+/// Skips whitespace and comments starting at `pos`.
+fn skip_trivia(bytes: &[u8], mut pos: usize) -> usize {
+    loop {
+        while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+        if bytes[pos..].starts_with(b"//") {
+            while pos < bytes.len() && bytes[pos] != b'\n' {
+                pos += 1;
+            }
+        } else if bytes[pos..].starts_with(b"/*") {
+            pos += 2;
+            while pos < bytes.len() && !bytes[pos..].starts_with(b"*/") {
+                pos += 1;
+            }
+            pos = (pos + 2).min(bytes.len());
+        } else {
+            return pos;
+        }
+    }
+}
+
+/// Records byte spans of object members; everything that isn't an object is
+/// an opaque span.
+struct Scanner<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text, pos: 0 }
+    }
+
+    fn bytes(&self) -> &'a [u8] {
+        self.text.as_bytes()
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes().get(self.pos).copied()
+    }
+
+    fn skip_trivia(&mut self) {
+        self.pos = skip_trivia(self.bytes(), self.pos);
+    }
+
+    fn parse_document(mut self) -> Result<Node> {
+        self.skip_trivia();
+        self.parse_node()
+    }
+
+    fn parse_node(&mut self) -> Result<Node> {
+        let start = self.pos;
+        match self.peek() {
+            Some(b'{') => self.parse_object(),
+            Some(b'[') => {
+                self.skip_array()?;
+                Ok(Node {
+                    start,
+                    end: self.pos,
+                    kind: NodeKind::Other,
+                })
+            }
+            Some(b'"') | Some(b'\'') => {
+                self.skip_string()?;
+                Ok(Node {
+                    start,
+                    end: self.pos,
+                    kind: NodeKind::Other,
+                })
+            }
+            Some(_) => {
+                self.skip_word();
+                if self.pos == start {
+                    bail!("Unexpected character at byte {start} in config");
+                }
+                Ok(Node {
+                    start,
+                    end: self.pos,
+                    kind: NodeKind::Other,
+                })
+            }
+            None => bail!("Unexpected end of config"),
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<Node> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut members = Vec::new();
+
+        loop {
+            self.skip_trivia();
+            match self.peek() {
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Node {
+                        start,
+                        end: self.pos,
+                        kind: NodeKind::Object(members),
+                    });
+                }
+                Some(b',') => self.pos += 1,
+                Some(_) => {
+                    let key_start = self.pos;
+                    let key = self.parse_key()?;
+                    self.skip_trivia();
+                    if self.peek() != Some(b':') {
+                        bail!("Expected ':' after key `{key}` in config");
+                    }
+                    self.pos += 1;
+                    self.skip_trivia();
+                    let value = self.parse_node()?;
+                    members.push(Member {
+                        key,
+                        key_start,
+                        value,
+                    });
+                }
+                None => bail!("Unterminated object in config"),
+            }
+        }
+    }
+
+    fn parse_key(&mut self) -> Result<String> {
+        let start = self.pos;
+        match self.peek() {
+            Some(b'"') => {
+                self.skip_string()?;
+                Ok(serde_json::from_str(&self.text[start..self.pos])?)
+            }
+            Some(b'\'') => {
+                self.skip_string()?;
+                Ok(self.text[start + 1..self.pos - 1].to_string())
+            }
+            _ => {
+                self.skip_word();
+                Ok(self.text[start..self.pos].to_string())
+            }
+        }
+    }
+
+    fn skip_string(&mut self) -> Result<()> {
+        let quote = self.bytes()[self.pos];
+        self.pos += 1;
+        while let Some(byte) = self.peek() {
+            self.pos += 1;
+            match byte {
+                b'\\' => self.pos += 1,
+                _ if byte == quote => return Ok(()),
+                _ => {}
+            }
+        }
+        bail!("Unterminated string in config")
+    }
+
+    fn skip_word(&mut self) {
+        while let Some(byte) = self.peek() {
+            if byte.is_ascii_whitespace() || b",:{}[]/".contains(&byte) {
+                break;
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn skip_array(&mut self) -> Result<()> {
+        self.pos += 1;
+        loop {
+            self.skip_trivia();
+            match self.peek() {
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                Some(b',') => self.pos += 1,
+                Some(_) => {
+                    self.parse_node()?;
+                }
+                None => bail!("Unterminated array in config"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SOURCE: &str = r#"{
+  // Keybinds
+  "shortcuts": {
+    "press": "SUPER+ALT+D", // toggle
+  },
+  /* sounds */
+  "audio_feedback": false,
+  "model": "base" // legacy
+}"#;
+
+    #[test]
+    fn replaces_values_and_keeps_comments() {
+        let patched = apply(
+            SOURCE,
+            &[Change::Set(
+                vec!["audio_feedback".to_string()],
+                Value::Bool(true),
+            )],
+        )
+        .unwrap();
+
+        assert!(patched.contains("// Keybinds"));
+        assert!(patched.contains("/* sounds */"));
+        assert!(patched.contains("\"audio_feedback\": true,"));
+    }
+
+    #[test]
+    fn inserts_nested_keys_and_removes_members() {
+        let patched = apply(
+            SOURCE,
+            &[
+                Change::Remove(vec!["model".to_string()]),
+                Change::Set(
+                    vec!["shortcuts".to_string(), "hold".to_string()],
+                    json!("SUPER+CTRL"),
+                ),
+                Change::Set(
+                    vec!["transcription".to_string(), "provider".to_string()],
+                    json!("groq"),
+                ),
+            ],
+        )
+        .unwrap();
+
+        let value = parse_value(&patched).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "shortcuts": { "press": "SUPER+ALT+D", "hold": "SUPER+CTRL" },
+                "audio_feedback": false,
+                "transcription": { "provider": "groq" }
+            })
+        );
+        assert!(patched.contains("// toggle"));
+        assert!(!patched.contains("legacy"));
+    }
+}
@@ -0,0 +1,132 @@
+//! Do-not-disturb awareness: follows the notification daemon's DND switch
+//! (mako, swaync, dunst) and an optional quiet-hours schedule. Only audible
+//! and pop-up feedback is silenced; status files keep updating.
+
+use crate::config::DoNotDisturbConfig;
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use time::OffsetDateTime;
+use tracing::{debug, warn};
+
+/// Notification daemons are asked at most this often.
+const DESKTOP_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A notification daemon query and how to read "DND on" from its output.
+type DesktopProbe = (&'static str, &'static [&'static str], fn(&str) -> bool);
+
+const DESKTOP_PROBES: &[DesktopProbe] = &[
+    ("makoctl", &["mode"], |out| {
+        out.lines().any(|mode| mode.trim() == "do-not-disturb")
+    }),
+    ("swaync-client", &["--get-dnd", "--skip-wait"], |out| {
+        out.trim() == "true"
+    }),
+    ("dunstctl", &["is-paused"], |out| out.trim() == "true"),
+];
+
+pub struct DoNotDisturb {
+    follow_desktop: bool,
+    /// Minutes after midnight; the window may wrap past midnight.
+    quiet_hours: Option<(u16, u16)>,
+    desktop: Mutex<Option<(Instant, bool)>>,
+}
+
+impl DoNotDisturb {
+    pub fn new(config: &DoNotDisturbConfig) -> Self {
+        let quiet_hours = config.quiet_hours.as_ref().and_then(|hours| {
+            match (parse_clock(&hours.start), parse_clock(&hours.end)) {
+                (Some(start), Some(end)) => Some((start, end)),
+                _ => {
+                    warn!(
+                        "Ignoring quiet_hours {:?}-{:?}; expected HH:MM",
+                        hours.start, hours.end
+                    );
+                    None
+                }
+            }
+        });
+
+        Self {
+            follow_desktop: config.follow_desktop,
+            quiet_hours,
+            desktop: Mutex::new(None),
+        }
+    }
+
+    /// Whether sounds and notifications should be held back right now.
+    pub fn active(&self) -> bool {
+        if let Some((start, end)) = self.quiet_hours {
+            let now = OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc());
+            let minute = u16::from(now.hour()) * 60 + u16::from(now.minute());
+            if in_window(minute, start, end) {
+                return true;
+            }
+        }
+
+        self.follow_desktop && self.desktop_dnd()
+    }
+
+    fn desktop_dnd(&self) -> bool {
+        let Ok(mut cached) = self.desktop.lock() else {
+            return false;
+        };
+        if let Some((checked_at, active)) = *cached {
+            if checked_at.elapsed() < DESKTOP_POLL_INTERVAL {
+                return active;
+            }
+        }
+
+        let active = query_desktop();
+        *cached = Some((Instant::now(), active));
+        active
+    }
+}
+
+/// Asks the first notification daemon that answers.
+fn query_desktop() -> bool {
+    for (program, args, is_active) in DESKTOP_PROBES {
+        let Ok(output) = Command::new(program).args(*args).output() else {
+            continue;
+        };
+        if output.status.success() {
+            let active = is_active(&String::from_utf8_lossy(&output.stdout));
+            debug!("{} reports do-not-disturb {}", program, active);
+            return active;
+        }
+    }
+    false
+}
+
+fn parse_clock(value: &str) -> Option<u16> {
+    let (hours, minutes) = value.trim().split_once(':')?;
+    let hours: u16 = hours.parse().ok()?;
+    let minutes: u16 = minutes.parse().ok()?;
+    (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+fn in_window(minute: u16, start: u16, end: u16) -> bool {
+    if start <= end {
+        (start..end).contains(&minute)
+    } else {
+        minute >= start || minute < end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quiet_hours_wrap_past_midnight() {
+        let start = parse_clock("22:30").unwrap();
+        let end = parse_clock("07:00").unwrap();
+
+        assert!(in_window(parse_clock("23:59").unwrap(), start, end));
+        assert!(in_window(parse_clock("06:59").unwrap(), start, end));
+        assert!(!in_window(parse_clock("07:00").unwrap(), start, end));
+        assert!(!in_window(parse_clock("12:00").unwrap(), start, end));
+        assert!(in_window(parse_clock("13:00").unwrap(), 780, 840));
+        assert_eq!(parse_clock("24:00"), None);
+    }
+}
@@ -0,0 +1,110 @@
+//! In-process broadcast of the daemon's state changes. The status files and
+//! the D-Bus service subscribe here rather than being called from the
+//! recording flow, so a new integration is one receiver away.
+
+use crate::input::InjectionReport;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::warn;
+
+/// Events held for the slowest subscriber before it starts missing some.
+const CAPACITY: usize = 64;
+
+#[derive(Debug, Clone)]
+pub enum AppEvent {
+    RecordingStarted,
+    /// Cancelled recordings are thrown away instead of transcribed.
+    RecordingStopped {
+        cancelled: bool,
+    },
+    ProcessingStarted,
+    ProcessingFinished,
+    /// A non-empty transcript, before cleanup and injection.
+    TranscriptReady {
+        text: String,
+        backend: &'static str,
+    },
+    /// The backend returned no speech; nothing gets injected.
+    EmptyTranscript {
+        backend: &'static str,
+    },
+    InjectionDone(InjectionReport),
+    /// A transcript of audio spooled before a restart; it is copied (when
+    /// `copied`) rather than pasted into whatever has focus now.
+    Recovered {
+        text: String,
+        copied: bool,
+    },
+    Error {
+        message: String,
+    },
+}
+
+#[derive(Clone)]
+pub struct EventBus {
+    tx: broadcast::Sender<AppEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (tx, _) = broadcast::channel(CAPACITY);
+        Self { tx }
+    }
+
+    /// Events published while nobody is subscribed are dropped.
+    pub fn publish(&self, event: AppEvent) {
+        let _ = self.tx.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<AppEvent> {
+        self.tx.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Next event for `subscriber`, skipping any it fell behind on; `None` once
+/// the bus is gone.
+pub async fn next_event(
+    rx: &mut broadcast::Receiver<AppEvent>,
+    subscriber: &str,
+) -> Option<AppEvent> {
+    loop {
+        match rx.recv().await {
+            Ok(event) => return Some(event),
+            Err(RecvError::Lagged(missed)) => {
+                warn!("{subscriber} fell behind and missed {missed} event(s)");
+            }
+            Err(RecvError::Closed) => return None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn subscribers_see_events_in_order() {
+        let bus = EventBus::new();
+        bus.publish(AppEvent::RecordingStarted);
+
+        let mut rx = bus.subscribe();
+        bus.publish(AppEvent::RecordingStarted);
+        bus.publish(AppEvent::RecordingStopped { cancelled: true });
+        drop(bus);
+
+        assert!(matches!(
+            next_event(&mut rx, "test").await,
+            Some(AppEvent::RecordingStarted)
+        ));
+        assert!(matches!(
+            next_event(&mut rx, "test").await,
+            Some(AppEvent::RecordingStopped { cancelled: true })
+        ));
+        assert!(next_event(&mut rx, "test").await.is_none());
+    }
+}
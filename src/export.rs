@@ -0,0 +1,680 @@
+//! Appends transcripts to note apps (Obsidian, Logseq, Joplin) through
+//! their local HTTP APIs, or to plain Markdown files, alongside the normal
+//! injection. File targets in notes mode also follow spoken structure:
+//! "new note" and "note title …" start a new headed note, "new paragraph"
+//! leaves a blank line, and other dictations join the current note.
+
+use crate::config::ExportTarget;
+use crate::transcription::TranscriptSegment;
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+#[cfg(feature = "remote")]
+use reqwest::Client;
+#[cfg(feature = "remote")]
+use serde::Deserialize;
+#[cfg(feature = "remote")]
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+use time::macros::format_description;
+use time::{Date, OffsetDateTime};
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+use url::Url;
+
+/// One transcript, with what the file target's header templates can show.
+#[derive(Debug, Clone)]
+pub struct ExportEntry {
+    pub text: String,
+    pub backend: &'static str,
+    pub audio_duration: Duration,
+    pub meeting: Option<String>,
+    /// Timed spans of the raw transcript, when the backend reports them.
+    pub segments: Vec<TranscriptSegment>,
+    pub at: OffsetDateTime,
+}
+
+impl ExportEntry {
+    pub fn new(
+        text: &str,
+        backend: &'static str,
+        audio_duration: Duration,
+        meeting: Option<String>,
+        segments: Vec<TranscriptSegment>,
+    ) -> Self {
+        Self {
+            text: text.to_string(),
+            backend,
+            audio_duration,
+            meeting,
+            segments,
+            at: OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc()),
+        }
+    }
+}
+
+static NOTE_COMMAND: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(new note|new paragraph|note title)\b[\s,.;:!?]*")
+        .expect("valid note command regex")
+});
+
+/// Hands transcripts to one background task, so they are exported in the
+/// order they were dictated and a Joplin note's read-modify-write never
+/// overlaps another.
+#[derive(Clone, Default)]
+pub struct Exporter {
+    queue: Option<mpsc::UnboundedSender<(ExportEntry, Option<String>)>>,
+}
+
+struct ExportWorker {
+    #[cfg(feature = "remote")]
+    client: Client,
+    targets: Vec<ExportTarget>,
+    /// Replacements for `targets` while a profile is active, keyed like
+    /// `profiles` (window class or name, case-insensitive).
+    profiles: HashMap<String, Vec<ExportTarget>>,
+    /// The open note per notes-mode file target, keyed by its path template.
+    notes: Arc<Mutex<HashMap<String, NoteSession>>>,
+}
+
+/// The note a notes-mode file target is currently writing.
+#[derive(Debug, Default)]
+struct NoteSession {
+    title: Option<String>,
+    /// Set once the note's header is written.
+    path: Option<PathBuf>,
+    paragraph_break: bool,
+}
+
+/// Spoken structure in a transcript bound for a notes file.
+#[derive(Debug, Clone, PartialEq)]
+enum NotePart {
+    Text(String),
+    NewParagraph,
+    NewNote,
+    Title(String),
+}
+
+impl Exporter {
+    /// Starts the export task unless there is nothing to export to.
+    pub fn new(
+        targets: Vec<ExportTarget>,
+        profiles: HashMap<String, Vec<ExportTarget>>,
+    ) -> Result<Self> {
+        if targets.is_empty() && profiles.values().all(Vec::is_empty) {
+            return Ok(Self::default());
+        }
+        let worker = ExportWorker::new(targets, profiles)?;
+        let (queue, rx) = mpsc::unbounded_channel();
+        tokio::spawn(worker.run(rx));
+        Ok(Self { queue: Some(queue) })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_none()
+    }
+
+    /// Queues `entry` for the targets of `profile` (the global ones when it
+    /// has none of its own).
+    pub fn export(&self, entry: ExportEntry, profile: Option<String>) {
+        if let Some(queue) = &self.queue {
+            let _ = queue.send((entry, profile));
+        }
+    }
+}
+
+impl ExportWorker {
+    fn new(
+        targets: Vec<ExportTarget>,
+        profiles: HashMap<String, Vec<ExportTarget>>,
+    ) -> Result<Self> {
+        let all_targets = || targets.iter().chain(profiles.values().flatten());
+
+        #[cfg(not(feature = "remote"))]
+        if let Some(target) = all_targets().find(|target| target_url(target).is_some()) {
+            warn!(
+                "{} export needs a build with the `remote` feature; only file targets will be written",
+                target_name(target)
+            );
+        }
+
+        // Obsidian's REST plugin serves a self-signed certificate on
+        // loopback; nothing else is contacted.
+        #[cfg(feature = "remote")]
+        let client = Client::builder()
+            .user_agent("hyprwhspr-rs (export)")
+            .connect_timeout(Duration::from_secs(3))
+            .timeout(Duration::from_secs(10))
+            .danger_accept_invalid_certs(true)
+            .build()
+            .context("Failed to build export HTTP client")?;
+
+        for target in all_targets() {
+            if let Some(url) = target_url(target).filter(|url| !is_loopback(url)) {
+                warn!("Export endpoint {url} is not on this machine; it will be skipped");
+            }
+        }
+
+        Ok(Self {
+            #[cfg(feature = "remote")]
+            client,
+            targets,
+            profiles,
+            notes: Arc::default(),
+        })
+    }
+
+    /// Exports queued transcripts one at a time until the exporter is
+    /// dropped.
+    async fn run(self, mut rx: mpsc::UnboundedReceiver<(ExportEntry, Option<String>)>) {
+        while let Some((entry, profile)) = rx.recv().await {
+            self.export(&entry, profile.as_deref()).await;
+        }
+    }
+
+    fn targets_for(&self, profile: Option<&str>) -> &[ExportTarget] {
+        profile
+            .and_then(|profile| {
+                self.profiles
+                    .iter()
+                    .find(|(key, _)| key.trim().eq_ignore_ascii_case(profile))
+            })
+            .map_or(&self.targets, |(_, targets)| targets)
+    }
+
+    /// Sends `entry` to every target; failures are logged, not returned.
+    async fn export(&self, entry: &ExportEntry, profile: Option<&str>) {
+        for target in self.targets_for(profile) {
+            if target_url(target).is_some_and(|url| !is_loopback(url)) {
+                continue;
+            }
+            match self.export_to(target, entry).await {
+                Ok(()) => debug!("Exported transcript to {}", target_name(target)),
+                Err(err) => warn!(
+                    "Failed to export transcript to {}: {:#}",
+                    target_name(target),
+                    err
+                ),
+            }
+        }
+    }
+
+    async fn export_to(&self, target: &ExportTarget, entry: &ExportEntry) -> Result<()> {
+        let text = entry.text.as_str();
+        match target {
+            #[cfg(feature = "remote")]
+            ExportTarget::Obsidian { url, api_key } => {
+                let endpoint = join(url, "periodic/daily/")?;
+                self.client
+                    .post(endpoint)
+                    .bearer_auth(api_key)
+                    .header("Content-Type", "text/markdown")
+                    .body(format!("\n- {text}"))
+                    .send()
+                    .await?
+                    .error_for_status()?;
+            }
+            #[cfg(feature = "remote")]
+            ExportTarget::Logseq { url, token, page } => {
+                let page = page
+                    .clone()
+                    .unwrap_or_else(|| logseq_journal_title(today()));
+                self.client
+                    .post(join(url, "api")?)
+                    .bearer_auth(token)
+                    .json(&json!({
+                        "method": "logseq.Editor.appendBlockInPage",
+                        "args": [page, text],
+                    }))
+                    .send()
+                    .await?
+                    .error_for_status()?;
+            }
+            #[cfg(feature = "remote")]
+            ExportTarget::Joplin {
+                url,
+                token,
+                note_id,
+            } => {
+                #[derive(Deserialize)]
+                struct Note {
+                    body: String,
+                }
+
+                let mut endpoint = join(url, &format!("notes/{note_id}"))?;
+                endpoint.query_pairs_mut().append_pair("token", token);
+
+                let mut read = endpoint.clone();
+                read.query_pairs_mut().append_pair("fields", "body");
+                let note: Note = self
+                    .client
+                    .get(read)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await
+                    .context("Unexpected Joplin note response")?;
+
+                let body = if note.body.trim().is_empty() {
+                    text.to_string()
+                } else {
+                    format!("{}\n\n{text}", note.body.trim_end())
+                };
+                self.client
+                    .put(endpoint)
+                    .json(&json!({ "body": body }))
+                    .send()
+                    .await?
+                    .error_for_status()?;
+            }
+            #[cfg(not(feature = "remote"))]
+            ExportTarget::Obsidian { .. }
+            | ExportTarget::Logseq { .. }
+            | ExportTarget::Joplin { .. } => {
+                bail!("this build lacks the `remote` feature")
+            }
+            ExportTarget::File {
+                path,
+                header,
+                notes: false,
+                timestamps,
+            } => {
+                let path = expand_home(&fill_template(path, entry));
+                let header = fill_template(header, entry);
+                let text = if *timestamps && !entry.segments.is_empty() {
+                    timestamped(&entry.segments)
+                } else {
+                    text.trim().to_string()
+                };
+                tokio::task::spawn_blocking(move || append_entry(path, &header, &text)).await??;
+            }
+            ExportTarget::File {
+                path,
+                header,
+                notes: true,
+                ..
+            } => {
+                let notes = Arc::clone(&self.notes);
+                let (path, header, entry) = (path.clone(), header.clone(), entry.clone());
+                tokio::task::spawn_blocking(move || {
+                    let mut notes = notes
+                        .lock()
+                        .unwrap_or_else(|poisoned| poisoned.into_inner());
+                    let session = notes.entry(path.clone()).or_default();
+                    append_to_notes(session, &path, &header, &entry)
+                })
+                .await??;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Appends a headed entry, creating the file (and its directory) for the
+/// first entry of a new day.
+fn append_entry(path: PathBuf, header: &str, text: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    let entry = if header.trim().is_empty() {
+        format!("{text}\n\n")
+    } else {
+        format!("{}\n\n{text}\n\n", header.trim_end())
+    };
+    file.write_all(entry.as_bytes())
+        .with_context(|| format!("Failed to append to {}", path.display()))
+}
+
+/// Writes `entry` into the session's current note, opening a new one (with
+/// its header) at each spoken boundary and before the first text.
+fn append_to_notes(
+    session: &mut NoteSession,
+    path_template: &str,
+    header_template: &str,
+    entry: &ExportEntry,
+) -> Result<()> {
+    for part in parse_note_parts(&entry.text) {
+        match part {
+            NotePart::NewNote => *session = NoteSession::default(),
+            NotePart::Title(title) => {
+                if session.path.is_some() {
+                    *session = NoteSession::default();
+                }
+                session.title = Some(title);
+            }
+            NotePart::NewParagraph => session.paragraph_break = session.path.is_some(),
+            NotePart::Text(text) => {
+                let path = match &session.path {
+                    Some(path) => path.clone(),
+                    None => {
+                        let title = session
+                            .title
+                            .clone()
+                            .unwrap_or_else(|| fill_template("{date} {time}", entry));
+                        let path = expand_home(&fill_template(
+                            &path_template.replace("{title}", &title.replace('/', "-")),
+                            entry,
+                        ));
+                        let header =
+                            fill_template(&header_template.replace("{title}", &title), entry);
+                        let separator = fs::metadata(&path).is_ok_and(|meta| meta.len() > 0);
+                        let header = if header.trim().is_empty() {
+                            String::new()
+                        } else {
+                            format!("{}\n\n", header.trim_end())
+                        };
+                        append_text(
+                            &path,
+                            &format!("{}{header}", if separator { "\n" } else { "" }),
+                        )?;
+                        session.path = Some(path.clone());
+                        path
+                    }
+                };
+                let lead = if std::mem::take(&mut session.paragraph_break) {
+                    "\n"
+                } else {
+                    ""
+                };
+                append_text(&path, &format!("{lead}{text}\n"))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Splits `text` at "new note", "new paragraph" and "note title …"; a
+/// title runs to the end of its sentence.
+fn parse_note_parts(text: &str) -> Vec<NotePart> {
+    let mut parts = Vec::new();
+    let mut title_pending = false;
+    let mut rest = 0;
+    for captures in NOTE_COMMAND.captures_iter(text) {
+        let command = captures.get(0).expect("whole match");
+        push_note_text(&mut parts, &text[rest..command.start()], &mut title_pending);
+        match captures[1].to_ascii_lowercase().as_str() {
+            "new note" => parts.push(NotePart::NewNote),
+            "new paragraph" => parts.push(NotePart::NewParagraph),
+            _ => title_pending = true,
+        }
+        rest = command.end();
+    }
+    push_note_text(&mut parts, &text[rest..], &mut title_pending);
+    parts
+}
+
+fn push_note_text(parts: &mut Vec<NotePart>, segment: &str, title_pending: &mut bool) {
+    let mut segment = segment;
+    if std::mem::take(title_pending) {
+        let end = segment.find(['.', '!', '?', '\n']).unwrap_or(segment.len());
+        let title = segment[..end].trim_matches(|c: char| c.is_whitespace() || ",;:".contains(c));
+        if !title.is_empty() {
+            parts.push(NotePart::Title(title.to_string()));
+        }
+        segment = &segment[end..];
+    }
+    let text = segment
+        .trim_start_matches(|c: char| c.is_whitespace() || ",.;:!?".contains(c))
+        .trim_end_matches(|c: char| c.is_whitespace() || ",;:".contains(c));
+    if !text.is_empty() {
+        parts.push(NotePart::Text(text.to_string()));
+    }
+}
+
+fn append_text(path: &PathBuf, text: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(text.as_bytes()))
+        .with_context(|| format!("Failed to append to {}", path.display()))
+}
+
+/// One `[m:ss] text` line per segment, timed from the start of the
+/// recording.
+fn timestamped(segments: &[TranscriptSegment]) -> String {
+    segments
+        .iter()
+        .map(|segment| {
+            let secs = segment.start_secs.max(0.0) as u32;
+            format!("[{}:{:02}] {}", secs / 60, secs % 60, segment.text.trim())
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Expands `{date}`, `{time}`, `{year}`, `{month}`, `{day}`, `{backend}`,
+/// `{duration}` (whole seconds) and `{meeting}` in a path or header template.
+fn fill_template(template: &str, entry: &ExportEntry) -> String {
+    let date = format_description!("[year]-[month]-[day]");
+    let time = format_description!("[hour]:[minute]");
+    let format = |description| entry.at.format(description).unwrap_or_default();
+
+    template
+        .replace("{date}", &format(date))
+        .replace("{time}", &format(time))
+        .replace("{year}", &entry.at.year().to_string())
+        .replace("{month}", &format!("{:02}", u8::from(entry.at.month())))
+        .replace("{day}", &format!("{:02}", entry.at.day()))
+        .replace("{backend}", entry.backend)
+        .replace(
+            "{duration}",
+            &entry.audio_duration.as_secs_f32().round().to_string(),
+        )
+        .replace("{meeting}", entry.meeting.as_deref().unwrap_or(""))
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+fn target_name(target: &ExportTarget) -> &'static str {
+    match target {
+        ExportTarget::Obsidian { .. } => "Obsidian",
+        ExportTarget::Logseq { .. } => "Logseq",
+        ExportTarget::Joplin { .. } => "Joplin",
+        ExportTarget::File { .. } => "file",
+    }
+}
+
+/// HTTP endpoint of a note app; `None` for local files.
+fn target_url(target: &ExportTarget) -> Option<&str> {
+    match target {
+        ExportTarget::Obsidian { url, .. }
+        | ExportTarget::Logseq { url, .. }
+        | ExportTarget::Joplin { url, .. } => Some(url),
+        ExportTarget::File { .. } => None,
+    }
+}
+
+/// Certificate checks are off, so only local endpoints are trusted.
+fn is_loopback(url: &str) -> bool {
+    Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .is_some_and(|host| matches!(host.as_str(), "localhost" | "127.0.0.1" | "[::1]"))
+}
+
+#[cfg(feature = "remote")]
+fn join(base: &str, path: &str) -> Result<Url> {
+    let mut base = base.to_string();
+    if !base.ends_with('/') {
+        base.push('/');
+    }
+    let Ok(url) = Url::parse(&base).and_then(|base| base.join(path)) else {
+        bail!("Invalid export URL: {base}");
+    };
+    Ok(url)
+}
+
+#[cfg(feature = "remote")]
+fn today() -> Date {
+    OffsetDateTime::now_local()
+        .unwrap_or_else(|_| OffsetDateTime::now_utc())
+        .date()
+}
+
+/// Logseq's default journal title format, `MMM do, yyyy` ("Oct 16th, 2026").
+#[cfg_attr(not(feature = "remote"), allow(dead_code))]
+fn logseq_journal_title(date: Date) -> String {
+    let day = date.day();
+    let suffix = match (day % 10, day % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    let month = date.month().to_string();
+    format!("{} {day}{suffix}, {}", &month[..3], date.year())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Month;
+
+    #[test]
+    fn formats_logseq_journal_titles() {
+        let date = |day| Date::from_calendar_date(2026, Month::October, day).unwrap();
+        assert_eq!(logseq_journal_title(date(1)), "Oct 1st, 2026");
+        assert_eq!(logseq_journal_title(date(12)), "Oct 12th, 2026");
+        assert_eq!(logseq_journal_title(date(22)), "Oct 22nd, 2026");
+        assert!(is_loopback("https://127.0.0.1:27124"));
+        assert!(!is_loopback("https://notes.example.com"));
+    }
+
+    #[test]
+    fn fills_journal_templates() {
+        let entry = ExportEntry {
+            text: "Ship it".to_string(),
+            backend: "Groq",
+            audio_duration: Duration::from_millis(23_400),
+            meeting: Some("Standup".to_string()),
+            segments: Vec::new(),
+            at: time::macros::datetime!(2024-05-03 14:12 UTC),
+        };
+        assert_eq!(
+            fill_template("## {date} {time} ({backend}, {duration}s)", &entry),
+            "## 2024-05-03 14:12 (Groq, 23s)"
+        );
+        assert_eq!(
+            fill_template("journal/{year}/{month}/{day} {meeting}.md", &entry),
+            "journal/2024/05/03 Standup.md"
+        );
+    }
+
+    #[test]
+    fn timestamps_segments_from_the_recording_start() {
+        let segment = |start_secs: f32, text: &str| TranscriptSegment {
+            start_secs,
+            end_secs: start_secs + 2.0,
+            text: text.to_string(),
+            avg_logprob: None,
+            no_speech_prob: None,
+        };
+        assert_eq!(
+            timestamped(&[
+                segment(0.4, " Hello there."),
+                segment(75.9, " Second point.")
+            ]),
+            "[0:00] Hello there.\n[1:15] Second point."
+        );
+    }
+
+    #[test]
+    fn splits_spoken_note_structure() {
+        assert_eq!(
+            parse_note_parts("Note title groceries. Eggs, milk. New paragraph and bread"),
+            vec![
+                NotePart::Title("groceries".to_string()),
+                NotePart::Text("Eggs, milk.".to_string()),
+                NotePart::NewParagraph,
+                NotePart::Text("and bread".to_string()),
+            ]
+        );
+        assert_eq!(
+            parse_note_parts("That's all. New note, call the landlord"),
+            vec![
+                NotePart::Text("That's all.".to_string()),
+                NotePart::NewNote,
+                NotePart::Text("call the landlord".to_string()),
+            ]
+        );
+        assert_eq!(
+            parse_note_parts("a renewed notebook"),
+            vec![NotePart::Text("a renewed notebook".to_string())]
+        );
+    }
+
+    #[test]
+    fn writes_one_file_per_titled_note() {
+        let dir = std::env::temp_dir().join(format!("hyprwhspr-notes-{}", std::process::id()));
+        let template = format!("{}/{{title}}.md", dir.display());
+        let entry = |text: &str| ExportEntry {
+            text: text.to_string(),
+            backend: "Groq",
+            audio_duration: Duration::from_secs(3),
+            meeting: None,
+            segments: Vec::new(),
+            at: time::macros::datetime!(2024-05-03 14:12 UTC),
+        };
+        let mut session = NoteSession::default();
+        for text in [
+            "Note title groceries. Eggs",
+            "milk. New paragraph bread",
+            "note title ideas/later. Voice notes",
+        ] {
+            append_to_notes(&mut session, &template, "# {title}", &entry(text)).unwrap();
+        }
+
+        let read = |name: &str| fs::read_to_string(dir.join(name)).unwrap();
+        assert_eq!(
+            read("groceries.md"),
+            "# groceries\n\nEggs\nmilk.\n\nbread\n"
+        );
+        assert_eq!(read("ideas-later.md"), "# ideas/later\n\nVoice notes\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn profiles_replace_the_global_targets() {
+        let file = |path: &str| ExportTarget::File {
+            path: path.to_string(),
+            header: String::new(),
+            notes: false,
+            timestamps: false,
+        };
+        let worker = ExportWorker::new(
+            vec![file("journal.md")],
+            HashMap::from([
+                ("kitty".to_string(), vec![file("shell.md")]),
+                ("signal".to_string(), Vec::new()),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(worker.targets_for(Some("Kitty")), [file("shell.md")]);
+        assert!(worker.targets_for(Some("signal")).is_empty());
+        assert_eq!(worker.targets_for(Some("firefox")), [file("journal.md")]);
+        assert_eq!(worker.targets_for(None), [file("journal.md")]);
+    }
+}
@@ -0,0 +1,526 @@
+//! Keeps every final transcription in a JSON Lines file under the data
+//! directory so past dictations can be searched and copied again.
+
+use crate::config::{Config, ConfigManager};
+use crate::transcription::TranscriptSegment;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use time::macros::format_description;
+use time::{format_description::well_known::Rfc3339, Date, OffsetDateTime, UtcOffset};
+use tracing::warn;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HistoryEntry {
+    pub id: u64,
+    /// RFC 3339, UTC.
+    pub timestamp: String,
+    pub backend: String,
+    pub duration_secs: f32,
+    pub words: usize,
+    /// Transcript as the backend returned it.
+    pub raw: String,
+    /// Text after the cleanup pipeline, as injected.
+    pub text: String,
+    /// Calendar event the utterance was dictated in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meeting: Option<String>,
+    /// Timed spans of `raw`, from backends that report them (Groq).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub segments: Vec<TranscriptSegment>,
+}
+
+impl HistoryEntry {
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        self.text.to_lowercase().contains(&query) || self.raw.to_lowercase().contains(&query)
+    }
+
+    fn recorded_at(&self) -> Option<OffsetDateTime> {
+        OffsetDateTime::parse(&self.timestamp, &Rfc3339).ok()
+    }
+}
+
+/// Narrows `history search` results. Dates are inclusive, in local time.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    pub query: Option<String>,
+    pub since: Option<Date>,
+    pub until: Option<Date>,
+    /// Backend label, e.g. `groq` or `local`; case-insensitive.
+    pub provider: Option<String>,
+}
+
+impl HistoryFilter {
+    pub fn matches(&self, entry: &HistoryEntry, local: UtcOffset) -> bool {
+        if let Some(provider) = &self.provider {
+            if !entry.backend.eq_ignore_ascii_case(provider) {
+                return false;
+            }
+        }
+        if self.since.is_some() || self.until.is_some() {
+            let Ok(at) = OffsetDateTime::parse(&entry.timestamp, &Rfc3339) else {
+                return false;
+            };
+            let day = at.to_offset(local).date();
+            if self.since.is_some_and(|since| day < since)
+                || self.until.is_some_and(|until| day > until)
+            {
+                return false;
+            }
+        }
+        self.query
+            .as_deref()
+            .is_none_or(|query| entry.matches(query))
+    }
+}
+
+/// Parses a `YYYY-MM-DD` command-line date.
+pub fn parse_date(value: &str) -> Result<Date, String> {
+    Date::parse(value, format_description!("[year]-[month]-[day]"))
+        .map_err(|_| format!("expected YYYY-MM-DD, got {value:?}"))
+}
+
+/// Retention checks on the files next to the history run at most this
+/// often.
+const ARTIFACT_CHECK_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Audio and request logs kept outside the history file, erased along with
+/// it.
+pub struct Artifacts {
+    last_recording: PathBuf,
+    request_log: PathBuf,
+    pending_dir: PathBuf,
+}
+
+impl Artifacts {
+    pub fn new(config_manager: &ConfigManager, config: &Config) -> Self {
+        Self {
+            last_recording: config_manager.get_last_recording_path(),
+            request_log: config_manager.get_request_log_path(config),
+            pending_dir: config_manager.get_pending_dir(),
+        }
+    }
+
+    /// Erases audio files written and request log lines stamped before
+    /// `before` (everything when `None`); returns how many of each went.
+    pub fn purge(&self, before: Option<OffsetDateTime>) -> Result<(usize, usize)> {
+        let mut files = vec![self.last_recording.clone()];
+        if let Ok(pending) = fs::read_dir(&self.pending_dir) {
+            files.extend(pending.flatten().map(|entry| entry.path()));
+        }
+        let files = purge_files(files, before.map(SystemTime::from))?;
+        let lines = purge_log(&self.request_log, before)?;
+        Ok((files, lines))
+    }
+}
+
+pub struct History {
+    path: PathBuf,
+    max_entries: usize,
+    retention: Option<time::Duration>,
+    /// Pruned with `retention` too, when set.
+    artifacts: Option<Artifacts>,
+    state: Mutex<StoreState>,
+}
+
+struct StoreState {
+    next_id: u64,
+    entries: usize,
+    oldest: Option<OffsetDateTime>,
+    artifacts_checked: Option<Instant>,
+}
+
+impl History {
+    /// The store configured in `history`, or `None` when disabled.
+    pub fn maybe_open(config_manager: &ConfigManager, config: &Config) -> Option<Self> {
+        if !config.history.enabled {
+            return None;
+        }
+        let path = config_manager.get_history_path(config);
+        Self::open(
+            path,
+            config.history.max_entries,
+            config.history.retention_days,
+        )
+        .map(|history| history.with_artifacts(Artifacts::new(config_manager, config)))
+        .inspect_err(|err| warn!("Transcription history disabled: {:#}", err))
+        .ok()
+    }
+
+    /// Opens (or starts) the store at `path`, dropping entries past the
+    /// limits right away. A limit of 0 keeps everything.
+    pub fn open(path: PathBuf, max_entries: usize, retention_days: u32) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create history directory {}", parent.display())
+            })?;
+        }
+
+        let entries = read_entries(&path)?;
+        let history = Self {
+            path,
+            max_entries,
+            retention: (retention_days > 0)
+                .then(|| time::Duration::days(i64::from(retention_days))),
+            artifacts: None,
+            state: Mutex::new(StoreState {
+                next_id: entries.last().map_or(1, |entry| entry.id + 1),
+                entries: entries.len(),
+                oldest: entries.first().and_then(HistoryEntry::recorded_at),
+                artifacts_checked: None,
+            }),
+        };
+        if let Ok(mut state) = history.state.lock() {
+            history.enforce_limits(&mut state);
+        }
+        Ok(history)
+    }
+
+    /// Applies `retention_days` to `artifacts` as well, starting now.
+    pub fn with_artifacts(mut self, artifacts: Artifacts) -> Self {
+        self.artifacts = Some(artifacts);
+        if let Ok(mut state) = self.state.lock() {
+            self.enforce_limits(&mut state);
+        }
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends one transcription; failures are logged, not returned.
+    pub fn record(
+        &self,
+        raw: &str,
+        text: &str,
+        backend: &str,
+        duration: Duration,
+        meeting: Option<String>,
+        segments: &[TranscriptSegment],
+    ) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        let now = OffsetDateTime::now_utc();
+        let entry = HistoryEntry {
+            id: state.next_id,
+            timestamp: now.format(&Rfc3339).unwrap_or_default(),
+            backend: backend.to_string(),
+            duration_secs: duration.as_secs_f32(),
+            words: text.split_whitespace().count(),
+            raw: raw.to_string(),
+            text: text.to_string(),
+            meeting,
+            segments: segments.to_vec(),
+        };
+
+        let result = serde_json::to_string(&entry)
+            .map_err(anyhow::Error::from)
+            .and_then(|line| {
+                let mut file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.path)?;
+                writeln!(file, "{line}")?;
+                Ok(())
+            });
+        if let Err(err) = result {
+            warn!("Failed to write history {}: {:#}", self.path.display(), err);
+            return;
+        }
+        state.next_id += 1;
+        state.entries += 1;
+        state.oldest.get_or_insert(now);
+
+        self.enforce_limits(&mut state);
+    }
+
+    /// Drops the oldest entries beyond `max_entries` or `retention_days`,
+    /// and artifacts past `retention_days`.
+    fn enforce_limits(&self, state: &mut StoreState) {
+        let cutoff = self
+            .retention
+            .map(|retention| OffsetDateTime::now_utc() - retention);
+        if let (Some(cutoff), Some(artifacts)) = (cutoff, &self.artifacts) {
+            let due = state
+                .artifacts_checked
+                .is_none_or(|checked| checked.elapsed() >= ARTIFACT_CHECK_INTERVAL);
+            if due {
+                state.artifacts_checked = Some(Instant::now());
+                if let Err(err) = artifacts.purge(Some(cutoff)) {
+                    warn!("Failed to prune old recordings and logs: {:#}", err);
+                }
+            }
+        }
+        let too_many = self.max_entries > 0 && state.entries > self.max_entries;
+        let too_old = cutoff.is_some_and(|cutoff| state.oldest.is_some_and(|at| at < cutoff));
+        if !too_many && !too_old {
+            return;
+        }
+
+        let result = read_entries(&self.path).and_then(|entries| {
+            let mut keep: Vec<HistoryEntry> = entries
+                .into_iter()
+                .filter(|entry| !is_before(entry, cutoff))
+                .collect();
+            if self.max_entries > 0 && keep.len() > self.max_entries {
+                keep.drain(..keep.len() - self.max_entries);
+            }
+            write_entries(&self.path, &keep)?;
+            Ok(keep)
+        });
+        match result {
+            Ok(keep) => {
+                state.entries = keep.len();
+                state.oldest = keep.first().and_then(HistoryEntry::recorded_at);
+            }
+            Err(err) => warn!("Failed to prune history: {:#}", err),
+        }
+    }
+}
+
+/// Erases entries recorded before `before`, or all of them; returns how many
+/// were removed.
+pub fn purge(path: &Path, before: Option<OffsetDateTime>) -> Result<usize> {
+    let entries = read_entries(path)?;
+    let total = entries.len();
+    let keep: Vec<HistoryEntry> = match before {
+        Some(before) => entries
+            .into_iter()
+            .filter(|entry| !is_before(entry, Some(before)))
+            .collect(),
+        None => Vec::new(),
+    };
+    if keep.len() < total {
+        write_entries(path, &keep)?;
+    }
+    Ok(total - keep.len())
+}
+
+/// Shreds the files last modified before `before` (all when `None`);
+/// returns how many were removed.
+fn purge_files(
+    paths: impl IntoIterator<Item = PathBuf>,
+    before: Option<SystemTime>,
+) -> Result<usize> {
+    let mut removed = 0;
+    for path in paths {
+        let Ok(metadata) = fs::metadata(&path) else {
+            continue;
+        };
+        let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        if metadata.is_file() && before.is_none_or(|before| modified < before) {
+            shred(&path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Erases JSON Lines log entries whose `timestamp` is before `before`, or
+/// all of them; returns how many were removed.
+fn purge_log(path: &Path, before: Option<OffsetDateTime>) -> Result<usize> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(err) => return Err(err).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    let lines: Vec<&str> = contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    let keep: Vec<&str> = match before {
+        Some(before) => lines
+            .iter()
+            .copied()
+            .filter(|line| logged_at(line).is_some_and(|at| at >= before))
+            .collect(),
+        None => Vec::new(),
+    };
+    if keep.len() < lines.len() {
+        let mut kept = keep.join("\n");
+        if !kept.is_empty() {
+            kept.push('\n');
+        }
+        replace_file(path, &kept)?;
+    }
+    Ok(lines.len() - keep.len())
+}
+
+fn logged_at(line: &str) -> Option<OffsetDateTime> {
+    let entry: Value = serde_json::from_str(line).ok()?;
+    OffsetDateTime::parse(entry.get("timestamp")?.as_str()?, &Rfc3339).ok()
+}
+
+/// Unparseable timestamps count as old so they can't dodge retention.
+fn is_before(entry: &HistoryEntry, cutoff: Option<OffsetDateTime>) -> bool {
+    cutoff.is_some_and(|cutoff| entry.recorded_at().is_none_or(|at| at < cutoff))
+}
+
+/// Overwrites `path` with zeros before unlinking it, so deleted transcripts
+/// and audio don't linger in free blocks. Best effort on copy-on-write
+/// filesystems and SSDs.
+fn shred(path: &Path) -> Result<()> {
+    let file = OpenOptions::new()
+        .write(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    overwrite(file).with_context(|| format!("Failed to overwrite {}", path.display()))?;
+    fs::remove_file(path).with_context(|| format!("Failed to remove {}", path.display()))
+}
+
+/// Zeros the whole of `file`.
+fn overwrite(mut file: File) -> Result<()> {
+    let zeros = [0u8; 8192];
+    let mut remaining = file.metadata()?.len();
+    while remaining > 0 {
+        let chunk = remaining.min(zeros.len() as u64) as usize;
+        file.write_all(&zeros[..chunk])?;
+        remaining -= chunk as u64;
+    }
+    file.sync_all()?;
+    Ok(())
+}
+
+/// Swaps `contents` in for `path` (erasing it when empty), then zeros the
+/// old copy through a handle opened beforehand, so a crash midway leaves
+/// either version in place rather than neither.
+fn replace_file(path: &Path, contents: &str) -> Result<()> {
+    if contents.is_empty() {
+        return if path.exists() { shred(path) } else { Ok(()) };
+    }
+
+    let old = OpenOptions::new().write(true).open(path).ok();
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    fs::write(&tmp, contents).with_context(|| format!("Failed to write {}", tmp.display()))?;
+    fs::rename(&tmp, path).with_context(|| format!("Failed to replace {}", path.display()))?;
+    match old {
+        Some(old) => overwrite(old)
+            .with_context(|| format!("Failed to overwrite the old {}", path.display())),
+        None => Ok(()),
+    }
+}
+
+/// All stored entries, oldest first. A missing file is an empty history.
+pub fn read_entries(path: &Path) -> Result<Vec<HistoryEntry>> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err).with_context(|| format!("Failed to read {}", path.display())),
+    };
+
+    Ok(contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| {
+            serde_json::from_str(line)
+                .inspect_err(|err| warn!("Skipping unreadable history entry: {}", err))
+                .ok()
+        })
+        .collect())
+}
+
+fn write_entries(path: &Path, entries: &[HistoryEntry]) -> Result<()> {
+    let mut contents = String::new();
+    for entry in entries {
+        contents.push_str(&serde_json::to_string(entry)?);
+        contents.push('\n');
+    }
+    replace_file(path, &contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn appends_and_prunes_oldest_entries() {
+        let dir = std::env::temp_dir().join(format!("hyprwhspr-history-{}", std::process::id()));
+        let path = dir.join("history.jsonl");
+        let _ = fs::remove_file(&path);
+
+        let history = History::open(path.clone(), 2, 0).unwrap();
+        for text in ["first invoice", "second", "third Invoice"] {
+            history.record(text, text, "Groq", Duration::from_secs(3), None, &[]);
+        }
+
+        let entries = read_entries(&path).unwrap();
+        assert_eq!(
+            entries.iter().map(|entry| entry.id).collect::<Vec<_>>(),
+            [2, 3]
+        );
+        assert!(entries[1].matches("invoice"));
+        assert!(!entries[0].matches("invoice"));
+        let reopened = History::open(path.clone(), 2, 0).unwrap();
+        assert_eq!(reopened.state.lock().unwrap().next_id, 4);
+
+        let mut old = entries[0].clone();
+        old.timestamp = "2024-01-01T09:00:00Z".to_string();
+        write_entries(&path, &[old, entries[1].clone()]).unwrap();
+        let cutoff = OffsetDateTime::parse("2024-03-01T00:00:00Z", &Rfc3339).ok();
+        assert_eq!(purge(&path, cutoff).unwrap(), 1);
+        assert_eq!(purge(&path, None).unwrap(), 1);
+        assert!(!path.exists());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn purges_request_log_entries_by_their_timestamps() {
+        let dir = std::env::temp_dir().join(format!("hyprwhspr-log-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("requests.jsonl");
+        fs::write(
+            &path,
+            "{\"timestamp\":\"2024-01-01T09:00:00Z\",\"body\":\"old\"}\n\
+             not json\n\
+             {\"timestamp\":\"2024-06-01T09:00:00Z\",\"body\":\"new\"}\n",
+        )
+        .unwrap();
+
+        let cutoff = OffsetDateTime::parse("2024-03-01T00:00:00Z", &Rfc3339).ok();
+        assert_eq!(purge_log(&path, cutoff).unwrap(), 2);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "{\"timestamp\":\"2024-06-01T09:00:00Z\",\"body\":\"new\"}\n"
+        );
+        assert_eq!(purge_log(&path, None).unwrap(), 1);
+        assert!(!path.exists());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn filters_by_local_date_and_provider() {
+        let entry = HistoryEntry {
+            id: 1,
+            timestamp: "2024-05-03T23:30:00Z".to_string(),
+            backend: "Groq".to_string(),
+            duration_secs: 4.0,
+            words: 2,
+            raw: "send invoice".to_string(),
+            text: "Send invoice.".to_string(),
+            meeting: None,
+            segments: Vec::new(),
+        };
+        let berlin = UtcOffset::from_hms(2, 0, 0).unwrap();
+        let filter = |since: &str, provider: &str| HistoryFilter {
+            query: Some("INVOICE".to_string()),
+            since: Some(parse_date(since).unwrap()),
+            until: None,
+            provider: Some(provider.to_string()),
+        };
+
+        assert!(filter("2024-05-04", "groq").matches(&entry, berlin));
+        assert!(!filter("2024-05-04", "groq").matches(&entry, UtcOffset::UTC));
+        assert!(!filter("2024-05-01", "local").matches(&entry, berlin));
+        assert!(parse_date("05/03/2024").is_err());
+    }
+}
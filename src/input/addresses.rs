@@ -0,0 +1,141 @@
+//! Rebuilds spoken email addresses and URLs ("john dot doe at example dot
+//! com", "h t t p s colon slash slash") into their written forms.
+
+use regex::{Captures, Regex};
+use std::sync::LazyLock;
+
+/// Top-level domains recognised when deciding whether "x dot y" is a host.
+const TLDS: &str = "com|org|net|io|dev|edu|gov|mil|int|info|biz|app|ai|co|me|tv|us|uk|ca|de|fr|es|it|nl|se|no|fi|dk|ch|at|be|pl|cz|eu|ru|jp|cn|in|au|nz|br|mx|rs|sh|xyz|tech|cloud|page|site";
+
+const SEPARATOR: &str = r"(?:\s+dot\s+|\.)";
+
+/// Words that put "at" in a sentence rather than an address when they come
+/// right before it: "find us at example dot com", "look at docs dot rs".
+const NOT_LOCAL_PARTS: [&str; 24] = [
+    "i", "me", "us", "you", "him", "her", "them", "it", "we", "they", "find", "reach", "contact",
+    "email", "mail", "write", "message", "look", "see", "visit", "meet", "online", "here", "there",
+];
+
+static SCHEME_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)\b(?P<scheme>h\s+t\s+t\s+p(?P<spelled_s>\s+s)?|https?)\s*(?:colon|:)\s*(?:(?:forward\s+)?slash\s*(?:forward\s+)?slash|/\s*/)\s*",
+    )
+    .expect("valid URL scheme regex")
+});
+
+static WWW_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\bw\s+w\s+w\b").expect("valid www regex"));
+
+static EMAIL_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    let pattern = format!(
+        r"(?i)\b(?P<local>[a-z0-9_%+-]+(?:{SEPARATOR}[a-z0-9_%+-]+)*)\s+at\s+(?P<domain>[a-z0-9-]+(?:{SEPARATOR}[a-z0-9-]+)*{SEPARATOR}(?:{TLDS}))\b"
+    );
+    Regex::new(&pattern).expect("valid spoken email regex")
+});
+
+static DOMAIN_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    let pattern = format!(r"(?i)\b[a-z0-9-]+(?:\s+dot\s+[a-z0-9-]+)*\s+dot\s+(?:{TLDS})\b");
+    Regex::new(&pattern).expect("valid spoken domain regex")
+});
+
+static PATH_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    let pattern = format!(
+        r"(?i)(?P<host>\b[a-z0-9-]+(?:\.[a-z0-9-]+)*\.(?:{TLDS})\b)(?P<path>(?:\s+slash\s+[\w-]+)+)"
+    );
+    Regex::new(&pattern).expect("valid spoken URL path regex")
+});
+
+static DOT_WORD_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\s+dot\s+").expect("valid dot word regex"));
+
+static SLASH_WORD_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\s+slash\s+").expect("valid slash word regex"));
+
+/// Returns the normalized text and how many addresses were rewritten.
+pub fn normalize(input: &str) -> (String, usize) {
+    let mut count = 0;
+
+    let text = SCHEME_REGEX.replace_all(input, |caps: &Captures| {
+        count += 1;
+        let scheme = caps["scheme"].to_ascii_lowercase();
+        let secure = caps.name("spelled_s").is_some() || scheme == "https";
+        if secure {
+            "https://".to_string()
+        } else {
+            "http://".to_string()
+        }
+    });
+
+    let text = WWW_REGEX.replace_all(&text, "www");
+
+    let text = EMAIL_REGEX.replace_all(&text, |caps: &Captures| {
+        let local = &caps["local"];
+        if NOT_LOCAL_PARTS
+            .iter()
+            .any(|word| local.eq_ignore_ascii_case(word))
+        {
+            return caps[0].to_string();
+        }
+        count += 1;
+        let local = DOT_WORD_REGEX.replace_all(local, ".");
+        let domain = DOT_WORD_REGEX.replace_all(&caps["domain"], ".");
+        format!("{local}@{}", domain.to_ascii_lowercase())
+    });
+
+    let text = DOMAIN_REGEX.replace_all(&text, |caps: &Captures| {
+        count += 1;
+        DOT_WORD_REGEX
+            .replace_all(&caps[0], ".")
+            .to_ascii_lowercase()
+    });
+
+    let text = PATH_REGEX.replace_all(&text, |caps: &Captures| {
+        let path = SLASH_WORD_REGEX.replace_all(&caps["path"], "/");
+        format!("{}/{}", &caps["host"], path.trim_start_matches('/'))
+    });
+
+    (text.into_owned(), count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rebuilds_spoken_emails() {
+        assert_eq!(
+            normalize("mail john dot doe at example dot com please").0,
+            "mail john.doe@example.com please"
+        );
+        assert_eq!(
+            normalize("It's jane at Gmail.com.").0,
+            "It's jane@gmail.com."
+        );
+    }
+
+    #[test]
+    fn rebuilds_spoken_urls() {
+        assert_eq!(
+            normalize("h t t p s colon slash slash example dot com").0,
+            "https://example.com"
+        );
+        assert_eq!(
+            normalize("go to http: // w w w dot rust-lang dot org slash learn").0,
+            "go to http://www.rust-lang.org/learn"
+        );
+        assert_eq!(
+            normalize("see docs dot rs slash serde").0,
+            "see docs.rs/serde"
+        );
+    }
+
+    #[test]
+    fn leaves_ordinary_prose_alone() {
+        let text = "meet me at home, then look at the dot on the map";
+        assert_eq!(normalize(text), (text.to_string(), 0));
+        assert_eq!(
+            normalize("find us at example.com or look at docs dot rs"),
+            ("find us at example.com or look at docs.rs".to_string(), 1)
+        );
+    }
+}
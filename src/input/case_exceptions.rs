@@ -0,0 +1,110 @@
+//! Words whose spelling the pipeline must not touch ("iPhone", "macOS",
+//! "hyprwhspr"): sentence capitalization and word overrides can reshape
+//! them, so a last pass restores each one exactly as configured.
+
+use regex::Regex;
+use std::collections::HashMap;
+use tracing::warn;
+
+#[derive(Debug, Clone)]
+pub struct CaseExceptions {
+    regex: Regex,
+    spellings: HashMap<String, String>,
+}
+
+impl CaseExceptions {
+    /// `None` when `words` has nothing but blanks.
+    pub fn new(words: &[String]) -> Option<Self> {
+        let mut spellings = HashMap::new();
+        for word in words.iter().map(|word| word.trim()) {
+            if !word.is_empty() {
+                spellings.insert(word.to_lowercase(), word.to_string());
+            }
+        }
+        if spellings.is_empty() {
+            return None;
+        }
+
+        // Longest first, so "GitHub Actions" wins over "GitHub".
+        let mut alternatives: Vec<&String> = spellings.values().collect();
+        alternatives.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        let pattern = alternatives
+            .iter()
+            .map(|word| regex::escape(word))
+            .collect::<Vec<_>>()
+            .join("|");
+        match Regex::new(&format!("(?i)(?:{pattern})")) {
+            Ok(regex) => Some(Self { regex, spellings }),
+            Err(err) => {
+                warn!("Ignoring case exceptions: {err}");
+                None
+            }
+        }
+    }
+
+    /// Respells each standalone occurrence, returning the text and the
+    /// number of words changed.
+    pub fn apply(&self, input: &str) -> (String, usize) {
+        let mut output = String::with_capacity(input.len());
+        let mut count = 0;
+        let mut last = 0;
+
+        for found in self.regex.find_iter(input) {
+            let before = input[..found.start()].chars().next_back();
+            let after = input[found.end()..].chars().next();
+            let standalone = !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char);
+            let Some(spelling) = self
+                .spellings
+                .get(&found.as_str().to_lowercase())
+                .filter(|_| standalone)
+            else {
+                continue;
+            };
+
+            output.push_str(&input[last..found.start()]);
+            output.push_str(spelling);
+            if found.as_str() != spelling {
+                count += 1;
+            }
+            last = found.end();
+        }
+
+        output.push_str(&input[last..]);
+        (output, count)
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restores_exact_spelling_of_standalone_words() {
+        let exceptions = CaseExceptions::new(&[
+            "iPhone".to_string(),
+            "hyprwhspr".to_string(),
+            "GitHub".to_string(),
+            "GitHub Actions".to_string(),
+            "C++".to_string(),
+            "  ".to_string(),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            exceptions.apply("Iphone and IPHONE. Hyprwhspr runs github actions on GITHUB."),
+            (
+                "iPhone and iPhone. hyprwhspr runs GitHub Actions on GitHub.".to_string(),
+                5
+            )
+        );
+        assert_eq!(
+            exceptions.apply("iPhones, myiphone and c++ stay put"),
+            ("iPhones, myiphone and C++ stay put".to_string(), 1)
+        );
+        assert!(CaseExceptions::new(&[" ".to_string()]).is_none());
+    }
+}
@@ -0,0 +1,290 @@
+//! Code dictation grammar: spoken operators become code tokens and casing
+//! commands ("snake case alpha beta") build identifiers. Runs ahead of the
+//! prose speech replacements so multi-word operators aren't split up.
+
+/// How a token joins its neighbours.
+#[derive(Clone, Copy, PartialEq)]
+enum Spacing {
+    /// Glued to both sides (`foo::bar`, `self.value`).
+    Tight,
+    /// Separated by single spaces (`a => b`).
+    Spaced,
+}
+
+struct CodeToken {
+    phrase: &'static str,
+    token: &'static str,
+    spacing: Spacing,
+}
+
+const fn tight(phrase: &'static str, token: &'static str) -> CodeToken {
+    CodeToken {
+        phrase,
+        token,
+        spacing: Spacing::Tight,
+    }
+}
+
+const fn spaced(phrase: &'static str, token: &'static str) -> CodeToken {
+    CodeToken {
+        phrase,
+        token,
+        spacing: Spacing::Spaced,
+    }
+}
+
+static CODE_TOKENS: &[CodeToken] = &[
+    spaced("fat arrow", "=>"),
+    spaced("arrow", "->"),
+    tight("colon colon", "::"),
+    tight("double colon", "::"),
+    tight("dot", "."),
+    spaced("triple equals", "==="),
+    spaced("double equals", "=="),
+    spaced("equals equals", "=="),
+    spaced("not equals", "!="),
+    spaced("less than or equal", "<="),
+    spaced("greater than or equal", ">="),
+    spaced("plus equals", "+="),
+    spaced("minus equals", "-="),
+    spaced("and and", "&&"),
+    spaced("or or", "||"),
+    spaced("equals", "="),
+    tight("open paren", "("),
+    tight("close paren", ")"),
+    tight("open bracket", "["),
+    tight("close bracket", "]"),
+    spaced("open brace", "{"),
+    spaced("close brace", "}"),
+    tight("open angle", "<"),
+    tight("close angle", ">"),
+    tight("bang", "!"),
+    tight("ampersand", "&"),
+    tight("dollar", "$"),
+    tight("at sign", "@"),
+    tight("hash", "#"),
+];
+
+#[derive(Clone, Copy)]
+enum Casing {
+    Camel,
+    Pascal,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+}
+
+static CASING_COMMANDS: &[(&str, Casing)] = &[
+    ("camel case", Casing::Camel),
+    ("pascal case", Casing::Pascal),
+    ("snake case", Casing::Snake),
+    ("screaming snake case", Casing::ScreamingSnake),
+    ("constant case", Casing::ScreamingSnake),
+    ("kebab case", Casing::Kebab),
+    ("kebab", Casing::Kebab),
+];
+
+/// Ends a casing command early: "snake case max size done equals ten".
+const CASING_TERMINATOR: &str = "done";
+
+enum Piece {
+    Word(String),
+    Token(&'static str, Spacing),
+}
+
+/// Rewrites `text` with the code grammar, returning the result and the
+/// number of constructs replaced. Line breaks are kept as-is.
+pub fn apply(text: &str) -> (String, usize) {
+    let mut count = 0;
+    let lines: Vec<String> = text
+        .split('\n')
+        .map(|line| {
+            let (output, replaced) = apply_line(line);
+            count += replaced;
+            output
+        })
+        .collect();
+    (lines.join("\n"), count)
+}
+
+fn apply_line(text: &str) -> (String, usize) {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let keys: Vec<String> = words.iter().map(|word| match_key(word)).collect();
+    let mut pieces = Vec::with_capacity(words.len());
+    let mut count = 0;
+    let mut index = 0;
+
+    while index < words.len() {
+        if let Some((len, casing)) = longest_match(&keys[index..], CASING_COMMANDS, |c| c.0) {
+            let start = index + len;
+            let mut end = start;
+            let mut parts = Vec::new();
+            let mut trailing = "";
+            while end < words.len() {
+                if keys[end] == CASING_TERMINATOR {
+                    end += 1;
+                    break;
+                }
+                if keys[end].is_empty()
+                    || longest_match(&keys[end..], CODE_TOKENS, |t| t.phrase).is_some()
+                {
+                    break;
+                }
+                let (word, punct) = split_trailing_punct(words[end]);
+                parts.push(word);
+                end += 1;
+                if !punct.is_empty() {
+                    trailing = punct;
+                    break;
+                }
+            }
+
+            if !parts.is_empty() {
+                pieces.push(Piece::Word(format!(
+                    "{}{}",
+                    render_identifier(&parts, casing.1),
+                    trailing
+                )));
+                count += 1;
+                index = end;
+                continue;
+            }
+        }
+
+        if let Some((len, token)) = longest_match(&keys[index..], CODE_TOKENS, |t| t.phrase) {
+            pieces.push(Piece::Token(token.token, token.spacing));
+            let (_, punct) = split_trailing_punct(words[index + len - 1]);
+            if !punct.is_empty() {
+                pieces.push(Piece::Word(punct.to_string()));
+            }
+            count += 1;
+            index += len;
+            continue;
+        }
+
+        pieces.push(Piece::Word(words[index].to_string()));
+        index += 1;
+    }
+
+    (join(&pieces), count)
+}
+
+fn join(pieces: &[Piece]) -> String {
+    let mut output = String::new();
+    let mut glue_next = true;
+
+    for piece in pieces {
+        match piece {
+            Piece::Word(word) => {
+                if !glue_next {
+                    output.push(' ');
+                }
+                output.push_str(word);
+                glue_next = false;
+            }
+            Piece::Token(token, Spacing::Tight) => {
+                output.push_str(token);
+                glue_next = true;
+            }
+            Piece::Token(token, Spacing::Spaced) => {
+                if !output.is_empty() {
+                    output.push(' ');
+                }
+                output.push_str(token);
+                glue_next = false;
+            }
+        }
+    }
+
+    output
+}
+
+fn longest_match<'a, T>(
+    keys: &[String],
+    table: &'a [T],
+    phrase: impl Fn(&T) -> &str,
+) -> Option<(usize, &'a T)> {
+    table
+        .iter()
+        .filter_map(|entry| {
+            let phrase_words: Vec<&str> = phrase(entry).split(' ').collect();
+            let matches = phrase_words.len() <= keys.len()
+                && phrase_words
+                    .iter()
+                    .zip(keys)
+                    .all(|(expected, key)| key == expected);
+            matches.then_some((phrase_words.len(), entry))
+        })
+        .max_by_key(|(len, _)| *len)
+}
+
+fn match_key(word: &str) -> String {
+    split_trailing_punct(word).0.to_ascii_lowercase()
+}
+
+fn split_trailing_punct(word: &str) -> (&str, &str) {
+    let trimmed = word.trim_end_matches(['.', ',', '!', '?', ';', ':']);
+    (trimmed, &word[trimmed.len()..])
+}
+
+fn render_identifier(parts: &[&str], casing: Casing) -> String {
+    let lower: Vec<String> = parts
+        .iter()
+        .map(|part| {
+            part.chars()
+                .filter(|c| c.is_alphanumeric())
+                .collect::<String>()
+                .to_lowercase()
+        })
+        .filter(|part| !part.is_empty())
+        .collect();
+
+    match casing {
+        Casing::Camel => lower
+            .iter()
+            .enumerate()
+            .map(|(i, part)| {
+                if i == 0 {
+                    part.clone()
+                } else {
+                    capitalize(part)
+                }
+            })
+            .collect(),
+        Casing::Pascal => lower.iter().map(|part| capitalize(part)).collect(),
+        Casing::Snake => lower.join("_"),
+        Casing::ScreamingSnake => lower.join("_").to_uppercase(),
+        Casing::Kebab => lower.join("-"),
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_operators_to_tokens() {
+        let (output, count) = apply("self dot value fat arrow std colon colon mem");
+        assert_eq!(output, "self.value => std::mem");
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn applies_casing_commands() {
+        assert_eq!(apply("snake case alpha beta").0, "alpha_beta");
+        assert_eq!(
+            apply("let camel case max size done equals ten").0,
+            "let maxSize = ten"
+        );
+        assert_eq!(apply("Pascal case http client.").0, "HttpClient.");
+        assert_eq!(apply("kebab case my new branch").0, "my-new-branch");
+    }
+}
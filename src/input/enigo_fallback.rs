@@ -0,0 +1,102 @@
+//! Last-resort key synthesis through Enigo, used when no compositor IPC,
+//! virtual keyboard or XTEST backend took the paste. Builds without the
+//! `enigo` feature get a stand-in that reports every attempt as failed.
+
+#[cfg(feature = "enigo")]
+mod keyboard {
+    use crate::config::{PasteKeys, PasteModifier};
+    use anyhow::{anyhow, Context, Result};
+    use enigo::{Direction, Enigo, Key, Keyboard, Settings};
+
+    pub struct EnigoKeyboard {
+        enigo: Enigo,
+    }
+
+    impl EnigoKeyboard {
+        pub fn new() -> Result<Self> {
+            let enigo = Enigo::new(&Settings::default())
+                .context("Failed to initialize Enigo for text injection")?;
+            Ok(Self { enigo })
+        }
+
+        pub fn send_keys(&mut self, keys: &PasteKeys) -> Result<()> {
+            let key = match keys.key.as_str() {
+                "insert" => Key::Insert,
+                "return" | "enter" => Key::Return,
+                "tab" => Key::Tab,
+                name => {
+                    let mut chars = name.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => Key::Unicode(c),
+                        _ => return Err(anyhow!("Enigo can't press key {name:?}")),
+                    }
+                }
+            };
+            let modifiers: Vec<Key> = keys
+                .modifiers
+                .iter()
+                .map(|modifier| match modifier {
+                    PasteModifier::Ctrl => Key::Control,
+                    PasteModifier::Shift => Key::Shift,
+                    PasteModifier::Alt => Key::Alt,
+                    PasteModifier::Super => Key::Meta,
+                })
+                .collect();
+
+            for modifier in &modifiers {
+                self.enigo
+                    .key(*modifier, Direction::Press)
+                    .with_context(|| format!("Failed to press {modifier:?}"))?;
+            }
+            self.enigo
+                .key(key, Direction::Click)
+                .with_context(|| format!("Failed to press {}", keys.key))?;
+            for modifier in modifiers.iter().rev() {
+                self.enigo
+                    .key(*modifier, Direction::Release)
+                    .with_context(|| format!("Failed to release {modifier:?}"))?;
+            }
+            Ok(())
+        }
+
+        pub fn type_text(&mut self, text: &str) -> Result<()> {
+            self.enigo
+                .text(text)
+                .context("Failed to type text via Enigo")
+        }
+
+        pub fn backspace(&mut self) -> Result<()> {
+            self.enigo
+                .key(Key::Backspace, Direction::Click)
+                .context("Failed to press Backspace")
+        }
+    }
+}
+
+#[cfg(not(feature = "enigo"))]
+mod keyboard {
+    use crate::config::PasteKeys;
+    use anyhow::{bail, Result};
+
+    pub struct EnigoKeyboard;
+
+    impl EnigoKeyboard {
+        pub fn new() -> Result<Self> {
+            Ok(Self)
+        }
+
+        pub fn send_keys(&mut self, _keys: &PasteKeys) -> Result<()> {
+            bail!("this build lacks the `enigo` feature")
+        }
+
+        pub fn type_text(&mut self, _text: &str) -> Result<()> {
+            bail!("this build lacks the `enigo` feature")
+        }
+
+        pub fn backspace(&mut self) -> Result<()> {
+            bail!("this build lacks the `enigo` feature")
+        }
+    }
+}
+
+pub use keyboard::EnigoKeyboard;
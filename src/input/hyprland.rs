@@ -0,0 +1,348 @@
+use crate::config::{InjectionAction, InjectionRule};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::path::PathBuf;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
+use tracing::debug;
+
+/// Hyprland modifier bits as reported in `binds` `modmask`.
+const MOD_SHIFT: u32 = 1 << 0;
+const MOD_CTRL: u32 = 1 << 2;
+const MOD_ALT: u32 = 1 << 3;
+const MOD_SUPER: u32 = 1 << 6;
+
+/// Request/response client for Hyprland's command socket
+/// (`$XDG_RUNTIME_DIR/hypr/<signature>/.socket.sock`).
+#[derive(Debug, Clone)]
+pub struct HyprlandIpc {
+    socket_path: PathBuf,
+}
+
+impl HyprlandIpc {
+    pub fn from_env() -> Option<Self> {
+        let runtime_dir = env::var("XDG_RUNTIME_DIR").ok()?;
+        let signature = env::var("HYPRLAND_INSTANCE_SIGNATURE").ok()?;
+        let socket_path = PathBuf::from(runtime_dir)
+            .join("hypr")
+            .join(signature)
+            .join(".socket.sock");
+
+        if socket_path.exists() {
+            Some(Self { socket_path })
+        } else {
+            None
+        }
+    }
+
+    pub async fn send_command(&self, command: &str) -> Result<String> {
+        let mut stream = UnixStream::connect(&self.socket_path)
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to connect to Hyprland socket at {}",
+                    self.socket_path.display()
+                )
+            })?;
+
+        stream
+            .write_all(command.as_bytes())
+            .await
+            .with_context(|| format!("Failed to send IPC command: {command}"))?;
+        stream
+            .flush()
+            .await
+            .context("Failed to flush Hyprland IPC command")?;
+        stream
+            .shutdown()
+            .await
+            .context("Failed to finish Hyprland IPC write")?;
+
+        let mut response = Vec::new();
+        stream
+            .read_to_end(&mut response)
+            .await
+            .context("Failed to read Hyprland IPC response")?;
+        let text = String::from_utf8_lossy(&response).trim().to_string();
+        debug!(
+            command,
+            response = text.as_str(),
+            "Hyprland IPC response (trimmed)"
+        );
+        Ok(text)
+    }
+
+    /// Geometry and identity of the focused window, or `None` when nothing
+    /// is focused (Hyprland replies with an empty object).
+    pub async fn active_window(&self) -> Result<Option<HyprlandWindow>> {
+        let response = self.send_command("j/activewindow").await?;
+        let window: HyprlandWindow =
+            serde_json::from_str(&response).context("Failed to parse Hyprland activewindow")?;
+        Ok((!window.address.is_empty()).then_some(window))
+    }
+
+    pub async fn active_workspace(&self) -> Result<HyprlandWorkspace> {
+        let response = self.send_command("j/activeworkspace").await?;
+        serde_json::from_str(&response).context("Failed to parse Hyprland activeworkspace")
+    }
+
+    /// Current `general:col.active_border`, in a form `keyword` accepts.
+    pub async fn active_border(&self) -> Result<String> {
+        #[derive(Deserialize)]
+        struct OptionValue {
+            #[serde(default)]
+            custom: Option<String>,
+            #[serde(default)]
+            str: Option<String>,
+        }
+
+        let response = self
+            .send_command("j/getoption general:col.active_border")
+            .await?;
+        let option: OptionValue =
+            serde_json::from_str(&response).context("Failed to parse Hyprland getoption")?;
+        option
+            .custom
+            .or(option.str)
+            .map(|value| keyword_gradient(&value))
+            .context("Hyprland did not report general:col.active_border")
+    }
+
+    pub async fn set_active_border(&self, value: &str) -> Result<()> {
+        let response = self
+            .send_command(&format!("keyword general:col.active_border {value}"))
+            .await?;
+        if response != "ok" {
+            anyhow::bail!("Hyprland rejected border color {value:?}: {response}");
+        }
+        Ok(())
+    }
+
+    pub async fn binds(&self) -> Result<Vec<HyprlandBind>> {
+        let response = self.send_command("j/binds").await?;
+        serde_json::from_str(&response).context("Failed to parse Hyprland binds")
+    }
+}
+
+/// `getoption` prints gradient stops as bare `AARRGGBB`; `keyword` wants
+/// them prefixed with `0x`.
+fn keyword_gradient(value: &str) -> String {
+    value
+        .split_whitespace()
+        .map(|part| {
+            if part.len() == 8 && part.chars().all(|c| c.is_ascii_hexdigit()) {
+                format!("0x{part}")
+            } else {
+                part.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Class of the focused window when Hyprland is running and reachable.
+pub async fn focused_window_class() -> Option<String> {
+    let ipc = HyprlandIpc::from_env()?;
+    match ipc.active_window().await {
+        Ok(window) => window.map(|window| window.class),
+        Err(err) => {
+            debug!("Failed to query Hyprland active window: {err:#}");
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct HyprlandWindow {
+    pub address: String,
+    pub class: String,
+    pub title: String,
+    pub at: [i32; 2],
+    pub size: [i32; 2],
+    pub monitor: i64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct HyprlandWorkspace {
+    pub id: i64,
+    pub name: String,
+    /// Connector name of the monitor showing the workspace.
+    pub monitor: String,
+}
+
+impl HyprlandWorkspace {
+    /// Action of the first rule matching this workspace.
+    pub fn injection_action(&self, rules: &[InjectionRule]) -> Option<InjectionAction> {
+        rules
+            .iter()
+            .find(|rule| rule.matches(self.id, &self.name, &self.monitor))
+            .map(|rule| rule.action)
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct HyprlandBind {
+    pub modmask: u32,
+    pub key: String,
+    pub submap: String,
+    pub mouse: bool,
+    pub dispatcher: String,
+    pub arg: String,
+}
+
+impl HyprlandBind {
+    pub fn describe(&self) -> String {
+        let mut parts: Vec<&str> = Vec::new();
+        for (bit, name) in [
+            (MOD_SUPER, "SUPER"),
+            (MOD_CTRL, "CTRL"),
+            (MOD_ALT, "ALT"),
+            (MOD_SHIFT, "SHIFT"),
+        ] {
+            if self.modmask & bit != 0 {
+                parts.push(name);
+            }
+        }
+        parts.push(&self.key);
+
+        if self.arg.is_empty() {
+            format!("{} → {}", parts.join("+"), self.dispatcher)
+        } else {
+            format!("{} → {} {}", parts.join("+"), self.dispatcher, self.arg)
+        }
+    }
+}
+
+/// Returns the global-submap keyboard binds that fire on the same chord as
+/// `shortcut`. Modifier-only shortcuts (e.g. `SUPER+ALT+CTRL`) never match
+/// since Hyprland binds need a non-modifier key.
+pub fn conflicting_binds<'a>(shortcut: &str, binds: &'a [HyprlandBind]) -> Vec<&'a HyprlandBind> {
+    let mut modmask = 0;
+    let mut key = None;
+
+    for part in shortcut.split('+') {
+        match part.trim().to_uppercase().as_str() {
+            "SUPER" | "META" | "WIN" | "WINDOWS" => modmask |= MOD_SUPER,
+            "ALT" => modmask |= MOD_ALT,
+            "CTRL" | "CONTROL" => modmask |= MOD_CTRL,
+            "SHIFT" => modmask |= MOD_SHIFT,
+            other => key = Some(other.to_string()),
+        }
+    }
+
+    let Some(key) = key else {
+        return Vec::new();
+    };
+    let aliases = key_aliases(&key);
+
+    binds
+        .iter()
+        .filter(|bind| !bind.mouse && bind.submap.is_empty() && bind.modmask == modmask)
+        .filter(|bind| {
+            aliases
+                .iter()
+                .any(|alias| bind.key.eq_ignore_ascii_case(alias))
+        })
+        .collect()
+}
+
+/// Maps our shortcut key names onto the xkb keysym names Hyprland reports.
+fn key_aliases(key: &str) -> Vec<&str> {
+    match key {
+        "ENTER" | "RETURN" => vec!["Return", "Enter"],
+        "ESC" | "ESCAPE" => vec!["Escape"],
+        "DELETE" | "DEL" => vec!["Delete"],
+        "INSERT" | "INS" => vec!["Insert"],
+        "PAGEUP" | "PGUP" => vec!["Prior", "Page_Up"],
+        "PAGEDOWN" | "PGDOWN" => vec!["Next", "Page_Down"],
+        // Hyprland names mouse buttons by their evdev code.
+        "MOUSE_MIDDLE" | "BTN_MIDDLE" => vec!["mouse:274"],
+        "MOUSE_SIDE" | "BTN_SIDE" => vec!["mouse:275"],
+        "MOUSE_EXTRA" | "BTN_EXTRA" => vec!["mouse:276"],
+        "MOUSE_FORWARD" | "BTN_FORWARD" => vec!["mouse:277"],
+        "MOUSE_BACK" | "BTN_BACK" => vec!["mouse:278"],
+        other => vec![other],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bind(modmask: u32, key: &str) -> HyprlandBind {
+        HyprlandBind {
+            modmask,
+            key: key.to_string(),
+            dispatcher: "exec".to_string(),
+            ..HyprlandBind::default()
+        }
+    }
+
+    #[test]
+    fn matches_same_chord_case_insensitively() {
+        let binds = vec![
+            bind(MOD_SUPER | MOD_ALT, "d"),
+            bind(MOD_SUPER, "D"),
+            bind(MOD_SUPER | MOD_ALT, "Return"),
+            bind(0, "mouse:275"),
+        ];
+
+        let conflicts = conflicting_binds("SUPER+ALT+D", &binds);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].key, "d");
+
+        assert_eq!(conflicting_binds("SUPER+ALT+ENTER", &binds).len(), 1);
+        assert!(conflicting_binds("SUPER+ALT+CTRL", &binds).is_empty());
+        assert_eq!(conflicting_binds("MOUSE_SIDE", &binds).len(), 1);
+    }
+
+    #[test]
+    fn first_matching_injection_rule_wins() {
+        let rule = |workspace: Option<&str>, monitor: Option<&str>, action| InjectionRule {
+            workspace: workspace.map(str::to_string),
+            monitor: monitor.map(str::to_string),
+            action,
+        };
+        let rules = vec![
+            rule(Some("obs"), None, InjectionAction::Skip),
+            rule(None, Some("hdmi-a-1"), InjectionAction::ClipboardOnly),
+            rule(Some("3"), Some("DP-1"), InjectionAction::Skip),
+        ];
+        let workspace = |id, name: &str, monitor: &str| HyprlandWorkspace {
+            id,
+            name: name.to_string(),
+            monitor: monitor.to_string(),
+        };
+
+        assert_eq!(
+            workspace(7, "obs", "HDMI-A-1").injection_action(&rules),
+            Some(InjectionAction::Skip)
+        );
+        assert_eq!(
+            workspace(2, "2", "HDMI-A-1").injection_action(&rules),
+            Some(InjectionAction::ClipboardOnly)
+        );
+        assert_eq!(
+            workspace(3, "3", "DP-1").injection_action(&rules),
+            Some(InjectionAction::Skip)
+        );
+        assert_eq!(workspace(3, "3", "DP-2").injection_action(&rules), None);
+        assert_eq!(
+            workspace(1, "1", "DP-1").injection_action(&[rule(None, None, InjectionAction::Skip)]),
+            None
+        );
+    }
+
+    #[test]
+    fn border_gradients_round_trip_through_keyword() {
+        assert_eq!(
+            keyword_gradient("ee33ccff ee00ff99 45deg"),
+            "0xee33ccff 0xee00ff99 45deg"
+        );
+        assert_eq!(keyword_gradient("rgb(ff4444)"), "rgb(ff4444)");
+    }
+}
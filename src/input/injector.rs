@@ -1,18 +1,36 @@
+use crate::config::{
+    profile_for, AppProfile, ClipboardHistory, CodeDictationConfig, Config, DictationMode,
+    InjectionAction, InjectionConfig, InjectionMethod, InjectionRule, PasteHintsConfig, PasteKeys,
+    PluginConfig, PluginPosition, PostProcessConfig, SpeechCommand, SpeechCommandsConfig,
+    TrailingWhitespace, TypographyConfig, WhitespaceConfig, WhitespaceOverride,
+};
+use crate::input::case_exceptions::CaseExceptions;
+use crate::input::enigo_fallback::EnigoKeyboard;
+use crate::input::hyprland::{HyprlandIpc, HyprlandWindow};
+use crate::input::plugins::SubprocessPlugin;
+use crate::input::post_check::PostCheck;
+use crate::input::script::ScriptStage;
+use crate::input::sway::SwayIpc;
+use crate::input::typography::{self, Locale};
+use crate::input::virtual_keyboard::VirtualKeyboard;
+use crate::input::x11::X11Keyboard;
+use crate::input::{addresses, code_dictation, scratch};
 use crate::logging::{record_text_pipeline, PipelineStepRecord, TextPipelineRecord};
 use anyhow::{anyhow, Context, Result};
-use arboard::Clipboard;
-use enigo::{Enigo, Keyboard, Settings};
+use arboard::{Clipboard, LinuxClipboardKind, SetExtLinux};
 use regex::Regex;
+use serde::Serialize;
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
 use std::env;
-use std::path::PathBuf;
+use std::io::Write;
+use std::process::{Command, Stdio};
 use std::sync::LazyLock;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::UnixStream;
+use std::thread;
 use tracing::{debug, info, warn};
-use wl_clipboard_rs::copy::{ClipboardType, Error as WlCopyError, MimeType, Options, Source};
-use wrtype::{Modifier, WrtypeClient};
+use wl_clipboard_rs::copy::{
+    ClipboardType, Error as WlCopyError, MimeSource, MimeType, Options, Source,
+};
 
 static SPACE_REGEX: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r" +").expect("valid space collapse regex"));
@@ -108,33 +126,38 @@ const SHIFT_PASTE_CLASS_COMPONENTS: &[&str] = &[
     "urxvt",
 ];
 
+/// MIME type clipboard managers check before recording an entry.
+const PASSWORD_MANAGER_HINT: &str = "x-kde-passwordManagerHint";
+
+/// Sway can't send keys to a window, so this only reports focus; pastes go
+/// through the virtual keyboard.
+struct SwayDispatcher {
+    ipc: SwayIpc,
+}
+
+impl SwayDispatcher {
+    fn new() -> Option<Self> {
+        SwayIpc::from_env().map(|ipc| Self { ipc })
+    }
+
+    async fn active_window_class(&self) -> Result<Option<String>> {
+        self.ipc.focused_app_id().await
+    }
+}
+
 struct HyprlandDispatcher {
-    socket_path: PathBuf,
+    ipc: HyprlandIpc,
 }
 
 impl HyprlandDispatcher {
     fn new() -> Option<Self> {
-        let runtime_dir = env::var("XDG_RUNTIME_DIR").ok()?;
-        let signature = env::var("HYPRLAND_INSTANCE_SIGNATURE").ok()?;
-        let socket_path = PathBuf::from(runtime_dir)
-            .join("hypr")
-            .join(signature)
-            .join(".socket.sock");
-
-        if socket_path.exists() {
-            Some(Self { socket_path })
-        } else {
-            None
-        }
+        HyprlandIpc::from_env().map(|ipc| Self { ipc })
     }
 
-    async fn send_paste_shortcut(&self, use_shift: bool) -> Result<()> {
-        let modifiers = if use_shift {
-            &["ctrl", "shift"][..]
-        } else {
-            &["ctrl"][..]
-        };
-        self.send_shortcut(modifiers, "v", Some("active")).await
+    async fn send_paste_shortcut(&self, keys: &PasteKeys) -> Result<()> {
+        let modifiers: Vec<&str> = keys.modifiers.iter().map(|m| m.label()).collect();
+        self.send_shortcut(&modifiers, &keys.key, Some("active"))
+            .await
     }
 
     async fn send_shortcut(
@@ -208,40 +231,7 @@ impl HyprlandDispatcher {
     }
 
     async fn send_command(&self, command: &str) -> Result<String> {
-        let mut stream = UnixStream::connect(&self.socket_path)
-            .await
-            .with_context(|| {
-                format!(
-                    "Failed to connect to Hyprland socket at {}",
-                    self.socket_path.display()
-                )
-            })?;
-
-        stream
-            .write_all(command.as_bytes())
-            .await
-            .with_context(|| format!("Failed to send IPC command: {command}"))?;
-        stream
-            .flush()
-            .await
-            .context("Failed to flush Hyprland IPC command")?;
-        stream
-            .shutdown()
-            .await
-            .context("Failed to finish Hyprland IPC write")?;
-
-        let mut response = Vec::new();
-        stream
-            .read_to_end(&mut response)
-            .await
-            .context("Failed to read Hyprland IPC response")?;
-        let text = String::from_utf8_lossy(&response).trim().to_string();
-        debug!(
-            command,
-            response = text.as_str(),
-            "Hyprland IPC response (trimmed)"
-        );
-        Ok(text)
+        self.ipc.send_command(command).await
     }
 
     fn handle_activewindow_response(
@@ -461,6 +451,11 @@ static SPEECH_REPLACEMENTS: &[SpeechReplacement] = &[
         replacement: "\n",
         adjust_preceding_punct: false,
     },
+    SpeechReplacement {
+        phrase: "new paragraph",
+        replacement: "\n\n",
+        adjust_preceding_punct: false,
+    },
     SpeechReplacement {
         phrase: "tab",
         replacement: "\t",
@@ -638,51 +633,105 @@ static SPEECH_REPLACEMENTS: &[SpeechReplacement] = &[
     },
 ];
 
-static SPEECH_REPLACEMENT_REGEX: LazyLock<Regex> = LazyLock::new(|| {
-    let mut entries: Vec<&SpeechReplacement> = SPEECH_REPLACEMENTS.iter().collect();
-    entries.sort_by(|a, b| b.phrase.len().cmp(&a.phrase.len()));
+/// Built-in speech replacements merged with the user's `speech_commands`.
+struct SpeechCommands {
+    enabled: bool,
+    regex: Option<Regex>,
+    /// Lowercased phrase to (replacement, adjust_preceding_punct).
+    lookup: HashMap<String, (String, bool)>,
+}
 
-    let alternates = entries
-        .into_iter()
-        .map(|entry| regex::escape(entry.phrase))
-        .collect::<Vec<_>>()
-        .join("|");
+impl Default for SpeechCommands {
+    fn default() -> Self {
+        Self::new(&SpeechCommandsConfig::default())
+    }
+}
 
-    let pattern = format!(r"(?i)\b(?P<command>{})\b[.!?,;:]*", alternates);
-    Regex::new(&pattern).expect("valid speech replacement regex")
-});
+impl SpeechCommands {
+    fn new(config: &SpeechCommandsConfig) -> Self {
+        let mut lookup: HashMap<String, (String, bool)> = SPEECH_REPLACEMENTS
+            .iter()
+            .map(|entry| {
+                (
+                    entry.phrase.to_string(),
+                    (entry.replacement.to_string(), entry.adjust_preceding_punct),
+                )
+            })
+            .collect();
 
-static SPEECH_REPLACEMENT_LOOKUP: LazyLock<HashMap<&'static str, &'static SpeechReplacement>> =
-    LazyLock::new(|| {
-        let mut map = HashMap::new();
-        for entry in SPEECH_REPLACEMENTS {
-            map.insert(entry.phrase, entry);
+        for (phrase, command) in &config.commands {
+            let phrase = phrase.split_whitespace().collect::<Vec<_>>().join(" ");
+            if phrase.is_empty() {
+                continue;
+            }
+            let phrase = phrase.to_lowercase();
+            match command {
+                SpeechCommand::Enabled(false) => {
+                    lookup.remove(&phrase);
+                }
+                SpeechCommand::Enabled(true) => {}
+                SpeechCommand::Replacement(replacement) => {
+                    let attach = lookup.get(&phrase).is_some_and(|(_, attach)| *attach);
+                    lookup.insert(phrase, (replacement.clone(), attach));
+                }
+                SpeechCommand::Custom {
+                    replacement,
+                    attach,
+                } => {
+                    lookup.insert(phrase, (replacement.clone(), *attach));
+                }
+            }
         }
-        map
-    });
 
-fn apply_speech_replacements(text: &str) -> (String, usize) {
-    let mut result = String::with_capacity(text.len());
-    let mut last_end = 0;
-    let mut count = 0;
+        let mut phrases: Vec<&String> = lookup.keys().collect();
+        phrases.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        let regex = (!phrases.is_empty()).then(|| {
+            let alternates = phrases
+                .into_iter()
+                .map(|phrase| regex::escape(phrase))
+                .collect::<Vec<_>>()
+                .join("|");
+            let pattern = format!(r"(?i)\b(?P<command>{})\b[.!?,;:]*", alternates);
+            Regex::new(&pattern).expect("valid speech replacement regex")
+        });
+
+        Self {
+            enabled: config.enabled,
+            regex,
+            lookup,
+        }
+    }
 
-    for caps in SPEECH_REPLACEMENT_REGEX.captures_iter(text) {
-        let matched = caps.get(0).expect("regex match");
-        result.push_str(&text[last_end..matched.start()]);
+    fn apply(&self, text: &str) -> (String, usize) {
+        let Some(regex) = &self.regex else {
+            return (text.to_string(), 0);
+        };
+        let mut result = String::with_capacity(text.len());
+        let mut last_end = 0;
+        let mut count = 0;
 
-        if let Some(command) = caps.name("command") {
-            let key = command.as_str().to_ascii_lowercase();
-            if let Some(entry) = SPEECH_REPLACEMENT_LOOKUP.get(key.as_str()) {
-                apply_speech_replacement_entry(&mut result, entry);
-                count += 1;
+        for caps in regex.captures_iter(text) {
+            let matched = caps.get(0).expect("regex match");
+            result.push_str(&text[last_end..matched.start()]);
+
+            if let Some(command) = caps.name("command") {
+                let key = command.as_str().to_lowercase();
+                if let Some((replacement, adjust_preceding_punct)) = self.lookup.get(&key) {
+                    apply_speech_replacement_entry(
+                        &mut result,
+                        replacement,
+                        *adjust_preceding_punct,
+                    );
+                    count += 1;
+                }
             }
+
+            last_end = matched.end();
         }
 
-        last_end = matched.end();
+        result.push_str(&text[last_end..]);
+        (result, count)
     }
-
-    result.push_str(&text[last_end..]);
-    (result, count)
 }
 
 fn sanitize_word_overrides(mut overrides: HashMap<String, String>) -> HashMap<String, String> {
@@ -690,8 +739,12 @@ fn sanitize_word_overrides(mut overrides: HashMap<String, String>) -> HashMap<St
     overrides
 }
 
-fn apply_speech_replacement_entry(buffer: &mut String, entry: &SpeechReplacement) {
-    if entry.adjust_preceding_punct {
+fn apply_speech_replacement_entry(
+    buffer: &mut String,
+    replacement: &str,
+    adjust_preceding_punct: bool,
+) {
+    if adjust_preceding_punct {
         let mut trailing_ws: Vec<char> = Vec::new();
 
         loop {
@@ -717,12 +770,12 @@ fn apply_speech_replacement_entry(buffer: &mut String, entry: &SpeechReplacement
             }
         }
 
-        buffer.push_str(entry.replacement);
+        buffer.push_str(replacement);
         for ch in trailing_ws.into_iter().rev() {
             buffer.push(ch);
         }
     } else {
-        buffer.push_str(entry.replacement);
+        buffer.push_str(replacement);
     }
 }
 
@@ -838,112 +891,386 @@ fn trim_spaces_around_newlines(input: &str) -> (String, usize) {
     (final_result, count)
 }
 
-pub struct TextInjector {
-    enigo: Enigo,
-    clipboard: Clipboard,
+/// Outcome of a successful injection, published for overlay tools.
+#[derive(Debug, Clone, Serialize)]
+pub struct InjectionReport {
+    /// The cleaned-up text that was pasted; kept out of the status file.
+    #[serde(skip)]
+    pub text: String,
+    pub chars: usize,
+    pub method: &'static str,
+    pub window_class: Option<String>,
+    pub window: Option<HyprlandWindow>,
+}
+
+/// The text cleanup stages applied before pasting, usable without a
+/// display or clipboard (e.g. from benchmarks).
+pub struct TextPipeline {
     word_overrides: HashMap<String, String>,
+    profiles: HashMap<String, AppProfile>,
+    /// Profile a meeting in progress selected, used instead of the window
+    /// class's.
+    profile_override: Option<String>,
+    speech_commands: SpeechCommands,
+    mode: DictationMode,
+    extra_shift_classes: HashSet<String>,
+    plugins: Vec<SubprocessPlugin>,
+    script: Option<ScriptStage>,
+    case_exceptions: Option<CaseExceptions>,
+    normalize_addresses: bool,
+    typography: TypographyConfig,
+    whitespace: WhitespaceConfig,
+    code_dictation: CodeDictationConfig,
+}
+
+pub struct TextInjector {
+    /// Both opened on first use, so nothing needs a display until text is
+    /// actually delivered.
+    enigo: Option<EnigoKeyboard>,
+    clipboard: Option<Clipboard>,
     extra_shift_classes: HashSet<String>,
     default_shift_paste: bool,
+    paste_hints: PasteHintsConfig,
     hyprland_dispatcher: Option<HyprlandDispatcher>,
-    wrtype_client: Option<WrtypeClient>,
+    sway_dispatcher: Option<SwayDispatcher>,
+    wrtype_client: Option<VirtualKeyboard>,
     wrtype_attempted: bool,
+    x11: Option<X11Keyboard>,
     wayland_env: bool,
     wayland_clipboard_enabled: bool,
+    pipeline: TextPipeline,
+    last_injected_chars: usize,
+    injection_rules: Vec<InjectionRule>,
+    injection: InjectionConfig,
+    post_check: PostCheck,
+    /// Nothing is typed, pasted or copied (screen sharing).
+    read_only: bool,
 }
 
 impl TextInjector {
     pub fn new(
         shift_paste_default: bool,
-        extra_shift_classes: Vec<String>,
+        paste_hints: PasteHintsConfig,
         word_overrides: HashMap<String, String>,
         _auto_copy_clipboard: bool,
+        plugins: Vec<PluginConfig>,
+        postprocess: PostProcessConfig,
+        code_dictation: CodeDictationConfig,
     ) -> Result<Self> {
-        let enigo = Enigo::new(&Settings::default())
-            .context("Failed to initialize Enigo for text injection")?;
-
-        let clipboard = Clipboard::new().context("Failed to initialize clipboard")?;
-
-        let sanitized_overrides = sanitize_word_overrides(word_overrides);
+        let extra_shift_classes: HashSet<String> = paste_hints
+            .shift
+            .iter()
+            .map(|entry| entry.trim().to_ascii_lowercase())
+            .filter(|entry| !entry.is_empty())
+            .collect();
+        let pipeline = TextPipeline::new(
+            extra_shift_classes.clone(),
+            word_overrides,
+            plugins,
+            postprocess,
+            code_dictation,
+        );
         let wayland_env = env::var("WAYLAND_DISPLAY").is_ok();
         let hyprland_dispatcher = HyprlandDispatcher::new();
+        let sway_dispatcher = hyprland_dispatcher
+            .is_none()
+            .then(SwayDispatcher::new)
+            .flatten();
 
         if hyprland_dispatcher.is_some() {
             debug!("Hyprland IPC detected; enabling sendshortcut paste integration");
+        } else if sway_dispatcher.is_some() {
+            debug!("Sway IPC detected; using the focused app_id for paste rules");
         } else if wayland_env {
             debug!("Wayland session detected without Hyprland IPC; virtual keyboard fallback will be used");
         }
+        let x11 = match X11Keyboard::from_env() {
+            Some(Ok(keyboard)) => {
+                debug!("X11 session detected; pasting through XTEST");
+                Some(keyboard)
+            }
+            Some(Err(err)) => {
+                warn!("X11 session detected but XTEST is unavailable: {err:#}");
+                None
+            }
+            None => None,
+        };
 
         Ok(Self {
-            enigo,
-            clipboard,
-            word_overrides: sanitized_overrides,
-            extra_shift_classes: extra_shift_classes
-                .into_iter()
-                .map(|entry| entry.trim().to_ascii_lowercase())
-                .filter(|entry| !entry.is_empty())
-                .collect(),
+            enigo: None,
+            clipboard: None,
+            extra_shift_classes,
             default_shift_paste: shift_paste_default,
+            paste_hints,
             hyprland_dispatcher,
+            sway_dispatcher,
             wrtype_client: None,
             wrtype_attempted: false,
+            x11,
             wayland_env,
             wayland_clipboard_enabled: wayland_env,
+            pipeline,
+            last_injected_chars: 0,
+            injection_rules: Vec::new(),
+            injection: InjectionConfig::default(),
+            post_check: PostCheck::default(),
+            read_only: false,
         })
     }
 
-    pub async fn inject_text(&mut self, text: &str) -> Result<()> {
+    /// Injector set up from `config` exactly as the daemon uses it.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let mut injector = Self::new(
+            config.shift_paste,
+            config.paste_hints.clone(),
+            config.word_overrides.clone(),
+            config.auto_copy_clipboard,
+            config.plugins.clone(),
+            config.postprocess.clone(),
+            config.code_dictation.clone(),
+        )?;
+        injector.set_injection_rules(config.injection_rules.clone());
+        injector.set_injection(config.injection.clone());
+        injector.set_speech_commands(&config.speech_commands);
+        injector.set_profiles(config.profiles.clone());
+        injector.set_mode(config.dictation_mode);
+        Ok(injector)
+    }
+
+    pub fn set_mode(&mut self, mode: DictationMode) {
+        self.pipeline.set_mode(mode);
+    }
+
+    /// Stops injection and clipboard writes; transcripts still go through
+    /// the pipeline so history sees what would have been pasted. Forgets
+    /// the previous injection so "scratch that" can't type either.
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+        if read_only {
+            self.last_injected_chars = 0;
+        }
+    }
+
+    /// See [`TextPipeline::set_speech_commands`].
+    pub fn set_speech_commands(&mut self, config: &SpeechCommandsConfig) {
+        self.pipeline.set_speech_commands(config);
+    }
+
+    /// Per-application cleanup overrides; see [`TextPipeline::set_profiles`].
+    pub fn set_profiles(&mut self, profiles: HashMap<String, AppProfile>) {
+        self.pipeline.set_profiles(profiles);
+    }
+
+    /// Applies the profile named `name` whatever window has focus, until
+    /// cleared with `None`.
+    pub fn set_profile_override(&mut self, name: Option<String>) {
+        self.pipeline.profile_override = name;
+    }
+
+    fn clipboard_history_for(&self, window_class: Option<&str>) -> ClipboardHistory {
+        self.pipeline
+            .profile(window_class)
+            .and_then(|profile| profile.clipboard_history)
+            .unwrap_or(self.injection.clipboard_history)
+    }
+
+    /// Workspace/monitor rules consulted before every paste.
+    pub fn set_injection_rules(&mut self, rules: Vec<InjectionRule>) {
+        self.injection_rules = rules;
+    }
+
+    /// Paste or type, globally or per window class.
+    pub fn set_injection(&mut self, injection: InjectionConfig) {
+        self.post_check = PostCheck::new(&injection.post_check);
+        self.injection = injection;
+    }
+
+    /// What the rules say about the focused workspace, if anything. When the
+    /// workspace can't be read the text is only copied, since a rule might
+    /// have been meant to keep it out of that window.
+    async fn rule_action(&self) -> Option<InjectionAction> {
+        if self.injection_rules.is_empty() {
+            return None;
+        }
+        let dispatcher = self.hyprland_dispatcher.as_ref()?;
+        match dispatcher.ipc.active_workspace().await {
+            Ok(workspace) => workspace.injection_action(&self.injection_rules),
+            Err(err) => {
+                warn!(
+                    "Failed to query Hyprland active workspace, copying without pasting: {err:#}"
+                );
+                Some(InjectionAction::ClipboardOnly)
+            }
+        }
+    }
+
+    /// Copies `text` and pastes it unless a rule holds it back; returns the
+    /// paste backend, or `None` when a `skip` rule matched.
+    async fn deliver(
+        &mut self,
+        text: &str,
+        window_class: Option<&str>,
+    ) -> Result<Option<&'static str>> {
+        if self.read_only {
+            info!("🔒 Read-only mode, not pasting");
+            return Ok(None);
+        }
+        let action = self.rule_action().await;
+        if action == Some(InjectionAction::Skip) {
+            info!("🙈 Injection skipped on this workspace (injection_rules)");
+            return Ok(None);
+        }
+
+        // Only text that reached the window counts for "scratch that"; a
+        // failed paste or a clipboard-only rule leaves nothing to erase.
+        let typed = self.injection.method_for(window_class) == InjectionMethod::Type;
+        if typed && action != Some(InjectionAction::ClipboardOnly) {
+            tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+            let method = self.type_text(text)?;
+            self.last_injected_chars = text.chars().count();
+            return Ok(Some(method));
+        }
+
+        // Copy to clipboard using available backends
+        self.copy_processed_text(text, self.clipboard_history_for(window_class))?;
+
+        if action == Some(InjectionAction::ClipboardOnly) {
+            info!("📋 Copied to clipboard only on this workspace (injection_rules)");
+            self.last_injected_chars = 0;
+            return Ok(Some("clipboard"));
+        }
+
+        // Small delay to ensure window focus is ready for input (especially on Wayland/XWayland)
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+        let method = self.paste(window_class).await?;
+        self.last_injected_chars = text.chars().count();
+        Ok(Some(method))
+    }
+
+    /// Processes and pastes `text`, returning what was injected where, or
+    /// `None` when nothing was left to paste.
+    pub async fn inject_text(&mut self, text: &str) -> Result<Option<InjectionReport>> {
+        let scratch = scratch::apply(text);
+        if scratch.erase_previous {
+            self.erase_previous_injection().await?;
+        }
+        let text = scratch.text.as_str();
+
         if text.trim().is_empty() {
             debug!("No text to inject (empty or whitespace)");
-            return Ok(());
+            return Ok(None);
         }
 
+        let window_class = self.active_window_class().await;
+
         // Preprocess text
-        let processed = self.preprocess_text(text);
+        let processed = self.pipeline.process(text, window_class.as_deref());
+
+        if self.read_only {
+            info!("🔒 Read-only mode, not injecting the transcript");
+            return Ok(Some(InjectionReport {
+                text: processed,
+                chars: 0,
+                method: "none",
+                window_class,
+                window: None,
+            }));
+        }
+
+        if let Some(reason) = self.post_check.violation(&processed) {
+            warn!("🛑 Not injecting the transcript ({reason}); it is on the clipboard instead");
+            self.copy_processed_text(
+                &processed,
+                self.clipboard_history_for(window_class.as_deref()),
+            )?;
+            self.last_injected_chars = 0;
+            return Ok(Some(InjectionReport {
+                text: processed,
+                chars: 0,
+                method: "clipboard",
+                window_class,
+                window: None,
+            }));
+        }
 
         info!("Injecting text: {} characters", processed.len());
 
-        // Copy to clipboard using available backends
-        self.copy_processed_text(&processed)?;
+        let Some(method) = self.deliver(&processed, window_class.as_deref()).await? else {
+            return Ok(None);
+        };
 
-        // Small delay to ensure window focus is ready for input (especially on Wayland/XWayland)
-        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+        let window = match self.hyprland_dispatcher.as_ref() {
+            Some(dispatcher) => dispatcher.ipc.active_window().await.unwrap_or_else(|err| {
+                debug!("Failed to query Hyprland active window: {err:?}");
+                None
+            }),
+            None => None,
+        };
 
-        let mut shift_hint: Option<bool> = None;
-        let default_shift = self.default_shift_paste;
+        Ok(Some(InjectionReport {
+            text: processed,
+            chars: self.last_injected_chars,
+            method,
+            window_class,
+            window,
+        }))
+    }
 
+    /// Pastes already cleaned-up text (e.g. from history) as-is, skipping the
+    /// text pipeline. `None` when an injection rule skipped it.
+    pub async fn paste_verbatim(&mut self, text: &str) -> Result<Option<&'static str>> {
+        let window_class = self.active_window_class().await;
+        self.deliver(text, window_class.as_deref()).await
+    }
+
+    /// Focused window class from Hyprland or Sway, or from X11 in an X
+    /// session.
+    async fn active_window_class(&self) -> Option<String> {
         if let Some(dispatcher) = self.hyprland_dispatcher.as_ref() {
-            match dispatcher.active_window_class().await {
-                Ok(class_opt) => {
-                    if let Some(class) = class_opt {
-                        if let Some(needs_shift) =
-                            shift_hint_for_class(&class, &self.extra_shift_classes)
-                        {
-                            debug!(
-                                class = class.as_str(),
-                                needs_shift, "Hyprland active window classification"
-                            );
-                            shift_hint = Some(needs_shift);
-                        } else {
-                            debug!(
-                                class = class.as_str(),
-                                default = default_shift,
-                                "Hyprland active window classification has no explicit shift rule"
-                            );
-                        }
-                    }
-                }
+            return match dispatcher.active_window_class().await {
+                Ok(class) => class,
                 Err(err) => {
                     warn!("Failed to query Hyprland active window class: {err:?}");
+                    None
                 }
-            }
+            };
+        }
+        if let Some(dispatcher) = self.sway_dispatcher.as_ref() {
+            return dispatcher
+                .active_window_class()
+                .await
+                .unwrap_or_else(|err| {
+                    warn!("Failed to query Sway focused window: {err:#}");
+                    None
+                });
+        }
+
+        let x11 = self.x11.as_ref()?;
+        x11.active_window_class().unwrap_or_else(|err| {
+            warn!("Failed to query X11 active window class: {err:#}");
+            None
+        })
+    }
+
+    /// Sends the paste shortcut through the best available backend and
+    /// returns its name.
+    async fn paste(&mut self, window_class: Option<&str>) -> Result<&'static str> {
+        let keys = paste_keys_for(
+            &self.paste_hints,
+            self.default_shift_paste,
+            &self.extra_shift_classes,
+            window_class,
+        );
+        debug!(class = window_class, keys = %keys, "Paste keys for the active window");
 
-            let use_shift = shift_hint.unwrap_or(default_shift);
-            debug!(use_shift, "Hyprland sendshortcut paste attempt");
+        if let Some(dispatcher) = self.hyprland_dispatcher.as_ref() {
+            debug!("Hyprland sendshortcut paste attempt");
 
-            match dispatcher.send_paste_shortcut(use_shift).await {
+            match dispatcher.send_paste_shortcut(&keys).await {
                 Ok(_) => {
                     info!("✅ Text injected via Hyprland sendshortcut");
-                    return Ok(());
+                    return Ok("hyprland");
                 }
                 Err(err) => {
                     warn!("Hyprland sendshortcut paste failed: {err:?}");
@@ -952,11 +1279,10 @@ impl TextInjector {
         }
 
         if let Some(client) = self.ensure_wrtype_client() {
-            let use_shift = shift_hint.unwrap_or(default_shift);
-            match send_virtual_keyboard_paste(client, use_shift) {
+            match client.send_keys(&keys) {
                 Ok(_) => {
                     info!("✅ Text injected via Wayland virtual keyboard");
-                    return Ok(());
+                    return Ok("virtual_keyboard");
                 }
                 Err(err) => {
                     warn!("Wayland virtual keyboard paste failed: {err:?}");
@@ -965,13 +1291,47 @@ impl TextInjector {
             }
         }
 
-        debug!("Falling back to Ctrl+Shift+V paste via Enigo");
-        self.inject_via_enigo_shift_paste()
+        if let Some(x11) = self.x11.as_ref() {
+            match x11.send_keys(&keys) {
+                Ok(()) => {
+                    info!("✅ Text injected via X11 XTEST");
+                    return Ok("xtest");
+                }
+                Err(err) => warn!("X11 XTEST paste failed: {err:#}"),
+            }
+        }
+
+        debug!("Falling back to {keys} paste via Enigo");
+        self.enigo()?.send_keys(&keys)?;
+        info!("✅ Text injected via Enigo fallback paste");
+        Ok("enigo")
     }
 
-    fn copy_processed_text(&mut self, text: &str) -> Result<()> {
+    /// Types `text` key by key instead of pasting, leaving the clipboard
+    /// untouched.
+    fn type_text(&mut self, text: &str) -> Result<&'static str> {
+        if let Some(client) = self.ensure_wrtype_client() {
+            match client.type_text(text) {
+                Ok(_) => {
+                    info!("✅ Text typed via Wayland virtual keyboard");
+                    return Ok("typed_virtual_keyboard");
+                }
+                Err(err) => {
+                    warn!("Wayland virtual keyboard typing failed: {err:?}");
+                    self.invalidate_wrtype_client();
+                }
+            }
+        }
+
+        self.enigo()?.type_text(text)?;
+        info!("✅ Text typed via Enigo");
+        Ok("typed_enigo")
+    }
+
+    fn copy_processed_text(&mut self, text: &str, history: ClipboardHistory) -> Result<()> {
+        let exclude = history == ClipboardHistory::Exclude;
         if self.wayland_clipboard_enabled {
-            match self.copy_wayland_clipboard(text) {
+            match copy_wayland_clipboard(text, exclude) {
                 Ok(_) => {
                     debug!("Text copied to Wayland clipboard");
                 }
@@ -982,40 +1342,61 @@ impl TextInjector {
             }
         }
 
-        self.clipboard
-            .set_text(text)
-            .context("Failed to copy text to clipboard")?;
+        let set = self.clipboard()?.set();
+        let set = if exclude {
+            set.exclude_from_history()
+        } else {
+            set
+        };
+        set.text(text).context("Failed to copy text to clipboard")?;
         debug!("Text copied to clipboard");
+
+        // X11 users expect middle-click to paste the dictation too.
+        if self.x11.is_some() {
+            let set = self
+                .clipboard()?
+                .set()
+                .clipboard(LinuxClipboardKind::Primary);
+            let set = if exclude {
+                set.exclude_from_history()
+            } else {
+                set
+            };
+            if let Err(err) = set.text(text) {
+                debug!("Failed to set the X11 primary selection: {err}");
+            }
+        }
+
+        if history == ClipboardHistory::Store {
+            store_in_cliphist(text.to_string());
+        }
         Ok(())
     }
 
-    fn copy_wayland_clipboard(&self, text: &str) -> Result<(), WlCopyError> {
-        let bytes = text.as_bytes().to_vec();
-
-        let mut both = Options::new();
-        both.clipboard(ClipboardType::Both);
-        match both.copy(
-            Source::Bytes(bytes.clone().into_boxed_slice()),
-            MimeType::Text,
-        ) {
-            Ok(_) => Ok(()),
-            Err(WlCopyError::PrimarySelectionUnsupported) => {
-                let mut regular = Options::new();
-                regular.clipboard(ClipboardType::Regular);
-                regular.copy(Source::Bytes(bytes.into_boxed_slice()), MimeType::Text)
+    fn enigo(&mut self) -> Result<&mut EnigoKeyboard> {
+        match &mut self.enigo {
+            Some(enigo) => Ok(enigo),
+            enigo => Ok(enigo.insert(EnigoKeyboard::new()?)),
+        }
+    }
+
+    fn clipboard(&mut self) -> Result<&mut Clipboard> {
+        match &mut self.clipboard {
+            Some(clipboard) => Ok(clipboard),
+            clipboard => {
+                Ok(clipboard.insert(Clipboard::new().context("Failed to initialize clipboard")?))
             }
-            Err(err) => Err(err),
         }
     }
 
-    fn ensure_wrtype_client(&mut self) -> Option<&mut WrtypeClient> {
+    fn ensure_wrtype_client(&mut self) -> Option<&mut VirtualKeyboard> {
         if !self.wayland_env {
             return None;
         }
 
         if self.wrtype_client.is_none() && !self.wrtype_attempted {
             self.wrtype_attempted = true;
-            match WrtypeClient::new() {
+            match VirtualKeyboard::new() {
                 Ok(client) => {
                     debug!("Initialized Wayland virtual keyboard client");
                     self.wrtype_client = Some(client);
@@ -1034,30 +1415,189 @@ impl TextInjector {
         self.wrtype_attempted = false;
     }
 
-    fn inject_via_enigo_shift_paste(&mut self) -> Result<()> {
-        use enigo::{Direction, Key};
-
-        self.enigo
-            .key(Key::Control, Direction::Press)
-            .context("Failed to press Ctrl")?;
-        self.enigo
-            .key(Key::Shift, Direction::Press)
-            .context("Failed to press Shift")?;
-        self.enigo
-            .key(Key::Unicode('v'), Direction::Click)
-            .context("Failed to press V")?;
-        self.enigo
-            .key(Key::Shift, Direction::Release)
-            .context("Failed to release Shift")?;
-        self.enigo
-            .key(Key::Control, Direction::Release)
-            .context("Failed to release Ctrl")?;
+    /// Backspaces over the previous injection ("scratch that", "undo"). Only
+    /// text pasted by this injector is tracked, so repeated commands stop
+    /// after one.
+    pub async fn erase_previous_injection(&mut self) -> Result<()> {
+        let mut remaining = std::mem::take(&mut self.last_injected_chars);
+        if remaining == 0 {
+            info!("Nothing to scratch");
+            return Ok(());
+        }
+        let total = remaining;
+
+        if let Some(dispatcher) = self.hyprland_dispatcher.as_ref() {
+            while remaining > 0 {
+                if let Err(err) = dispatcher
+                    .send_shortcut(&[], "BackSpace", Some("active"))
+                    .await
+                {
+                    warn!("Hyprland backspace failed: {err:?}");
+                    break;
+                }
+                remaining -= 1;
+            }
+        }
 
-        info!("✅ Text injected via Enigo fallback paste");
+        if let Some(x11) = self.x11.as_ref() {
+            while remaining > 0 {
+                if let Err(err) = x11.backspace() {
+                    warn!("X11 backspace failed: {err:#}");
+                    break;
+                }
+                remaining -= 1;
+            }
+        }
+
+        while remaining > 0 {
+            self.enigo()?.backspace()?;
+            remaining -= 1;
+        }
+
+        info!("🧹 Scratched previous dictation ({total} characters)");
         Ok(())
     }
+}
+
+/// Copies `text` to the clipboard outside the daemon (e.g. from the CLI);
+/// on Wayland the selection outlives the calling process.
+pub fn copy_to_clipboard(text: &str) -> Result<()> {
+    if env::var("WAYLAND_DISPLAY").is_ok() {
+        match copy_wayland_clipboard(text, false) {
+            Ok(()) => return Ok(()),
+            Err(err) => warn!("Wayland clipboard copy failed (falling back to arboard): {err:?}"),
+        }
+    }
+
+    Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(text))
+        .context("Failed to copy text to clipboard")
+}
+
+/// `exclude_from_history` adds the password-manager hint that cliphist,
+/// Klipper and friends skip.
+fn copy_wayland_clipboard(text: &str, exclude_from_history: bool) -> Result<(), WlCopyError> {
+    let sources = || {
+        let mut sources = vec![MimeSource {
+            source: Source::Bytes(text.as_bytes().into()),
+            mime_type: MimeType::Text,
+        }];
+        if exclude_from_history {
+            sources.push(MimeSource {
+                source: Source::Bytes(b"secret".as_slice().into()),
+                mime_type: MimeType::Specific(PASSWORD_MANAGER_HINT.to_string()),
+            });
+        }
+        sources
+    };
+
+    let mut both = Options::new();
+    both.clipboard(ClipboardType::Both);
+    match both.copy_multi(sources()) {
+        Ok(_) => Ok(()),
+        Err(WlCopyError::PrimarySelectionUnsupported) => {
+            let mut regular = Options::new();
+            regular.clipboard(ClipboardType::Regular);
+            regular.copy_multi(sources())
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Pipes `text` into `cliphist store` without holding up the paste.
+fn store_in_cliphist(text: String) {
+    thread::spawn(move || {
+        let result = Command::new("cliphist")
+            .arg("store")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .and_then(|mut child| {
+                if let Some(mut stdin) = child.stdin.take() {
+                    stdin.write_all(text.as_bytes())?;
+                }
+                child.wait_with_output()
+            });
+        match result {
+            Ok(output) if output.status.success() => debug!("Stored transcript in cliphist"),
+            Ok(output) => warn!(
+                "cliphist store failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            Err(err) => warn!("Failed to run cliphist: {err}"),
+        }
+    });
+}
+
+impl TextPipeline {
+    /// `extra_shift_classes` must already be trimmed and lowercased.
+    pub fn new(
+        extra_shift_classes: HashSet<String>,
+        word_overrides: HashMap<String, String>,
+        plugins: Vec<PluginConfig>,
+        postprocess: PostProcessConfig,
+        code_dictation: CodeDictationConfig,
+    ) -> Self {
+        Self {
+            word_overrides: sanitize_word_overrides(word_overrides),
+            profiles: HashMap::new(),
+            profile_override: None,
+            speech_commands: SpeechCommands::default(),
+            mode: DictationMode::default(),
+            extra_shift_classes,
+            plugins: plugins
+                .into_iter()
+                .filter(|plugin| plugin.enabled && !plugin.command.is_empty())
+                .map(SubprocessPlugin::new)
+                .collect(),
+            script: postprocess
+                .script
+                .filter(|source| !source.trim().is_empty())
+                .and_then(|source| ScriptStage::new(&source)),
+            case_exceptions: CaseExceptions::new(&postprocess.case_exceptions),
+            normalize_addresses: postprocess.normalize_addresses,
+            typography: postprocess.typography,
+            whitespace: postprocess.whitespace,
+            code_dictation,
+        }
+    }
+
+    /// Per-application overrides, selected by the focused window class.
+    pub fn set_profiles(&mut self, profiles: HashMap<String, AppProfile>) {
+        self.profiles = profiles
+            .into_iter()
+            .map(|(class, mut profile)| {
+                profile.word_overrides = sanitize_word_overrides(profile.word_overrides);
+                (class, profile)
+            })
+            .collect();
+    }
+
+    /// Merges `speech_commands` over the built-in spoken punctuation.
+    pub fn set_speech_commands(&mut self, config: &SpeechCommandsConfig) {
+        self.speech_commands = SpeechCommands::new(config);
+    }
+
+    pub fn set_mode(&mut self, mode: DictationMode) {
+        self.mode = mode;
+    }
+
+    fn profile(&self, window_class: Option<&str>) -> Option<&AppProfile> {
+        profile_for(
+            &self.profiles,
+            self.profile_override.as_deref().or(window_class),
+        )
+    }
+
+    /// Runs the cleanup stages of the current mode over `text` for the
+    /// window `window_class`.
+    pub fn process(&self, text: &str, window_class: Option<&str>) -> String {
+        if self.mode == DictationMode::Raw {
+            return text.to_string();
+        }
+        let code_mode = self.mode == DictationMode::Code;
 
-    fn preprocess_text(&self, text: &str) -> String {
         let mut steps = if tracing::level_enabled!(tracing::Level::DEBUG) {
             Some(Vec::new())
         } else {
@@ -1076,7 +1616,11 @@ impl TextInjector {
         }
         current = normalized;
 
-        let (after_overrides, override_count) = self.apply_word_overrides_with_count(&current);
+        current = self.apply_plugins(PluginPosition::BeforeCleanup, current, &mut steps);
+
+        let profile = self.profile(window_class);
+        let (after_overrides, override_count) =
+            self.apply_word_overrides_with_count(&current, profile);
         if let Some(ref mut logged_steps) = steps {
             logged_steps.push(PipelineStepRecord::new(
                 "word_overrides",
@@ -1091,20 +1635,59 @@ impl TextInjector {
         }
         current = after_overrides;
 
-        let (after_speech, speech_count) = self.apply_speech_replacements_with_count(&current);
-        if let Some(ref mut logged_steps) = steps {
-            logged_steps.push(PipelineStepRecord::new(
-                "speech_replacements",
-                current.clone(),
-                after_speech.clone(),
-                if speech_count > 0 {
-                    Some(speech_count)
-                } else {
-                    None
-                },
-            ));
+        if code_mode || self.code_dictation.active_for(window_class) {
+            let (after_code, code_count) = code_dictation::apply(&current);
+            if let Some(ref mut logged_steps) = steps {
+                logged_steps.push(PipelineStepRecord::new(
+                    "code_dictation",
+                    current.clone(),
+                    after_code.clone(),
+                    if code_count > 0 {
+                        Some(code_count)
+                    } else {
+                        None
+                    },
+                ));
+            }
+            current = after_code;
+        }
+
+        if self.normalize_addresses {
+            let (after_addresses, address_count) = addresses::normalize(&current);
+            if let Some(ref mut logged_steps) = steps {
+                logged_steps.push(PipelineStepRecord::new(
+                    "normalize_addresses",
+                    current.clone(),
+                    after_addresses.clone(),
+                    if address_count > 0 {
+                        Some(address_count)
+                    } else {
+                        None
+                    },
+                ));
+            }
+            current = after_addresses;
+        }
+
+        let speech_enabled = profile
+            .and_then(|profile| profile.speech_replacements_enabled)
+            .unwrap_or(self.speech_commands.enabled);
+        if speech_enabled {
+            let (after_speech, speech_count) = self.apply_speech_replacements_with_count(&current);
+            if let Some(ref mut logged_steps) = steps {
+                logged_steps.push(PipelineStepRecord::new(
+                    "speech_replacements",
+                    current.clone(),
+                    after_speech.clone(),
+                    if speech_count > 0 {
+                        Some(speech_count)
+                    } else {
+                        None
+                    },
+                ));
+            }
+            current = after_speech;
         }
-        current = after_speech;
 
         let cleaned_control = clean_control_artifacts(&current);
         if let Some(ref mut logged_steps) = steps {
@@ -1173,32 +1756,101 @@ impl TextInjector {
         }
         current = bridged_underscores;
 
-        let (capitalized, capitalized_count) = capitalize_after_period(&current);
+        if !code_mode {
+            let (capitalized, capitalized_count) = capitalize_after_period(&current);
+            if let Some(ref mut logged_steps) = steps {
+                logged_steps.push(PipelineStepRecord::new(
+                    "capitalize_after_period",
+                    current.clone(),
+                    capitalized.clone(),
+                    if capitalized_count > 0 {
+                        Some(capitalized_count)
+                    } else {
+                        None
+                    },
+                ));
+            }
+            current = capitalized;
+        }
+
+        if !code_mode && self.typography_active(window_class) {
+            let locale = Locale::from_language(&self.typography.language);
+            let (typeset, typeset_count) =
+                typography::apply(&current, locale, self.typography.smart_quotes);
+            if let Some(ref mut logged_steps) = steps {
+                logged_steps.push(PipelineStepRecord::new(
+                    "typography",
+                    current.clone(),
+                    typeset.clone(),
+                    if typeset_count > 0 {
+                        Some(typeset_count)
+                    } else {
+                        None
+                    },
+                ));
+            }
+            current = typeset;
+        }
+
+        current = self.apply_plugins(PluginPosition::AfterCleanup, current, &mut steps);
+
+        if let Some(script) = &self.script {
+            let scripted = script.apply(&current, window_class);
+            if let Some(ref mut logged_steps) = steps {
+                logged_steps.push(PipelineStepRecord::new(
+                    "postprocess_script",
+                    current.clone(),
+                    scripted.clone(),
+                    None,
+                ));
+            }
+            current = scripted;
+        }
+
+        if let Some(exceptions) = &self.case_exceptions {
+            let (respelled, respelled_count) = exceptions.apply(&current);
+            if let Some(ref mut logged_steps) = steps {
+                logged_steps.push(PipelineStepRecord::new(
+                    "case_exceptions",
+                    current.clone(),
+                    respelled.clone(),
+                    if respelled_count > 0 {
+                        Some(respelled_count)
+                    } else {
+                        None
+                    },
+                ));
+            }
+            current = respelled;
+        }
+
+        let trimmed = current.trim().to_string();
         if let Some(ref mut logged_steps) = steps {
             logged_steps.push(PipelineStepRecord::new(
-                "capitalize_after_period",
+                "trim_whitespace",
                 current.clone(),
-                capitalized.clone(),
-                if capitalized_count > 0 {
-                    Some(capitalized_count)
-                } else {
-                    None
-                },
+                trimmed.clone(),
+                None,
             ));
         }
-        current = capitalized;
 
-        let trimmed = current.trim().to_string();
+        let padded = apply_whitespace_policy(
+            &trimmed,
+            &self.whitespace,
+            window_class,
+            self.profile(window_class)
+                .and_then(|profile| profile.whitespace.as_ref()),
+        );
         if let Some(ref mut logged_steps) = steps {
             logged_steps.push(PipelineStepRecord::new(
-                "trim_whitespace",
-                current.clone(),
+                "whitespace_policy",
                 trimmed.clone(),
+                padded.clone(),
                 None,
             ));
         }
 
-        let final_result = trimmed;
+        let final_result = padded;
 
         if let Some(logged_steps) = steps {
             record_text_pipeline(TextPipelineRecord::new(
@@ -1211,15 +1863,60 @@ impl TextInjector {
         final_result
     }
 
-    fn apply_word_overrides_with_count(&self, text: &str) -> (String, usize) {
-        let mut result = text.to_string();
-        let mut count = 0;
+    fn typography_active(&self, window_class: Option<&str>) -> bool {
+        if !self.typography.enabled {
+            return false;
+        }
+        let in_terminal = window_class.is_some_and(|class| {
+            shift_hint_for_class(class, &self.extra_shift_classes) == Some(true)
+        });
+        !(self.typography.skip_terminals && in_terminal)
+    }
 
-        if self.word_overrides.is_empty() {
-            return (result, 0);
+    fn apply_plugins(
+        &self,
+        position: PluginPosition,
+        mut current: String,
+        steps: &mut Option<Vec<PipelineStepRecord>>,
+    ) -> String {
+        for plugin in self.plugins.iter().filter(|p| p.position() == position) {
+            let output = plugin.apply(&current);
+            if let Some(ref mut logged_steps) = steps {
+                logged_steps.push(PipelineStepRecord::new(
+                    format!("plugin:{}", plugin.name()),
+                    current.clone(),
+                    output.clone(),
+                    None,
+                ));
+            }
+            current = output;
         }
+        current
+    }
 
-        for (original, replacement) in &self.word_overrides {
+    fn apply_word_overrides_with_count(
+        &self,
+        text: &str,
+        profile: Option<&AppProfile>,
+    ) -> (String, usize) {
+        let mut result = text.to_string();
+        let mut count = 0;
+
+        // A profile's entry replaces the global one for the same words,
+        // rather than running first and then being rewritten by it.
+        let profile_overrides = profile.map(|profile| &profile.word_overrides);
+        let shadowed = |original: &String| {
+            profile_overrides.is_some_and(|overrides| {
+                overrides
+                    .keys()
+                    .any(|key| key.eq_ignore_ascii_case(original))
+            })
+        };
+        let global = self
+            .word_overrides
+            .iter()
+            .filter(|(original, _)| !shadowed(original));
+        for (original, replacement) in profile_overrides.into_iter().flatten().chain(global) {
             // Case-insensitive word boundary replacement
             let pattern = format!(r"\b{}\b", regex::escape(original));
             if let Ok(re) = Regex::new(&format!("(?i){}", pattern)) {
@@ -1235,16 +1932,30 @@ impl TextInjector {
     }
 
     fn apply_speech_replacements_with_count(&self, text: &str) -> (String, usize) {
-        // Built-in speech-to-text replacements
-        apply_speech_replacements(text)
+        self.speech_commands.apply(text)
     }
 }
 
-fn send_virtual_keyboard_paste(client: &mut WrtypeClient, use_shift: bool) -> Result<()> {
-    if use_shift {
-        client.send_shortcut(&[Modifier::Ctrl, Modifier::Shift], "v")
-    } else {
-        client.send_shortcut(&[Modifier::Ctrl], "v")
+/// A class's own `paste_hints` entry, else Ctrl+Shift+V for terminals, else
+/// the configured default.
+fn paste_keys_for(
+    hints: &PasteHintsConfig,
+    shift_paste_default: bool,
+    extra_shift_classes: &HashSet<String>,
+    window_class: Option<&str>,
+) -> PasteKeys {
+    if let Some(keys) = hints.keys_for(window_class) {
+        return keys.clone();
+    }
+    if window_class
+        .is_some_and(|class| shift_hint_for_class(class, extra_shift_classes) == Some(true))
+    {
+        return PasteKeys::ctrl_shift_v();
+    }
+    match &hints.default {
+        Some(keys) => keys.clone(),
+        None if shift_paste_default => PasteKeys::ctrl_shift_v(),
+        None => PasteKeys::ctrl_v(),
     }
 }
 
@@ -1272,6 +1983,30 @@ fn shift_hint_for_class(class: &str, extra_shift_classes: &HashSet<String>) -> O
     None
 }
 
+fn apply_whitespace_policy(
+    text: &str,
+    policy: &WhitespaceConfig,
+    window_class: Option<&str>,
+    profile: Option<&WhitespaceOverride>,
+) -> String {
+    if text.is_empty() {
+        return String::new();
+    }
+
+    let (leading_space, trailing) = policy.resolve(window_class, profile);
+    let mut result = String::with_capacity(text.len() + 2);
+    if leading_space {
+        result.push(' ');
+    }
+    result.push_str(text);
+    match trailing {
+        TrailingWhitespace::None => {}
+        TrailingWhitespace::Space => result.push(' '),
+        TrailingWhitespace::Newline => result.push('\n'),
+    }
+    result
+}
+
 fn normalize_line_breaks(input: &str) -> String {
     if input.contains(['\r', '\n']) {
         input
@@ -1311,6 +2046,185 @@ fn clean_control_artifacts(input: &str) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn app_profiles_override_cleanup_per_class() {
+        let mut pipeline = TextPipeline::new(
+            HashSet::new(),
+            HashMap::from([("hub".to_string(), "Hub".to_string())]),
+            Vec::new(),
+            PostProcessConfig::default(),
+            CodeDictationConfig::default(),
+        );
+        let kitty = AppProfile {
+            word_overrides: HashMap::from([("git hub".to_string(), "gh".to_string())]),
+            speech_replacements_enabled: Some(false),
+            ..AppProfile::default()
+        };
+        pipeline.set_profiles(HashMap::from([("kitty".to_string(), kitty)]));
+
+        let in_kitty = pipeline.process("open git hub period", Some("Kitty"));
+        assert!(in_kitty.contains("gh period"), "{in_kitty}");
+
+        let elsewhere = pipeline.process("open git hub period", Some("firefox"));
+        assert!(elsewhere.contains("git Hub."), "{elsewhere}");
+    }
+
+    #[tokio::test]
+    async fn read_only_delivers_and_copies_nothing() {
+        let mut injector = TextInjector::new(
+            false,
+            PasteHintsConfig::default(),
+            HashMap::new(),
+            false,
+            Vec::new(),
+            PostProcessConfig::default(),
+            CodeDictationConfig::default(),
+        )
+        .unwrap();
+        injector.set_read_only(true);
+
+        let report = injector.inject_text("hello there").await.unwrap().unwrap();
+        assert_eq!((report.chars, report.method), (0, "none"));
+        assert_eq!(injector.paste_verbatim("hello there").await.unwrap(), None);
+        assert_eq!(injector.last_injected_chars, 0);
+        assert!(injector.clipboard.is_none() && injector.enigo.is_none());
+    }
+
+    #[test]
+    fn profile_word_overrides_shadow_global_ones() {
+        let mut pipeline = TextPipeline::new(
+            HashSet::new(),
+            HashMap::from([("hub".to_string(), "Hub".to_string())]),
+            Vec::new(),
+            PostProcessConfig::default(),
+            CodeDictationConfig::default(),
+        );
+        let kitty = AppProfile {
+            word_overrides: HashMap::from([("hub".to_string(), "HUB".to_string())]),
+            ..AppProfile::default()
+        };
+        pipeline.set_profiles(HashMap::from([("kitty".to_string(), kitty)]));
+
+        let in_kitty = pipeline.process("push to hub", Some("kitty"));
+        assert!(in_kitty.contains("to HUB"), "{in_kitty}");
+
+        let elsewhere = pipeline.process("push to hub", Some("firefox"));
+        assert!(elsewhere.contains("to Hub"), "{elsewhere}");
+    }
+
+    #[test]
+    fn whitespace_policy_layers_class_and_profile_overrides() {
+        let policy = WhitespaceConfig {
+            leading_space: true,
+            trailing: TrailingWhitespace::Space,
+            per_class: HashMap::from([(
+                "Code".to_string(),
+                WhitespaceOverride {
+                    leading_space: Some(false),
+                    trailing: Some(TrailingWhitespace::None),
+                },
+            )]),
+        };
+        let profile = WhitespaceOverride {
+            leading_space: None,
+            trailing: Some(TrailingWhitespace::Newline),
+        };
+
+        assert_eq!(
+            apply_whitespace_policy("hi", &policy, Some("kitty"), None),
+            " hi "
+        );
+        assert_eq!(
+            apply_whitespace_policy("hi", &policy, Some("code"), None),
+            "hi"
+        );
+        assert_eq!(
+            apply_whitespace_policy("hi", &policy, Some("code"), Some(&profile)),
+            "hi\n"
+        );
+        assert_eq!(
+            apply_whitespace_policy("hi", &policy, None, Some(&profile)),
+            " hi\n"
+        );
+        assert_eq!(
+            apply_whitespace_policy("", &policy, None, Some(&profile)),
+            ""
+        );
+    }
+
+    #[test]
+    fn profiles_set_their_own_whitespace() {
+        let mut postprocess = PostProcessConfig::default();
+        postprocess.whitespace.trailing = TrailingWhitespace::Space;
+        let mut pipeline = TextPipeline::new(
+            HashSet::new(),
+            HashMap::new(),
+            Vec::new(),
+            postprocess,
+            CodeDictationConfig::default(),
+        );
+        let kitty = AppProfile {
+            whitespace: Some(WhitespaceOverride {
+                leading_space: Some(true),
+                trailing: Some(TrailingWhitespace::Newline),
+            }),
+            ..AppProfile::default()
+        };
+        pipeline.set_profiles(HashMap::from([("kitty".to_string(), kitty)]));
+
+        let in_kitty = pipeline.process("list files", Some("kitty"));
+        assert!(
+            in_kitty.starts_with(' ') && in_kitty.ends_with('\n'),
+            "{in_kitty:?}"
+        );
+
+        let elsewhere = pipeline.process("list files", Some("firefox"));
+        assert!(
+            !elsewhere.starts_with(' ') && elsewhere.ends_with(' '),
+            "{elsewhere:?}"
+        );
+    }
+
+    #[test]
+    fn dictation_modes_select_pipeline_layers() {
+        let mut pipeline = TextPipeline::new(
+            HashSet::new(),
+            HashMap::new(),
+            Vec::new(),
+            PostProcessConfig::default(),
+            CodeDictationConfig::default(),
+        );
+        let input = "let snake case max size done equals ten period";
+
+        let prose = pipeline.process(input, None);
+        assert!(prose.starts_with("Let snake case"), "{prose}");
+
+        pipeline.set_mode(DictationMode::Code);
+        let code = pipeline.process(input, None);
+        assert!(code.starts_with("let max_size = ten"), "{code}");
+
+        pipeline.set_mode(DictationMode::Raw);
+        assert_eq!(pipeline.process(input, None), input);
+    }
+
+    #[test]
+    fn case_exceptions_outlast_capitalization_and_overrides() {
+        let pipeline = TextPipeline::new(
+            HashSet::new(),
+            HashMap::from([("hyper whisper".to_string(), "Hyprwhspr".to_string())]),
+            Vec::new(),
+            PostProcessConfig {
+                case_exceptions: vec!["iPhone".to_string(), "hyprwhspr".to_string()],
+                ..PostProcessConfig::default()
+            },
+            CodeDictationConfig::default(),
+        );
+        assert_eq!(
+            pipeline.process("iphone works. hyper whisper types on my iphone", None),
+            "iPhone works. hyprwhspr types on my iPhone"
+        );
+    }
+
     #[test]
     fn removes_parenthesis_commas_and_spaces() {
         let input = "(, value, )";
@@ -1378,7 +2292,7 @@ mod tests {
     #[test]
     fn speech_replacements_normalize_commanded_punctuation() {
         let input = "This is awesome. Period. I love this. Comma. Fuck. Yeah. Comma. Fuck. Period.";
-        let (after_speech, count) = apply_speech_replacements(input);
+        let (after_speech, count) = SpeechCommands::default().apply(input);
         let cleaned = clean_control_artifacts(&after_speech);
         let collapsed = collapse_spaces(&cleaned);
 
@@ -1400,11 +2314,30 @@ mod tests {
     #[test]
     fn speech_replacements_collapse_dash_dash() {
         let input = "prepare dash dash go";
-        let (after_speech, count) = apply_speech_replacements(input);
+        let (after_speech, count) = SpeechCommands::default().apply(input);
         assert_eq!(after_speech, "prepare -- go");
         assert_eq!(count, 1);
     }
 
+    #[test]
+    fn speech_commands_merge_over_builtins() {
+        let config: SpeechCommandsConfig = serde_json::from_value(serde_json::json!({
+            "commands": {
+                "tab": false,
+                "hash": "#!",
+                "Full  Stop": { "replacement": ".", "attach": true },
+                "smiley": ":)",
+            }
+        }))
+        .unwrap();
+        let commands = SpeechCommands::new(&config);
+
+        let (text, count) = commands.apply("done full stop tab hash smiley comma");
+        assert_eq!(collapse_spaces(&text).trim(), "done. tab #! :),");
+        assert_eq!(count, 4);
+        assert!(commands.enabled);
+    }
+
     #[test]
     fn control_cleanup_preserves_colon_after_symbols() {
         let input = "— { chaos,  yes }:  coordinate";
@@ -1480,4 +2413,32 @@ Title: sample
         let class = super::HyprlandDispatcher::extract_window_class_from_response(sample).unwrap();
         assert_eq!(class, Some("foot".to_string()));
     }
+
+    #[test]
+    fn paste_keys_follow_class_then_terminal_then_default() {
+        let hints: PasteHintsConfig = serde_json::from_value(serde_json::json!({
+            "emacs": "ctrl+y",
+            "kitty": "Shift+Insert",
+            "default": "ctrl+v",
+            "shift": ["mytty"]
+        }))
+        .unwrap();
+        let extra = HashSet::from(["mytty".to_string()]);
+        let keys = |class| paste_keys_for(&hints, true, &extra, class).to_string();
+
+        assert_eq!(keys(Some("Emacs")), "ctrl+y");
+        assert_eq!(keys(Some("kitty")), "shift+insert");
+        assert_eq!(keys(Some("Alacritty")), "ctrl+shift+v");
+        assert_eq!(keys(Some("mytty")), "ctrl+shift+v");
+        assert_eq!(keys(Some("firefox")), "ctrl+v");
+        assert_eq!(keys(None), "ctrl+v");
+
+        let legacy = PasteHintsConfig::default();
+        assert_eq!(
+            paste_keys_for(&legacy, true, &HashSet::new(), Some("firefox")),
+            PasteKeys::ctrl_shift_v()
+        );
+        assert!(PasteKeys::parse("ctrl+").is_err());
+        assert!(PasteKeys::parse("hyper+v").is_err());
+    }
 }
@@ -1,5 +1,28 @@
+pub mod addresses;
+pub mod case_exceptions;
+pub mod code_dictation;
+pub mod enigo_fallback;
+pub mod hyprland;
 pub mod injector;
+pub mod plugins;
+pub mod post_check;
+pub mod scratch;
+pub mod script;
 pub mod shortcuts;
+pub mod sway;
+pub mod typography;
+pub mod virtual_keyboard;
+pub mod x11;
 
-pub use injector::TextInjector;
-pub use shortcuts::{GlobalShortcuts, ShortcutEvent, ShortcutKind, ShortcutPhase};
+pub use injector::{copy_to_clipboard, InjectionReport, TextInjector, TextPipeline};
+pub use shortcuts::{
+    DoublePress, GlobalShortcuts, PressBehavior, ShortcutEvent, ShortcutKind, ShortcutPhase, Tap,
+};
+
+/// Class of the focused window from whichever compositor IPC is reachable.
+pub async fn focused_window_class() -> Option<String> {
+    match hyprland::focused_window_class().await {
+        Some(class) => Some(class),
+        None => sway::focused_window_class().await,
+    }
+}
@@ -0,0 +1,154 @@
+use crate::config::{PluginConfig, PluginPosition};
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+#[derive(Serialize)]
+struct PluginRequest<'a> {
+    text: &'a str,
+    position: PluginPosition,
+}
+
+#[derive(Deserialize)]
+struct PluginResponse {
+    text: String,
+}
+
+/// External post-processor speaking line-delimited JSON: one
+/// `{"text", "position"}` object per transcript on stdin, one `{"text"}`
+/// object back on stdout. The process is started on first use and kept alive.
+pub struct SubprocessPlugin {
+    config: PluginConfig,
+    process: Mutex<Option<PluginProcess>>,
+}
+
+struct PluginProcess {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+}
+
+impl Drop for PluginProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl SubprocessPlugin {
+    pub fn new(config: PluginConfig) -> Self {
+        Self {
+            config,
+            process: Mutex::new(None),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    pub fn position(&self) -> PluginPosition {
+        self.config.position
+    }
+
+    /// Runs the plugin, returning `text` unchanged when it fails or times out.
+    pub fn apply(&self, text: &str) -> String {
+        match self.exchange(text) {
+            Ok(output) => output,
+            Err(err) => {
+                warn!("Post-processing plugin '{}' failed: {err:#}", self.name());
+                text.to_string()
+            }
+        }
+    }
+
+    fn exchange(&self, text: &str) -> Result<String> {
+        let mut guard = self
+            .process
+            .lock()
+            .map_err(|_| anyhow!("plugin lock poisoned"))?;
+        if guard.is_none() {
+            *guard = Some(self.spawn()?);
+        }
+        let process = guard.as_mut().expect("plugin process just spawned");
+
+        let mut line = serde_json::to_string(&PluginRequest {
+            text,
+            position: self.config.position,
+        })?;
+        line.push('\n');
+
+        let reply = process
+            .stdin
+            .write_all(line.as_bytes())
+            .and_then(|_| process.stdin.flush())
+            .context("Failed to write to plugin")
+            .and_then(|_| {
+                process
+                    .lines
+                    .recv_timeout(Duration::from_millis(self.config.timeout_ms))
+                    .context("No reply from plugin before timeout")
+            });
+
+        let reply = match reply {
+            Ok(reply) => reply,
+            Err(err) => {
+                // Drop the process so the next transcript starts a fresh one.
+                *guard = None;
+                return Err(err);
+            }
+        };
+
+        let response: PluginResponse =
+            serde_json::from_str(&reply).context("Plugin replied with invalid JSON")?;
+        Ok(response.text)
+    }
+
+    fn spawn(&self) -> Result<PluginProcess> {
+        let (program, args) = self
+            .config
+            .command
+            .split_first()
+            .ok_or_else(|| anyhow!("no command configured"))?;
+
+        let mut child = Command::new(expand_home(program))
+            .args(args.iter().map(|arg| expand_home(arg)))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .with_context(|| format!("Failed to start {program}"))?;
+
+        let stdin = child.stdin.take().context("Plugin stdin unavailable")?;
+        let stdout = child.stdout.take().context("Plugin stdout unavailable")?;
+
+        let (tx, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        debug!("Started post-processing plugin '{}'", self.config.name);
+        Ok(PluginProcess {
+            child,
+            stdin,
+            lines,
+        })
+    }
+}
+
+fn expand_home(value: &str) -> String {
+    match (value.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{home}/{rest}"),
+        _ => value.to_string(),
+    }
+}
@@ -0,0 +1,77 @@
+//! A last look at the final text before it reaches the focused window, so a
+//! runaway or hallucinated reply lands on the clipboard instead of being
+//! typed into a terminal or chat.
+
+use crate::config::{PostCheckConfig, WritingScript};
+use crate::transcription::is_unexpected_script;
+use regex::Regex;
+use tracing::warn;
+
+#[derive(Debug, Default)]
+pub struct PostCheck {
+    max_chars: usize,
+    deny: Vec<Regex>,
+    scripts: Vec<WritingScript>,
+}
+
+impl PostCheck {
+    /// Invalid `deny_patterns` are logged and skipped.
+    pub fn new(config: &PostCheckConfig) -> Self {
+        let deny = config
+            .deny_patterns
+            .iter()
+            .filter_map(|pattern| {
+                Regex::new(pattern)
+                    .inspect_err(|err| warn!("Ignoring post_check pattern {pattern:?}: {err}"))
+                    .ok()
+            })
+            .collect();
+        Self {
+            max_chars: config.max_chars,
+            deny,
+            scripts: config.scripts.clone(),
+        }
+    }
+
+    /// Why `text` should not be injected, if it shouldn't.
+    pub fn violation(&self, text: &str) -> Option<String> {
+        let chars = text.chars().count();
+        if self.max_chars > 0 && chars > self.max_chars {
+            return Some(format!(
+                "{chars} characters, over the limit of {}",
+                self.max_chars
+            ));
+        }
+        if let Some(pattern) = self.deny.iter().find(|pattern| pattern.is_match(text)) {
+            return Some(format!("matches deny pattern {:?}", pattern.as_str()));
+        }
+        if !self.scripts.is_empty() && is_unexpected_script(text, &self.scripts) {
+            return Some("written in an unexpected writing system".to_string());
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_text_breaking_a_rule() {
+        let check = PostCheck::new(&PostCheckConfig {
+            max_chars: 40,
+            deny_patterns: vec!["(?i)^sure[,!]".to_string(), "(".to_string()],
+            scripts: vec![WritingScript::Latin],
+        });
+
+        assert_eq!(check.violation("Ship it on Friday."), None);
+        assert!(check
+            .violation("Sure! Here is the transcript.")
+            .is_some_and(|reason| reason.contains("deny pattern")));
+        assert!(check
+            .violation("Это расшифровка аудио")
+            .is_some_and(|reason| reason.contains("writing system")));
+        assert!(check.violation(&"word ".repeat(20)).is_some());
+        assert_eq!(PostCheck::default().violation(&"word ".repeat(20)), None);
+    }
+}
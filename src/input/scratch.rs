@@ -0,0 +1,103 @@
+//! "Scratch that" / "delete that" dictation commands. Said mid-utterance the
+//! command drops the sentence before it; said on its own it asks the
+//! injector to erase the previous utterance.
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+static SCRATCH_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(?:scratch|delete) that\b[.!?,]*").expect("valid scratch command regex")
+});
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ScratchOutcome {
+    /// Text left after removing commands and the sentences they cancel.
+    pub text: String,
+    /// The command opened the utterance, so the previous injection should go.
+    pub erase_previous: bool,
+}
+
+pub fn apply(input: &str) -> ScratchOutcome {
+    let mut kept = String::new();
+    let mut erase_previous = false;
+    let mut last_end = 0;
+
+    for found in SCRATCH_REGEX.find_iter(input) {
+        let before = &input[last_end..found.start()];
+        let before = if kept.is_empty() {
+            before
+        } else {
+            before.trim_start()
+        };
+        let after = &input[found.end()..];
+
+        // Only treat it as a command when it stands as its own clause;
+        // "you should delete that file" is prose.
+        let pending = format!("{kept}{before}");
+        let opens_clause = pending
+            .trim_end()
+            .chars()
+            .last()
+            .is_none_or(|ch| matches!(ch, '.' | '!' | '?' | ','));
+        let closes_clause =
+            found.as_str().ends_with(['.', '!', '?', ',']) || after.trim().is_empty();
+        if !opens_clause || !closes_clause {
+            continue;
+        }
+
+        let sentence_body = pending.trim_end().trim_end_matches(',');
+        if sentence_body.trim().is_empty() {
+            erase_previous = true;
+            kept.clear();
+        } else {
+            kept = drop_last_sentence(sentence_body);
+        }
+        last_end = found.end();
+    }
+
+    let rest = &input[last_end..];
+    kept.push_str(if kept.is_empty() {
+        rest
+    } else {
+        rest.trim_start()
+    });
+    ScratchOutcome {
+        text: kept.trim().to_string(),
+        erase_previous,
+    }
+}
+
+fn drop_last_sentence(text: &str) -> String {
+    let body = text.trim_end_matches(['.', '!', '?']);
+    match body.rfind(['.', '!', '?']) {
+        Some(index) => format!("{} ", &body[..=index]),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_the_sentence_before_the_command() {
+        let outcome =
+            apply("Lunch is at noon. I went to the store. Scratch that. I went to the mall.");
+        assert_eq!(outcome.text, "Lunch is at noon. I went to the mall.");
+        assert!(!outcome.erase_previous);
+    }
+
+    #[test]
+    fn leading_command_erases_previous_utterance() {
+        let outcome = apply("Scratch that.");
+        assert_eq!(outcome.text, "");
+        assert!(outcome.erase_previous);
+    }
+
+    #[test]
+    fn ignores_prose_usage() {
+        let outcome = apply("You should delete that file first.");
+        assert_eq!(outcome.text, "You should delete that file first.");
+        assert!(!outcome.erase_previous);
+    }
+}
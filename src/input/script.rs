@@ -0,0 +1,75 @@
+//! User-supplied transformation stage (`postprocess.script`) evaluated with
+//! the embedded Rhai engine. The script sees `text` and `window_class` in
+//! scope and either evaluates to the new text or reassigns `text`.
+
+#[cfg(feature = "scripting")]
+mod engine {
+    use rhai::{Engine, Scope, AST};
+    use tracing::warn;
+
+    /// Upper bound on script work per transcript so a runaway loop can't
+    /// stall injection.
+    const MAX_OPERATIONS: u64 = 200_000;
+
+    pub struct ScriptStage {
+        engine: Engine,
+        ast: AST,
+    }
+
+    impl ScriptStage {
+        pub fn new(source: &str) -> Option<Self> {
+            let mut engine = Engine::new();
+            engine.set_max_operations(MAX_OPERATIONS);
+
+            match engine.compile(source) {
+                Ok(ast) => Some(Self { engine, ast }),
+                Err(err) => {
+                    warn!("postprocess.script failed to compile: {err}");
+                    None
+                }
+            }
+        }
+
+        pub fn apply(&self, text: &str, window_class: Option<&str>) -> String {
+            let mut scope = Scope::new();
+            scope.push("text", text.to_string());
+            scope.push("window_class", window_class.unwrap_or_default().to_string());
+
+            match self
+                .engine
+                .eval_ast_with_scope::<rhai::Dynamic>(&mut scope, &self.ast)
+            {
+                Ok(result) if result.is_string() => {
+                    result.into_string().unwrap_or_else(|_| text.to_string())
+                }
+                Ok(_) => scope
+                    .get_value::<String>("text")
+                    .unwrap_or_else(|| text.to_string()),
+                Err(err) => {
+                    warn!("postprocess.script failed: {err}");
+                    text.to_string()
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "scripting"))]
+mod engine {
+    use tracing::warn;
+
+    pub struct ScriptStage;
+
+    impl ScriptStage {
+        pub fn new(_source: &str) -> Option<Self> {
+            warn!("postprocess.script is set but this build lacks the `scripting` feature; ignoring it");
+            None
+        }
+
+        pub fn apply(&self, text: &str, _window_class: Option<&str>) -> String {
+            text.to_string()
+        }
+    }
+}
+
+pub use engine::ScriptStage;
@@ -8,13 +8,16 @@ use std::sync::{
     Arc,
 };
 use std::time::{Duration, Instant};
-use tokio::sync::mpsc;
+use tokio::sync::mpsc::{self, error::TrySendError};
 use tracing::{debug, error, info, warn};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ShortcutKind {
     Hold,
     Press,
+    Cancel,
+    /// Cycles the dictation mode.
+    Mode,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -30,17 +33,159 @@ pub struct ShortcutEvent {
     pub phase: ShortcutPhase,
 }
 
+/// Buttons that live on pointer devices rather than keyboards.
+const MOUSE_BUTTONS: &[Key] = &[
+    Key::BTN_SIDE,
+    Key::BTN_EXTRA,
+    Key::BTN_MIDDLE,
+    Key::BTN_FORWARD,
+    Key::BTN_BACK,
+];
+
+/// Synthetic key repeat (release/press pairs emitted by some keyboards and
+/// remappers instead of evdev value 2) re-presses within this window.
+const REPEAT_RELEASE_GAP: Duration = Duration::from_millis(50);
+
+/// How long a listener waits between attempts to hand an event to a busy app.
+const SEND_RETRY_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Hands events to the app without dropping the ones that matter. Hold and
+/// cancel events (above all hold releases) wait for room in the channel;
+/// toggle presses made while the app is backed up collapse into the latest
+/// one, which is retried on every poll.
+struct EventSender {
+    tx: mpsc::Sender<ShortcutEvent>,
+    pending_toggle: Option<ShortcutEvent>,
+}
+
+impl EventSender {
+    fn new(tx: mpsc::Sender<ShortcutEvent>) -> Self {
+        Self {
+            tx,
+            pending_toggle: None,
+        }
+    }
+
+    fn send(&mut self, event: ShortcutEvent, stop: &AtomicBool) {
+        if event.kind == ShortcutKind::Press {
+            if self.pending_toggle.replace(event).is_some() {
+                debug!("App busy; coalesced toggle shortcut presses");
+            }
+            self.flush();
+            return;
+        }
+
+        let mut event = event;
+        loop {
+            match self.tx.try_send(event) {
+                Ok(()) => return,
+                Err(TrySendError::Full(returned)) => {
+                    // Stopping joins this thread from the app, which then
+                    // isn't draining the channel.
+                    if stop.load(Ordering::Relaxed) {
+                        return;
+                    }
+                    event = returned;
+                    std::thread::sleep(SEND_RETRY_INTERVAL);
+                }
+                Err(TrySendError::Closed(_)) => {
+                    warn!("Shortcut event dropped; the app is no longer listening");
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Retries a toggle press that found the channel full.
+    fn flush(&mut self) {
+        let Some(event) = self.pending_toggle.take() else {
+            return;
+        };
+        match self.tx.try_send(event) {
+            Ok(()) => {}
+            Err(TrySendError::Full(event)) => self.pending_toggle = Some(event),
+            Err(TrySendError::Closed(_)) => {
+                warn!("Shortcut event dropped; the app is no longer listening");
+            }
+        }
+    }
+}
+
+/// Timing rules applied to press shortcuts; hold shortcuts ignore them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PressBehavior {
+    pub debounce: Duration,
+    pub suppress_repeat: bool,
+}
+
+impl Default for PressBehavior {
+    fn default() -> Self {
+        Self {
+            debounce: Duration::from_millis(300),
+            suppress_repeat: true,
+        }
+    }
+}
+
+/// Tells single press-shortcut taps from double ones: each tap is held back
+/// for the double-press window in case a second one follows.
+#[derive(Debug, Default)]
+pub struct DoublePress {
+    pending: Option<Instant>,
+}
+
+/// What a tap turned out to complete.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tap {
+    /// Second tap within the window.
+    Double,
+    /// The previous tap, whose window had passed; the new one is held back.
+    Single(Instant),
+}
+
+impl DoublePress {
+    /// Records a tap at `at`; `None` while it waits for a second one.
+    pub fn tap(&mut self, at: Instant, window: Duration) -> Option<Tap> {
+        match self.pending.take() {
+            Some(first) if at.saturating_duration_since(first) <= window => Some(Tap::Double),
+            earlier => {
+                self.pending = Some(at);
+                earlier.map(Tap::Single)
+            }
+        }
+    }
+
+    /// When the held-back tap becomes a single press.
+    pub fn deadline(&self, window: Duration) -> Option<Instant> {
+        self.pending.map(|pressed| pressed + window)
+    }
+
+    /// Releases the held-back tap once its window has passed.
+    pub fn expire(&mut self) -> Option<Instant> {
+        self.pending.take()
+    }
+}
+
 pub struct GlobalShortcuts {
     devices: Vec<Device>,
     target_keys: HashSet<Key>,
     shortcut_name: String,
     kind: ShortcutKind,
+    behavior: PressBehavior,
 }
 
 impl GlobalShortcuts {
-    pub fn new(shortcut: &str, kind: ShortcutKind) -> Result<Self> {
+    pub fn new(shortcut: &str, kind: ShortcutKind, behavior: PressBehavior) -> Result<Self> {
         let target_keys = Self::parse_shortcut(shortcut)?;
-        let devices = Self::find_keyboard_devices()?;
+        let mut devices = Self::find_keyboard_devices()?;
+        let buttons: Vec<Key> = target_keys
+            .iter()
+            .copied()
+            .filter(|key| MOUSE_BUTTONS.contains(key))
+            .collect();
+        if !buttons.is_empty() {
+            devices.extend(Self::find_pointer_devices(&buttons));
+        }
 
         if devices.is_empty() {
             return Err(anyhow::anyhow!("No keyboard devices found"));
@@ -49,6 +194,8 @@ impl GlobalShortcuts {
         let mode_label = match kind {
             ShortcutKind::Hold => "hold",
             ShortcutKind::Press => "press",
+            ShortcutKind::Cancel => "cancel",
+            ShortcutKind::Mode => "mode",
         };
 
         info!(
@@ -64,18 +211,24 @@ impl GlobalShortcuts {
             target_keys,
             shortcut_name: shortcut.to_string(),
             kind,
+            behavior,
         })
     }
 
     pub fn run(mut self, tx: mpsc::Sender<ShortcutEvent>, stop: Arc<AtomicBool>) -> Result<()> {
+        let mut sender = EventSender::new(tx);
         let mut pressed_keys: HashSet<Key> = HashSet::new();
         let mut last_trigger = Instant::now() - Duration::from_secs(10);
-        let debounce_duration = Duration::from_millis(500);
+        let debounce_duration = self.behavior.debounce;
+        let suppress_repeat = self.behavior.suppress_repeat;
+        let mut released_at: Option<Instant> = None;
         let mut combination_active = false;
 
         let listen_label = match self.kind {
             ShortcutKind::Hold => "hold",
             ShortcutKind::Press => "press",
+            ShortcutKind::Cancel => "cancel",
+            ShortcutKind::Mode => "mode",
         };
         info!(
             "🎯 Listening for {} shortcut: {}",
@@ -87,6 +240,7 @@ impl GlobalShortcuts {
                 info!("Stopping shortcut listener: {}", self.shortcut_name);
                 break 'outer;
             }
+            sender.flush();
             // Check each device
             let target_keys = &self.target_keys;
             let shortcut_name = &self.shortcut_name;
@@ -117,16 +271,29 @@ impl GlobalShortcuts {
                                             {
                                                 let now = Instant::now();
 
+                                                let is_repeat = suppress_repeat
+                                                    && !matches!(self.kind, ShortcutKind::Hold)
+                                                    && released_at.is_some_and(|at| {
+                                                        now.duration_since(at) < REPEAT_RELEASE_GAP
+                                                    });
+
                                                 // Debounce: only trigger if enough time has passed
                                                 let should_trigger = match self.kind {
                                                     ShortcutKind::Hold => true,
-                                                    ShortcutKind::Press => {
+                                                    ShortcutKind::Press
+                                                    | ShortcutKind::Cancel
+                                                    | ShortcutKind::Mode => {
                                                         now.duration_since(last_trigger)
                                                             > debounce_duration
                                                     }
                                                 };
 
-                                                if should_trigger {
+                                                if is_repeat {
+                                                    // Treat the combination as still held so the
+                                                    // whole repeat burst stays suppressed.
+                                                    debug!("Shortcut suppressed (key repeat)");
+                                                    combination_active = true;
+                                                } else if should_trigger {
                                                     debug!(
                                                         "✓ Combination active: {:?}",
                                                         target_keys
@@ -138,16 +305,14 @@ impl GlobalShortcuts {
                                                     last_trigger = now;
                                                     combination_active = true;
 
-                                                    if let Err(e) = tx.try_send(ShortcutEvent {
-                                                        triggered_at: now,
-                                                        kind: self.kind,
-                                                        phase: ShortcutPhase::Start,
-                                                    }) {
-                                                        warn!(
-                                                            "Failed to send shortcut event: {}",
-                                                            e
-                                                        );
-                                                    }
+                                                    sender.send(
+                                                        ShortcutEvent {
+                                                            triggered_at: now,
+                                                            kind: self.kind,
+                                                            phase: ShortcutPhase::Start,
+                                                        },
+                                                        &stop,
+                                                    );
                                                 } else {
                                                     debug!("Shortcut debounced (too soon)");
                                                 }
@@ -165,21 +330,22 @@ impl GlobalShortcuts {
                                                     key
                                                 );
                                                 combination_active = false;
+                                                released_at = Some(Instant::now());
 
                                                 if matches!(self.kind, ShortcutKind::Hold) {
-                                                    if let Err(e) = tx.try_send(ShortcutEvent {
-                                                        triggered_at: Instant::now(),
-                                                        kind: self.kind,
-                                                        phase: ShortcutPhase::End,
-                                                    }) {
-                                                        warn!(
-                                                            "Failed to send shortcut release event: {}",
-                                                            e
-                                                        );
-                                                    }
+                                                    sender.send(
+                                                        ShortcutEvent {
+                                                            triggered_at: Instant::now(),
+                                                            kind: self.kind,
+                                                            phase: ShortcutPhase::End,
+                                                        },
+                                                        &stop,
+                                                    );
                                                 }
                                             }
                                         }
+                                        // Autorepeat (value 2) never re-triggers; the
+                                        // combination is already active.
                                         _ => {}
                                     }
                                 }
@@ -303,6 +469,13 @@ impl GlobalShortcuts {
             "LEFT" => Ok(Key::KEY_LEFT),
             "RIGHT" => Ok(Key::KEY_RIGHT),
 
+            // Mouse buttons (thumb buttons are SIDE/EXTRA on most mice)
+            "MOUSE_SIDE" | "BTN_SIDE" => Ok(Key::BTN_SIDE),
+            "MOUSE_EXTRA" | "BTN_EXTRA" => Ok(Key::BTN_EXTRA),
+            "MOUSE_MIDDLE" | "BTN_MIDDLE" => Ok(Key::BTN_MIDDLE),
+            "MOUSE_FORWARD" | "BTN_FORWARD" => Ok(Key::BTN_FORWARD),
+            "MOUSE_BACK" | "BTN_BACK" => Ok(Key::BTN_BACK),
+
             _ => Err(anyhow::anyhow!("Unknown key: {}", key_str)),
         }
     }
@@ -337,6 +510,36 @@ impl GlobalShortcuts {
         Ok(keyboards)
     }
 
+    /// Mice (and trackballs) reporting one of `buttons`. Their other events
+    /// are ignored, so pointer motion never reaches the shortcut logic.
+    fn find_pointer_devices(buttons: &[Key]) -> Vec<Device> {
+        let mut pointers = Vec::new();
+
+        for (path, device) in evdev::enumerate() {
+            let Some(keys) = device.supported_keys() else {
+                continue;
+            };
+            if keys.contains(Key::KEY_A) || !buttons.iter().any(|button| keys.contains(*button)) {
+                continue;
+            }
+            if let Err(err) = set_device_nonblocking(&device) {
+                warn!("Failed to set non-blocking mode for {:?}: {}", path, err);
+            }
+            let name = device.name().unwrap_or("Unknown");
+            info!("Found pointer device: {} at {:?}", name, path);
+            pointers.push(device);
+        }
+
+        if pointers.is_empty() {
+            warn!(
+                "No mouse reports {:?}; check /dev/input permissions",
+                buttons
+            );
+        }
+
+        pointers
+    }
+
     pub fn list_available_keyboards() -> Result<Vec<(PathBuf, String)>> {
         let mut keyboards = Vec::new();
 
@@ -378,3 +581,85 @@ fn set_device_nonblocking(device: &Device) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(kind: ShortcutKind, phase: ShortcutPhase) -> ShortcutEvent {
+        ShortcutEvent {
+            triggered_at: Instant::now(),
+            kind,
+            phase,
+        }
+    }
+
+    #[test]
+    fn taps_within_the_window_are_a_double_press() {
+        let window = Duration::from_millis(400);
+        let start = Instant::now();
+        let mut taps = DoublePress::default();
+
+        assert_eq!(taps.tap(start, window), None);
+        assert_eq!(taps.deadline(window), Some(start + window));
+        assert_eq!(
+            taps.tap(start + Duration::from_millis(250), window),
+            Some(Tap::Double)
+        );
+        assert_eq!(taps.deadline(window), None);
+        assert_eq!(taps.expire(), None);
+    }
+
+    #[test]
+    fn a_lone_tap_is_a_single_press_once_the_window_passes() {
+        let window = Duration::from_millis(400);
+        let start = Instant::now();
+        let mut taps = DoublePress::default();
+
+        assert_eq!(taps.tap(start, window), None);
+        assert_eq!(taps.expire(), Some(start));
+        assert_eq!(taps.expire(), None);
+
+        // A late second tap releases the first and waits in its place.
+        let late = start + Duration::from_secs(1);
+        assert_eq!(taps.tap(start, window), None);
+        assert_eq!(taps.tap(late, window), Some(Tap::Single(start)));
+        assert_eq!(taps.expire(), Some(late));
+    }
+
+    #[test]
+    fn toggles_coalesce_while_the_app_is_busy() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let mut sender = EventSender::new(tx);
+        let stop = AtomicBool::new(false);
+
+        sender.send(event(ShortcutKind::Press, ShortcutPhase::Start), &stop);
+        assert!(sender.pending_toggle.is_none());
+
+        sender.send(event(ShortcutKind::Press, ShortcutPhase::Start), &stop);
+        let latest = event(ShortcutKind::Press, ShortcutPhase::Start);
+        let latest_at = latest.triggered_at;
+        sender.send(latest, &stop);
+        assert_eq!(
+            sender
+                .pending_toggle
+                .as_ref()
+                .map(|event| event.triggered_at),
+            Some(latest_at)
+        );
+
+        rx.try_recv().unwrap();
+        sender.flush();
+        assert!(sender.pending_toggle.is_none());
+        assert_eq!(rx.try_recv().unwrap().triggered_at, latest_at);
+
+        // A release is retried until it fits, not dropped.
+        sender.send(event(ShortcutKind::Hold, ShortcutPhase::Start), &stop);
+        let release = std::thread::spawn(move || {
+            sender.send(event(ShortcutKind::Hold, ShortcutPhase::End), &stop);
+        });
+        assert_eq!(rx.blocking_recv().unwrap().phase, ShortcutPhase::Start);
+        release.join().unwrap();
+        assert_eq!(rx.try_recv().unwrap().phase, ShortcutPhase::End);
+    }
+}
@@ -0,0 +1,126 @@
+//! Minimal client for Sway's i3-compatible IPC (`$SWAYSOCK`), used to find
+//! the focused window outside Hyprland. Sway has no command for sending
+//! keys to a window, so pastes still go through the virtual keyboard; this
+//! only feeds the window class to the shift-paste rules and profiles.
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use std::env;
+use std::path::PathBuf;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
+use tracing::debug;
+
+const MAGIC: &[u8; 6] = b"i3-ipc";
+const GET_TREE: u32 = 4;
+
+#[derive(Debug, Clone)]
+pub struct SwayIpc {
+    socket_path: PathBuf,
+}
+
+impl SwayIpc {
+    pub fn from_env() -> Option<Self> {
+        let socket_path = PathBuf::from(env::var_os("SWAYSOCK")?);
+        socket_path.exists().then_some(Self { socket_path })
+    }
+
+    async fn request(&self, kind: u32, payload: &[u8]) -> Result<Vec<u8>> {
+        let mut stream = UnixStream::connect(&self.socket_path)
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to connect to Sway socket at {}",
+                    self.socket_path.display()
+                )
+            })?;
+
+        let mut message = Vec::with_capacity(14 + payload.len());
+        message.extend_from_slice(MAGIC);
+        message.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+        message.extend_from_slice(&kind.to_ne_bytes());
+        message.extend_from_slice(payload);
+        stream
+            .write_all(&message)
+            .await
+            .context("Failed to send Sway IPC message")?;
+
+        let mut header = [0u8; 14];
+        stream
+            .read_exact(&mut header)
+            .await
+            .context("Failed to read Sway IPC reply header")?;
+        if &header[..6] != MAGIC {
+            bail!("Unexpected Sway IPC reply");
+        }
+        let len = u32::from_ne_bytes(header[6..10].try_into().expect("four bytes"));
+        let mut reply = vec![0u8; len as usize];
+        stream
+            .read_exact(&mut reply)
+            .await
+            .context("Failed to read Sway IPC reply")?;
+        Ok(reply)
+    }
+
+    /// `app_id` of the focused window, or its X11 class under XWayland.
+    pub async fn focused_app_id(&self) -> Result<Option<String>> {
+        let reply = self.request(GET_TREE, &[]).await?;
+        let tree: Value = serde_json::from_slice(&reply).context("Failed to parse Sway tree")?;
+        let app_id = focused_app_id(&tree);
+        debug!(app_id = app_id.as_deref(), "Sway focused window");
+        Ok(app_id)
+    }
+}
+
+/// Class of the focused window when Sway is running and reachable.
+pub async fn focused_window_class() -> Option<String> {
+    let ipc = SwayIpc::from_env()?;
+    ipc.focused_app_id().await.unwrap_or_else(|err| {
+        debug!("Failed to query the Sway tree: {err:#}");
+        None
+    })
+}
+
+/// Walks the layout tree down to the focused leaf.
+fn focused_app_id(node: &Value) -> Option<String> {
+    if node["focused"].as_bool() == Some(true) {
+        return node["app_id"]
+            .as_str()
+            .or_else(|| node["window_properties"]["class"].as_str())
+            .filter(|id| !id.is_empty())
+            .map(str::to_string);
+    }
+
+    ["nodes", "floating_nodes"]
+        .iter()
+        .filter_map(|key| node[key].as_array())
+        .flatten()
+        .find_map(focused_app_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn finds_the_focused_window() {
+        let tree = json!({
+            "focused": false,
+            "nodes": [{
+                "focused": false,
+                "nodes": [
+                    { "focused": false, "app_id": "firefox", "nodes": [] },
+                    { "focused": false, "nodes": [], "floating_nodes": [
+                        { "focused": true, "app_id": null, "window_properties": { "class": "Steam" } }
+                    ] }
+                ]
+            }]
+        });
+        assert_eq!(focused_app_id(&tree).as_deref(), Some("Steam"));
+
+        let tree = json!({ "focused": false, "nodes": [{ "focused": true, "app_id": "foot" }] });
+        assert_eq!(focused_app_id(&tree).as_deref(), Some("foot"));
+        assert_eq!(focused_app_id(&json!({ "focused": false })), None);
+    }
+}
@@ -0,0 +1,152 @@
+//! Locale-aware typography: curly quotes and apostrophes, German „…“
+//! quotes, and French non-breaking spacing before high punctuation.
+
+const NBSP: char = '\u{00A0}';
+const NARROW_NBSP: char = '\u{202F}';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    English,
+    French,
+    German,
+}
+
+impl Locale {
+    /// Maps a language code (`fr`, `de-AT`, `en_US`) onto a typography
+    /// locale, defaulting to English conventions.
+    pub fn from_language(language: &str) -> Self {
+        let primary = language
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match primary.as_str() {
+            "fr" => Self::French,
+            "de" => Self::German,
+            _ => Self::English,
+        }
+    }
+
+    fn quotes(self) -> (&'static str, &'static str) {
+        match self {
+            Self::English => ("\u{201C}", "\u{201D}"),
+            Self::German => ("\u{201E}", "\u{201C}"),
+            Self::French => ("\u{00AB}\u{00A0}", "\u{00A0}\u{00BB}"),
+        }
+    }
+}
+
+/// Applies the locale's conventions, returning the text and the number of
+/// characters changed or inserted.
+pub fn apply(input: &str, locale: Locale, smart_quotes: bool) -> (String, usize) {
+    let mut count = 0;
+    let mut text = input.to_string();
+
+    if smart_quotes {
+        let (quoted, quote_count) = curl_quotes(&text, locale);
+        text = quoted;
+        count += quote_count;
+    }
+
+    if locale == Locale::French {
+        let (spaced, space_count) = french_spacing(&text);
+        text = spaced;
+        count += space_count;
+    }
+
+    (text, count)
+}
+
+fn curl_quotes(input: &str, locale: Locale) -> (String, usize) {
+    let (open, close) = locale.quotes();
+    let chars: Vec<char> = input.chars().collect();
+    let mut result = String::with_capacity(input.len());
+    let mut count = 0;
+
+    for (index, &ch) in chars.iter().enumerate() {
+        let previous = index.checked_sub(1).map(|i| chars[i]);
+        let next = chars.get(index + 1).copied();
+        let opens = previous.is_none_or(|p| p.is_whitespace() || "([{".contains(p));
+
+        match ch {
+            '"' => {
+                result.push_str(if opens { open } else { close });
+                count += 1;
+            }
+            '\'' if previous.is_some_and(char::is_alphanumeric)
+                || next.is_some_and(|n| n.is_ascii_digit()) =>
+            {
+                // Apostrophe (don't, rock 'n' roll, '90s).
+                result.push('\u{2019}');
+                count += 1;
+            }
+            '\'' if opens => {
+                result.push('\u{2018}');
+                count += 1;
+            }
+            '\'' => {
+                result.push('\u{2019}');
+                count += 1;
+            }
+            _ => result.push(ch),
+        }
+    }
+
+    (result, count)
+}
+
+fn french_spacing(input: &str) -> (String, usize) {
+    let chars: Vec<char> = input.chars().collect();
+    let mut result = String::with_capacity(input.len());
+    let mut count = 0;
+
+    for (index, &ch) in chars.iter().enumerate() {
+        let space = match ch {
+            '?' | '!' | ';' => Some(NARROW_NBSP),
+            // Leave `https://` and `12:30` alone.
+            ':' if chars.get(index + 1).is_none_or(|next| next.is_whitespace()) => Some(NBSP),
+            _ => None,
+        };
+
+        if let Some(space) = space {
+            let previous = result.chars().last();
+            if previous == Some(' ') {
+                result.pop();
+                result.push(space);
+                count += 1;
+            } else if previous.is_some_and(|p| !p.is_whitespace() && !"?!;:".contains(p)) {
+                result.push(space);
+                count += 1;
+            }
+        }
+
+        result.push(ch);
+    }
+
+    (result, count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curls_quotes_per_locale() {
+        assert_eq!(
+            apply(r#"She said "don't" twice"#, Locale::English, true).0,
+            "She said \u{201C}don\u{2019}t\u{201D} twice"
+        );
+        assert_eq!(
+            apply(r#"Er sagte "Hallo""#, Locale::German, true).0,
+            "Er sagte \u{201E}Hallo\u{201C}"
+        );
+    }
+
+    #[test]
+    fn french_spacing_before_high_punctuation() {
+        assert_eq!(
+            apply("Vraiment? Oui : voir https://a.fr", Locale::French, false).0,
+            "Vraiment\u{202F}? Oui\u{00A0}: voir https://a.fr"
+        );
+    }
+}
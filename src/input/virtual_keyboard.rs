@@ -0,0 +1,64 @@
+//! Wayland virtual keyboard client (wrtype). Builds without the `wrtype`
+//! feature get a stand-in that never connects, so pastes move on to the
+//! next backend.
+
+#[cfg(feature = "wrtype")]
+mod client {
+    use crate::config::{PasteKeys, PasteModifier};
+    use anyhow::Result;
+    use wrtype::{Modifier, WrtypeClient};
+
+    pub struct VirtualKeyboard {
+        client: WrtypeClient,
+    }
+
+    impl VirtualKeyboard {
+        pub fn new() -> Result<Self> {
+            Ok(Self {
+                client: WrtypeClient::new()?,
+            })
+        }
+
+        pub fn send_keys(&mut self, keys: &PasteKeys) -> Result<()> {
+            let modifiers: Vec<Modifier> = keys
+                .modifiers
+                .iter()
+                .map(|modifier| match modifier {
+                    PasteModifier::Ctrl => Modifier::Ctrl,
+                    PasteModifier::Shift => Modifier::Shift,
+                    PasteModifier::Alt => Modifier::Alt,
+                    PasteModifier::Super => Modifier::Logo,
+                })
+                .collect();
+            self.client.send_shortcut(&modifiers, &keys.key)
+        }
+
+        pub fn type_text(&mut self, text: &str) -> Result<()> {
+            self.client.type_text(text)
+        }
+    }
+}
+
+#[cfg(not(feature = "wrtype"))]
+mod client {
+    use crate::config::PasteKeys;
+    use anyhow::{bail, Result};
+
+    pub struct VirtualKeyboard;
+
+    impl VirtualKeyboard {
+        pub fn new() -> Result<Self> {
+            bail!("this build lacks the `wrtype` feature")
+        }
+
+        pub fn send_keys(&mut self, _keys: &PasteKeys) -> Result<()> {
+            bail!("this build lacks the `wrtype` feature")
+        }
+
+        pub fn type_text(&mut self, _text: &str) -> Result<()> {
+            bail!("this build lacks the `wrtype` feature")
+        }
+    }
+}
+
+pub use client::VirtualKeyboard;
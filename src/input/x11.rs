@@ -0,0 +1,195 @@
+//! Paste backend for X11 sessions (i3 and friends): synthesizes the paste
+//! shortcut through the XTEST extension and reads the focused window's class
+//! from `_NET_ACTIVE_WINDOW`, so terminals still get Ctrl+Shift+V.
+
+use crate::config::{PasteKeys, PasteModifier};
+use anyhow::{anyhow, Context, Result};
+use std::env;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{
+    AtomEnum, ConnectionExt as _, Keycode, Window, KEY_PRESS_EVENT, KEY_RELEASE_EVENT,
+};
+use x11rb::protocol::xtest::ConnectionExt as _;
+use x11rb::rust_connection::RustConnection;
+use x11rb::wrapper::ConnectionExt as _;
+
+const XK_CONTROL_L: u32 = 0xffe3;
+const XK_SHIFT_L: u32 = 0xffe1;
+const XK_ALT_L: u32 = 0xffe9;
+const XK_SUPER_L: u32 = 0xffeb;
+const XK_BACKSPACE: u32 = 0xff08;
+
+pub struct X11Keyboard {
+    conn: RustConnection,
+    root: Window,
+    active_window_atom: u32,
+    min_keycode: Keycode,
+    keysyms_per_keycode: usize,
+    keysyms: Vec<u32>,
+}
+
+impl X11Keyboard {
+    /// Only for X11 sessions: `DISPLAY` set without `WAYLAND_DISPLAY`.
+    pub fn from_env() -> Option<Result<Self>> {
+        if env::var_os("WAYLAND_DISPLAY").is_some() || env::var_os("DISPLAY").is_none() {
+            return None;
+        }
+        Some(Self::connect())
+    }
+
+    fn connect() -> Result<Self> {
+        let (conn, screen) = RustConnection::connect(None).context("Failed to connect to X11")?;
+        conn.xtest_get_version(2, 2)
+            .context("Failed to query XTEST")?
+            .reply()
+            .context("X server lacks the XTEST extension")?;
+        let root = conn.setup().roots[screen].root;
+        let active_window_atom = conn
+            .intern_atom(false, b"_NET_ACTIVE_WINDOW")?
+            .reply()
+            .context("Failed to intern _NET_ACTIVE_WINDOW")?
+            .atom;
+
+        let min_keycode = conn.setup().min_keycode;
+        let max_keycode = conn.setup().max_keycode;
+        let mapping = conn
+            .get_keyboard_mapping(min_keycode, max_keycode - min_keycode + 1)?
+            .reply()
+            .context("Failed to read the X11 keyboard mapping")?;
+
+        Ok(Self {
+            conn,
+            root,
+            active_window_atom,
+            min_keycode,
+            keysyms_per_keycode: usize::from(mapping.keysyms_per_keycode.max(1)),
+            keysyms: mapping.keysyms,
+        })
+    }
+
+    fn keycode(&self, keysym: u32) -> Result<Keycode> {
+        self.keysyms
+            .chunks(self.keysyms_per_keycode)
+            .position(|syms| syms.contains(&keysym))
+            .map(|index| self.min_keycode + index as u8)
+            .ok_or_else(|| anyhow!("No keycode for keysym {keysym:#x}"))
+    }
+
+    pub fn send_keys(&self, keys: &PasteKeys) -> Result<()> {
+        let modifiers = keys
+            .modifiers
+            .iter()
+            .map(|modifier| {
+                self.keycode(match modifier {
+                    PasteModifier::Ctrl => XK_CONTROL_L,
+                    PasteModifier::Shift => XK_SHIFT_L,
+                    PasteModifier::Alt => XK_ALT_L,
+                    PasteModifier::Super => XK_SUPER_L,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let keysym =
+            keysym_for(&keys.key).ok_or_else(|| anyhow!("No X11 keysym for key {:?}", keys.key))?;
+        self.chord(&modifiers, self.keycode(keysym)?)
+    }
+
+    pub fn backspace(&self) -> Result<()> {
+        self.chord(&[], self.keycode(XK_BACKSPACE)?)
+    }
+
+    fn chord(&self, modifiers: &[Keycode], key: Keycode) -> Result<()> {
+        for &modifier in modifiers {
+            self.fake(KEY_PRESS_EVENT, modifier)?;
+        }
+        self.fake(KEY_PRESS_EVENT, key)?;
+        self.fake(KEY_RELEASE_EVENT, key)?;
+        for &modifier in modifiers.iter().rev() {
+            self.fake(KEY_RELEASE_EVENT, modifier)?;
+        }
+        self.conn.sync().context("Failed to flush XTEST input")?;
+        Ok(())
+    }
+
+    fn fake(&self, event: u8, keycode: Keycode) -> Result<()> {
+        self.conn
+            .xtest_fake_input(event, keycode, 0, self.root, 0, 0, 0)
+            .context("Failed to send XTEST input")?;
+        Ok(())
+    }
+
+    /// `WM_CLASS` class of the focused window, if the window manager
+    /// publishes `_NET_ACTIVE_WINDOW`.
+    pub fn active_window_class(&self) -> Result<Option<String>> {
+        let active = self
+            .conn
+            .get_property(
+                false,
+                self.root,
+                self.active_window_atom,
+                AtomEnum::WINDOW,
+                0,
+                1,
+            )?
+            .reply()
+            .context("Failed to read _NET_ACTIVE_WINDOW")?;
+        let Some(window) = active.value32().and_then(|mut ids| ids.next()) else {
+            return Ok(None);
+        };
+        if window == x11rb::NONE {
+            return Ok(None);
+        }
+
+        let class = self
+            .conn
+            .get_property(false, window, AtomEnum::WM_CLASS, AtomEnum::STRING, 0, 256)?
+            .reply()
+            .context("Failed to read WM_CLASS")?;
+        Ok(parse_wm_class(&class.value))
+    }
+}
+
+/// Latin-1 characters share their keysym value; a few named keys for paste
+/// bindings such as Shift+Insert.
+fn keysym_for(key: &str) -> Option<u32> {
+    match key {
+        "insert" => Some(0xff63),
+        "return" | "enter" => Some(0xff0d),
+        "tab" => Some(0xff09),
+        _ => {
+            let mut chars = key.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if u32::from(c) <= 0xff => Some(u32::from(c)),
+                _ => None,
+            }
+        }
+    }
+}
+
+/// `WM_CLASS` holds the instance and class names, each NUL-terminated.
+fn parse_wm_class(value: &[u8]) -> Option<String> {
+    let mut parts = value
+        .split(|&byte| byte == 0)
+        .filter(|part| !part.is_empty());
+    let instance = parts.next()?;
+    let class = parts.next().unwrap_or(instance);
+    Some(String::from_utf8_lossy(class).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wm_class_prefers_the_class_name() {
+        assert_eq!(parse_wm_class(b"urxvt\0URxvt\0").as_deref(), Some("URxvt"));
+        assert_eq!(parse_wm_class(b"xterm\0").as_deref(), Some("xterm"));
+        assert_eq!(parse_wm_class(b""), None);
+    }
+
+    #[test]
+    fn maps_paste_keys_to_keysyms() {
+        assert_eq!(keysym_for("v"), Some(0x76));
+        assert_eq!(keysym_for("insert"), Some(0xff63));
+        assert_eq!(keysym_for("f13"), None);
+    }
+}
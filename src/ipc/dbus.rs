@@ -0,0 +1,183 @@
+//! `org.hyprwhspr.Daemon` on the session bus, so widgets and scripts can
+//! drive recording and follow its progress:
+//!
+//! ```text
+//! busctl --user call org.hyprwhspr.Daemon /org/hyprwhspr/Daemon org.hyprwhspr.Daemon Toggle
+//! ```
+
+use super::IpcCommand;
+use crate::config::{DictationMode, TranscriptionProvider};
+use crate::events::{next_event, AppEvent, EventBus};
+use anyhow::{Context, Result};
+use tokio::sync::mpsc;
+use tracing::warn;
+use zbus::object_server::SignalEmitter;
+use zbus::{connection, fdo, interface, Connection};
+
+pub const BUS_NAME: &str = "org.hyprwhspr.Daemon";
+pub const OBJECT_PATH: &str = "/org/hyprwhspr/Daemon";
+
+struct Daemon {
+    commands: mpsc::Sender<IpcCommand>,
+}
+
+impl Daemon {
+    async fn send(&self, command: IpcCommand) -> fdo::Result<()> {
+        self.commands
+            .send(command)
+            .await
+            .map_err(|_| fdo::Error::Failed("daemon is shutting down".to_string()))
+    }
+}
+
+#[interface(name = "org.hyprwhspr.Daemon")]
+impl Daemon {
+    async fn toggle(&self) -> fdo::Result<()> {
+        self.send(IpcCommand::Toggle).await
+    }
+
+    async fn start(&self) -> fdo::Result<()> {
+        self.send(IpcCommand::Start).await
+    }
+
+    async fn stop(&self) -> fdo::Result<()> {
+        self.send(IpcCommand::Stop).await
+    }
+
+    async fn cancel(&self) -> fdo::Result<()> {
+        self.send(IpcCommand::Cancel).await
+    }
+
+    async fn cycle_mode(&self) -> fdo::Result<()> {
+        self.send(IpcCommand::CycleMode).await
+    }
+
+    /// Records system output instead of the microphone, or stops.
+    async fn toggle_monitor(&self) -> fdo::Result<()> {
+        self.send(IpcCommand::ToggleMonitor).await
+    }
+
+    /// `mode` is "raw", "prose" or "code".
+    async fn set_mode(&self, mode: &str) -> fdo::Result<()> {
+        let mode = DictationMode::from_label(mode)
+            .ok_or_else(|| fdo::Error::InvalidArgs(format!("unknown dictation mode {mode:?}")))?;
+        self.send(IpcCommand::SetMode(mode)).await
+    }
+
+    /// `provider` is "local", "groq", "gemini", "ssh" or "echo"; saved to
+    /// the config file.
+    async fn set_provider(&self, provider: &str) -> fdo::Result<()> {
+        let provider = TranscriptionProvider::from_label(provider)
+            .ok_or_else(|| fdo::Error::InvalidArgs(format!("unknown provider {provider:?}")))?;
+        self.send(IpcCommand::SetProvider(provider)).await
+    }
+
+    /// Types the session's word count and duration at the cursor.
+    async fn insert_stats(&self) -> fdo::Result<()> {
+        self.send(IpcCommand::InsertStats).await
+    }
+
+    /// Stops or resumes pasting transcripts.
+    async fn toggle_read_only(&self) -> fdo::Result<()> {
+        self.send(IpcCommand::ToggleReadOnly).await
+    }
+
+    /// Turns the provider request log on or off; saved to the config file.
+    async fn toggle_request_log(&self) -> fdo::Result<()> {
+        self.send(IpcCommand::ToggleRequestLog).await
+    }
+
+    /// Plays the kept last recording; `speed` is "normal" or "fast" (1.5×).
+    async fn replay_last(&self, speed: &str) -> fdo::Result<()> {
+        let fast = match speed {
+            "normal" => false,
+            "fast" => true,
+            _ => {
+                return Err(fdo::Error::InvalidArgs(format!(
+                    "unknown replay speed {speed:?}"
+                )))
+            }
+        };
+        self.send(IpcCommand::ReplayLast { fast }).await
+    }
+
+    #[zbus(signal)]
+    async fn recording_started(emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn recording_stopped(emitter: &SignalEmitter<'_>, cancelled: bool) -> zbus::Result<()>;
+
+    /// `text` is empty unless `ipc.dbus_transcripts` is on.
+    #[zbus(signal)]
+    async fn transcription_ready(
+        emitter: &SignalEmitter<'_>,
+        chars: u32,
+        backend: &str,
+        text: &str,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn error(emitter: &SignalEmitter<'_>, message: &str) -> zbus::Result<()>;
+}
+
+pub struct DbusService {
+    connection: Connection,
+}
+
+impl DbusService {
+    /// Claims the bus name and forwards method calls to `commands`.
+    pub async fn start(commands: mpsc::Sender<IpcCommand>) -> Result<Self> {
+        let connection = connection::Builder::session()
+            .context("Failed to connect to the session bus")?
+            .name(BUS_NAME)?
+            .serve_at(OBJECT_PATH, Daemon { commands })?
+            .build()
+            .await
+            .with_context(|| format!("Failed to register {BUS_NAME} on the session bus"))?;
+
+        Ok(Self { connection })
+    }
+
+    /// Re-emits the app's events as signals for as long as the bus lives;
+    /// transcripts themselves only go out with `include_text`.
+    pub fn follow(&self, bus: &EventBus, include_text: bool) {
+        let connection = self.connection.clone();
+        let mut events = bus.subscribe();
+        tokio::spawn(async move {
+            while let Some(event) = next_event(&mut events, "D-Bus service").await {
+                let emitter = match SignalEmitter::new(&connection, OBJECT_PATH) {
+                    Ok(emitter) => emitter,
+                    Err(err) => {
+                        warn!("Failed to emit D-Bus signal: {}", err);
+                        continue;
+                    }
+                };
+                let (signal, result) = match &event {
+                    AppEvent::RecordingStarted => (
+                        "RecordingStarted",
+                        Daemon::recording_started(&emitter).await,
+                    ),
+                    AppEvent::RecordingStopped { cancelled } => (
+                        "RecordingStopped",
+                        Daemon::recording_stopped(&emitter, *cancelled).await,
+                    ),
+                    AppEvent::TranscriptReady { text, backend } => {
+                        let chars = u32::try_from(text.chars().count()).unwrap_or(u32::MAX);
+                        let text = if include_text { text.as_str() } else { "" };
+                        (
+                            "TranscriptionReady",
+                            Daemon::transcription_ready(&emitter, chars, backend, text).await,
+                        )
+                    }
+                    AppEvent::Error { message } => {
+                        ("Error", Daemon::error(&emitter, message).await)
+                    }
+                    _ => continue,
+                };
+                if let Err(err) = result {
+                    warn!("Failed to emit {}: {}", signal, err);
+                }
+            }
+        });
+    }
+}
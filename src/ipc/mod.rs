@@ -0,0 +1,34 @@
+pub mod dbus;
+pub mod signals;
+pub mod socket;
+
+pub use dbus::DbusService;
+pub use socket::ControlSocket;
+
+use crate::config::{DictationMode, TranscriptionProvider};
+
+/// Recording control requested by another process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IpcCommand {
+    Toggle,
+    Start,
+    Stop,
+    /// Stop recording and throw the audio away.
+    Cancel,
+    CycleMode,
+    /// Start recording system output, or stop the running recording.
+    ToggleMonitor,
+    SetMode(DictationMode),
+    /// Switch `transcription.provider` and save it to the config file.
+    SetProvider(TranscriptionProvider),
+    /// Insert the session's word count and duration at the cursor.
+    InsertStats,
+    /// Stop or resume pasting transcripts, e.g. around a screen share.
+    ToggleReadOnly,
+    /// Flip `transcription.request_log.enabled` and save it to the config.
+    ToggleRequestLog,
+    /// Play the kept last recording, at 1.5× when `fast`.
+    ReplayLast {
+        fast: bool,
+    },
+}
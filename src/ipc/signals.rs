@@ -0,0 +1,67 @@
+//! `SIGUSR1`/`SIGUSR2` as quick controls, so minimal setups can drive the
+//! daemon from any keybinding system without a client:
+//!
+//! ```text
+//! pkill -USR1 hyprwhspr-rs
+//! ```
+//!
+//! Each signal runs a control socket command from `ipc.signals`. Both are
+//! always caught, since their default action would kill the daemon.
+
+use super::socket::parse_command;
+use super::IpcCommand;
+use crate::config::SignalsConfig;
+use anyhow::{Context, Result};
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+/// Starts forwarding the configured commands to `commands`. A command that
+/// doesn't parse is logged and its signal ignored.
+pub fn listen(config: &SignalsConfig, commands: mpsc::Sender<IpcCommand>) -> Result<()> {
+    for (name, kind, command) in [
+        ("SIGUSR1", SignalKind::user_defined1(), &config.usr1),
+        ("SIGUSR2", SignalKind::user_defined2(), &config.usr2),
+    ] {
+        let command = mapped_command(command).unwrap_or_else(|err| {
+            warn!("Ignoring {name}: invalid ipc.signals command: {err}");
+            None
+        });
+        let mut stream =
+            signal(kind).with_context(|| format!("Failed to install the {name} handler"))?;
+        let commands = commands.clone();
+        tokio::spawn(async move {
+            while stream.recv().await.is_some() {
+                let Some(command) = command else {
+                    debug!("Ignoring {name}");
+                    continue;
+                };
+                debug!("{name} received, running {command:?}");
+                if commands.send(command).await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+    Ok(())
+}
+
+/// The command `line` names, or `None` for an ignored signal.
+fn mapped_command(line: &str) -> Result<Option<IpcCommand>> {
+    if line.trim().is_empty() {
+        return Ok(None);
+    }
+    parse_command(line).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_signals_to_commands() {
+        assert_eq!(mapped_command("toggle").unwrap(), Some(IpcCommand::Toggle));
+        assert_eq!(mapped_command(" ").unwrap(), None);
+        assert!(mapped_command("record").is_err());
+    }
+}
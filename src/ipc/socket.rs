@@ -0,0 +1,304 @@
+//! Line-based control socket at `$XDG_RUNTIME_DIR/hyprwhspr-rs.sock`, for
+//! bar buttons and keybind scripts that would rather not speak D-Bus:
+//!
+//! ```text
+//! echo toggle | socat - UNIX-CONNECT:$XDG_RUNTIME_DIR/hyprwhspr-rs.sock
+//! ```
+//!
+//! Each line is one command (`toggle`, `start`, `stop`, `cancel`,
+//! `cycle_mode`, `toggle_monitor`, `mode <raw|prose|code>`, `provider <name>`,
+//! `insert_stats`, `toggle_read_only`, `toggle_request_log`,
+//! `replay_last [fast]`) answered with `ok` or `error: <reason>`. Under systemd socket activation (`LISTEN_FDS`) the
+//! socket handed over by `hyprwhspr-rs.socket` is used instead, so the
+//! daemon and its audio/model stack only start once something connects;
+//! connections made while it is loading wait in the socket's backlog.
+
+use super::IpcCommand;
+use crate::config::{DictationMode, TranscriptionProvider};
+use anyhow::{anyhow, bail, Context, Result};
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::os::fd::FromRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+pub const SOCKET_NAME: &str = "hyprwhspr-rs.sock";
+
+/// First descriptor passed by systemd (`SD_LISTEN_FDS_START`).
+const LISTEN_FDS_START: i32 = 3;
+
+/// Number of sockets systemd passed us, recorded by [`take_activation`].
+static ACTIVATED_FDS: OnceLock<i32> = OnceLock::new();
+
+pub struct ControlSocket {
+    listener: UnixListener,
+    /// Set when we bound the path ourselves and should remove it on exit.
+    owned_path: Option<PathBuf>,
+}
+
+impl ControlSocket {
+    /// Takes the socket from systemd when activated, whatever `bind` says;
+    /// otherwise binds [`socket_path`] if `bind`, replacing a stale file left
+    /// by a crashed run but refusing to take over one that is still served.
+    pub fn open(bind: bool) -> Result<Option<Self>> {
+        if let Some(listener) = activated_listener()? {
+            debug!("Using the control socket passed by systemd");
+            return Ok(Some(Self {
+                listener,
+                owned_path: None,
+            }));
+        }
+        if !bind {
+            return Ok(None);
+        }
+
+        let path = socket_path()?;
+        remove_stale_socket(&path)?;
+        let listener = UnixListener::bind(&path)
+            .with_context(|| format!("Failed to bind control socket {}", path.display()))?;
+        Ok(Some(Self {
+            listener,
+            owned_path: Some(path),
+        }))
+    }
+
+    pub fn is_activated(&self) -> bool {
+        self.owned_path.is_none()
+    }
+
+    /// Accepts connections until the daemon exits, forwarding commands.
+    pub fn serve(self, commands: mpsc::Sender<IpcCommand>) {
+        tokio::spawn(async move {
+            loop {
+                match self.listener.accept().await {
+                    Ok((stream, _)) => {
+                        tokio::spawn(handle_client(stream, commands.clone()));
+                    }
+                    Err(err) => {
+                        warn!("Control socket accept failed: {}", err);
+                        break;
+                    }
+                }
+            }
+        });
+    }
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        if let Some(path) = &self.owned_path {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+pub fn socket_path() -> Result<PathBuf> {
+    let runtime_dir =
+        env::var_os("XDG_RUNTIME_DIR").ok_or_else(|| anyhow!("XDG_RUNTIME_DIR is not set"))?;
+    Ok(PathBuf::from(runtime_dir).join(SOCKET_NAME))
+}
+
+/// Removes the socket file at `path` unless a daemon still answers on it.
+fn remove_stale_socket(path: &Path) -> Result<()> {
+    match std::os::unix::net::UnixStream::connect(path) {
+        Ok(_) => bail!(
+            "Another hyprwhspr-rs is already listening on {}",
+            path.display()
+        ),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+        Err(err) if err.kind() == ErrorKind::ConnectionRefused => fs::remove_file(path)
+            .with_context(|| format!("Failed to remove stale socket {}", path.display())),
+        Err(err) => {
+            Err(err).with_context(|| format!("Failed to probe control socket {}", path.display()))
+        }
+    }
+}
+
+/// Claims systemd's socket-activation variables (`sd_listen_fds` with
+/// `unset_environment`) so scripts and helpers we spawn don't inherit them.
+/// Must run before any other thread starts, as changing the environment is
+/// only sound while the process is single-threaded.
+pub fn take_activation() {
+    let for_us = env::var("LISTEN_PID")
+        .ok()
+        .and_then(|pid| pid.parse::<u32>().ok())
+        .is_some_and(|pid| pid == std::process::id());
+    if !for_us {
+        return;
+    }
+    let fds = env::var("LISTEN_FDS")
+        .ok()
+        .and_then(|fds| fds.parse::<i32>().ok())
+        .unwrap_or(0);
+    for var in ["LISTEN_PID", "LISTEN_FDS", "LISTEN_FDNAMES"] {
+        env::remove_var(var);
+    }
+    let _ = ACTIVATED_FDS.set(fds);
+}
+
+/// The listening socket from `sd_listen_fds`, if systemd started us for it.
+fn activated_listener() -> Result<Option<UnixListener>> {
+    let fds = ACTIVATED_FDS.get().copied().unwrap_or(0);
+    if fds < 1 {
+        return Ok(None);
+    }
+    if fds > 1 {
+        warn!("systemd passed {fds} sockets; using the first");
+    }
+
+    // SAFETY: systemd hands descriptors from LISTEN_FDS_START onwards to the
+    // process named by LISTEN_PID, which `take_activation` checked; nothing
+    // else in the daemon takes ownership of them.
+    let listener = unsafe { std::os::unix::net::UnixListener::from_raw_fd(LISTEN_FDS_START) };
+    listener
+        .set_nonblocking(true)
+        .context("Failed to configure the activated socket")?;
+    UnixListener::from_std(listener)
+        .map(Some)
+        .context("Activated socket is not a Unix stream socket")
+}
+
+async fn handle_client(stream: UnixStream, commands: mpsc::Sender<IpcCommand>) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match parse_command(&line) {
+            Ok(command) => match commands.send(command).await {
+                Ok(()) => "ok".to_string(),
+                Err(_) => "error: daemon is shutting down".to_string(),
+            },
+            Err(err) => format!("error: {err}"),
+        };
+        if writer
+            .write_all(format!("{reply}\n").as_bytes())
+            .await
+            .is_err()
+        {
+            break;
+        }
+    }
+}
+
+/// Sends one command line to the daemon and waits for its answer; used by
+/// `hyprwhsprctl`.
+pub async fn request(line: &str) -> Result<()> {
+    let path = socket_path()?;
+    let stream = UnixStream::connect(&path)
+        .await
+        .with_context(|| format!("Failed to connect to {}", path.display()))?;
+    let (reader, mut writer) = stream.into_split();
+    writer
+        .write_all(format!("{}\n", line.trim()).as_bytes())
+        .await
+        .context("Failed to send command")?;
+    let mut reply = String::new();
+    BufReader::new(reader)
+        .read_line(&mut reply)
+        .await
+        .context("Failed to read the daemon's reply")?;
+    match reply.trim() {
+        "ok" => Ok(()),
+        "" => bail!("daemon closed the connection"),
+        reply => bail!("{}", reply.strip_prefix("error: ").unwrap_or(reply)),
+    }
+}
+
+pub fn parse_command(line: &str) -> Result<IpcCommand> {
+    let mut words = line.split_whitespace();
+    let command = words.next().unwrap_or_default().to_ascii_lowercase();
+    let argument = words.next();
+    if words.next().is_some() {
+        bail!("too many arguments");
+    }
+
+    let command = match (command.as_str(), argument) {
+        ("toggle", None) => IpcCommand::Toggle,
+        ("start", None) => IpcCommand::Start,
+        ("stop", None) => IpcCommand::Stop,
+        ("cancel", None) => IpcCommand::Cancel,
+        ("cycle_mode", None) => IpcCommand::CycleMode,
+        ("toggle_monitor", None) => IpcCommand::ToggleMonitor,
+        ("insert_stats", None) => IpcCommand::InsertStats,
+        ("toggle_read_only", None) => IpcCommand::ToggleReadOnly,
+        ("toggle_request_log", None) => IpcCommand::ToggleRequestLog,
+        ("replay_last", None) => IpcCommand::ReplayLast { fast: false },
+        ("replay_last", Some("fast")) => IpcCommand::ReplayLast { fast: true },
+        ("replay_last", Some(_)) => bail!("replay_last only takes \"fast\""),
+        ("mode", Some(mode)) => IpcCommand::SetMode(
+            DictationMode::from_label(mode)
+                .ok_or_else(|| anyhow!("unknown dictation mode {mode:?}"))?,
+        ),
+        ("mode", None) => bail!("mode needs raw, prose or code"),
+        ("provider", Some(provider)) => IpcCommand::SetProvider(
+            TranscriptionProvider::from_label(provider)
+                .ok_or_else(|| anyhow!("unknown provider {provider:?}"))?,
+        ),
+        ("provider", None) => bail!("provider needs local, groq, gemini, ssh or echo"),
+        (
+            "toggle" | "start" | "stop" | "cancel" | "cycle_mode" | "toggle_monitor"
+            | "insert_stats" | "toggle_read_only" | "toggle_request_log",
+            Some(_),
+        ) => {
+            bail!("{command} takes no argument")
+        }
+        _ => bail!("unknown command {command:?}"),
+    };
+    Ok(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(parse_command("toggle").unwrap(), IpcCommand::Toggle);
+        assert_eq!(parse_command("  Cancel \n").unwrap(), IpcCommand::Cancel);
+        assert_eq!(
+            parse_command("mode prose").unwrap(),
+            IpcCommand::SetMode(DictationMode::Prose)
+        );
+        assert!(parse_command("mode").is_err());
+        assert!(parse_command("mode shouting").is_err());
+        assert_eq!(
+            parse_command("provider groq").unwrap(),
+            IpcCommand::SetProvider(TranscriptionProvider::Groq)
+        );
+        assert_eq!(
+            parse_command("provider whisper_cpp").unwrap(),
+            IpcCommand::SetProvider(TranscriptionProvider::WhisperCpp)
+        );
+        assert_eq!(
+            parse_command("toggle_monitor").unwrap(),
+            IpcCommand::ToggleMonitor
+        );
+        assert_eq!(
+            parse_command("insert_stats").unwrap(),
+            IpcCommand::InsertStats
+        );
+        assert_eq!(
+            parse_command("toggle_read_only").unwrap(),
+            IpcCommand::ToggleReadOnly
+        );
+        assert_eq!(
+            parse_command("toggle_request_log").unwrap(),
+            IpcCommand::ToggleRequestLog
+        );
+        assert!(parse_command("toggle_request_log on").is_err());
+        assert_eq!(
+            parse_command("replay_last fast").unwrap(),
+            IpcCommand::ReplayLast { fast: true }
+        );
+        assert!(parse_command("replay_last slow").is_err());
+        assert!(parse_command("start now").is_err());
+        assert!(parse_command("record").is_err());
+    }
+}
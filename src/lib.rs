@@ -1,13 +1,29 @@
 pub mod app;
 pub mod app_test;
 pub mod audio;
+pub mod batch;
 pub mod benchmark;
+pub mod border;
+pub mod calendar;
 pub mod config;
+pub mod dnd;
+pub mod events;
+pub mod export;
+pub mod history;
 pub mod input;
+pub mod ipc;
 pub mod logging;
+pub mod models;
+pub mod notify;
+pub mod report;
+pub mod session;
+pub mod state;
 pub mod status;
+pub mod summary;
 pub mod transcription;
+pub mod voice_commands;
 pub mod whisper;
+pub mod workers;
 
 pub use app::HyprwhsprApp;
 pub use config::{Config, ConfigManager};
@@ -1,63 +1,215 @@
 use anyhow::Result;
+use clap::{Parser, Subcommand};
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, Table};
 use hyprwhspr_rs::{
-    config::TranscriptionProvider, logging::TextPipelineFormatter, ConfigManager, HyprwhsprApp,
+    audio::{recording, AudioCapture, AudioFeedback},
+    batch::FileTranscriber,
+    config::{Config, StartupSummary, TranscriptionProvider},
+    history::{self, Artifacts, HistoryEntry, HistoryFilter},
+    input::{copy_to_clipboard, TextInjector},
+    ipc,
+    logging::TextPipelineFormatter,
+    models::{self, ModelSpec},
+    report, status, ConfigManager, HyprwhsprApp,
 };
-use std::env;
+use std::fs;
+use std::path::PathBuf;
 use tokio::signal;
-use tracing::info;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing::{info, warn};
+use tracing_subscriber::{layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter};
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    // Initialize logging
+#[derive(Parser)]
+#[command(name = "hyprwhspr-rs", version, about)]
+struct Cli {
+    /// Toggle recording with Enter instead of the global shortcuts
+    #[arg(long)]
+    test: bool,
+
+    /// Play back the last kept recording
+    #[arg(long)]
+    replay: bool,
+
+    /// Playback speed for --replay
+    #[arg(long, default_value_t = 1.0, requires = "replay")]
+    speed: f32,
+
+    /// Start without pasting or copying transcripts (screen sharing);
+    /// `hyprwhsprctl toggle-read-only` switches it back
+    #[arg(long)]
+    read_only: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List audio input devices with the indices `audio_device` accepts
+    Devices,
+    /// List installed whisper.cpp models
+    Models,
+    /// Download whisper.cpp and Silero VAD models
+    Model {
+        #[command(subcommand)]
+        action: ModelCommand,
+    },
+    /// Transcribe audio files (WAV, FLAC, MP3, Ogg) with the configured backend
+    Transcribe {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+
+        /// Also copy the transcript to the clipboard
+        #[arg(long)]
+        copy: bool,
+    },
+    /// Browse past transcriptions (needs `history.enabled`)
+    History {
+        #[command(subcommand)]
+        action: HistoryCommand,
+    },
+    /// Print compositor, audio, whisper.cpp and provider details with recent
+    /// errors as markdown for a bug report (secrets masked)
+    Report,
+}
+
+#[derive(Subcommand)]
+enum ModelCommand {
+    /// Fetch a model from Hugging Face into the first `models_dirs` entry,
+    /// resuming an interrupted download
+    Pull {
+        /// Model name as used by `whisper_cpp.model` (e.g. base.en,
+        /// large-v3-turbo), or `silero` for the VAD model
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum HistoryCommand {
+    /// Show the most recent entries
+    List {
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Show entries containing a phrase (case-insensitive)
+    Search {
+        query: Option<String>,
+
+        /// Only entries from this day on (YYYY-MM-DD, local time)
+        #[arg(long, value_parser = history::parse_date)]
+        since: Option<time::Date>,
+
+        /// Only entries up to and including this day
+        #[arg(long, value_parser = history::parse_date)]
+        until: Option<time::Date>,
+
+        /// Only entries from this backend (local, groq, gemini, ssh, echo)
+        #[arg(long)]
+        provider: Option<String>,
+
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Copy an entry's text back to the clipboard (latest when no id)
+    Copy { id: Option<u64> },
+    /// Paste an entry into the focused window again (latest when no id);
+    /// meant for a compositor keybinding
+    Inject { id: Option<u64> },
+    /// Erase stored transcripts, kept recordings, unsent audio and the
+    /// request log, overwriting them before deletion
+    #[command(group = clap::ArgGroup::new("range").required(true))]
+    Purge {
+        /// Only what was recorded before this day (YYYY-MM-DD, local time)
+        #[arg(long, group = "range", value_parser = history::parse_date)]
+        before: Option<time::Date>,
+
+        /// Everything
+        #[arg(long, group = "range")]
+        all: bool,
+    },
+}
+
+fn main() -> Result<()> {
+    // Read before the runtime spawns its workers; see `take_activation`.
+    ipc::socket::take_activation();
+
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?
+        .block_on(run())
+}
+
+async fn run() -> Result<()> {
+    let cli = Cli::parse();
+
+    // Initialize logging; the filter is swapped once the config is read
+    let env_filter = EnvFilter::try_from_default_env().ok();
+    let explicit_filter = env_filter.is_some();
+    let (filter, filter_handle) =
+        reload::Layer::new(env_filter.unwrap_or_else(|| "hyprwhspr=info".into()));
     tracing_subscriber::registry()
+        .with(filter)
         .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "hyprwhspr=info".into()),
+            tracing_subscriber::fmt::layer()
+                .event_format(TextPipelineFormatter::new())
+                .with_writer(std::io::stderr),
         )
-        .with(tracing_subscriber::fmt::layer().event_format(TextPipelineFormatter::new()))
         .init();
 
-    // Check for test mode
-    let args: Vec<String> = env::args().collect();
-    let test_mode = args.contains(&"--test".to_string());
+    match cli.command {
+        Some(Command::Devices) => return list_devices(),
+        Some(Command::Models) => return list_models(),
+        Some(Command::Model {
+            action: ModelCommand::Pull { name },
+        }) => return pull_model(&name).await,
+        Some(Command::Transcribe { files, copy }) => return transcribe_files(&files, copy).await,
+        Some(Command::History {
+            action: HistoryCommand::Purge { before, .. },
+        }) => return purge_history(before),
+        Some(Command::History { action }) => return browse_history(action).await,
+        Some(Command::Report) => {
+            println!("{}", report::gather(&ConfigManager::load()?));
+            return Ok(());
+        }
+        None => {}
+    }
 
-    if test_mode {
+    if cli.test {
         return run_test_mode().await;
     }
 
-    info!("🚀 hyprwhspr-rs starting up!");
-    info!("{}", "=".repeat(50));
+    if cli.replay {
+        return replay_last_recording(cli.speed);
+    }
 
     // Load configuration
     let config_manager = ConfigManager::load()?;
     config_manager.start_watching();
     let config = config_manager.get();
-    info!("✅ Configuration loaded");
-    info!(
-        "   Transcription backend: {}",
-        config.transcription.provider.label()
-    );
-    if matches!(
-        config.transcription.provider,
-        TranscriptionProvider::WhisperCpp
-    ) {
-        info!("   Model: {}", config.transcription.whisper_cpp.model);
-    }
-    if let Some(shortcut) = config.press_shortcut() {
-        info!("   Press shortcut: {}", shortcut);
-    } else {
-        info!("   Press shortcut: disabled");
-    }
-    if let Some(shortcut) = config.hold_shortcut() {
-        info!("   Hold shortcut: {}", shortcut);
-    } else {
-        info!("   Hold shortcut: disabled");
+    match config.logging.startup_summary {
+        StartupSummary::Full => log_startup_banner(&config),
+        StartupSummary::Minimal => {
+            info!(
+                "hyprwhspr-rs {} started ({})",
+                env!("CARGO_PKG_VERSION"),
+                config.transcription.provider.label()
+            );
+            if !explicit_filter {
+                if let Err(err) = filter_handle.reload(EnvFilter::new("hyprwhspr=warn")) {
+                    warn!("Failed to quiet logging: {err}");
+                }
+            }
+        }
+        StartupSummary::Off => {}
     }
-    info!("   Audio feedback: {}", config.audio_feedback);
+
+    download_missing_model(&config_manager).await;
 
     // Initialize application
-    let app = HyprwhsprApp::new(config_manager)?;
+    let mut app = HyprwhsprApp::new(config_manager)?;
+    if cli.read_only {
+        app.set_read_only(true).await;
+    }
 
     // Set up signal handling
     let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel();
@@ -110,6 +262,40 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+fn log_startup_banner(config: &Config) {
+    info!("🚀 hyprwhspr-rs starting up!");
+    info!("{}", "=".repeat(50));
+    info!("✅ Configuration loaded");
+    info!(
+        "   Transcription backend: {}",
+        config.transcription.provider.label()
+    );
+    if matches!(
+        config.transcription.provider,
+        TranscriptionProvider::WhisperCpp
+    ) {
+        info!("   Model: {}", config.transcription.whisper_cpp.model);
+    }
+    if let Some(shortcut) = config.press_shortcut() {
+        info!("   Press shortcut: {}", shortcut);
+    } else {
+        info!("   Press shortcut: disabled");
+    }
+    if let Some(shortcut) = config.hold_shortcut() {
+        info!("   Hold shortcut: {}", shortcut);
+    } else {
+        info!("   Hold shortcut: disabled");
+    }
+    if let Some(shortcut) = config.cancel_shortcut() {
+        info!("   Cancel shortcut: {}", shortcut);
+    }
+    if let Some(shortcut) = config.mode_shortcut() {
+        info!("   Mode shortcut: {}", shortcut);
+    }
+    info!("   Dictation mode: {}", config.dictation_mode);
+    info!("   Audio feedback: {}", config.audio_feedback);
+}
+
 async fn run_test_mode() -> Result<()> {
     use hyprwhspr_rs::app_test::HyprwhsprAppTest;
     use tokio::io::{AsyncBufReadExt, BufReader};
@@ -126,6 +312,8 @@ async fn run_test_mode() -> Result<()> {
     info!("   Model: {}", config.transcription.whisper_cpp.model);
     info!("   Audio feedback: {}", config.audio_feedback);
 
+    download_missing_model(&config_manager).await;
+
     // Initialize application
     let mut app = HyprwhsprAppTest::new(config_manager)?;
 
@@ -153,6 +341,11 @@ async fn run_test_mode() -> Result<()> {
     // Main loop
     loop {
         tokio::select! {
+            _ = app.recording_deadline().reached() => {
+                if let Err(e) = app.stop_at_recording_limit().await {
+                    info!("Error: {}", e);
+                }
+            }
             line = reader.next_line() => {
                 match line {
                     Ok(Some(_)) => {
@@ -196,3 +389,289 @@ async fn run_test_mode() -> Result<()> {
 
     Ok(())
 }
+
+/// `--replay [--speed <factor>]`: plays back the last kept recording so you can
+/// hear exactly what the microphone captured.
+fn replay_last_recording(speed: f32) -> Result<()> {
+    let config_manager = ConfigManager::load()?;
+    let path = config_manager.get_last_recording_path();
+    if !path.exists() {
+        anyhow::bail!(
+            "No recording found at {:?}; is keep_last_recording enabled?",
+            path
+        );
+    }
+
+    let audio = recording::load_recording(&path)?;
+    info!(
+        "🔁 Replaying {:.1}s recording ({} Hz) at {}x",
+        audio.len() as f32 / audio.sample_rate.max(1) as f32,
+        audio.sample_rate,
+        speed
+    );
+    AudioFeedback::play_captured_blocking(&audio, speed)
+}
+
+/// `devices`: input devices with the indices and names `audio_device` accepts.
+fn list_devices() -> Result<()> {
+    let devices = AudioCapture::get_available_devices()?;
+    if devices.is_empty() {
+        println!("No audio input devices found");
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_header(vec!["Index", "Name", "Sample rate", "Channels"]);
+    for device in devices {
+        let name = if device.is_default {
+            format!("{} (default)", device.name)
+        } else {
+            device.name
+        };
+        table.add_row(vec![
+            Cell::new(device.index),
+            Cell::new(name),
+            Cell::new(
+                device
+                    .sample_rate
+                    .map_or_else(|| "?".to_string(), |rate| format!("{rate} Hz")),
+            ),
+            Cell::new(
+                device
+                    .channels
+                    .map_or_else(|| "?".to_string(), |channels| channels.to_string()),
+            ),
+        ]);
+    }
+    println!("{table}");
+    println!("Set \"audio_device\" to an index or part of a name.");
+    Ok(())
+}
+
+/// `models`: ggml models in the model search directories, as values for
+/// `transcription.whisper_cpp.model`.
+fn list_models() -> Result<()> {
+    let config_manager = ConfigManager::load()?;
+    let models = config_manager.installed_models();
+    if models.is_empty() {
+        println!("No whisper.cpp models found; check transcription.whisper_cpp.models_dirs");
+        return Ok(());
+    }
+
+    let active = config_manager.get_model_path();
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_header(vec!["Model", "Size", "Path"]);
+    for path in models {
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mut model = file_name
+            .trim_start_matches("ggml-")
+            .trim_end_matches(".bin")
+            .to_string();
+        if path == active {
+            model.push_str(" (active)");
+        }
+        let size = fs::metadata(&path)
+            .map(|metadata| format!("{:.0} MB", metadata.len() as f64 / 1_048_576.0))
+            .unwrap_or_else(|_| "?".to_string());
+        table.add_row(vec![
+            Cell::new(model),
+            Cell::new(size),
+            Cell::new(path.display()),
+        ]);
+    }
+    println!("{table}");
+    Ok(())
+}
+
+/// `model pull <name>`: downloads with a percentage on stderr.
+async fn pull_model(name: &str) -> Result<()> {
+    let spec = ModelSpec::parse(name)?;
+    let config_manager = ConfigManager::load()?;
+    let dir = config_manager.model_download_dir();
+    println!("Fetching {} into {}", spec.file_name, dir.display());
+
+    let mut shown = None;
+    let result = models::pull(&spec, &dir, |progress| {
+        let percent = (progress.downloaded * 100)
+            .checked_div(progress.total)
+            .unwrap_or(100);
+        if shown != Some(percent) {
+            shown = Some(percent);
+            eprint!(
+                "\r  {:>3}%  {:.0} / {:.0} MB",
+                percent,
+                progress.downloaded as f64 / 1_048_576.0,
+                progress.total as f64 / 1_048_576.0
+            );
+        }
+    })
+    .await;
+    if shown.is_some() {
+        eprintln!();
+    }
+
+    println!("✅ Saved {}", result?.display());
+    Ok(())
+}
+
+/// With `whisper_cpp.auto_download`, fetches the configured model when it
+/// isn't installed. On failure the closest installed model is used instead.
+async fn download_missing_model(config_manager: &ConfigManager) {
+    let config = config_manager.get();
+    if config.transcription.provider != TranscriptionProvider::WhisperCpp
+        || !config.transcription.whisper_cpp.auto_download
+    {
+        return;
+    }
+    let Some(name) = config_manager.missing_model() else {
+        return;
+    };
+
+    info!("⬇️  Downloading missing model {}...", name);
+    let mut logged = 0;
+    let log_progress = |progress: models::Progress| {
+        let percent = (progress.downloaded * 100)
+            .checked_div(progress.total)
+            .unwrap_or(100);
+        if percent >= logged + 10 {
+            logged = percent - percent % 10;
+            info!("   {}%", logged);
+        }
+    };
+    let result = match ModelSpec::parse(&name) {
+        Ok(spec) => models::pull(&spec, &config_manager.model_download_dir(), log_progress).await,
+        Err(err) => Err(err),
+    };
+    match result {
+        Ok(path) => info!("✅ Downloaded {}", path.display()),
+        Err(err) => warn!("Failed to download model {}: {:#}", name, err),
+    }
+}
+
+/// `transcribe <files>...`: prints each file's transcript; with several
+/// files, each one is headed by its path.
+async fn transcribe_files(files: &[PathBuf], copy: bool) -> Result<()> {
+    let config_manager = ConfigManager::load()?;
+    let transcriber = FileTranscriber::new(&config_manager)?;
+
+    let mut transcripts = Vec::with_capacity(files.len());
+    for path in files {
+        let text = transcriber.transcribe(path).await?;
+        if files.len() > 1 {
+            println!("# {}", path.display());
+        }
+        println!("{text}");
+        transcripts.push(text);
+    }
+
+    if copy {
+        copy_to_clipboard(&transcripts.join("\n\n"))?;
+        info!("📋 Copied transcript to clipboard");
+    }
+    Ok(())
+}
+
+/// `history list|search|copy|inject`: reads the store the daemon appends to.
+async fn browse_history(action: HistoryCommand) -> Result<()> {
+    let config_manager = ConfigManager::load()?;
+    let config = config_manager.get();
+    let entries = history::read_entries(&config_manager.get_history_path(&config))?;
+    if entries.is_empty() && !config.history.enabled {
+        println!("No history yet; set \"history\": {{ \"enabled\": true }} to start keeping it");
+        return Ok(());
+    }
+
+    let find = |id: Option<u64>| {
+        let entry = match id {
+            Some(id) => entries.iter().find(|entry| entry.id == id),
+            None => entries.last(),
+        };
+        entry.ok_or_else(|| match id {
+            Some(id) => anyhow::anyhow!("No history entry with id {id}"),
+            None => anyhow::anyhow!("History is empty"),
+        })
+    };
+
+    match action {
+        HistoryCommand::List { limit } => print_history(entries.iter().rev().take(limit)),
+        HistoryCommand::Search {
+            query,
+            since,
+            until,
+            provider,
+            limit,
+        } => {
+            let filter = HistoryFilter {
+                query,
+                since,
+                until,
+                provider,
+            };
+            let local = time::UtcOffset::current_local_offset().unwrap_or(time::UtcOffset::UTC);
+            print_history(
+                entries
+                    .iter()
+                    .rev()
+                    .filter(|entry| filter.matches(entry, local))
+                    .take(limit),
+            );
+        }
+        HistoryCommand::Copy { id } => {
+            let entry = find(id)?;
+            copy_to_clipboard(&entry.text)?;
+            println!("Copied entry {} ({} words)", entry.id, entry.words);
+        }
+        HistoryCommand::Inject { id } => {
+            let entry = find(id)?;
+            let mut injector = TextInjector::from_config(&config)?;
+            injector.set_read_only(status::daemon_read_only());
+            if let Some(method) = injector.paste_verbatim(&entry.text).await? {
+                info!("Re-injected entry {} via {}", entry.id, method);
+            }
+        }
+        HistoryCommand::Purge { .. } => unreachable!("dispatched to purge_history"),
+    }
+    Ok(())
+}
+
+/// `history purge --before <date> | --all`.
+fn purge_history(before: Option<time::Date>) -> Result<()> {
+    let config_manager = ConfigManager::load()?;
+    let config = config_manager.get();
+    let local = time::UtcOffset::current_local_offset().unwrap_or(time::UtcOffset::UTC);
+    let cutoff = before.map(|date| date.midnight().assume_offset(local));
+
+    let transcripts = history::purge(&config_manager.get_history_path(&config), cutoff)?;
+    let (files, log_lines) = Artifacts::new(&config_manager, &config).purge(cutoff)?;
+
+    println!(
+        "Erased {transcripts} transcript(s), {files} audio file(s) and {log_lines} request log entries"
+    );
+    Ok(())
+}
+
+fn print_history<'a>(entries: impl Iterator<Item = &'a HistoryEntry>) {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_header(vec!["ID", "When", "Backend", "Length", "Text"]);
+    for entry in entries {
+        let when = entry
+            .timestamp
+            .get(..16)
+            .unwrap_or(&entry.timestamp)
+            .replace('T', " ");
+        table.add_row(vec![
+            Cell::new(entry.id),
+            Cell::new(format!("{when} UTC")),
+            Cell::new(&entry.backend),
+            Cell::new(format!("{:.0}s", entry.duration_secs)),
+            Cell::new(&entry.text),
+        ]);
+    }
+    println!("{table}");
+}
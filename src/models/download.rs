@@ -0,0 +1,161 @@
+//! Resumable downloads, checked against the SHA-256 Hugging Face publishes
+//! for each LFS file.
+
+use super::ModelSpec;
+use anyhow::{bail, Context, Result};
+use reqwest::header::RANGE;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{info, warn};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub downloaded: u64,
+    pub total: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct TreeEntry {
+    path: String,
+    lfs: Option<LfsPointer>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LfsPointer {
+    /// SHA-256 of the file contents.
+    oid: String,
+    size: u64,
+}
+
+/// Downloads `spec` into `dir` and returns the model's path. A `.part` file
+/// left by an interrupted pull is resumed; a model already on disk that
+/// matches the published checksum is kept as is.
+pub async fn pull(
+    spec: &ModelSpec,
+    dir: &Path,
+    mut on_progress: impl FnMut(Progress),
+) -> Result<PathBuf> {
+    let client = Client::builder()
+        .user_agent("hyprwhspr-rs (models)")
+        .connect_timeout(Duration::from_secs(10))
+        .build()
+        .context("Failed to build model download HTTP client")?;
+    let expected = published_checksum(&client, spec).await?;
+
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+    let target = dir.join(&spec.file_name);
+    if target.exists() {
+        if sha256_of(&target).await? == expected.oid {
+            info!("{} is already up to date", spec.file_name);
+            return Ok(target);
+        }
+        warn!(
+            "{} doesn't match the published checksum; downloading it again",
+            spec.file_name
+        );
+    }
+
+    let part = dir.join(format!("{}.part", spec.file_name));
+    let mut downloaded = fs::metadata(&part).map(|meta| meta.len()).unwrap_or(0);
+    if downloaded > expected.size {
+        downloaded = 0;
+    }
+
+    if downloaded < expected.size {
+        let mut request = client.get(spec.url());
+        if downloaded > 0 {
+            info!("Resuming {} at {} bytes", spec.file_name, downloaded);
+            request = request.header(RANGE, format!("bytes={downloaded}-"));
+        }
+        let mut response = request
+            .send()
+            .await
+            .with_context(|| format!("Failed to request {}", spec.url()))?
+            .error_for_status()
+            .with_context(|| format!("Failed to download {}", spec.file_name))?;
+
+        let mut file = if response.status() == StatusCode::PARTIAL_CONTENT {
+            OpenOptions::new().append(true).open(&part)
+        } else {
+            downloaded = 0;
+            File::create(&part)
+        }
+        .with_context(|| format!("Failed to open {:?}", part))?;
+
+        on_progress(Progress {
+            downloaded,
+            total: expected.size,
+        });
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .with_context(|| format!("Download of {} was interrupted", spec.file_name))?
+        {
+            file.write_all(&chunk)
+                .with_context(|| format!("Failed to write {:?}", part))?;
+            downloaded += chunk.len() as u64;
+            on_progress(Progress {
+                downloaded,
+                total: expected.size,
+            });
+        }
+        file.sync_all()
+            .with_context(|| format!("Failed to flush {:?}", part))?;
+    }
+
+    let actual = sha256_of(&part).await?;
+    if actual != expected.oid {
+        let _ = fs::remove_file(&part);
+        bail!(
+            "Checksum mismatch for {} (expected {}, got {}); the partial download was removed",
+            spec.file_name,
+            expected.oid,
+            actual
+        );
+    }
+    fs::rename(&part, &target).with_context(|| format!("Failed to move model to {:?}", target))?;
+    Ok(target)
+}
+
+/// Size and SHA-256 from the repository listing. Also how unknown model
+/// names are caught before anything is downloaded.
+async fn published_checksum(client: &Client, spec: &ModelSpec) -> Result<LfsPointer> {
+    let entries: Vec<TreeEntry> = client
+        .get(spec.tree_url())
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .with_context(|| format!("Failed to list models in {}", spec.repo))?
+        .json()
+        .await
+        .with_context(|| format!("Unexpected model listing from {}", spec.repo))?;
+
+    let entry = entries
+        .into_iter()
+        .find(|entry| entry.path == spec.file_name)
+        .with_context(|| format!("No model {} in {}", spec.file_name, spec.repo))?;
+    entry
+        .lfs
+        .with_context(|| format!("{} has no published checksum", spec.file_name))
+}
+
+async fn sha256_of(path: &Path) -> Result<String> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let mut file = File::open(&path).with_context(|| format!("Failed to open {:?}", path))?;
+        let mut hasher = Sha256::new();
+        io::copy(&mut file, &mut hasher).with_context(|| format!("Failed to read {:?}", path))?;
+        Ok(hex(&hasher.finalize()))
+    })
+    .await
+    .context("Checksum task panicked")?
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
@@ -0,0 +1,216 @@
+//! Fetches whisper.cpp ggml models and Silero VAD models from Hugging Face
+//! so a missing model can be fixed with `hyprwhspr-rs model pull <name>`.
+
+#[cfg(feature = "remote")]
+pub mod download;
+
+#[cfg(not(feature = "remote"))]
+pub mod download {
+    //! Stand-in for builds without the `remote` feature, which have no HTTP
+    //! client to fetch models with.
+
+    use super::ModelSpec;
+    use anyhow::{bail, Result};
+    use std::path::{Path, PathBuf};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Progress {
+        pub downloaded: u64,
+        pub total: u64,
+    }
+
+    pub async fn pull(
+        spec: &ModelSpec,
+        _dir: &Path,
+        _on_progress: impl FnMut(Progress),
+    ) -> Result<PathBuf> {
+        bail!(
+            "Can't download {}: this build lacks the `remote` feature",
+            spec.file_name
+        )
+    }
+}
+
+pub use download::{pull, Progress};
+
+use anyhow::{bail, Result};
+use std::cmp::Reverse;
+use std::path::{Path, PathBuf};
+
+const WHISPER_REPO: &str = "ggerganov/whisper.cpp";
+const VAD_REPO: &str = "ggml-org/whisper-vad";
+/// Pulled for a bare `silero`; matches the default `vad.model`.
+const DEFAULT_SILERO: &str = "silero-v5.1.2";
+
+/// A model file in one of the Hugging Face repositories above.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelSpec {
+    pub repo: &'static str,
+    pub file_name: String,
+}
+
+impl ModelSpec {
+    /// Accepts the names used for `whisper_cpp.model` (`base.en`,
+    /// `large-v3-turbo`), `silero` / `silero-v5.1.2` for VAD, or a full
+    /// `ggml-*.bin` file name.
+    pub fn parse(name: &str) -> Result<Self> {
+        let name = name.trim();
+        let stem = name.strip_prefix("ggml-").unwrap_or(name);
+        let stem = stem.strip_suffix(".bin").unwrap_or(stem);
+        if stem.is_empty()
+            || !stem
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+        {
+            bail!("Invalid model name {name:?}");
+        }
+
+        let (repo, stem) = match stem {
+            "silero" => (VAD_REPO, DEFAULT_SILERO),
+            stem if stem.starts_with("silero") => (VAD_REPO, stem),
+            stem => (WHISPER_REPO, stem),
+        };
+        Ok(Self {
+            repo,
+            file_name: format!("ggml-{stem}.bin"),
+        })
+    }
+
+    pub fn url(&self) -> String {
+        format!(
+            "https://huggingface.co/{}/resolve/main/{}",
+            self.repo, self.file_name
+        )
+    }
+
+    /// Repository listing carrying each file's size and SHA-256.
+    pub fn tree_url(&self) -> String {
+        format!("https://huggingface.co/api/models/{}/tree/main", self.repo)
+    }
+}
+
+/// Picks an installed model to stand in for a missing `wanted` one: the
+/// closest of the same family (`large-v3` for `large-v3-turbo`), keeping
+/// English-only models for English-only requests, otherwise the smallest.
+/// A multilingual request only falls back to an English-only model when
+/// nothing else is installed. `installed` pairs each model file with its
+/// size.
+pub fn closest_model<'a>(wanted: &str, installed: &'a [(PathBuf, u64)]) -> Option<&'a Path> {
+    let wanted = wanted.strip_prefix("ggml-").unwrap_or(wanted);
+    let wanted = wanted.strip_suffix(".bin").unwrap_or(wanted);
+    let family = model_family(wanted);
+    let english_only = wanted.ends_with(".en");
+
+    let multilingual: Vec<_> = installed
+        .iter()
+        .filter(|(path, _)| !model_stem(path).is_some_and(|stem| stem.ends_with(".en")))
+        .collect();
+    let candidates = if english_only || multilingual.is_empty() {
+        installed.iter().collect()
+    } else {
+        multilingual
+    };
+
+    candidates
+        .iter()
+        .filter_map(|(path, _)| {
+            let stem = model_stem(path)?;
+            (model_family(stem) == family).then(|| {
+                let shared = stem
+                    .chars()
+                    .zip(wanted.chars())
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                let english = stem.ends_with(".en") == english_only;
+                ((english, shared, Reverse(stem.len())), path)
+            })
+        })
+        .max_by_key(|(score, _)| *score)
+        .map(|(_, path)| path)
+        .or_else(|| {
+            candidates
+                .iter()
+                .min_by_key(|(_, size)| *size)
+                .map(|(path, _)| path)
+        })
+        .map(PathBuf::as_path)
+}
+
+fn model_stem(path: &Path) -> Option<&str> {
+    path.file_name()?
+        .to_str()?
+        .strip_prefix("ggml-")?
+        .strip_suffix(".bin")
+}
+
+fn model_family(stem: &str) -> &str {
+    stem.split(['.', '-']).next().unwrap_or(stem)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn model_names_map_to_repository_files() {
+        let cases = [
+            ("base.en", WHISPER_REPO, "ggml-base.en.bin"),
+            (
+                "ggml-large-v3-turbo.bin",
+                WHISPER_REPO,
+                "ggml-large-v3-turbo.bin",
+            ),
+            ("ggml-small", WHISPER_REPO, "ggml-small.bin"),
+            ("silero", VAD_REPO, "ggml-silero-v5.1.2.bin"),
+            ("ggml-silero-v5.1.2.bin", VAD_REPO, "ggml-silero-v5.1.2.bin"),
+        ];
+        for (name, repo, file_name) in cases {
+            let spec = ModelSpec::parse(name).unwrap();
+            assert_eq!((spec.repo, spec.file_name.as_str()), (repo, file_name));
+        }
+
+        assert_eq!(
+            ModelSpec::parse("tiny").unwrap().url(),
+            "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-tiny.bin"
+        );
+        for bad in ["", "../base", "base/en", "ggml-.bin"] {
+            assert!(ModelSpec::parse(bad).is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn missing_models_fall_back_to_the_closest_installed_one() {
+        let installed: Vec<(PathBuf, u64)> = [
+            ("ggml-base.en.bin", 148),
+            ("ggml-large-v3.bin", 3100),
+            ("ggml-large-v3-turbo.bin", 1600),
+            ("ggml-small.bin", 488),
+            ("ggml-tiny.bin", 78),
+        ]
+        .into_iter()
+        .map(|(name, size)| (PathBuf::from("/models").join(name), size))
+        .collect();
+        let pick = |wanted| {
+            closest_model(wanted, &installed)
+                .and_then(|path| path.file_name())
+                .map(|name| name.to_string_lossy().into_owned())
+        };
+
+        assert_eq!(
+            pick("large-v3-turbo-q5_0").as_deref(),
+            Some("ggml-large-v3-turbo.bin")
+        );
+        assert_eq!(pick("large-v2").as_deref(), Some("ggml-large-v3.bin"));
+        assert_eq!(pick("base").as_deref(), Some("ggml-tiny.bin"));
+        assert_eq!(pick("base.en").as_deref(), Some("ggml-base.en.bin"));
+        assert_eq!(pick("small.en").as_deref(), Some("ggml-small.bin"));
+        assert_eq!(pick("medium.en").as_deref(), Some("ggml-tiny.bin"));
+        assert_eq!(closest_model("base", &[]), None);
+
+        let english_only = [(PathBuf::from("/models/ggml-base.en.bin"), 148)];
+        assert_eq!(
+            closest_model("small", &english_only),
+            Some(english_only[0].0.as_path())
+        );
+    }
+}
@@ -0,0 +1,211 @@
+//! Desktop notifications through `org.freedesktop.Notifications` for
+//! transcription outcomes, so a failed request shows up somewhere other
+//! than the log. Each event type is switched on separately; do-not-disturb
+//! holds them back like it does the feedback sounds.
+
+use crate::config::{DoNotDisturbConfig, NotificationsConfig};
+use crate::dnd::DoNotDisturb;
+use crate::events::{next_event, AppEvent, EventBus};
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tracing::{debug, warn};
+use zbus::zvariant::Value;
+use zbus::Connection;
+
+const APP_NAME: &str = "hyprwhspr-rs";
+const ICON: &str = "audio-input-microphone";
+/// Milliseconds a notification stays up; errors wait to be dismissed.
+const EXPIRE_MS: i32 = 5_000;
+
+/// What a notification says; kept apart from sending so it can be tested.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Notification {
+    summary: String,
+    body: String,
+    critical: bool,
+}
+
+#[derive(Clone)]
+pub struct Notifier {
+    settings: Arc<Mutex<(NotificationsConfig, Arc<DoNotDisturb>)>>,
+}
+
+impl Notifier {
+    pub fn new(config: &NotificationsConfig, dnd: &DoNotDisturbConfig) -> Self {
+        Self {
+            settings: Arc::new(Mutex::new((
+                config.clone(),
+                Arc::new(DoNotDisturb::new(dnd)),
+            ))),
+        }
+    }
+
+    /// Applies a reloaded config to notifications sent from now on.
+    pub fn update(&self, config: &NotificationsConfig, dnd: &DoNotDisturbConfig) {
+        if let Ok(mut settings) = self.settings.lock() {
+            *settings = (config.clone(), Arc::new(DoNotDisturb::new(dnd)));
+        }
+    }
+
+    /// Pops up the enabled outcomes from `bus` until it is dropped. The
+    /// session bus is only connected once something is worth showing.
+    pub fn follow(&self, bus: &EventBus) {
+        let notifier = self.clone();
+        let mut events = bus.subscribe();
+        tokio::spawn(async move {
+            let mut connection: Option<Connection> = None;
+            let mut last_id = 0;
+            while let Some(event) = next_event(&mut events, "Notifier").await {
+                let Some((config, dnd)) = notifier.settings.lock().ok().map(|s| s.clone()) else {
+                    continue;
+                };
+                let Some(notification) = notification_for(&config, &event) else {
+                    continue;
+                };
+                if dnd.active() {
+                    debug!("Holding back notification during do-not-disturb");
+                    continue;
+                }
+
+                let connection = match &connection {
+                    Some(connection) => connection.clone(),
+                    None => match Connection::session().await {
+                        Ok(session) => connection.insert(session).clone(),
+                        Err(err) => {
+                            warn!("Failed to connect to the session bus for notifications: {err}");
+                            continue;
+                        }
+                    },
+                };
+                match send(&connection, &notification, last_id).await {
+                    Ok(id) => last_id = id,
+                    Err(err) => warn!("Failed to show notification: {err:#}"),
+                }
+            }
+        });
+    }
+}
+
+fn notification_for(config: &NotificationsConfig, event: &AppEvent) -> Option<Notification> {
+    match event {
+        AppEvent::TranscriptReady { text, backend } if config.transcription_complete => {
+            Some(Notification {
+                summary: format!("Transcribed with {backend}"),
+                body: preview(text, config.preview_chars),
+                critical: false,
+            })
+        }
+        AppEvent::EmptyTranscript { backend } if config.empty_result => Some(Notification {
+            summary: "Nothing transcribed".to_string(),
+            body: format!("{backend} heard no speech in the recording"),
+            critical: false,
+        }),
+        // The only sign of a transcript that was never pasted.
+        AppEvent::Recovered { text, copied } => Some(Notification {
+            summary: "Recovered a dictation from before the restart".to_string(),
+            body: if *copied {
+                format!("Copied: {}", preview(text, config.preview_chars))
+            } else {
+                preview(text, config.preview_chars)
+            },
+            critical: false,
+        }),
+        AppEvent::Error { message } if config.transcription_failed => Some(Notification {
+            summary: "Transcription failed".to_string(),
+            body: message.clone(),
+            critical: true,
+        }),
+        _ => None,
+    }
+}
+
+/// The first `max_chars` characters of `text`, on one line.
+fn preview(text: &str, max_chars: usize) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= max_chars {
+        return text;
+    }
+    let cut: String = text.chars().take(max_chars).collect();
+    format!("{}…", cut.trim_end())
+}
+
+/// Shows `notification` in place of `replaces` (0 for none) and returns its
+/// id, so a burst of dictations updates one popup instead of stacking.
+async fn send(connection: &Connection, notification: &Notification, replaces: u32) -> Result<u32> {
+    let mut hints: HashMap<&str, Value> = HashMap::new();
+    hints.insert(
+        "urgency",
+        Value::U8(if notification.critical { 2 } else { 1 }),
+    );
+    let expire = if notification.critical { 0 } else { EXPIRE_MS };
+
+    let reply = connection
+        .call_method(
+            Some("org.freedesktop.Notifications"),
+            "/org/freedesktop/Notifications",
+            Some("org.freedesktop.Notifications"),
+            "Notify",
+            &(
+                APP_NAME,
+                replaces,
+                ICON,
+                notification.summary.as_str(),
+                notification.body.as_str(),
+                Vec::<&str>::new(),
+                hints,
+                expire,
+            ),
+        )
+        .await
+        .context("Notify call failed")?;
+    reply
+        .body()
+        .deserialize::<u32>()
+        .context("Unexpected Notify reply")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn previews_long_transcripts() {
+        assert_eq!(preview("short  one\n", 20), "short one");
+        assert_eq!(preview("one two three four", 9), "one two t…");
+        assert_eq!(preview("one two three", 8), "one two…");
+    }
+
+    #[test]
+    fn only_enabled_events_notify() {
+        let config = NotificationsConfig::default();
+        let error = AppEvent::Error {
+            message: "GROQ_API_KEY environment variable is not set".to_string(),
+        };
+        let notification = notification_for(&config, &error).unwrap();
+        assert!(notification.critical);
+
+        let ready = AppEvent::TranscriptReady {
+            text: "hello".to_string(),
+            backend: "Groq",
+        };
+        assert_eq!(notification_for(&config, &ready), None);
+        let recovered = AppEvent::Recovered {
+            text: "hello".to_string(),
+            copied: true,
+        };
+        assert_eq!(
+            notification_for(&config, &recovered).map(|n| n.body),
+            Some("Copied: hello".to_string())
+        );
+
+        let config = NotificationsConfig {
+            transcription_complete: true,
+            ..config
+        };
+        assert_eq!(
+            notification_for(&config, &ready).map(|n| n.body),
+            Some("hello".to_string())
+        );
+    }
+}
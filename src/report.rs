@@ -0,0 +1,253 @@
+//! `hyprwhspr-rs report`: what an issue usually has to ask for (compositor,
+//! Wayland protocols, audio devices, whisper.cpp build, provider settings,
+//! recent errors) as one markdown block to paste. Secrets in the provider
+//! settings are masked.
+
+use crate::audio::AudioCapture;
+use crate::config::{is_secret_key, ConfigManager};
+use serde_json::Value;
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::process::Command;
+
+/// Protocols the injector and clipboard rely on, reported as present or
+/// missing before the full list.
+const USED_PROTOCOLS: [&str; 3] = [
+    "zwp_virtual_keyboard_manager_v1",
+    "zwlr_data_control_manager_v1",
+    "ext_data_control_manager_v1",
+];
+
+const JOURNAL_LINES: &str = "30";
+
+pub fn gather(config_manager: &ConfigManager) -> String {
+    let config = config_manager.get();
+    let mut report = String::from("## hyprwhspr-rs report\n");
+
+    let mut system = format!("- hyprwhspr-rs {}\n", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(system, "- OS: {}", os_name().as_deref().unwrap_or("?"));
+    let _ = writeln!(
+        system,
+        "- Kernel: {}",
+        run("uname", &["-r"]).as_deref().unwrap_or("?")
+    );
+    push_section(&mut report, "System", &system);
+
+    let mut compositor = format!("- {}\n", compositor());
+    for var in ["XDG_SESSION_TYPE", "XDG_CURRENT_DESKTOP", "WAYLAND_DISPLAY"] {
+        let value = env::var(var).unwrap_or_else(|_| "unset".to_string());
+        let _ = writeln!(compositor, "- `{var}`: {value}");
+    }
+    push_section(&mut report, "Compositor", &compositor);
+
+    let protocols = match run("wayland-info", &[]) {
+        Some(output) => protocol_summary(&parse_interfaces(&output)),
+        None => "`wayland-info` not installed or failed\n".to_string(),
+    };
+    push_section(&mut report, "Wayland protocols", &protocols);
+
+    let mut audio = format!("- Host: {}\n", cpal::default_host().id().name());
+    let _ = writeln!(
+        audio,
+        "- `audio_device`: {}",
+        serde_json::to_string(&config.audio_device).unwrap_or_default()
+    );
+    match AudioCapture::get_available_devices() {
+        Ok(devices) if devices.is_empty() => audio.push_str("- No input devices\n"),
+        Ok(devices) => {
+            for device in devices {
+                let default = if device.is_default { " (default)" } else { "" };
+                let _ = writeln!(
+                    audio,
+                    "- [{}] {}{}: {} Hz, {} ch",
+                    device.index,
+                    device.name,
+                    default,
+                    device
+                        .sample_rate
+                        .map_or_else(|| "?".to_string(), |rate| rate.to_string()),
+                    device
+                        .channels
+                        .map_or_else(|| "?".to_string(), |channels| channels.to_string())
+                );
+            }
+        }
+        Err(err) => {
+            let _ = writeln!(audio, "- Listing devices failed: {err:#}");
+        }
+    }
+    push_section(&mut report, "Audio", &audio);
+
+    let whisper = match config_manager.get_whisper_binary_candidates(true).first() {
+        Some(binary) => {
+            let path = binary.display().to_string();
+            let version = run(&path, &["--version"])
+                .and_then(|output| output.lines().next().map(str::to_string))
+                .unwrap_or_else(|| "version unknown".to_string());
+            format!("- `{path}`: {version}\n")
+        }
+        None => "- No whisper-cli found\n".to_string(),
+    };
+    push_section(&mut report, "whisper.cpp", &whisper);
+
+    let mut transcription = serde_json::to_value(&config.transcription).unwrap_or_default();
+    redact_secrets(&mut transcription);
+    let provider = format!(
+        "```json\n{}\n```\n",
+        serde_json::to_string_pretty(&transcription).unwrap_or_default()
+    );
+    push_section(&mut report, "Provider config", &provider);
+
+    let errors = match run(
+        "journalctl",
+        &[
+            "--user",
+            "-u",
+            "hyprwhspr-rs",
+            "-p",
+            "warning",
+            "-n",
+            JOURNAL_LINES,
+            "--no-pager",
+            "-o",
+            "short-iso",
+        ],
+    ) {
+        Some(log) if !log.contains("-- No entries --") && !log.is_empty() => {
+            format!("```\n{log}\n```\n")
+        }
+        Some(_) => "None in the journal\n".to_string(),
+        None => "`journalctl` unavailable (not running under systemd?)\n".to_string(),
+    };
+    push_section(&mut report, "Recent warnings and errors", &errors);
+
+    report
+}
+
+fn push_section(report: &mut String, title: &str, body: &str) {
+    let _ = write!(report, "\n### {title}\n\n{body}");
+}
+
+/// Trimmed stdout of a command that succeeded.
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn os_name() -> Option<String> {
+    let release = fs::read_to_string("/etc/os-release").ok()?;
+    release.lines().find_map(|line| {
+        line.strip_prefix("PRETTY_NAME=")
+            .map(|name| name.trim_matches('"').to_string())
+    })
+}
+
+fn compositor() -> String {
+    let first_line = |output: String| output.lines().next().unwrap_or_default().to_string();
+    if env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some() {
+        return run("hyprctl", &["version"])
+            .map(first_line)
+            .unwrap_or_else(|| "Hyprland (hyprctl failed)".to_string());
+    }
+    if env::var_os("SWAYSOCK").is_some() {
+        return run("swaymsg", &["--version"])
+            .map(first_line)
+            .unwrap_or_else(|| "Sway (swaymsg failed)".to_string());
+    }
+    match env::var("XDG_CURRENT_DESKTOP") {
+        Ok(desktop) if !desktop.is_empty() => format!("{desktop} (version unknown)"),
+        _ => "Unknown".to_string(),
+    }
+}
+
+/// Global interface names from `wayland-info` output, in order.
+fn parse_interfaces(output: &str) -> Vec<String> {
+    let mut interfaces: Vec<String> = Vec::new();
+    for line in output.lines() {
+        let Some(rest) = line.trim_start().strip_prefix("interface: '") else {
+            continue;
+        };
+        if let Some((name, _)) = rest.split_once('\'') {
+            if !interfaces.iter().any(|known| known == name) {
+                interfaces.push(name.to_string());
+            }
+        }
+    }
+    interfaces
+}
+
+fn protocol_summary(interfaces: &[String]) -> String {
+    let mut summary = String::new();
+    for protocol in USED_PROTOCOLS {
+        let present = interfaces.iter().any(|name| name == protocol);
+        let mark = if present { "present" } else { "missing" };
+        let _ = writeln!(summary, "- `{protocol}`: {mark}");
+    }
+    let _ = writeln!(summary, "- All: {}", interfaces.join(", "));
+    summary
+}
+
+/// Masks values under keys that look like credentials, at any depth.
+fn redact_secrets(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, entry) in map.iter_mut() {
+                if is_secret_key(key) && !entry.is_null() {
+                    *entry = Value::String("<redacted>".to_string());
+                } else {
+                    redact_secrets(entry);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_secrets),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn masks_secrets_and_lists_protocols() {
+        let mut config = json!({
+            "provider": "groq",
+            "groq": { "api_key": "gsk_live", "model": "whisper-large-v3" },
+            "gemini": { "api_key": null },
+            "lan": [{ "auth_token": "abc", "host": "box" }],
+        });
+        redact_secrets(&mut config);
+        assert_eq!(
+            config,
+            json!({
+                "provider": "groq",
+                "groq": { "api_key": "<redacted>", "model": "whisper-large-v3" },
+                "gemini": { "api_key": null },
+                "lan": [{ "auth_token": "<redacted>", "host": "box" }],
+            })
+        );
+
+        let output = "\
+interface: 'wl_compositor',                              version:  6, name:  1
+interface: 'zwp_virtual_keyboard_manager_v1',            version:  1, name: 14
+interface: 'wl_output',                                  version:  4, name: 40
+\tname: DP-1
+interface: 'wl_output',                                  version:  4, name: 41";
+        let interfaces = parse_interfaces(output);
+        assert_eq!(
+            interfaces,
+            [
+                "wl_compositor",
+                "zwp_virtual_keyboard_manager_v1",
+                "wl_output"
+            ]
+        );
+        assert!(protocol_summary(&interfaces)
+            .starts_with("- `zwp_virtual_keyboard_manager_v1`: present\n"));
+    }
+}
@@ -0,0 +1,112 @@
+//! Running totals for the daemon's dictation session, inserted on request
+//! ("insert word count") for journaling, or to check that a spoken command
+//! took a different route from plain dictation.
+
+use std::time::{Duration, Instant};
+use time::macros::format_description;
+use time::OffsetDateTime;
+
+pub struct SessionStats {
+    started: Instant,
+    dictations: usize,
+    words: usize,
+    audio: Duration,
+}
+
+impl Default for SessionStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SessionStats {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            dictations: 0,
+            words: 0,
+            audio: Duration::ZERO,
+        }
+    }
+
+    /// Counts an injected transcript.
+    pub fn record(&mut self, text: &str, audio: Duration) {
+        self.dictations += 1;
+        self.words += text.split_whitespace().count();
+        self.audio += audio;
+    }
+
+    /// `template` with the current totals filled in.
+    pub fn render(&self, template: &str) -> String {
+        let now = OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc());
+        self.fill(template, self.started.elapsed(), now)
+    }
+
+    /// Expands `{words}`, `{dictations}`, `{audio}` (seconds of speech),
+    /// `{duration}` (time since the daemon started), `{date}` and `{time}`.
+    fn fill(&self, template: &str, elapsed: Duration, now: OffsetDateTime) -> String {
+        let date = format_description!("[year]-[month]-[day]");
+        let time = format_description!("[hour]:[minute]");
+        let format = |description| now.format(description).unwrap_or_default();
+
+        template
+            .replace("{words}", &self.words.to_string())
+            .replace("{dictations}", &self.dictations.to_string())
+            .replace("{audio}", &self.audio.as_secs_f32().round().to_string())
+            .replace("{duration}", &format_elapsed(elapsed))
+            .replace("{date}", &format(date))
+            .replace("{time}", &format(time))
+    }
+}
+
+/// Whether `transcript` is just the spoken `phrase`, ignoring case and the
+/// punctuation a backend adds. An empty phrase never matches.
+pub fn is_stats_phrase(transcript: &str, phrase: &str) -> bool {
+    let phrase = normalize(phrase);
+    !phrase.is_empty() && normalize(transcript) == phrase
+}
+
+/// Lowercase words without punctuation, for comparing spoken phrases.
+pub(crate) fn normalize(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    let minutes = elapsed.as_secs() / 60;
+    match minutes / 60 {
+        0 => format!("{minutes}m"),
+        hours => format!("{hours}h {:02}m", minutes % 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    #[test]
+    fn fills_totals_and_matches_the_phrase() {
+        let mut stats = SessionStats::new();
+        stats.record("Dear diary, today went well.", Duration::from_secs(3));
+        stats.record("More tomorrow.", Duration::from_millis(2_600));
+        assert_eq!(
+            stats.fill(
+                "{words} words in {dictations} dictations ({audio}s) over {duration}, {date} {time}",
+                Duration::from_secs(3_900),
+                datetime!(2026-10-16 21:07 UTC),
+            ),
+            "7 words in 2 dictations (6s) over 1h 05m, 2026-10-16 21:07"
+        );
+
+        assert!(is_stats_phrase("Insert word count.", "insert word count"));
+        assert!(!is_stats_phrase(
+            "insert word count twice",
+            "insert word count"
+        ));
+        assert!(!is_stats_phrase("", ""));
+    }
+}
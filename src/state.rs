@@ -0,0 +1,193 @@
+//! What the daemon is doing right now. Every change goes through
+//! [`AppState::transition`], which logs it and refuses ones that make no
+//! sense (injecting while recording, say) instead of letting separate flags
+//! drift apart.
+
+use anyhow::{bail, Result};
+use std::fmt;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordingTrigger {
+    HoldShortcut,
+    PressShortcut,
+    Ipc,
+    /// Enter in the terminal test mode.
+    Stdin,
+    /// The `toggle_monitor` action: records system output, not the mic.
+    Monitor,
+    /// The spoken `wake_word.phrase`; a pause ends the recording.
+    WakeWord,
+}
+
+/// When the current recording hits `max_recording_secs`; the default has
+/// no limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RecordingDeadline(Option<Instant>);
+
+impl RecordingDeadline {
+    pub fn new(limit: Option<Duration>, started: Instant) -> Self {
+        Self(limit.map(|limit| started + limit))
+    }
+
+    /// Resolves once the limit passes; never when there is none.
+    pub async fn reached(self) {
+        match self.0 {
+            Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+            None => std::future::pending().await,
+        }
+    }
+}
+
+/// Where the audio being transcribed came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessingJob {
+    Recording,
+    /// An utterance spooled before the previous shutdown.
+    Resumed,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AppState {
+    #[default]
+    Idle,
+    Recording {
+        trigger: RecordingTrigger,
+    },
+    Processing {
+        job: ProcessingJob,
+    },
+    Injecting,
+}
+
+impl AppState {
+    pub fn is_idle(self) -> bool {
+        self == Self::Idle
+    }
+
+    pub fn is_recording(self) -> bool {
+        matches!(self, Self::Recording { .. })
+    }
+
+    /// Transcribing or typing out a transcript; new recordings wait.
+    pub fn is_busy(self) -> bool {
+        matches!(self, Self::Processing { .. } | Self::Injecting)
+    }
+
+    pub fn trigger(self) -> Option<RecordingTrigger> {
+        match self {
+            Self::Recording { trigger } => Some(trigger),
+            _ => None,
+        }
+    }
+
+    /// Any state can fall back to idle (finished, cancelled or failed);
+    /// everything else has to follow the recording flow.
+    fn allows(self, next: Self) -> bool {
+        match (self, next) {
+            (Self::Idle, Self::Idle) => false,
+            (_, Self::Idle) => true,
+            (Self::Idle, Self::Recording { .. }) => true,
+            (Self::Idle, Self::Processing { job }) => job == ProcessingJob::Resumed,
+            (Self::Recording { .. }, Self::Processing { job }) => job == ProcessingJob::Recording,
+            (Self::Processing { .. }, Self::Injecting) => true,
+            // The next track of a recording transcribed track by track.
+            (Self::Injecting, Self::Processing { job }) => job == ProcessingJob::Recording,
+            _ => false,
+        }
+    }
+
+    pub fn transition(&mut self, next: Self) -> Result<()> {
+        if !self.allows(next) {
+            bail!("Invalid state transition: {} -> {}", self, next);
+        }
+        debug!("State: {} -> {}", self, next);
+        *self = next;
+        Ok(())
+    }
+}
+
+impl fmt::Display for AppState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Idle => f.write_str("idle"),
+            Self::Recording { trigger } => write!(f, "recording ({trigger:?})"),
+            Self::Processing { job } => write!(f, "processing ({job:?})"),
+            Self::Injecting => f.write_str("injecting"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follows_the_recording_flow() {
+        let mut state = AppState::default();
+        for next in [
+            AppState::Recording {
+                trigger: RecordingTrigger::HoldShortcut,
+            },
+            AppState::Processing {
+                job: ProcessingJob::Recording,
+            },
+            AppState::Injecting,
+            AppState::Processing {
+                job: ProcessingJob::Recording,
+            },
+            AppState::Injecting,
+            AppState::Idle,
+            AppState::Processing {
+                job: ProcessingJob::Resumed,
+            },
+            AppState::Idle,
+        ] {
+            state.transition(next).unwrap();
+            assert_eq!(state, next);
+        }
+    }
+
+    #[test]
+    fn rejects_transitions_out_of_order() {
+        let recording = AppState::Recording {
+            trigger: RecordingTrigger::Ipc,
+        };
+        let cases = [
+            (AppState::Idle, AppState::Idle),
+            (AppState::Idle, AppState::Injecting),
+            (recording, recording),
+            (recording, AppState::Injecting),
+            (
+                recording,
+                AppState::Processing {
+                    job: ProcessingJob::Resumed,
+                },
+            ),
+            (AppState::Injecting, recording),
+        ];
+        for (from, to) in cases {
+            let mut state = from;
+            assert!(state.transition(to).is_err(), "{from} -> {to}");
+            assert_eq!(state, from);
+        }
+    }
+
+    #[tokio::test]
+    async fn recording_limit_ends_the_recording() {
+        let started = Instant::now();
+        let limited = RecordingDeadline::new(Some(Duration::from_millis(30)), started);
+        tokio::time::timeout(Duration::from_secs(5), limited.reached())
+            .await
+            .expect("the limit should stop the recording");
+        assert!(started.elapsed() >= Duration::from_millis(30));
+
+        let unlimited = RecordingDeadline::new(None, started);
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), unlimited.reached())
+                .await
+                .is_err()
+        );
+    }
+}
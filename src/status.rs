@@ -1,10 +1,119 @@
+use crate::border::BorderIndicator;
+use crate::config::{BorderIndicatorConfig, DictationMode};
+use crate::events::{next_event, AppEvent, EventBus};
+use crate::input::InjectionReport;
 use anyhow::{Context, Result};
-use std::fs;
-use std::path::PathBuf;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::{FileTypeExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+use tokio::task::JoinHandle;
+
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+/// How often the live status is refreshed while recording, for the timer.
+const LIVE_INTERVAL: Duration = Duration::from_millis(500);
+/// Live status file in the config directory, read by Waybar and
+/// `hyprwhsprctl status`.
+pub const LIVE_STATUS_FILE: &str = "waybar.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LiveState {
+    Idle,
+    Recording,
+    Processing,
+}
+
+/// State behind `waybar.json` and the optional status FIFO.
+struct LiveStatus {
+    file: PathBuf,
+    fifo: Option<PathBuf>,
+    state: LiveState,
+    recording_started: Option<Instant>,
+    backend: String,
+    last_transcript_chars: Option<usize>,
+    meeting: Option<String>,
+    mode: DictationMode,
+    read_only: bool,
+    /// Microphone RMS and peak while recording, 0.0..=1.0.
+    level: (f32, f32),
+}
+
+impl LiveStatus {
+    /// Waybar `return-type: json` payload; `alt`/`class` carry the state so
+    /// `format-icons` and CSS can key off it.
+    fn render(&self) -> Value {
+        let elapsed = self
+            .recording_started
+            .filter(|_| self.state == LiveState::Recording)
+            .map(|started| started.elapsed().as_secs())
+            .unwrap_or_default();
+        let state = match self.state {
+            LiveState::Idle => "idle",
+            LiveState::Recording => "recording",
+            LiveState::Processing => "processing",
+        };
+        let text = match self.state {
+            LiveState::Idle => String::new(),
+            LiveState::Recording => format!("{}:{:02}", elapsed / 60, elapsed % 60),
+            LiveState::Processing => "…".to_string(),
+        };
+        let mut tooltip = format!("hyprwhspr: {state} ({}, {} mode)", self.backend, self.mode);
+        if let Some(chars) = self.last_transcript_chars {
+            tooltip.push_str(&format!("\nLast transcript: {chars} characters"));
+        }
+        if let Some(meeting) = &self.meeting {
+            tooltip.push_str(&format!("\nMeeting: {meeting}"));
+        }
+        if self.read_only {
+            tooltip.push_str("\nRead-only: transcripts are not pasted");
+        }
+
+        json!({
+            "text": text,
+            "alt": state,
+            "class": state,
+            "tooltip": tooltip,
+            "state": self.state,
+            "elapsed_secs": elapsed,
+            "backend": self.backend,
+            "last_transcript_chars": self.last_transcript_chars,
+            "meeting": self.meeting,
+            "mode": self.mode,
+            "read_only": self.read_only,
+            "level": self.level.0,
+            "peak": self.level.1,
+            "percentage": (self.level.0 * 100.0).round() as u8,
+        })
+    }
+
+    fn publish(&self) {
+        let line = self.render().to_string();
+        if let Err(err) = write_atomic(&self.file, &line) {
+            tracing::debug!("Failed to write live status: {err:#}");
+        }
+        if let Some(fifo) = &self.fifo {
+            write_fifo(fifo, &line);
+        }
+    }
+}
 
 /// Writes recording status for Waybar tray script to read
 pub struct StatusWriter {
     status_file: PathBuf,
+    conflicts_file: PathBuf,
+    config_changes_file: PathBuf,
+    injection_file: PathBuf,
+    progress_file: PathBuf,
+    live: Arc<Mutex<LiveStatus>>,
+    live_ticker: Mutex<Option<JoinHandle<()>>>,
+    border_restore_file: PathBuf,
+    border: Mutex<Option<BorderIndicator>>,
 }
 
 impl StatusWriter {
@@ -18,13 +127,48 @@ impl StatusWriter {
 
         Ok(Self {
             status_file: config_dir.join("recording_status"),
+            conflicts_file: config_dir.join("shortcut_conflicts"),
+            config_changes_file: config_dir.join("config_changes"),
+            injection_file: config_dir.join("last_injection.json"),
+            progress_file: config_dir.join("transcription_progress.json"),
+            live: Arc::new(Mutex::new(LiveStatus {
+                file: config_dir.join(LIVE_STATUS_FILE),
+                fifo: None,
+                state: LiveState::Idle,
+                recording_started: None,
+                backend: String::new(),
+                last_transcript_chars: None,
+                meeting: None,
+                mode: DictationMode::default(),
+                read_only: false,
+                level: (0.0, 0.0),
+            })),
+            live_ticker: Mutex::new(None),
+            border_restore_file: config_dir.join("border_restore"),
+            border: Mutex::new(None),
         })
     }
 
     /// Set recording status
     /// - recording=true: writes "true" to file
     /// - recording=false: removes the file (matches Python behavior)
+    ///
+    /// Also drives the live state in `waybar.json`.
     pub fn set_recording(&self, recording: bool) -> Result<()> {
+        self.set_live_state(if recording {
+            LiveState::Recording
+        } else {
+            LiveState::Idle
+        });
+        if let Some(border) = self
+            .border
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+        {
+            border.set_recording(recording);
+        }
+
         if recording {
             fs::write(&self.status_file, "true").context("Failed to write recording status")?;
             tracing::debug!("Set recording status: true");
@@ -39,6 +183,222 @@ impl StatusWriter {
         Ok(())
     }
 
+    pub fn set_processing(&self, processing: bool) {
+        self.set_live_state(if processing {
+            LiveState::Processing
+        } else {
+            LiveState::Idle
+        });
+    }
+
+    /// Label of the active transcription backend, shown in the live status.
+    pub fn set_backend(&self, backend: &str) {
+        self.update_live(|live| live.backend = backend.to_string());
+    }
+
+    pub fn set_mode(&self, mode: DictationMode) {
+        self.update_live(|live| live.mode = mode);
+    }
+
+    pub fn set_read_only(&self, read_only: bool) {
+        self.update_live(|live| live.read_only = read_only);
+    }
+
+    pub fn record_transcript(&self, text: &str) {
+        self.update_live(|live| live.last_transcript_chars = Some(text.chars().count()));
+    }
+
+    /// Publishes the microphone level for VU meters.
+    pub fn set_level(&self, rms: f32, peak: f32) {
+        self.update_live(|live| live.level = (rms, peak));
+    }
+
+    /// Calendar event the latest utterance was dictated in.
+    pub fn set_meeting(&self, meeting: Option<String>) {
+        self.update_live(|live| live.meeting = meeting);
+    }
+
+    /// Also streams every live status update as a JSON line to `fifo`
+    /// (created if missing), for `exec`-style bar modules. Updates are
+    /// dropped while nothing is reading.
+    pub fn set_fifo(&self, fifo: Option<PathBuf>) {
+        if let Some(path) = &fifo {
+            if let Err(err) = ensure_fifo(path) {
+                tracing::warn!("Status FIFO unavailable at {:?}: {err:#}", path);
+            }
+        }
+        self.update_live(|live| live.fifo = fifo);
+    }
+
+    /// Replaces the Hyprland border indicator; the old one restores the
+    /// border if it was tinted.
+    pub fn set_border_indicator(&self, config: &BorderIndicatorConfig) {
+        let indicator = BorderIndicator::maybe_new(config, self.border_restore_file.clone());
+        *self.border.lock().unwrap_or_else(PoisonError::into_inner) = indicator;
+    }
+
+    fn update_live(&self, update: impl FnOnce(&mut LiveStatus)) {
+        let mut live = self.live.lock().unwrap_or_else(PoisonError::into_inner);
+        update(&mut live);
+        live.publish();
+    }
+
+    fn set_live_state(&self, state: LiveState) {
+        self.update_live(|live| {
+            if state == LiveState::Recording && live.state != LiveState::Recording {
+                live.recording_started = Some(Instant::now());
+            }
+            if state != LiveState::Recording {
+                live.level = (0.0, 0.0);
+            }
+            live.state = state;
+        });
+
+        let mut ticker = self
+            .live_ticker
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(task) = ticker.take() {
+            task.abort();
+        }
+        if state == LiveState::Recording {
+            let live = Arc::clone(&self.live);
+            *ticker = Some(tokio::spawn(async move {
+                loop {
+                    tokio::time::sleep(LIVE_INTERVAL).await;
+                    live.lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .publish();
+                }
+            }));
+        }
+    }
+
+    /// Lists shortcut/compositor bind collisions one per line, or removes the
+    /// file when there are none.
+    pub fn set_shortcut_conflicts(&self, conflicts: &[String]) -> Result<()> {
+        if conflicts.is_empty() {
+            if self.conflicts_file.exists() {
+                fs::remove_file(&self.conflicts_file)
+                    .context("Failed to remove shortcut conflicts file")?;
+            }
+        } else {
+            fs::write(&self.conflicts_file, conflicts.join("\n"))
+                .context("Failed to write shortcut conflicts")?;
+        }
+        Ok(())
+    }
+
+    /// Records the settings touched by the most recent config reload, one
+    /// per line, so tray scripts can show what changed.
+    pub fn set_config_changes(&self, changes: &[String]) -> Result<()> {
+        fs::write(&self.config_changes_file, changes.join("\n"))
+            .context("Failed to write config changes")
+    }
+
+    /// Publishes the latest injection (size, method, target window and its
+    /// geometry) as JSON so overlays can anchor a toast near the window.
+    pub fn record_injection(&self, report: &InjectionReport) -> Result<()> {
+        let timestamp = OffsetDateTime::now_utc()
+            .format(&Rfc3339)
+            .unwrap_or_default();
+        let mut event = serde_json::to_value(report).context("Failed to serialize injection")?;
+        event["timestamp"] = Value::String(timestamp);
+
+        write_atomic(&self.injection_file, &event.to_string())
+            .context("Failed to publish injection status")
+    }
+
+    /// Rewrites `transcription_progress.json` a few times a second with the
+    /// elapsed time against the expected duration and the request timeout,
+    /// until the returned ticker is dropped. Bars can render a spinner with
+    /// an ETA, and flag requests running past their estimate.
+    pub fn start_progress(
+        &self,
+        provider: &'static str,
+        audio: Duration,
+        expected: Duration,
+        timeout: Option<Duration>,
+    ) -> ProgressTicker {
+        let path = self.progress_file.clone();
+        let task_path = path.clone();
+        let finished = Arc::new(Mutex::new(false));
+        let task_finished = Arc::clone(&finished);
+        let started = Instant::now();
+        let task = tokio::spawn(async move {
+            loop {
+                let elapsed = started.elapsed();
+                let progress = if expected.is_zero() {
+                    0.0
+                } else {
+                    (elapsed.as_secs_f32() / expected.as_secs_f32()).min(0.99)
+                };
+                let status = json!({
+                    "state": "transcribing",
+                    "provider": provider,
+                    "audio_secs": audio.as_secs_f32(),
+                    "elapsed_ms": elapsed.as_millis() as u64,
+                    "expected_ms": expected.as_millis() as u64,
+                    "eta_ms": expected.saturating_sub(elapsed).as_millis() as u64,
+                    "progress": progress,
+                    "overdue": elapsed > expected,
+                    "timeout_ms": timeout.map(|timeout| timeout.as_millis() as u64),
+                });
+                {
+                    // Held across the write so a concurrent drop can't
+                    // remove the file only for this write to recreate it.
+                    let finished = task_finished.lock().unwrap_or_else(PoisonError::into_inner);
+                    if *finished {
+                        break;
+                    }
+                    if let Err(err) = write_atomic(&task_path, &status.to_string()) {
+                        tracing::debug!("Failed to write transcription progress: {err:#}");
+                    }
+                }
+                tokio::time::sleep(PROGRESS_INTERVAL).await;
+            }
+        });
+
+        ProgressTicker {
+            task,
+            path,
+            finished,
+        }
+    }
+
+    /// Keeps the status files in step with `bus` until it is dropped.
+    pub fn follow(self: &Arc<Self>, bus: &EventBus) {
+        let writer = Arc::clone(self);
+        let mut events = bus.subscribe();
+        tokio::spawn(async move {
+            while let Some(event) = next_event(&mut events, "Status writer").await {
+                let result = match event {
+                    AppEvent::RecordingStarted => writer.set_recording(true),
+                    AppEvent::RecordingStopped { .. } => writer.set_recording(false),
+                    AppEvent::InjectionDone(report) => writer.record_injection(&report),
+                    AppEvent::ProcessingStarted => {
+                        writer.set_processing(true);
+                        Ok(())
+                    }
+                    AppEvent::ProcessingFinished => {
+                        writer.set_processing(false);
+                        Ok(())
+                    }
+                    AppEvent::TranscriptReady { text, .. } => {
+                        writer.record_transcript(&text);
+                        Ok(())
+                    }
+                    AppEvent::EmptyTranscript { .. }
+                    | AppEvent::Recovered { .. }
+                    | AppEvent::Error { .. } => Ok(()),
+                };
+                if let Err(err) = result {
+                    tracing::warn!("Failed to update status: {err:#}");
+                }
+            }
+        });
+    }
+
     pub fn is_recording(&self) -> bool {
         if let Ok(content) = fs::read_to_string(&self.status_file) {
             content.trim() == "true"
@@ -48,6 +408,71 @@ impl StatusWriter {
     }
 }
 
+/// Whether the daemon last reported read-only mode in its live status file.
+/// Without a readable file, nothing is known to be read-only.
+pub fn daemon_read_only() -> bool {
+    let Some(dirs) = directories::ProjectDirs::from("", "", "hyprwhspr-rs") else {
+        return false;
+    };
+    fs::read_to_string(dirs.config_dir().join(LIVE_STATUS_FILE))
+        .ok()
+        .and_then(|contents| serde_json::from_str::<Value>(&contents).ok())
+        .and_then(|status| status["read_only"].as_bool())
+        .unwrap_or(false)
+}
+
+/// Stops publishing transcription progress and removes the file when dropped.
+pub struct ProgressTicker {
+    task: JoinHandle<()>,
+    path: PathBuf,
+    finished: Arc<Mutex<bool>>,
+}
+
+impl Drop for ProgressTicker {
+    fn drop(&mut self) {
+        let mut finished = self.finished.lock().unwrap_or_else(PoisonError::into_inner);
+        *finished = true;
+        self.task.abort();
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn ensure_fifo(path: &Path) -> Result<()> {
+    if let Ok(metadata) = fs::metadata(path) {
+        if metadata.file_type().is_fifo() {
+            return Ok(());
+        }
+        anyhow::bail!("{:?} exists and is not a FIFO", path);
+    }
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_encoded_bytes())
+        .context("FIFO path contains a NUL byte")?;
+    // SAFETY: `c_path` is a valid NUL-terminated string for the whole call.
+    if unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) } != 0 {
+        return Err(std::io::Error::last_os_error()).context("mkfifo failed");
+    }
+    Ok(())
+}
+
+/// Non-blocking, so a missing reader never stalls the daemon.
+fn write_fifo(path: &Path, line: &str) {
+    let Ok(mut fifo) = OpenOptions::new()
+        .write(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(path)
+    else {
+        return;
+    };
+    let _ = writeln!(fifo, "{line}");
+}
+
+/// Writes via rename so watchers never see a partial file.
+fn write_atomic(path: &Path, contents: &str) -> Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, contents).with_context(|| format!("Failed to write {:?}", tmp))?;
+    fs::rename(&tmp, path).with_context(|| format!("Failed to replace {:?}", path))
+}
+
 impl Default for StatusWriter {
     fn default() -> Self {
         Self::new().expect("Failed to create StatusWriter")
@@ -0,0 +1,115 @@
+//! Dual output: the full transcript is archived (see
+//! [`crate::config::DualOutputConfig::archive_target`]) while only a short
+//! line is injected, e.g. a status dropped into chat after dictating
+//! meeting notes.
+
+use crate::config::{DualOutputConfig, SummaryMethod};
+use anyhow::{bail, Context, Result};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::time::timeout;
+use tracing::{debug, warn};
+
+/// The line to inject in place of `text`.
+pub async fn summarize(text: &str, config: &DualOutputConfig) -> String {
+    if config.summary == SummaryMethod::Command {
+        match run_command(text, config).await {
+            Ok(summary) => return summary,
+            Err(err) => warn!("Summary command failed, injecting the first sentence: {err:#}"),
+        }
+    }
+    first_sentence(text).to_string()
+}
+
+/// `text` up to the end of its first sentence or line.
+pub fn first_sentence(text: &str) -> &str {
+    let text = text.trim_start();
+    let line = text.lines().next().unwrap_or_default();
+    let mut chars = line.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        let at_boundary = chars.peek().is_none_or(|&(_, next)| next.is_whitespace());
+        if matches!(c, '.' | '!' | '?') && at_boundary {
+            return &line[..index + c.len_utf8()];
+        }
+    }
+    line.trim_end()
+}
+
+async fn run_command(text: &str, config: &DualOutputConfig) -> Result<String> {
+    let Some((program, args)) = config.command.split_first() else {
+        bail!("dual_output.command is empty");
+    };
+    debug!("Summarizing {} characters with {}", text.len(), program);
+
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Failed to start {program}"))?;
+    let stdin = child.stdin.take();
+    // A command that never reads its input can't stall the write either.
+    let run = async move {
+        if let Some(mut stdin) = stdin {
+            stdin.write_all(text.as_bytes()).await?;
+        }
+        child.wait_with_output().await
+    };
+    let output = timeout(Duration::from_millis(config.timeout_ms), run)
+        .await
+        .with_context(|| format!("{program} timed out after {}ms", config.timeout_ms))??;
+    if !output.status.success() {
+        bail!("{program} exited with {}", output.status);
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    match stdout.lines().map(str::trim).find(|line| !line.is_empty()) {
+        Some(line) => Ok(line.to_string()),
+        None => bail!("{program} printed nothing"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cuts_at_the_first_sentence() {
+        assert_eq!(
+            first_sentence("  Shipped v2.1 today. Next up: the docs!"),
+            "Shipped v2.1 today."
+        );
+        assert_eq!(first_sentence("Agenda\nReview the roadmap."), "Agenda");
+        assert_eq!(
+            first_sentence("no punctuation at all "),
+            "no punctuation at all"
+        );
+    }
+
+    #[tokio::test]
+    async fn falls_back_when_the_command_fails() {
+        let mut config = DualOutputConfig {
+            summary: SummaryMethod::Command,
+            command: vec!["sh".into(), "-c".into(), "echo; tr a-z A-Z".into()],
+            ..DualOutputConfig::default()
+        };
+        assert_eq!(
+            summarize("ship it. then rest", &config).await,
+            "SHIP IT. THEN REST"
+        );
+
+        config.command = vec!["false".into()];
+        assert_eq!(summarize("ship it. then rest", &config).await, "ship it.");
+        // Never reads stdin, so a long transcript fills the pipe.
+        config.command = vec!["sleep".into(), "5".into()];
+        config.timeout_ms = 200;
+        let long = "ship it. ".repeat(50_000);
+        let started = std::time::Instant::now();
+        assert_eq!(summarize(&long, &config).await, "ship it.");
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::audio::recording::encode_wav;
 use anyhow::{Context, Result};
 use bytes::Bytes;
 use tokio::io::{AsyncReadExt, AsyncWriteExt, BufWriter};
@@ -10,6 +11,41 @@ pub struct EncodedAudio {
     pub content_type: &'static str,
 }
 
+impl EncodedAudio {
+    /// File name for multipart uploads, matching the content type.
+    pub fn file_name(&self) -> &'static str {
+        match self.content_type {
+            "audio/wav" => "audio.wav",
+            _ => "audio.flac",
+        }
+    }
+}
+
+/// Encodes audio for upload: FLAC normally, WAV for short commands, where
+/// starting ffmpeg costs more than the larger payload.
+pub async fn encode_for_upload(audio: &[f32], short_command: bool) -> Result<EncodedAudio> {
+    if short_command {
+        Ok(encode_to_wav(audio))
+    } else {
+        encode_to_flac(audio).await
+    }
+}
+
+/// Encodes raw PCM audio (mono, 16 kHz, f32 samples) into 16-bit WAV in memory.
+pub fn encode_to_wav(audio: &[f32]) -> EncodedAudio {
+    let mut data = Vec::with_capacity(44 + audio.len() * 2);
+    encode_wav(&mut data, audio, 16_000).expect("writing to a Vec cannot fail");
+    debug!(
+        "Encoded PCM into WAV ({} bytes -> {} bytes)",
+        std::mem::size_of_val(audio),
+        data.len()
+    );
+    EncodedAudio {
+        data: Bytes::from(data),
+        content_type: "audio/wav",
+    }
+}
+
 /// Encodes raw PCM audio (mono, 16 kHz, f32 samples) into FLAC using ffmpeg.
 ///
 /// FLAC offers lossless compression with ~40-60% smaller payloads compared to WAV
@@ -0,0 +1,124 @@
+use crate::config::{PromptBoostConfig, PromptBoostDomain};
+use tracing::info;
+
+/// Tracks which domain vocabularies are active based on keywords heard in
+/// recent utterances. A keyword hit boosts the following
+/// `sticky_utterances` requests, so specialised terms only ride along while
+/// the user is actually dictating about that topic.
+#[derive(Debug, Clone)]
+pub struct PromptBooster {
+    domains: Vec<PromptBoostDomain>,
+    sticky_utterances: u32,
+    remaining: Vec<u32>,
+}
+
+impl PromptBooster {
+    pub fn new(config: &PromptBoostConfig) -> Self {
+        Self {
+            domains: config.domains.clone(),
+            sticky_utterances: config.sticky_utterances,
+            remaining: vec![0; config.domains.len()],
+        }
+    }
+
+    /// Vocabulary to append to the next remote prompt, if any domain is active.
+    pub fn vocabulary(&self) -> Option<String> {
+        let mut terms: Vec<&str> = Vec::new();
+        for (domain, remaining) in self.domains.iter().zip(&self.remaining) {
+            if *remaining == 0 {
+                continue;
+            }
+            for term in &domain.vocabulary {
+                let term = term.trim();
+                if !term.is_empty() && !terms.contains(&term) {
+                    terms.push(term);
+                }
+            }
+        }
+
+        if terms.is_empty() {
+            None
+        } else {
+            Some(terms.join(", "))
+        }
+    }
+
+    /// Ages active boosts by one utterance and re-arms any domain whose
+    /// keywords appear in `transcript`.
+    pub fn observe(&mut self, transcript: &str) {
+        let normalized = normalize(transcript);
+
+        for (domain, remaining) in self.domains.iter().zip(self.remaining.iter_mut()) {
+            *remaining = remaining.saturating_sub(1);
+
+            let hit = domain.keywords.iter().any(|keyword| {
+                let keyword = normalize(keyword);
+                !keyword.trim().is_empty() && normalized.contains(&keyword)
+            });
+            if hit {
+                if *remaining == 0 {
+                    info!("🎯 Boosting prompt with {} vocabulary", domain.name);
+                }
+                *remaining = self.sticky_utterances;
+            }
+        }
+    }
+}
+
+/// Lowercases and collapses everything but letters and digits into single
+/// spaces, padded on both ends so `contains(" word ")` matches whole words.
+fn normalize(text: &str) -> String {
+    let mut normalized = String::with_capacity(text.len() + 2);
+    normalized.push(' ');
+    for word in text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+    {
+        normalized.push_str(&word.to_lowercase());
+        normalized.push(' ');
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn booster() -> PromptBooster {
+        PromptBooster::new(&PromptBoostConfig {
+            sticky_utterances: 2,
+            domains: vec![PromptBoostDomain {
+                name: "medical".to_string(),
+                keywords: vec!["patient".to_string(), "blood pressure".to_string()],
+                vocabulary: vec!["tachycardia".to_string(), "metoprolol".to_string()],
+            }],
+        })
+    }
+
+    #[test]
+    fn keyword_hit_boosts_following_utterances() {
+        let mut booster = booster();
+        assert_eq!(booster.vocabulary(), None);
+
+        booster.observe("The patient's Blood-Pressure was high.");
+        assert_eq!(
+            booster.vocabulary().as_deref(),
+            Some("tachycardia, metoprolol")
+        );
+
+        booster.observe("unrelated");
+        assert!(booster.vocabulary().is_some());
+
+        booster.observe("still unrelated");
+        assert_eq!(booster.vocabulary(), None);
+    }
+
+    #[test]
+    fn keywords_match_whole_words_only() {
+        let mut booster = booster();
+
+        booster.observe("outpatients");
+
+        assert_eq!(booster.vocabulary(), None);
+    }
+}
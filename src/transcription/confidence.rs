@@ -0,0 +1,127 @@
+//! Second opinions for transcripts that look too thin for their audio,
+//! like three words back from thirty seconds of speech.
+
+use super::{PromptContext, TranscriptionBackend, TranscriptionResult};
+use crate::config::{ListStyle, LowConfidenceRetryConfig};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Why `result` looks wrong for `audio` seconds of speech, if it does.
+pub fn low_confidence(
+    result: &TranscriptionResult,
+    audio: Duration,
+    config: &LowConfidenceRetryConfig,
+) -> Option<String> {
+    let secs = audio.as_secs_f32();
+    if secs < config.min_audio_secs {
+        return None;
+    }
+
+    let words = result.text.split_whitespace().count();
+    if (words as f32) < secs * config.min_words_per_sec {
+        return Some(format!("{words} words for {secs:.0}s of audio"));
+    }
+
+    let logprobs: Vec<f32> = result
+        .segments
+        .iter()
+        .filter_map(|segment| segment.avg_logprob)
+        .collect();
+    if logprobs.is_empty() {
+        return None;
+    }
+    let mean = logprobs.iter().sum::<f32>() / logprobs.len() as f32;
+    (mean < config.min_avg_logprob).then(|| format!("average log-probability {mean:.2}"))
+}
+
+impl TranscriptionBackend {
+    /// Transcribes `audio` again when `first` looks too thin for it, and
+    /// returns the retry if it heard more words. The decision is logged
+    /// either way.
+    pub async fn second_opinion(
+        &self,
+        first: &TranscriptionResult,
+        audio: Vec<f32>,
+        list_starts: &[usize],
+        list_style: ListStyle,
+        context: &PromptContext<'_>,
+        config: &LowConfidenceRetryConfig,
+    ) -> Option<TranscriptionResult> {
+        let duration = Duration::from_secs_f32(audio.len() as f32 / 16_000.0);
+        let reason = low_confidence(first, duration, config)?;
+        warn!(
+            "🔁 Transcript looks unreliable ({reason}); retrying with {}",
+            self.provider().label()
+        );
+
+        // A different request, so it must not share the first one's key.
+        let key = context.utterance_key.map(|key| format!("{key}-retry"));
+        let context = PromptContext {
+            utterance_key: key.as_deref(),
+            ..*context
+        };
+        let retry = match self
+            .transcribe_list(audio, list_starts, list_style, &context)
+            .await
+        {
+            Ok(retry) => retry,
+            Err(err) => {
+                warn!("Retry failed, keeping the first transcript: {err:#}");
+                return None;
+            }
+        };
+
+        let (before, after) = (
+            first.text.split_whitespace().count(),
+            retry.text.split_whitespace().count(),
+        );
+        if after > before {
+            info!("🔁 Using the retry ({after} words instead of {before})");
+            Some(retry)
+        } else {
+            info!("🔁 Retry heard no more ({after} words); keeping the first transcript");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcription::{BackendMetrics, TranscriptSegment};
+
+    fn result(text: &str, logprob: Option<f32>) -> TranscriptionResult {
+        TranscriptionResult {
+            text: text.to_string(),
+            segments: vec![TranscriptSegment {
+                start_secs: 0.0,
+                end_secs: 30.0,
+                text: text.to_string(),
+                avg_logprob: logprob,
+                no_speech_prob: None,
+            }],
+            metrics: BackendMetrics::default(),
+        }
+    }
+
+    #[test]
+    fn flags_thin_transcripts_of_long_audio() {
+        let config = LowConfidenceRetryConfig::default();
+        let long = Duration::from_secs(30);
+        assert_eq!(
+            low_confidence(&result("thanks for watching", None), long, &config),
+            Some("3 words for 30s of audio".to_string())
+        );
+        assert_eq!(
+            low_confidence(&result("ok", None), Duration::from_secs(3), &config),
+            None
+        );
+
+        let full = "word ".repeat(60);
+        assert_eq!(
+            low_confidence(&result(&full, Some(-0.3)), long, &config),
+            None
+        );
+        assert!(low_confidence(&result(&full, Some(-1.6)), long, &config).is_some());
+    }
+}
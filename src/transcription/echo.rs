@@ -0,0 +1,74 @@
+use crate::config::EchoConfig;
+use crate::transcription::{BackendMetrics, TranscriptionResult};
+use anyhow::Result;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tracing::info;
+
+/// Answers every recording with a templated string, without a model or the
+/// network, for demos, packaging tests and pipeline work.
+pub struct EchoTranscriber {
+    template: String,
+    delay: Duration,
+    utterances: AtomicU64,
+}
+
+impl EchoTranscriber {
+    pub fn new(config: &EchoConfig) -> Self {
+        Self {
+            template: config.template.clone(),
+            delay: Duration::from_millis(config.delay_ms),
+            utterances: AtomicU64::new(0),
+        }
+    }
+
+    pub fn initialize(&self) -> Result<()> {
+        info!("✅ Echo transcription ready (no audio is transcribed)");
+        Ok(())
+    }
+
+    pub async fn transcribe(&self, audio_data: Vec<f32>) -> Result<TranscriptionResult> {
+        let start = Instant::now();
+        if !self.delay.is_zero() {
+            tokio::time::sleep(self.delay).await;
+        }
+
+        let count = self.utterances.fetch_add(1, Ordering::Relaxed) + 1;
+        let text = render(&self.template, audio_data.len() as f32 / 16000.0, count);
+        info!("✅ Transcription (echo): {}", text);
+
+        Ok(TranscriptionResult {
+            text,
+            segments: Vec::new(),
+            metrics: BackendMetrics {
+                transcription_duration: start.elapsed(),
+                ..BackendMetrics::default()
+            },
+        })
+    }
+}
+
+/// Fills `{duration}` (seconds of audio) and `{count}` (recordings so far).
+fn render(template: &str, duration_secs: f32, count: u64) -> String {
+    template
+        .replace("{duration}", &format!("{duration_secs:.1}"))
+        .replace("{count}", &count.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn echoes_the_template_for_each_recording() {
+        let echo = EchoTranscriber::new(&EchoConfig {
+            template: "Take {count}: {duration} seconds".to_string(),
+            delay_ms: 0,
+        });
+
+        let first = echo.transcribe(vec![0.0; 24_000]).await.unwrap();
+        assert_eq!(first.text, "Take 1: 1.5 seconds");
+        let second = echo.transcribe(Vec::new()).await.unwrap();
+        assert_eq!(second.text, "Take 2: 0.0 seconds");
+    }
+}
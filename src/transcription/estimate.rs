@@ -0,0 +1,78 @@
+//! Predicts how long a transcription will take from the audio length and the
+//! provider's recent history, so status bars can show an ETA.
+
+use crate::config::TranscriptionProvider;
+use std::time::Duration;
+
+/// Weight of the newest observation in the moving average.
+const SMOOTHING: f64 = 0.3;
+
+/// Short clips are dominated by fixed overhead, not audio length.
+const MIN_AUDIO_SECS: f64 = 1.0;
+
+pub struct DurationEstimator {
+    provider: TranscriptionProvider,
+    /// Wall-clock seconds spent per second of audio.
+    secs_per_audio_sec: f64,
+}
+
+impl DurationEstimator {
+    pub fn new(provider: TranscriptionProvider) -> Self {
+        Self {
+            secs_per_audio_sec: Self::baseline(&provider),
+            provider,
+        }
+    }
+
+    /// Rough starting points until a few utterances have been observed.
+    fn baseline(provider: &TranscriptionProvider) -> f64 {
+        match provider {
+            TranscriptionProvider::WhisperCpp => 0.4,
+            TranscriptionProvider::Groq => 0.25,
+            TranscriptionProvider::Gemini => 0.6,
+            TranscriptionProvider::Ssh => 0.5,
+            TranscriptionProvider::Echo => 0.0,
+        }
+    }
+
+    pub fn expected(&self, provider: &TranscriptionProvider, audio: Duration) -> Duration {
+        let rate = if *provider == self.provider {
+            self.secs_per_audio_sec
+        } else {
+            Self::baseline(provider)
+        };
+        Duration::from_secs_f64(audio.as_secs_f64().max(MIN_AUDIO_SECS) * rate)
+    }
+
+    /// Folds a completed transcription into the history; switching providers
+    /// starts over from that provider's baseline.
+    pub fn observe(&mut self, provider: &TranscriptionProvider, audio: Duration, took: Duration) {
+        if *provider != self.provider {
+            *self = Self::new(*provider);
+        }
+
+        let observed = took.as_secs_f64() / audio.as_secs_f64().max(MIN_AUDIO_SECS);
+        self.secs_per_audio_sec += SMOOTHING * (observed - self.secs_per_audio_sec);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn learns_from_observed_durations() {
+        let provider = TranscriptionProvider::Groq;
+        let mut estimator = DurationEstimator::new(provider);
+        let audio = Duration::from_secs(10);
+        let baseline = estimator.expected(&provider, audio);
+
+        for _ in 0..20 {
+            estimator.observe(&provider, audio, Duration::from_secs(5));
+        }
+
+        let learned = estimator.expected(&provider, audio);
+        assert!(learned > baseline);
+        assert!((learned.as_secs_f64() - 5.0).abs() < 0.1);
+    }
+}
@@ -1,25 +1,43 @@
-use crate::config::GeminiConfig;
-use crate::transcription::audio::{encode_to_flac, EncodedAudio};
+use crate::config::{GeminiConfig, ResponseFilterConfig, TranscriptionProvider};
+use crate::transcription::audio::{encode_for_upload, EncodedAudio};
 use crate::transcription::postprocess::clean_transcription;
-use crate::transcription::{BackendMetrics, TranscriptionResult};
-use anyhow::{Context, Result};
+use crate::transcription::prompt::PromptContext;
+use crate::transcription::request_log::RequestLogger;
+use crate::transcription::response_filter;
+use crate::transcription::{BackendMetrics, TranscriptionResult, IDEMPOTENCY_KEY_HEADER};
+use anyhow::{bail, Context, Result};
 use base64::engine::general_purpose::STANDARD as BASE64;
 use base64::Engine;
-use reqwest::{Client, Url};
+use regex::Regex;
+use reqwest::{Client, Response, Url};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::cmp;
+use std::sync::{Arc, LazyLock};
 use std::time::{Duration, Instant};
 use tokio::time::sleep;
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
+
+/// Gemini rejects `generateContent` requests above 20 MB, so recordings whose
+/// base64 payload would get close to that are routed through the Files API.
+const INLINE_REQUEST_LIMIT_BYTES: usize = 20 * 1024 * 1024;
+const INLINE_REQUEST_HEADROOM_BYTES: usize = 256 * 1024;
+const FILE_ACTIVE_POLL_ATTEMPTS: u32 = 20;
+const FILE_ACTIVE_POLL_INTERVAL: Duration = Duration::from_millis(500);
 
 #[derive(Clone)]
 pub struct GeminiTranscriber {
     client: Client,
     endpoint: Url,
+    upload_endpoint: Url,
+    files_base: Url,
     api_key: String,
     prompt: String,
     temperature: f32,
     max_output_tokens: u32,
+    extra_body: Map<String, Value>,
+    response_filter: ResponseFilterConfig,
+    request_log: Option<Arc<RequestLogger>>,
     model: String,
     request_timeout: Duration,
     max_retries: u32,
@@ -32,6 +50,7 @@ impl GeminiTranscriber {
         request_timeout: Duration,
         max_retries: u32,
         prompt: String,
+        request_log: Option<Arc<RequestLogger>>,
     ) -> Result<Self> {
         let trimmed_endpoint = config.endpoint.trim_end_matches('/');
         let endpoint = Url::parse(&format!(
@@ -39,6 +58,12 @@ impl GeminiTranscriber {
             trimmed_endpoint, config.model
         ))
         .with_context(|| format!("Invalid Gemini endpoint: {}", config.endpoint))?;
+        // `.../v1beta/models` joined with `files/<id>` resolves to `.../v1beta/files/<id>`.
+        let files_base = Url::parse(trimmed_endpoint)
+            .with_context(|| format!("Invalid Gemini endpoint: {}", config.endpoint))?;
+        let upload_endpoint = Url::parse(&config.upload_endpoint).with_context(|| {
+            format!("Invalid Gemini upload endpoint: {}", config.upload_endpoint)
+        })?;
 
         let client = Client::builder()
             .user_agent("hyprwhspr-rs (gemini)")
@@ -51,10 +76,15 @@ impl GeminiTranscriber {
         Ok(Self {
             client,
             endpoint,
+            upload_endpoint,
+            files_base,
             api_key,
             prompt,
             temperature: config.temperature,
             max_output_tokens: config.max_output_tokens,
+            extra_body: config.extra_body.clone(),
+            response_filter: config.response_filter.clone(),
+            request_log,
             model: config.model.clone(),
             request_timeout,
             max_retries,
@@ -77,10 +107,15 @@ impl GeminiTranscriber {
         "Gemini 2.5 Pro Flash"
     }
 
-    pub async fn transcribe(&self, audio_data: Vec<f32>) -> Result<TranscriptionResult> {
+    pub async fn transcribe(
+        &self,
+        audio_data: Vec<f32>,
+        context: &PromptContext<'_>,
+    ) -> Result<TranscriptionResult> {
         if audio_data.is_empty() {
             return Ok(TranscriptionResult {
                 text: String::new(),
+                segments: Vec::new(),
                 metrics: BackendMetrics::default(),
             });
         }
@@ -92,15 +127,54 @@ impl GeminiTranscriber {
         );
 
         let encode_start = Instant::now();
-        let encoded = encode_to_flac(&audio_data).await?;
-        let audio_payload = BASE64.encode(encoded.data.as_ref());
+        let encoded = encode_for_upload(&audio_data, context.short_command).await?;
+        let audio_payload = if exceeds_inline_limit(encoded.data.len()) {
+            None
+        } else {
+            Some(BASE64.encode(encoded.data.as_ref()))
+        };
         let encode_duration = encode_start.elapsed();
-        let payload_bytes = audio_payload.len();
+        let payload_bytes = audio_payload
+            .as_ref()
+            .map(String::len)
+            .unwrap_or(encoded.data.len());
 
+        let prompt = context.render(&self.prompt, TranscriptionProvider::Gemini);
         let transcribe_start = Instant::now();
-        let (raw, timings) = self.send_with_retry(&encoded, &audio_payload).await?;
+        let (raw, timings) = match audio_payload.as_deref() {
+            Some(payload) => {
+                self.request_transcript(
+                    &encoded,
+                    AudioSource::Inline(payload),
+                    &prompt,
+                    context.utterance_key,
+                )
+                .await?
+            }
+            None => {
+                info!(
+                    "📤 Audio payload ({} bytes) exceeds Gemini inline limit; uploading via Files API",
+                    encoded.data.len()
+                );
+                let upload_start = Instant::now();
+                let file = self.upload_file(&encoded).await?;
+                let file_upload_duration = upload_start.elapsed();
+                let result = self
+                    .request_transcript(
+                        &encoded,
+                        AudioSource::File(&file.uri),
+                        &prompt,
+                        context.utterance_key,
+                    )
+                    .await;
+                self.delete_file(&file).await;
+                let (raw, mut timings) = result?;
+                timings.upload += file_upload_duration;
+                (raw, timings)
+            }
+        };
         let transcription_duration = transcribe_start.elapsed();
-        let cleaned = clean_transcription(&raw, &self.prompt);
+        let cleaned = clean_transcription(&raw, &prompt);
 
         if cleaned.is_empty() {
             warn!("Gemini returned empty or non-speech transcription");
@@ -114,23 +188,65 @@ impl GeminiTranscriber {
             upload_duration: Some(timings.upload),
             response_duration: Some(timings.response),
             transcription_duration,
+            fallback_used: false,
         };
 
         Ok(TranscriptionResult {
             text: cleaned,
+            segments: Vec::new(),
             metrics,
         })
     }
 
+    /// Sends the request and, when the reply trips the response filter,
+    /// re-asks once with a stricter instruction. A reply that is rejected
+    /// twice is an error, so the audio isn't lost silently.
+    async fn request_transcript(
+        &self,
+        audio: &EncodedAudio,
+        source: AudioSource<'_>,
+        prompt: &str,
+        utterance_key: Option<&str>,
+    ) -> Result<(String, NetworkTimings)> {
+        let instruction = build_instruction(prompt, false);
+        let (raw, mut timings) = self
+            .send_with_retry(audio, source, &instruction, utterance_key)
+            .await?;
+        let rejection = match response_filter::check(&raw, &self.response_filter) {
+            Ok(text) => return Ok((text.to_string(), timings)),
+            Err(rejection) => rejection,
+        };
+
+        warn!("Gemini {rejection}; retrying with a stricter instruction");
+        // The body differs, so the stricter request gets its own key.
+        let strict_key = utterance_key.map(|key| format!("{key}-strict"));
+        let strict_instruction = build_instruction(prompt, true);
+        let (retried, retry_timings) = self
+            .send_with_retry(audio, source, &strict_instruction, strict_key.as_deref())
+            .await?;
+        timings.upload += retry_timings.upload;
+        timings.response += retry_timings.response;
+
+        match response_filter::check(&retried, &self.response_filter) {
+            Ok(text) => Ok((text.to_string(), timings)),
+            Err(rejection) => bail!("Gemini {rejection} twice; not injecting it"),
+        }
+    }
+
     async fn send_with_retry(
         &self,
         audio: &EncodedAudio,
-        payload: &str,
+        source: AudioSource<'_>,
+        instruction: &str,
+        utterance_key: Option<&str>,
     ) -> Result<(String, NetworkTimings)> {
         let attempts = cmp::max(1, self.max_retries.saturating_add(1));
 
         for attempt in 0..attempts {
-            match self.send_once(audio, payload).await {
+            match self
+                .send_once(audio, source, instruction, utterance_key)
+                .await
+            {
                 Ok(result) => return Ok(result),
                 Err(err) => {
                     if attempt + 1 == attempts {
@@ -156,25 +272,32 @@ impl GeminiTranscriber {
     async fn send_once(
         &self,
         audio: &EncodedAudio,
-        payload: &str,
+        source: AudioSource<'_>,
+        instruction: &str,
+        utterance_key: Option<&str>,
     ) -> Result<(String, NetworkTimings)> {
         let mut url = self.endpoint.clone();
         url.query_pairs_mut().append_pair("key", &self.api_key);
 
-        let instruction = build_instruction(&self.prompt);
+        let audio_part = match source {
+            AudioSource::Inline(payload) => GeminiPart::InlineData {
+                inline_data: InlineData {
+                    mime_type: audio.content_type,
+                    data: payload,
+                },
+            },
+            AudioSource::File(uri) => GeminiPart::FileData {
+                file_data: FileData {
+                    mime_type: audio.content_type,
+                    file_uri: uri,
+                },
+            },
+        };
 
         let body = GeminiRequest {
             contents: vec![GeminiContent {
                 role: "user",
-                parts: vec![
-                    GeminiPart::Text { text: &instruction },
-                    GeminiPart::InlineData {
-                        inline_data: InlineData {
-                            mime_type: audio.content_type,
-                            data: payload,
-                        },
-                    },
-                ],
+                parts: vec![GeminiPart::Text { text: instruction }, audio_part],
             }],
             generation_config: GenerationConfig {
                 temperature: self.temperature,
@@ -182,25 +305,41 @@ impl GeminiTranscriber {
             },
         };
 
+        let mut body =
+            serde_json::to_value(&body).context("Failed to serialize Gemini request body")?;
+        merge_json(&mut body, &self.extra_body);
+
+        if let Some(log) = &self.request_log {
+            log.record_request("gemini", &url, &body);
+        }
+
+        let mut request = self.client.post(url).json(&body);
+        if let Some(key) = utterance_key {
+            request = request.header(IDEMPOTENCY_KEY_HEADER, key);
+        }
+
         let request_start = Instant::now();
-        let response = self
-            .client
-            .post(url)
-            .json(&body)
+        let response = request
             .send()
             .await
             .context("Failed to send Gemini transcription request")?;
 
         let upload_duration = request_start.elapsed();
+        let status = response.status();
 
-        if response.status().is_success() {
+        if status.is_success() {
             let parse_start = Instant::now();
-            let payload: GeminiResponse = response
-                .json()
+            let bytes = response
+                .bytes()
                 .await
+                .context("Failed to read Gemini transcription response")?;
+            self.log_response(status.as_u16(), &bytes);
+            let payload: GeminiResponse = serde_json::from_slice(&bytes)
                 .context("Failed to deserialize Gemini transcription response")?;
             let response_duration = parse_start.elapsed();
-            let text = extract_text(payload).unwrap_or_default();
+            let text = extract_text(payload)
+                .map(|text| strip_model_framing(&text))
+                .unwrap_or_default();
             return Ok((
                 text,
                 NetworkTimings {
@@ -210,11 +349,9 @@ impl GeminiTranscriber {
             ));
         }
 
-        let status = response.status();
-        let body = response
-            .json::<GeminiErrorResponse>()
-            .await
-            .unwrap_or_default();
+        let bytes = response.bytes().await.unwrap_or_default();
+        self.log_response(status.as_u16(), &bytes);
+        let body = serde_json::from_slice::<GeminiErrorResponse>(&bytes).unwrap_or_default();
         let message = body
             .error
             .and_then(|err| err.message)
@@ -222,6 +359,193 @@ impl GeminiTranscriber {
 
         Err(anyhow::anyhow!(message).context(format!("Gemini request failed ({status})")))
     }
+
+    /// Uploads the encoded audio with the Files API resumable protocol and waits
+    /// until Gemini reports the file as ready for use in `generateContent`.
+    async fn upload_file(&self, audio: &EncodedAudio) -> Result<GeminiFile> {
+        let mut start_url = self.upload_endpoint.clone();
+        start_url
+            .query_pairs_mut()
+            .append_pair("key", &self.api_key);
+
+        let start = self
+            .client
+            .post(start_url)
+            .header("X-Goog-Upload-Protocol", "resumable")
+            .header("X-Goog-Upload-Command", "start")
+            .header(
+                "X-Goog-Upload-Header-Content-Length",
+                audio.data.len().to_string(),
+            )
+            .header("X-Goog-Upload-Header-Content-Type", audio.content_type)
+            .json(&FileUploadRequest {
+                file: FileUploadMetadata {
+                    display_name: "hyprwhspr-rs recording",
+                },
+            })
+            .send()
+            .await
+            .context("Failed to start Gemini file upload")?;
+
+        if !start.status().is_success() {
+            return Err(self.response_error(start, "Gemini file upload").await);
+        }
+
+        let upload_url = start
+            .headers()
+            .get("x-goog-upload-url")
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+            .context("Gemini file upload response did not include an upload URL")?;
+
+        let response = self
+            .client
+            .post(upload_url)
+            .header("X-Goog-Upload-Offset", "0")
+            .header("X-Goog-Upload-Command", "upload, finalize")
+            .body(audio.data.clone())
+            .send()
+            .await
+            .context("Failed to upload audio to Gemini Files API")?;
+
+        if !response.status().is_success() {
+            return Err(self.response_error(response, "Gemini file upload").await);
+        }
+
+        let uploaded: FileUploadResponse = response
+            .json()
+            .await
+            .context("Failed to deserialize Gemini file upload response")?;
+        debug!(
+            name = uploaded.file.name.as_str(),
+            uri = uploaded.file.uri.as_str(),
+            "Uploaded audio to Gemini Files API"
+        );
+
+        self.wait_until_active(uploaded.file).await
+    }
+
+    async fn wait_until_active(&self, mut file: GeminiFile) -> Result<GeminiFile> {
+        for _ in 0..FILE_ACTIVE_POLL_ATTEMPTS {
+            match file.state.as_deref() {
+                Some("PROCESSING") => {}
+                Some("FAILED") => {
+                    self.delete_file(&file).await;
+                    anyhow::bail!("Gemini failed to process uploaded audio ({})", file.name);
+                }
+                _ => return Ok(file),
+            }
+
+            sleep(FILE_ACTIVE_POLL_INTERVAL).await;
+
+            let mut url = self
+                .files_base
+                .join(&file.name)
+                .with_context(|| format!("Invalid Gemini file name: {}", file.name))?;
+            url.query_pairs_mut().append_pair("key", &self.api_key);
+
+            let response = self
+                .client
+                .get(url)
+                .send()
+                .await
+                .context("Failed to query Gemini file state")?;
+            if !response.status().is_success() {
+                return Err(self.response_error(response, "Gemini file lookup").await);
+            }
+            file = response
+                .json()
+                .await
+                .context("Failed to deserialize Gemini file state")?;
+        }
+
+        self.delete_file(&file).await;
+        anyhow::bail!(
+            "Gemini did not finish processing uploaded audio ({})",
+            file.name
+        )
+    }
+
+    async fn response_error(&self, response: Response, action: &str) -> anyhow::Error {
+        let status = response.status();
+        let bytes = response.bytes().await.unwrap_or_default();
+        self.log_response(status.as_u16(), &bytes);
+        let body = serde_json::from_slice::<GeminiErrorResponse>(&bytes).unwrap_or_default();
+        let message = body
+            .error
+            .and_then(|err| err.message)
+            .unwrap_or_else(|| format!("{action} failed with status {status}"));
+
+        anyhow::anyhow!(message).context(format!("{action} failed ({status})"))
+    }
+
+    fn log_response(&self, status: u16, body: &[u8]) {
+        if let Some(log) = &self.request_log {
+            log.record_response("gemini", status, body);
+        }
+    }
+
+    /// Best-effort cleanup so recordings don't linger for the Files API's
+    /// default 48 hour retention.
+    async fn delete_file(&self, file: &GeminiFile) {
+        let Ok(mut url) = self.files_base.join(&file.name) else {
+            debug!(
+                name = file.name.as_str(),
+                "Skipping delete of unparsable Gemini file name"
+            );
+            return;
+        };
+        url.query_pairs_mut().append_pair("key", &self.api_key);
+
+        match self.client.delete(url).send().await {
+            Ok(response) if response.status().is_success() => {
+                debug!(name = file.name.as_str(), "Deleted uploaded Gemini file");
+            }
+            Ok(response) => {
+                debug!(
+                    name = file.name.as_str(),
+                    status = %response.status(),
+                    "Failed to delete uploaded Gemini file"
+                );
+            }
+            Err(err) => {
+                debug!(
+                    name = file.name.as_str(),
+                    "Failed to delete uploaded Gemini file: {}", err
+                );
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum AudioSource<'a> {
+    Inline(&'a str),
+    File(&'a str),
+}
+
+/// Deep-merges `extra` into `target`: nested objects are merged key by key,
+/// anything else in `extra` replaces the existing value.
+fn merge_json(target: &mut Value, extra: &Map<String, Value>) {
+    let Value::Object(target) = target else {
+        return;
+    };
+
+    for (key, value) in extra {
+        match (target.get_mut(key), value) {
+            (Some(existing), Value::Object(nested)) if existing.is_object() => {
+                merge_json(existing, nested);
+            }
+            _ => {
+                target.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+fn exceeds_inline_limit(encoded_len: usize) -> bool {
+    let base64_len = encoded_len.div_ceil(3) * 4;
+    base64_len + INLINE_REQUEST_HEADROOM_BYTES > INLINE_REQUEST_LIMIT_BYTES
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -230,11 +554,17 @@ struct NetworkTimings {
     response: Duration,
 }
 
-fn build_instruction(prompt: &str) -> String {
+fn build_instruction(prompt: &str, strict: bool) -> String {
     let mut instruction = String::from(
         "You are a dedicated speech-to-text engine. Return only the verbatim transcription of the provided audio.\n",
     );
 
+    if strict {
+        instruction.push_str(
+            "Output nothing but the spoken words, in the language they were spoken. No greetings, explanations, labels, or apologies. If there is no speech, output nothing.\n",
+        );
+    }
+
     if !prompt.trim().is_empty() {
         instruction.push_str("\nTranscription style guidance: ");
         instruction.push_str(prompt.trim());
@@ -243,6 +573,69 @@ fn build_instruction(prompt: &str) -> String {
     instruction
 }
 
+static PREAMBLE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)^(?:(?:sure|okay|ok|certainly|of course|absolutely)[!,.]*\s+)?here(?:'s| is) (?:the|your) (?:\w+ )?transcri\w*(?: of (?:the|your) (?:provided |attached )?audio(?: clip| file)?)?\s*:\s*",
+    )
+    .expect("valid Gemini preamble regex")
+});
+
+static LABEL_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^\**(?:verbatim )?transcri(?:ption|pt)\**\s*:\s*\**\s*")
+        .expect("valid Gemini label regex")
+});
+
+static FENCE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)^```[\w-]*[ \t]*\n?(?P<body>.*?)\n?```$").expect("valid code fence regex")
+});
+
+const QUOTE_PAIRS: &[(char, char)] = &[
+    ('"', '"'),
+    ('\'', '\''),
+    ('\u{201C}', '\u{201D}'),
+    ('\u{2018}', '\u{2019}'),
+    ('\u{00AB}', '\u{00BB}'),
+    ('\u{201E}', '\u{201C}'),
+];
+
+/// Removes framing Gemini sometimes adds despite the instruction: a
+/// "Here's the transcription:" preamble, a "Transcription:" label, markdown
+/// code fences, and quotes around the whole reply.
+fn strip_model_framing(text: &str) -> String {
+    let mut current = text.trim().to_string();
+
+    loop {
+        let mut next = current.clone();
+
+        if let Some(caps) = FENCE_REGEX.captures(&next) {
+            next = caps["body"].trim().to_string();
+        }
+        if let Some(found) = PREAMBLE_REGEX.find(&next) {
+            next = next[found.end()..].trim().to_string();
+        }
+        if let Some(found) = LABEL_REGEX.find(&next) {
+            next = next[found.end()..].trim().to_string();
+        }
+        if let Some(inner) = strip_wrapping_quotes(&next) {
+            next = inner.trim().to_string();
+        }
+
+        if next == current {
+            return current;
+        }
+        current = next;
+    }
+}
+
+fn strip_wrapping_quotes(text: &str) -> Option<&str> {
+    QUOTE_PAIRS.iter().find_map(|&(open, close)| {
+        let inner = text.strip_prefix(open)?.strip_suffix(close)?;
+        // `"a" and "b"` is two quotations, not one wrapped reply.
+        let nested = inner.contains(open) || inner.contains(close);
+        (!nested && !inner.trim().is_empty()).then_some(inner)
+    })
+}
+
 fn extract_text(response: GeminiResponse) -> Option<String> {
     response
         .candidates
@@ -273,6 +666,7 @@ struct GeminiContent<'a> {
 enum GeminiPart<'a> {
     Text { text: &'a str },
     InlineData { inline_data: InlineData<'a> },
+    FileData { file_data: FileData<'a> },
 }
 
 #[derive(Serialize)]
@@ -282,6 +676,36 @@ struct InlineData<'a> {
     data: &'a str,
 }
 
+#[derive(Serialize)]
+struct FileData<'a> {
+    #[serde(rename = "mimeType")]
+    mime_type: &'a str,
+    #[serde(rename = "fileUri")]
+    file_uri: &'a str,
+}
+
+#[derive(Serialize)]
+struct FileUploadRequest<'a> {
+    file: FileUploadMetadata<'a>,
+}
+
+#[derive(Serialize)]
+struct FileUploadMetadata<'a> {
+    display_name: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct FileUploadResponse {
+    file: GeminiFile,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiFile {
+    name: String,
+    uri: String,
+    state: Option<String>,
+}
+
 #[derive(Serialize)]
 struct GenerationConfig {
     temperature: f32,
@@ -318,3 +742,81 @@ struct GeminiErrorResponse {
 struct GeminiError {
     message: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn merge_json_extends_nested_objects() {
+        let mut body = json!({
+            "generationConfig": {"temperature": 0.0, "maxOutputTokens": 1024},
+            "contents": []
+        });
+        let extra = json!({
+            "generationConfig": {"topP": 0.9, "temperature": 0.3},
+            "safetySettings": [{"category": "HARM_CATEGORY_HARASSMENT", "threshold": "BLOCK_NONE"}]
+        });
+        merge_json(&mut body, extra.as_object().unwrap());
+
+        assert_eq!(body["generationConfig"]["maxOutputTokens"], 1024);
+        assert_eq!(body["generationConfig"]["topP"], 0.9);
+        assert_eq!(body["generationConfig"]["temperature"], 0.3);
+        assert!(body["safetySettings"].is_array());
+    }
+
+    #[test]
+    fn strips_preambles_and_labels() {
+        assert_eq!(
+            strip_model_framing(
+                "Sure! Here's the transcription of the audio:\n\nDeploy the staging branch."
+            ),
+            "Deploy the staging branch."
+        );
+        assert_eq!(
+            strip_model_framing("Here is the verbatim transcription: ok let's ship it"),
+            "ok let's ship it"
+        );
+        assert_eq!(
+            strip_model_framing("**Transcription:** Meeting moved to Thursday."),
+            "Meeting moved to Thursday."
+        );
+        assert_eq!(
+            strip_model_framing("Transcript: \"rename the helper\""),
+            "rename the helper"
+        );
+    }
+
+    #[test]
+    fn strips_code_fences_and_wrapping_quotes() {
+        assert_eq!(
+            strip_model_framing("```\nRun cargo test before pushing.\n```"),
+            "Run cargo test before pushing."
+        );
+        assert_eq!(
+            strip_model_framing("```text\n\"Add a retry to the uploader.\"\n```\n"),
+            "Add a retry to the uploader."
+        );
+        assert_eq!(
+            strip_model_framing("\u{201C}Call me back tomorrow.\u{201D}"),
+            "Call me back tomorrow."
+        );
+    }
+
+    #[test]
+    fn keeps_dictated_quotes_and_prose() {
+        assert_eq!(
+            strip_model_framing(r#""Yes" and "no" are both fine"#),
+            r#""Yes" and "no" are both fine"#
+        );
+        assert_eq!(
+            strip_model_framing("The transcription service is down again."),
+            "The transcription service is down again."
+        );
+        assert_eq!(
+            strip_model_framing("Here's the plan: we ship Friday."),
+            "Here's the plan: we ship Friday."
+        );
+    }
+}
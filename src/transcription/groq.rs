@@ -1,14 +1,25 @@
-use crate::config::GroqConfig;
-use crate::transcription::audio::{encode_to_flac, EncodedAudio};
+use crate::config::{GroqConfig, TranscriptionProvider};
+use crate::transcription::audio::{encode_for_upload, EncodedAudio};
 use crate::transcription::postprocess::clean_transcription;
-use crate::transcription::{BackendMetrics, TranscriptionResult};
+use crate::transcription::prompt::PromptContext;
+use crate::transcription::request_log::RequestLogger;
+use crate::transcription::{
+    BackendMetrics, TranscriptSegment, TranscriptionResult, IDEMPOTENCY_KEY_HEADER,
+};
 use anyhow::{Context, Result};
 use reqwest::{multipart, Client, Url};
 use serde::Deserialize;
+use serde_json::{json, Map, Value};
 use std::cmp;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::time::sleep;
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
+
+/// Segments whose average log-probability falls below this value are only
+/// treated as silence when the no-speech probability also exceeds the
+/// configured threshold (mirrors whisper.cpp's `--logprob-thold` default).
+const LOW_CONFIDENCE_LOGPROB: f32 = -1.0;
 
 #[derive(Clone)]
 pub struct GroqTranscriber {
@@ -17,6 +28,9 @@ pub struct GroqTranscriber {
     api_key: String,
     model: String,
     prompt: String,
+    no_speech_threshold: f32,
+    extra_fields: Vec<(String, String)>,
+    request_log: Option<Arc<RequestLogger>>,
     request_timeout: Duration,
     max_retries: u32,
 }
@@ -28,6 +42,7 @@ impl GroqTranscriber {
         request_timeout: Duration,
         max_retries: u32,
         prompt: String,
+        request_log: Option<Arc<RequestLogger>>,
     ) -> Result<Self> {
         let endpoint = Url::parse(&config.endpoint)
             .with_context(|| format!("Invalid Groq endpoint: {}", config.endpoint))?;
@@ -46,6 +61,9 @@ impl GroqTranscriber {
             api_key,
             model: config.model.clone(),
             prompt,
+            no_speech_threshold: config.no_speech_threshold,
+            extra_fields: extra_form_fields(&config.extra_body),
+            request_log,
             request_timeout,
             max_retries,
         })
@@ -67,10 +85,15 @@ impl GroqTranscriber {
         "Groq Whisper"
     }
 
-    pub async fn transcribe(&self, audio_data: Vec<f32>) -> Result<TranscriptionResult> {
+    pub async fn transcribe(
+        &self,
+        audio_data: Vec<f32>,
+        context: &PromptContext<'_>,
+    ) -> Result<TranscriptionResult> {
         if audio_data.is_empty() {
             return Ok(TranscriptionResult {
                 text: String::new(),
+                segments: Vec::new(),
                 metrics: BackendMetrics::default(),
             });
         }
@@ -82,14 +105,18 @@ impl GroqTranscriber {
         );
 
         let encode_start = Instant::now();
-        let encoded = encode_to_flac(&audio_data).await?;
+        let encoded = encode_for_upload(&audio_data, context.short_command).await?;
         let encode_duration = encode_start.elapsed();
         let encoded_len = encoded.data.len();
 
+        let prompt = context.render(&self.prompt, TranscriptionProvider::Groq);
         let transcribe_start = Instant::now();
-        let (raw, timings) = self.send_with_retry(&encoded).await?;
+        let (payload, timings) = self
+            .send_with_retry(&encoded, &prompt, context.utterance_key)
+            .await?;
         let transcription_duration = transcribe_start.elapsed();
-        let cleaned = clean_transcription(&raw, &self.prompt);
+        let (raw, segments) = payload.into_transcript(self.no_speech_threshold);
+        let cleaned = clean_transcription(&raw, &prompt);
 
         if cleaned.is_empty() {
             warn!("Groq returned empty or non-speech transcription");
@@ -103,19 +130,26 @@ impl GroqTranscriber {
             upload_duration: Some(timings.upload),
             response_duration: Some(timings.response),
             transcription_duration,
+            fallback_used: false,
         };
 
         Ok(TranscriptionResult {
             text: cleaned,
+            segments,
             metrics,
         })
     }
 
-    async fn send_with_retry(&self, audio: &EncodedAudio) -> Result<(String, NetworkTimings)> {
+    async fn send_with_retry(
+        &self,
+        audio: &EncodedAudio,
+        prompt: &str,
+        utterance_key: Option<&str>,
+    ) -> Result<(GroqTranscriptionResponse, NetworkTimings)> {
         let attempts = cmp::max(1, self.max_retries.saturating_add(1));
 
         for attempt in 0..attempts {
-            match self.send_once(audio).await {
+            match self.send_once(audio, prompt, utterance_key).await {
                 Ok(result) => return Ok(result),
                 Err(err) => {
                     let is_last_attempt = attempt + 1 == attempts;
@@ -139,44 +173,88 @@ impl GroqTranscriber {
         Err(anyhow::anyhow!("Unknown Groq transcription failure"))
     }
 
-    async fn send_once(&self, audio: &EncodedAudio) -> Result<(String, NetworkTimings)> {
-        let mut form = multipart::Form::new()
-            .text("model", self.model.clone())
-            .text("response_format", "json".to_string())
-            .text("temperature", "0");
+    async fn send_once(
+        &self,
+        audio: &EncodedAudio,
+        prompt: &str,
+        utterance_key: Option<&str>,
+    ) -> Result<(GroqTranscriptionResponse, NetworkTimings)> {
+        let mut fields = vec![
+            ("model".to_string(), self.model.clone()),
+            ("response_format".to_string(), "verbose_json".to_string()),
+            ("temperature".to_string(), "0".to_string()),
+        ];
+
+        if !prompt.trim().is_empty() {
+            fields.push(("prompt".to_string(), prompt.to_string()));
+        }
+
+        // Configured extras replace built-in fields of the same name.
+        fields.retain(|(name, _)| !self.extra_fields.iter().any(|(extra, _)| extra == name));
+        fields.extend(self.extra_fields.iter().cloned());
+
+        if let Some(log) = &self.request_log {
+            let logged_fields: Vec<Value> = fields
+                .iter()
+                .map(|(name, value)| json!({ "name": name, "value": value }))
+                .collect();
+            log.record_request(
+                "groq",
+                &self.endpoint,
+                &json!({
+                    "fields": logged_fields,
+                    "file": {
+                        "name": audio.file_name(),
+                        "content_type": audio.content_type,
+                        "bytes": audio.data.len(),
+                    },
+                }),
+            );
+        }
 
-        if !self.prompt.trim().is_empty() {
-            form = form.text("prompt", self.prompt.clone());
+        let mut form = multipart::Form::new();
+        for (name, value) in fields {
+            form = form.text(name, value);
         }
 
         let file_part = multipart::Part::stream(audio.data.clone())
-            .file_name("audio.flac")
+            .file_name(audio.file_name())
             .mime_str(audio.content_type)
             .context("Failed to set Groq audio content type")?;
 
         form = form.part("file", file_part);
 
-        let request_start = Instant::now();
-        let response = self
+        let mut request = self
             .client
             .post(self.endpoint.clone())
             .bearer_auth(&self.api_key)
-            .multipart(form)
+            .multipart(form);
+        // Retries of one utterance share a key so the server can collapse them.
+        if let Some(key) = utterance_key {
+            request = request.header(IDEMPOTENCY_KEY_HEADER, key);
+        }
+
+        let request_start = Instant::now();
+        let response = request
             .send()
             .await
             .context("Failed to send Groq transcription request")?;
 
         let upload_duration = request_start.elapsed();
+        let status = response.status();
 
-        if response.status().is_success() {
+        if status.is_success() {
             let parse_start = Instant::now();
-            let payload: GroqTranscriptionResponse = response
-                .json()
+            let bytes = response
+                .bytes()
                 .await
+                .context("Failed to read Groq transcription response")?;
+            self.log_response(status.as_u16(), &bytes);
+            let payload: GroqTranscriptionResponse = serde_json::from_slice(&bytes)
                 .context("Failed to deserialize Groq transcription response")?;
             let response_duration = parse_start.elapsed();
             return Ok((
-                payload.text.unwrap_or_default(),
+                payload,
                 NetworkTimings {
                     upload: upload_duration,
                     response: response_duration,
@@ -184,11 +262,9 @@ impl GroqTranscriber {
             ));
         }
 
-        let status = response.status();
-        let body = response
-            .json::<GroqErrorResponse>()
-            .await
-            .unwrap_or_default();
+        let bytes = response.bytes().await.unwrap_or_default();
+        self.log_response(status.as_u16(), &bytes);
+        let body = serde_json::from_slice::<GroqErrorResponse>(&bytes).unwrap_or_default();
 
         let message = body
             .error
@@ -197,6 +273,12 @@ impl GroqTranscriber {
 
         Err(anyhow::anyhow!(message).context(format!("Groq request failed ({status})")))
     }
+
+    fn log_response(&self, status: u16, body: &[u8]) {
+        if let Some(log) = &self.request_log {
+            log.record_response("groq", status, body);
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -208,6 +290,122 @@ struct NetworkTimings {
 #[derive(Debug, Deserialize, Default)]
 struct GroqTranscriptionResponse {
     text: Option<String>,
+    #[serde(default)]
+    segments: Vec<GroqSegment>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GroqSegment {
+    #[serde(default)]
+    start: f32,
+    #[serde(default)]
+    end: f32,
+    #[serde(default)]
+    text: String,
+    avg_logprob: Option<f32>,
+    no_speech_prob: Option<f32>,
+}
+
+impl GroqSegment {
+    fn is_likely_silence(&self, no_speech_threshold: f32) -> bool {
+        let Some(no_speech_prob) = self.no_speech_prob else {
+            return false;
+        };
+        let low_confidence = self
+            .avg_logprob
+            .map(|logprob| logprob < LOW_CONFIDENCE_LOGPROB)
+            .unwrap_or(true);
+        no_speech_prob > no_speech_threshold && low_confidence
+    }
+}
+
+impl GroqTranscriptionResponse {
+    /// Flattens the verbose response into text, dropping segments that Whisper
+    /// itself considers non-speech. Falls back to the top-level `text` field
+    /// when the provider returns no segments.
+    fn into_transcript(self, no_speech_threshold: f32) -> (String, Vec<TranscriptSegment>) {
+        if self.segments.is_empty() {
+            return (self.text.unwrap_or_default(), Vec::new());
+        }
+
+        let total = self.segments.len();
+        let kept: Vec<GroqSegment> = self
+            .segments
+            .into_iter()
+            .filter(|segment| !segment.is_likely_silence(no_speech_threshold))
+            .collect();
+
+        let text = if kept.len() == total {
+            self.text.unwrap_or_else(|| join_segment_text(&kept))
+        } else {
+            debug!(
+                dropped = total - kept.len(),
+                total, "Dropped low-confidence Groq segments"
+            );
+            join_segment_text(&kept)
+        };
+
+        let segments = kept
+            .into_iter()
+            .map(|segment| TranscriptSegment {
+                start_secs: segment.start,
+                end_secs: segment.end,
+                text: segment.text.trim().to_string(),
+                avg_logprob: segment.avg_logprob,
+                no_speech_prob: segment.no_speech_prob,
+            })
+            .collect();
+
+        (text, segments)
+    }
+}
+
+/// Flattens the configured `extra_body` map into multipart text fields. Arrays
+/// become repeated `name[]` fields, matching the OpenAI-compatible form layout.
+fn extra_form_fields(extra: &Map<String, Value>) -> Vec<(String, String)> {
+    let mut fields = Vec::new();
+
+    for (name, value) in extra {
+        match value {
+            Value::Null => {}
+            Value::Array(items) => {
+                let field_name = if name.ends_with("[]") {
+                    name.clone()
+                } else {
+                    format!("{name}[]")
+                };
+                for item in items {
+                    if let Some(text) = form_value(item) {
+                        fields.push((field_name.clone(), text));
+                    }
+                }
+            }
+            other => {
+                if let Some(text) = form_value(other) {
+                    fields.push((name.clone(), text));
+                }
+            }
+        }
+    }
+
+    fields
+}
+
+fn form_value(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(text) => Some(text.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+fn join_segment_text(segments: &[GroqSegment]) -> String {
+    segments
+        .iter()
+        .map(|segment| segment.text.trim())
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -219,3 +417,69 @@ struct GroqErrorResponse {
 struct GroqErrorDetail {
     message: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> GroqTranscriptionResponse {
+        serde_json::from_str(json).expect("valid verbose_json payload")
+    }
+
+    #[test]
+    fn verbose_json_keeps_confident_segments() {
+        let payload = parse(
+            r#"{"text":" Hello world.","segments":[
+                {"start":0.0,"end":1.2,"text":" Hello world.","avg_logprob":-0.2,"no_speech_prob":0.01}
+            ]}"#,
+        );
+        let (text, segments) = payload.into_transcript(0.6);
+        assert_eq!(text, " Hello world.");
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].text, "Hello world.");
+        assert_eq!(segments[0].end_secs, 1.2);
+    }
+
+    #[test]
+    fn verbose_json_drops_silent_segments() {
+        let payload = parse(
+            r#"{"text":" Hello. Thank you.","segments":[
+                {"start":0.0,"end":1.0,"text":" Hello.","avg_logprob":-0.3,"no_speech_prob":0.05},
+                {"start":1.0,"end":3.0,"text":" Thank you.","avg_logprob":-1.4,"no_speech_prob":0.92}
+            ]}"#,
+        );
+        let (text, segments) = payload.into_transcript(0.6);
+        assert_eq!(text, "Hello.");
+        assert_eq!(segments.len(), 1);
+    }
+
+    #[test]
+    fn extra_body_flattens_into_form_fields() {
+        let extra: Map<String, Value> = serde_json::from_str(
+            r#"{"timestamp_granularities":["segment","word"],"language":"en","temperature":0.2,"skip":null}"#,
+        )
+        .unwrap();
+        let mut fields = extra_form_fields(&extra);
+        fields.sort();
+        assert_eq!(
+            fields,
+            vec![
+                ("language".to_string(), "en".to_string()),
+                ("temperature".to_string(), "0.2".to_string()),
+                (
+                    "timestamp_granularities[]".to_string(),
+                    "segment".to_string()
+                ),
+                ("timestamp_granularities[]".to_string(), "word".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn plain_json_falls_back_to_text() {
+        let payload = parse(r#"{"text":"just text"}"#);
+        let (text, segments) = payload.into_transcript(0.6);
+        assert_eq!(text, "just text");
+        assert!(segments.is_empty());
+    }
+}
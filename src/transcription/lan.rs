@@ -0,0 +1,266 @@
+use crate::config::{LanOffloadConfig, TranscriptionProvider};
+use crate::transcription::audio::encode_for_upload;
+use crate::transcription::postprocess::clean_transcription;
+use crate::transcription::prompt::PromptContext;
+use crate::transcription::{BackendMetrics, TranscriptionResult};
+use anyhow::{Context, Result};
+use reqwest::{multipart, Client, Url};
+use serde::Deserialize;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::runtime::{Handle, RuntimeFlavor};
+use tracing::{debug, info, warn};
+
+/// A transcription server found on the local network.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LanService {
+    pub name: String,
+    pub endpoint: Url,
+}
+
+/// Browses for `service_type` with `avahi-browse` and returns the first
+/// resolved service. Returns `None` when avahi is unavailable, nothing answers
+/// within `discovery_timeout_ms`, or no advertisement resolves to a usable address.
+pub fn discover(config: &LanOffloadConfig) -> Option<LanService> {
+    // Backends are built synchronously from async code; waiting for avahi on
+    // a runtime worker would stall every task queued behind it.
+    match Handle::try_current() {
+        Ok(runtime) if runtime.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(|| browse(config))
+        }
+        _ => browse(config),
+    }
+}
+
+fn browse(config: &LanOffloadConfig) -> Option<LanService> {
+    let timeout = Duration::from_millis(config.discovery_timeout_ms);
+    let mut child = match Command::new("avahi-browse")
+        .arg("--resolve")
+        .arg("--parsable")
+        .arg("--terminate")
+        .arg(&config.service_type)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(err) => {
+            warn!("mDNS discovery unavailable (avahi-browse): {}", err);
+            return None;
+        }
+    };
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(25)),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                break;
+            }
+        }
+    }
+
+    let mut output = String::new();
+    if let Some(mut stdout) = child.stdout.take() {
+        let _ = stdout.read_to_string(&mut output);
+    }
+
+    let service = output
+        .lines()
+        .find_map(|line| parse_avahi_line(line, &config.default_path));
+    if service.is_none() {
+        debug!(
+            service_type = config.service_type.as_str(),
+            "No LAN transcription server answered"
+        );
+    }
+    service
+}
+
+/// Parses one `avahi-browse --parsable --resolve` record:
+/// `=;iface;protocol;name;type;domain;hostname;address;port;txt`.
+/// IPv6 records are skipped because link-local addresses lack a usable scope.
+fn parse_avahi_line(line: &str, default_path: &str) -> Option<LanService> {
+    let fields: Vec<&str> = line.split(';').collect();
+    if fields.len() < 9 || fields[0] != "=" || fields[2] != "IPv4" {
+        return None;
+    }
+
+    let name = fields[3].replace("\\032", " ");
+    let address = fields[7];
+    let port: u16 = fields[8].parse().ok()?;
+    let txt = fields.get(9).copied().unwrap_or_default();
+
+    let path = txt
+        .split('"')
+        .find_map(|entry| entry.strip_prefix("path="))
+        .unwrap_or(default_path);
+    let path = path.trim_start_matches('/');
+
+    let endpoint = Url::parse(&format!("http://{address}:{port}/{path}")).ok()?;
+    Some(LanService { name, endpoint })
+}
+
+/// Posts FLAC audio to a self-hosted whisper server. Speaks both whisper.cpp's
+/// `/inference` and OpenAI-style `/v1/audio/transcriptions` form fields, which
+/// both answer with `{"text": ...}`.
+#[derive(Clone)]
+pub struct LanTranscriber {
+    client: Client,
+    service: LanService,
+    model: Option<String>,
+    prompt: String,
+}
+
+impl LanTranscriber {
+    pub fn new(
+        service: LanService,
+        config: &LanOffloadConfig,
+        request_timeout: Duration,
+        prompt: String,
+    ) -> Result<Self> {
+        let client = Client::builder()
+            .user_agent("hyprwhspr-rs (lan)")
+            .connect_timeout(Duration::from_secs(2))
+            .timeout(request_timeout)
+            .pool_idle_timeout(Duration::from_secs(30))
+            .build()
+            .context("Failed to build LAN HTTP client")?;
+
+        Ok(Self {
+            client,
+            service,
+            model: config
+                .model
+                .clone()
+                .filter(|model| !model.trim().is_empty()),
+            prompt,
+        })
+    }
+
+    pub fn initialize(&self) -> Result<()> {
+        info!(
+            "✅ Offloading transcription to {} ({})",
+            self.service.name, self.service.endpoint
+        );
+        Ok(())
+    }
+
+    pub async fn transcribe(
+        &self,
+        audio_data: Vec<f32>,
+        context: &PromptContext<'_>,
+    ) -> Result<TranscriptionResult> {
+        let prompt = context.render(&self.prompt, TranscriptionProvider::WhisperCpp);
+        let encode_start = Instant::now();
+        let encoded = encode_for_upload(&audio_data, context.short_command).await?;
+        let encode_duration = encode_start.elapsed();
+        let encoded_len = encoded.data.len();
+
+        let mut form = multipart::Form::new()
+            .text("response_format", "json")
+            .text("temperature", "0");
+        if let Some(model) = &self.model {
+            form = form.text("model", model.clone());
+        }
+        if !prompt.trim().is_empty() {
+            form = form.text("prompt", prompt.clone());
+        }
+
+        let file_part = multipart::Part::stream(encoded.data.clone())
+            .file_name(encoded.file_name())
+            .mime_str(encoded.content_type)
+            .context("Failed to set LAN audio content type")?;
+        form = form.part("file", file_part);
+
+        let request_start = Instant::now();
+        let response = self
+            .client
+            .post(self.service.endpoint.clone())
+            .multipart(form)
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach {}", self.service.endpoint))?;
+        let upload_duration = request_start.elapsed();
+
+        let status = response.status();
+        if !status.is_success() {
+            anyhow::bail!("{} responded with status {status}", self.service.name);
+        }
+
+        let parse_start = Instant::now();
+        let payload: LanTranscriptionResponse = response
+            .json()
+            .await
+            .context("Failed to deserialize LAN transcription response")?;
+        let response_duration = parse_start.elapsed();
+
+        let cleaned = clean_transcription(&payload.text, &prompt);
+        if cleaned.is_empty() {
+            warn!("LAN server returned empty or non-speech transcription");
+        } else {
+            info!("✅ Transcription (LAN): {}", cleaned);
+        }
+
+        Ok(TranscriptionResult {
+            text: cleaned,
+            segments: Vec::new(),
+            metrics: BackendMetrics {
+                encode_duration: Some(encode_duration),
+                encoded_bytes: Some(encoded_len),
+                upload_duration: Some(upload_duration),
+                response_duration: Some(response_duration),
+                transcription_duration: request_start.elapsed(),
+                fallback_used: false,
+            },
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct LanTranscriptionResponse {
+    #[serde(default)]
+    text: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_resolved_ipv4_record_with_txt_path() {
+        let line = "=;eth0;IPv4;gpu\\032box;_hyprwhspr-stt._tcp;local;gpu-box.local;192.168.1.20;8080;\"path=/v1/audio/transcriptions\" \"model=large-v3\"";
+
+        let service = parse_avahi_line(line, "/inference").unwrap();
+
+        assert_eq!(service.name, "gpu box");
+        assert_eq!(
+            service.endpoint.as_str(),
+            "http://192.168.1.20:8080/v1/audio/transcriptions"
+        );
+    }
+
+    #[test]
+    fn skips_unresolved_and_ipv6_records() {
+        let browse = "+;eth0;IPv4;gpu-box;_hyprwhspr-stt._tcp;local";
+        let ipv6 = "=;eth0;IPv6;gpu-box;_hyprwhspr-stt._tcp;local;gpu-box.local;fe80::1;8080;";
+
+        assert!(parse_avahi_line(browse, "/inference").is_none());
+        assert!(parse_avahi_line(ipv6, "/inference").is_none());
+    }
+
+    #[test]
+    fn falls_back_to_default_path() {
+        let line = "=;eth0;IPv4;gpu-box;_hyprwhspr-stt._tcp;local;gpu-box.local;10.0.0.2;9000;";
+
+        let service = parse_avahi_line(line, "/inference").unwrap();
+
+        assert_eq!(service.endpoint.as_str(), "http://10.0.0.2:9000/inference");
+    }
+}
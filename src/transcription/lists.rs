@@ -0,0 +1,77 @@
+//! Dictated lists: a recording is cut at the long pauses fast VAD found,
+//! each piece is transcribed on its own, and the pieces become list items.
+
+use crate::audio::SegmentBreak;
+use crate::config::ListStyle;
+
+/// Offsets in the 16 kHz transcription audio where a new item starts: the
+/// breaks with a pause of at least `min_pause_ms`, rescaled from the
+/// `source_rate` fast VAD ran at.
+pub fn item_starts(breaks: &[SegmentBreak], source_rate: u32, min_pause_ms: u32) -> Vec<usize> {
+    breaks
+        .iter()
+        .filter(|segment| segment.pause_ms >= min_pause_ms)
+        .map(|segment| (segment.offset as u64 * 16_000 / source_rate.max(1) as u64) as usize)
+        .collect()
+}
+
+/// Joins item transcripts in `style`, skipping empty ones. Bullets and
+/// numbers drop the closing period a backend puts on each item.
+pub fn format_list(items: &[String], style: ListStyle) -> String {
+    let items = items
+        .iter()
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty());
+    let lines: Vec<String> = match style {
+        ListStyle::Newline => items.map(str::to_string).collect(),
+        ListStyle::Bullet => items.map(|item| format!("- {}", bare(item))).collect(),
+        ListStyle::Numbered => items
+            .enumerate()
+            .map(|(index, item)| format!("{}. {}", index + 1, bare(item)))
+            .collect(),
+    };
+    lines.join("\n")
+}
+
+fn bare(item: &str) -> &str {
+    match item.strip_suffix('.') {
+        Some(stripped) if !stripped.ends_with('.') => stripped,
+        _ => item,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_items_and_keeps_short_pauses() {
+        let breaks = [
+            SegmentBreak {
+                offset: 48_000,
+                pause_ms: 600,
+            },
+            SegmentBreak {
+                offset: 96_000,
+                pause_ms: 1_500,
+            },
+        ];
+        assert_eq!(item_starts(&breaks, 48_000, 1_200), vec![32_000]);
+
+        let items = vec![
+            "Milk.".to_string(),
+            " ".to_string(),
+            "Eggs, two dozen.".to_string(),
+            "Wait...".to_string(),
+        ];
+        assert_eq!(
+            format_list(&items, ListStyle::Newline),
+            "Milk.\nEggs, two dozen.\nWait..."
+        );
+        assert_eq!(
+            format_list(&items, ListStyle::Numbered),
+            "1. Milk\n2. Eggs, two dozen\n3. Wait..."
+        );
+        assert_eq!(format_list(&items[..1], ListStyle::Bullet), "- Milk");
+    }
+}
@@ -1,25 +1,98 @@
 mod audio;
+mod boost;
+mod confidence;
+mod echo;
+mod estimate;
+#[cfg(feature = "remote")]
 mod gemini;
+#[cfg(feature = "remote")]
 mod groq;
+#[cfg(feature = "remote")]
+mod lan;
+mod lists;
 mod postprocess;
 mod prompt;
+#[cfg(feature = "remote")]
+mod request_log;
+#[cfg(feature = "remote")]
+mod response_filter;
+mod ssh;
+mod writing_script;
 
-use crate::config::{Config, ConfigManager, TranscriptionProvider};
+use crate::config::{Config, ConfigManager, ListStyle, TranscriptionProvider};
 use crate::whisper::{WhisperManager, WhisperVadOptions};
-use anyhow::{Context, Result};
+#[cfg(not(feature = "remote"))]
+use anyhow::bail;
+#[cfg(feature = "remote")]
+use anyhow::Context;
+use anyhow::Result;
+use prompt::{fit_prompt, max_prompt_tokens};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "remote")]
 use std::env;
-use std::time::Duration;
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "remote")]
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+#[cfg(feature = "remote")]
+use tracing::info;
+use tracing::warn;
 
-pub use audio::{encode_to_flac, EncodedAudio};
+pub use audio::{encode_for_upload, encode_to_flac, encode_to_wav, EncodedAudio};
+pub use boost::PromptBooster;
+pub use confidence::low_confidence;
+pub use echo::EchoTranscriber;
+pub use estimate::DurationEstimator;
+#[cfg(feature = "remote")]
 pub use gemini::GeminiTranscriber;
+#[cfg(feature = "remote")]
 pub use groq::GroqTranscriber;
+#[cfg(feature = "remote")]
+pub use lan::{LanService, LanTranscriber};
+pub use lists::{format_list, item_starts};
 pub use postprocess::{clean_transcription, contains_only_non_speech_markers, is_prompt_artifact};
-pub use prompt::{PromptBlueprint, DEFAULT_PROMPT};
+pub use prompt::{PromptBlueprint, PromptContext, DEFAULT_PROMPT};
+#[cfg(feature = "remote")]
+pub use request_log::RequestLogger;
+pub use ssh::SshTranscriber;
+pub use writing_script::is_unexpected_script;
+
+/// Header carrying [`utterance_key`] on Groq and Gemini requests.
+#[cfg(feature = "remote")]
+pub(crate) const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Returns a fresh key identifying one utterance. Every attempt at
+/// transcribing that utterance reuses it, so a retried request and a late
+/// original can be recognised as the same dictation.
+pub fn utterance_key() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos())
+        .unwrap_or_default();
+    format!(
+        "hyprwhspr-{:x}-{:x}-{:x}",
+        std::process::id(),
+        nanos,
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
 
 pub enum TranscriptionBackend {
     Whisper(WhisperManager),
+    /// whisper_cpp with requests offloaded to a LAN server; `local` is used
+    /// whenever the server fails.
+    #[cfg(feature = "remote")]
+    LanOffload {
+        remote: LanTranscriber,
+        local: WhisperManager,
+    },
+    #[cfg(feature = "remote")]
     Groq(GroqTranscriber),
+    #[cfg(feature = "remote")]
     Gemini(GeminiTranscriber),
+    Ssh(SshTranscriber),
+    Echo(EchoTranscriber),
 }
 
 #[derive(Debug, Clone, Default)]
@@ -29,11 +102,26 @@ pub struct BackendMetrics {
     pub upload_duration: Option<Duration>,
     pub response_duration: Option<Duration>,
     pub transcription_duration: Duration,
+    /// The primary backend failed and a fallback produced the result.
+    pub fallback_used: bool,
+}
+
+/// A timed span of transcribed speech as reported by providers that expose segments.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptSegment {
+    pub start_secs: f32,
+    pub end_secs: f32,
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avg_logprob: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub no_speech_prob: Option<f32>,
 }
 
 #[derive(Debug, Clone)]
 pub struct TranscriptionResult {
     pub text: String,
+    pub segments: Vec<TranscriptSegment>,
     pub metrics: BackendMetrics,
 }
 
@@ -44,6 +132,7 @@ impl TranscriptionBackend {
         vad: WhisperVadOptions,
     ) -> Result<Self> {
         let timeout = Duration::from_secs(config.transcription.request_timeout_secs.max(5));
+        #[cfg(feature = "remote")]
         let retries = config.transcription.max_retries;
 
         match config.transcription.provider {
@@ -62,8 +151,28 @@ impl TranscriptionBackend {
                     vad,
                     whisper_cfg.no_speech_threshold,
                 )?;
+
+                let lan_cfg = &config.transcription.lan_offload;
+                #[cfg(not(feature = "remote"))]
+                if lan_cfg.enabled {
+                    warn!("LAN offload needs a build with the `remote` feature; using local whisper.cpp");
+                }
+                #[cfg(feature = "remote")]
+                if lan_cfg.enabled {
+                    if let Some(service) = lan::discover(lan_cfg) {
+                        let prompt = Self::prompt_for(config, TranscriptionProvider::WhisperCpp);
+                        let remote = LanTranscriber::new(service, lan_cfg, timeout, prompt)?;
+                        return Ok(Self::LanOffload {
+                            remote,
+                            local: manager,
+                        });
+                    }
+                    info!("🏠 No LAN transcription server found; using local whisper.cpp");
+                }
+
                 Ok(Self::Whisper(manager))
             }
+            #[cfg(feature = "remote")]
             TranscriptionProvider::Groq => {
                 let prompt = Self::prompt_for(config, TranscriptionProvider::Groq);
                 let api_key = env::var("GROQ_API_KEY")
@@ -74,9 +183,11 @@ impl TranscriptionBackend {
                     timeout,
                     retries,
                     prompt,
+                    Self::request_logger(config_manager, config)?,
                 )?;
                 Ok(Self::Groq(provider))
             }
+            #[cfg(feature = "remote")]
             TranscriptionProvider::Gemini => {
                 let prompt = Self::prompt_for(config, TranscriptionProvider::Gemini);
                 let api_key = env::var("GEMINI_API_KEY")
@@ -87,28 +198,121 @@ impl TranscriptionBackend {
                     timeout,
                     retries,
                     prompt,
+                    Self::request_logger(config_manager, config)?,
                 )?;
                 Ok(Self::Gemini(provider))
             }
+            #[cfg(not(feature = "remote"))]
+            TranscriptionProvider::Groq | TranscriptionProvider::Gemini => {
+                bail!(
+                    "{:?} transcription needs a build with the `remote` feature",
+                    config.transcription.provider
+                )
+            }
+            TranscriptionProvider::Ssh => {
+                let prompt = Self::prompt_for(config, TranscriptionProvider::Ssh);
+                let provider = SshTranscriber::new(&config.transcription.ssh, timeout, prompt)?;
+                Ok(Self::Ssh(provider))
+            }
+            TranscriptionProvider::Echo => {
+                Ok(Self::Echo(EchoTranscriber::new(&config.transcription.echo)))
+            }
+        }
+    }
+
+    /// Builds and initializes the backend for short commands when
+    /// `short_command.provider` differs from the main provider.
+    pub fn build_short_command(
+        config_manager: &ConfigManager,
+        config: &Config,
+        vad: WhisperVadOptions,
+    ) -> Result<Option<Self>> {
+        let Some(provider) = config
+            .transcription
+            .short_command
+            .provider
+            .filter(|provider| *provider != config.transcription.provider)
+        else {
+            return Ok(None);
+        };
+        let mut config = config.clone();
+        config.transcription.provider = provider;
+        let backend = Self::build(config_manager, &config, vad)?;
+        backend.initialize()?;
+        Ok(Some(backend))
+    }
+
+    /// The backend that retries low-confidence transcripts, when
+    /// `retry_low_confidence` is on and differs from the main one.
+    pub fn build_retry(
+        config_manager: &ConfigManager,
+        config: &Config,
+        vad: WhisperVadOptions,
+    ) -> Result<Option<Self>> {
+        let retry = &config.transcription.retry_low_confidence;
+        if !retry.enabled {
+            return Ok(None);
+        }
+        let mut retry_config = config.clone();
+        let transcription = &mut retry_config.transcription;
+        if let Some(provider) = retry.provider {
+            transcription.provider = provider;
+        }
+        if let (TranscriptionProvider::WhisperCpp, Some(model)) =
+            (transcription.provider, &retry.whisper_model)
+        {
+            transcription.whisper_cpp.model = model.clone();
         }
+        if retry_config.transcription == config.transcription {
+            warn!("retry_low_confidence names neither another provider nor another model");
+            return Ok(None);
+        }
+
+        let backend = Self::build(config_manager, &retry_config, vad)?;
+        backend.initialize()?;
+        Ok(Some(backend))
     }
 
     pub fn initialize(&self) -> Result<()> {
         match self {
             TranscriptionBackend::Whisper(manager) => manager.initialize(),
+            #[cfg(feature = "remote")]
+            TranscriptionBackend::LanOffload { remote, local } => {
+                local.initialize()?;
+                remote.initialize()
+            }
+            #[cfg(feature = "remote")]
             TranscriptionBackend::Groq(provider) => provider.initialize(),
+            #[cfg(feature = "remote")]
             TranscriptionBackend::Gemini(provider) => provider.initialize(),
+            TranscriptionBackend::Ssh(provider) => provider.initialize(),
+            TranscriptionBackend::Echo(provider) => provider.initialize(),
         }
     }
 
     pub fn provider(&self) -> TranscriptionProvider {
         match self {
             TranscriptionBackend::Whisper(_) => TranscriptionProvider::WhisperCpp,
+            #[cfg(feature = "remote")]
+            TranscriptionBackend::LanOffload { .. } => TranscriptionProvider::WhisperCpp,
+            #[cfg(feature = "remote")]
             TranscriptionBackend::Groq(_) => TranscriptionProvider::Groq,
+            #[cfg(feature = "remote")]
             TranscriptionBackend::Gemini(_) => TranscriptionProvider::Gemini,
+            TranscriptionBackend::Ssh(_) => TranscriptionProvider::Ssh,
+            TranscriptionBackend::Echo(_) => TranscriptionProvider::Echo,
         }
     }
 
+    /// Whether audio stays on this machine; LAN offload and SSH count as
+    /// remote.
+    pub fn is_local(&self) -> bool {
+        matches!(
+            self,
+            TranscriptionBackend::Whisper(_) | TranscriptionBackend::Echo(_)
+        )
+    }
+
     pub fn needs_refresh(current: &Config, new: &Config) -> bool {
         if current.transcription.provider != new.transcription.provider {
             return true;
@@ -117,11 +321,13 @@ impl TranscriptionBackend {
         match new.transcription.provider {
             TranscriptionProvider::WhisperCpp => {
                 current.transcription.whisper_cpp != new.transcription.whisper_cpp
+                    || current.transcription.lan_offload != new.transcription.lan_offload
             }
             TranscriptionProvider::Groq => {
                 current.transcription.request_timeout_secs != new.transcription.request_timeout_secs
                     || current.transcription.max_retries != new.transcription.max_retries
                     || current.transcription.groq != new.transcription.groq
+                    || current.transcription.request_log != new.transcription.request_log
                     || Self::prompt_for(current, TranscriptionProvider::Groq)
                         != Self::prompt_for(new, TranscriptionProvider::Groq)
             }
@@ -129,24 +335,132 @@ impl TranscriptionBackend {
                 current.transcription.request_timeout_secs != new.transcription.request_timeout_secs
                     || current.transcription.max_retries != new.transcription.max_retries
                     || current.transcription.gemini != new.transcription.gemini
+                    || current.transcription.request_log != new.transcription.request_log
                     || Self::prompt_for(current, TranscriptionProvider::Gemini)
                         != Self::prompt_for(new, TranscriptionProvider::Gemini)
             }
+            TranscriptionProvider::Ssh => {
+                current.transcription.request_timeout_secs != new.transcription.request_timeout_secs
+                    || current.transcription.ssh != new.transcription.ssh
+                    || Self::prompt_for(current, TranscriptionProvider::Ssh)
+                        != Self::prompt_for(new, TranscriptionProvider::Ssh)
+            }
+            TranscriptionProvider::Echo => current.transcription.echo != new.transcription.echo,
         }
     }
 
-    pub async fn transcribe(&self, audio_data: Vec<f32>) -> Result<TranscriptionResult> {
+    /// Transcribes `audio_data`. Vocabulary in `context` (see
+    /// [`PromptBooster`]) is appended to remote (Groq/Gemini) prompts for
+    /// this request only; local prompts get it only where they place
+    /// `{{vocabulary}}`.
+    pub async fn transcribe(
+        &self,
+        audio_data: Vec<f32>,
+        context: &PromptContext<'_>,
+    ) -> Result<TranscriptionResult> {
+        #[cfg(feature = "remote")]
+        let remote = PromptContext {
+            append_vocabulary: true,
+            ..*context
+        };
+        let local = PromptContext {
+            append_vocabulary: false,
+            ..*context
+        };
+
         match self {
-            TranscriptionBackend::Whisper(manager) => manager.transcribe(audio_data).await,
-            TranscriptionBackend::Groq(provider) => provider.transcribe(audio_data).await,
-            TranscriptionBackend::Gemini(provider) => provider.transcribe(audio_data).await,
+            TranscriptionBackend::Whisper(manager) => manager.transcribe(audio_data, &local).await,
+            #[cfg(feature = "remote")]
+            TranscriptionBackend::LanOffload {
+                remote: lan,
+                local: whisper,
+            } => match lan.transcribe(audio_data.clone(), &local).await {
+                Ok(result) => Ok(result),
+                Err(err) => {
+                    warn!("LAN transcription failed, falling back to local whisper.cpp: {err:#}");
+                    let mut result = whisper.transcribe(audio_data, &local).await?;
+                    result.metrics.fallback_used = true;
+                    Ok(result)
+                }
+            },
+            #[cfg(feature = "remote")]
+            TranscriptionBackend::Groq(provider) => provider.transcribe(audio_data, &remote).await,
+            #[cfg(feature = "remote")]
+            TranscriptionBackend::Gemini(provider) => {
+                provider.transcribe(audio_data, &remote).await
+            }
+            TranscriptionBackend::Ssh(provider) => provider.transcribe(audio_data, &local).await,
+            TranscriptionBackend::Echo(provider) => provider.transcribe(audio_data).await,
+        }
+    }
+
+    /// Transcribes each span of `audio_data` between `starts` on its own
+    /// and joins the transcripts as a list in `style`; without starts this
+    /// is [`Self::transcribe`].
+    pub async fn transcribe_list(
+        &self,
+        audio_data: Vec<f32>,
+        starts: &[usize],
+        style: ListStyle,
+        context: &PromptContext<'_>,
+    ) -> Result<TranscriptionResult> {
+        if starts.is_empty() {
+            return self.transcribe(audio_data, context).await;
         }
+
+        let len = audio_data.len();
+        let bounds: Vec<usize> = std::iter::once(0)
+            .chain(starts.iter().map(|&start| start.min(len)))
+            .chain(std::iter::once(len))
+            .collect();
+        let mut items = Vec::with_capacity(bounds.len() - 1);
+        let mut metrics = BackendMetrics::default();
+        for (index, span) in bounds.windows(2).enumerate() {
+            if span[0] >= span[1] {
+                continue;
+            }
+            // Each item is its own request, so it needs its own key.
+            let key = context.utterance_key.map(|key| format!("{key}-{index}"));
+            let item_context = PromptContext {
+                utterance_key: key.as_deref(),
+                ..*context
+            };
+            let result = self
+                .transcribe(audio_data[span[0]..span[1]].to_vec(), &item_context)
+                .await?;
+            metrics.transcription_duration += result.metrics.transcription_duration;
+            metrics.fallback_used |= result.metrics.fallback_used;
+            items.push(result.text);
+        }
+
+        Ok(TranscriptionResult {
+            text: format_list(&items, style),
+            segments: Vec::new(),
+            metrics,
+        })
     }
 }
 
 impl TranscriptionBackend {
+    #[cfg(feature = "remote")]
+    fn request_logger(
+        config_manager: &ConfigManager,
+        config: &Config,
+    ) -> Result<Option<Arc<RequestLogger>>> {
+        if !config.transcription.request_log.enabled {
+            return Ok(None);
+        }
+
+        let logger = RequestLogger::new(config_manager.get_request_log_path(config))?;
+        warn!(
+            "📝 Request logging enabled; transcripts are written to {}",
+            logger.path().display()
+        );
+        Ok(Some(Arc::new(logger)))
+    }
+
     fn prompt_for(config: &Config, provider: TranscriptionProvider) -> String {
-        match provider {
+        let prompt = match provider {
             TranscriptionProvider::WhisperCpp => {
                 PromptBlueprint::from(config.transcription.whisper_cpp.prompt.as_str()).resolve()
             }
@@ -156,6 +470,11 @@ impl TranscriptionBackend {
             TranscriptionProvider::Gemini => {
                 PromptBlueprint::from(config.transcription.gemini.prompt.as_str()).resolve()
             }
-        }
+            TranscriptionProvider::Ssh => {
+                PromptBlueprint::from(config.transcription.ssh.prompt.as_str()).resolve()
+            }
+            TranscriptionProvider::Echo => String::new(),
+        };
+        fit_prompt(&prompt, None, max_prompt_tokens(provider))
     }
 }
@@ -1,3 +1,8 @@
+use crate::config::TranscriptionProvider;
+use time::macros::format_description;
+use time::OffsetDateTime;
+use tracing::warn;
+
 pub const DEFAULT_PROMPT: &str = "Transcribe with proper capitalization, including sentence beginnings, proper nouns, titles, and standard English capitalization rules.";
 
 pub struct PromptBlueprint<'a> {
@@ -32,3 +37,208 @@ impl<'a> PromptBlueprint<'a> {
         chosen.trim().to_string()
     }
 }
+
+/// Words of the previous transcript exposed to `{{prev_transcript}}`.
+const PREV_TRANSCRIPT_WORDS: usize = 40;
+
+/// Request-time values for prompt template variables: `{{app_class}}`,
+/// `{{date}}`, `{{vocabulary}}`, and `{{prev_transcript}}`, plus the
+/// utterance's idempotency key and whether it takes the short-command path.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PromptContext<'a> {
+    pub vocabulary: Option<&'a str>,
+    pub app_class: Option<&'a str>,
+    pub prev_transcript: Option<&'a str>,
+    /// Used instead of the provider's prompt (an app profile's `whisper_prompt`).
+    pub prompt_override: Option<&'a str>,
+    /// Append vocabulary to prompts that don't place `{{vocabulary}}` themselves.
+    pub append_vocabulary: bool,
+    /// Sent as `Idempotency-Key` by remote providers; see [`utterance_key`].
+    ///
+    /// [`utterance_key`]: crate::transcription::utterance_key
+    pub utterance_key: Option<&'a str>,
+    /// Remote providers upload WAV instead of FLAC; see
+    /// [`ShortCommandConfig`](crate::config::ShortCommandConfig).
+    pub short_command: bool,
+}
+
+impl PromptContext<'_> {
+    /// Resolves template variables in `template` and fits the result within
+    /// `provider`'s prompt limit.
+    pub fn render(&self, template: &str, provider: TranscriptionProvider) -> String {
+        let mut prompt = self.prompt_override.unwrap_or(template).to_string();
+        if prompt.contains("{{") {
+            let date = OffsetDateTime::now_local()
+                .unwrap_or_else(|_| OffsetDateTime::now_utc())
+                .format(format_description!("[year]-[month]-[day]"))
+                .unwrap_or_default();
+            let previous = self
+                .prev_transcript
+                .map(|text| {
+                    let words: Vec<&str> = text.split_whitespace().collect();
+                    let start = words.len().saturating_sub(PREV_TRANSCRIPT_WORDS);
+                    words[start..].join(" ")
+                })
+                .unwrap_or_default();
+
+            prompt = prompt
+                .replace("{{app_class}}", self.app_class.unwrap_or_default())
+                .replace("{{date}}", &date)
+                .replace("{{prev_transcript}}", &previous);
+        }
+
+        let vocabulary = if prompt.contains(VOCABULARY_VARIABLE) || self.append_vocabulary {
+            self.vocabulary
+        } else {
+            None
+        };
+        fit_prompt(&prompt, vocabulary, max_prompt_tokens(provider))
+    }
+}
+
+const VOCABULARY_VARIABLE: &str = "{{vocabulary}}";
+
+/// Places vocabulary at `{{vocabulary}}` when the prompt has it, otherwise
+/// appends it.
+fn assemble(prompt: &str, vocabulary: Option<&str>) -> String {
+    if prompt.contains(VOCABULARY_VARIABLE) {
+        let terms = vocabulary.map(str::trim).unwrap_or_default();
+        return prompt
+            .replace(VOCABULARY_VARIABLE, terms)
+            .trim()
+            .to_string();
+    }
+    with_vocabulary(prompt, vocabulary)
+}
+
+/// Appends boosted domain vocabulary to a provider prompt for a single request.
+pub fn with_vocabulary(prompt: &str, vocabulary: Option<&str>) -> String {
+    match vocabulary.map(str::trim).filter(|terms| !terms.is_empty()) {
+        Some(terms) if prompt.is_empty() => format!("Vocabulary: {terms}."),
+        Some(terms) => format!("{prompt} Vocabulary: {terms}."),
+        None => prompt.to_string(),
+    }
+}
+
+/// Whisper-family models only condition on the last 224 prompt tokens
+/// (half the 448-token text context); Gemini prompts are effectively unbounded.
+pub fn max_prompt_tokens(provider: TranscriptionProvider) -> Option<usize> {
+    match provider {
+        TranscriptionProvider::WhisperCpp
+        | TranscriptionProvider::Groq
+        | TranscriptionProvider::Ssh => Some(224),
+        TranscriptionProvider::Gemini | TranscriptionProvider::Echo => None,
+    }
+}
+
+/// Conservative token estimate for English-ish text (~3.5 chars per token,
+/// never fewer tokens than words).
+pub fn estimate_tokens(text: &str) -> usize {
+    let by_chars = (text.chars().count() * 2).div_ceil(7);
+    by_chars.max(text.split_whitespace().count())
+}
+
+/// Assembles the prompt plus vocabulary within `limit` tokens. Vocabulary
+/// terms are dropped from the end first; if the base prompt alone is still
+/// too long it is cut back to whole leading sentences (or words).
+pub fn fit_prompt(prompt: &str, vocabulary: Option<&str>, limit: Option<usize>) -> String {
+    let assembled = assemble(prompt, vocabulary);
+    let Some(limit) = limit else {
+        return assembled;
+    };
+    if estimate_tokens(&assembled) <= limit {
+        return assembled;
+    }
+
+    if let Some(terms) = vocabulary {
+        let mut kept: Vec<&str> = terms.split(',').map(str::trim).collect();
+        while !kept.is_empty() {
+            kept.pop();
+            let candidate = assemble(prompt, Some(&kept.join(", ")));
+            if estimate_tokens(&candidate) <= limit {
+                warn!(
+                    limit,
+                    dropped = terms.split(',').count() - kept.len(),
+                    "Prompt too long; dropped trailing vocabulary terms"
+                );
+                return candidate;
+            }
+        }
+    }
+
+    let base = assemble(prompt, None);
+    let truncated = truncate_to_tokens(&base, limit);
+    warn!(
+        limit,
+        estimated = estimate_tokens(&base),
+        "Prompt exceeds the provider limit; truncated to \"{}\"",
+        truncated
+    );
+    truncated
+}
+
+fn truncate_to_tokens(prompt: &str, limit: usize) -> String {
+    let mut kept = String::new();
+    for sentence in prompt.split_inclusive(['.', '!', '?']) {
+        let candidate = format!("{kept}{sentence}");
+        if estimate_tokens(candidate.trim()) > limit {
+            break;
+        }
+        kept = candidate;
+    }
+    if !kept.trim().is_empty() {
+        return kept.trim().to_string();
+    }
+
+    let mut words = Vec::new();
+    for word in prompt.split_whitespace() {
+        words.push(word);
+        if estimate_tokens(&words.join(" ")) > limit {
+            words.pop();
+            break;
+        }
+    }
+    words.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_vocabulary_before_touching_the_prompt() {
+        let prompt = "Transcribe carefully.";
+        let fitted = fit_prompt(prompt, Some("kubectl, helm, istio, envoy"), Some(16));
+        assert!(fitted.starts_with("Transcribe carefully. Vocabulary: kubectl"));
+        assert!(estimate_tokens(&fitted) <= 16);
+        assert!(!fitted.contains("envoy"));
+    }
+
+    #[test]
+    fn resolves_template_variables() {
+        let context = PromptContext {
+            vocabulary: Some("kubectl, helm"),
+            app_class: Some("kitty"),
+            prev_transcript: Some("deploy the chart"),
+            prompt_override: None,
+            append_vocabulary: false,
+            utterance_key: None,
+            short_command: false,
+        };
+        let prompt = context.render(
+            "Dictating into {{app_class}}. Terms: {{vocabulary}}. Context: {{prev_transcript}}",
+            TranscriptionProvider::Groq,
+        );
+        assert_eq!(
+            prompt,
+            "Dictating into kitty. Terms: kubectl, helm. Context: deploy the chart"
+        );
+    }
+
+    #[test]
+    fn truncates_long_prompts_at_sentence_boundaries() {
+        let prompt = "First sentence here. Second sentence is quite a bit longer than the first.";
+        assert_eq!(fit_prompt(prompt, None, Some(8)), "First sentence here.");
+        assert_eq!(fit_prompt(prompt, None, None), prompt);
+    }
+}
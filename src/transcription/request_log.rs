@@ -0,0 +1,204 @@
+use anyhow::{Context, Result};
+use reqwest::Url;
+use serde_json::{json, Value};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+use tracing::warn;
+
+/// Object keys whose values are replaced before anything reaches disk. `data`
+/// covers Gemini's base64 `inline_data`; the rest catch credentials.
+const REDACTED_KEYS: &[&str] = &["data", "key", "api_key", "apikey", "authorization"];
+
+/// Non-JSON response bodies are truncated to keep the log readable.
+const MAX_RAW_BODY_CHARS: usize = 4096;
+
+/// Appends provider request/response bodies to a JSON Lines file for debugging
+/// wire-format issues. Audio payloads and API keys are redacted.
+pub struct RequestLogger {
+    path: PathBuf,
+    write_lock: Mutex<()>,
+}
+
+impl RequestLogger {
+    pub fn new(path: PathBuf) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|| {
+                format!(
+                    "Failed to create request log directory {}",
+                    parent.display()
+                )
+            })?;
+        }
+
+        Ok(Self {
+            path,
+            write_lock: Mutex::new(()),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record_request(&self, provider: &str, url: &Url, body: &Value) {
+        let mut body = body.clone();
+        redact(&mut body);
+
+        self.append(json!({
+            "direction": "request",
+            "provider": provider,
+            "url": redact_url(url),
+            "body": body,
+        }));
+    }
+
+    pub fn record_response(&self, provider: &str, status: u16, body: &[u8]) {
+        let body = match serde_json::from_slice::<Value>(body) {
+            Ok(mut value) => {
+                redact(&mut value);
+                value
+            }
+            Err(_) => {
+                let text = String::from_utf8_lossy(body);
+                Value::String(text.chars().take(MAX_RAW_BODY_CHARS).collect())
+            }
+        };
+
+        self.append(json!({
+            "direction": "response",
+            "provider": provider,
+            "status": status,
+            "body": body,
+        }));
+    }
+
+    fn append(&self, mut entry: Value) {
+        let timestamp = OffsetDateTime::now_utc()
+            .format(&Rfc3339)
+            .unwrap_or_default();
+        if let Value::Object(map) = &mut entry {
+            map.insert("timestamp".to_string(), Value::String(timestamp));
+        }
+
+        let _guard = self.write_lock.lock().expect("request log lock poisoned");
+        let result = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| writeln!(file, "{entry}"));
+
+        if let Err(err) = result {
+            warn!(
+                "Failed to write request log {}: {}",
+                self.path.display(),
+                err
+            );
+        }
+    }
+}
+
+/// Replaces sensitive values in place, recursing through objects and arrays.
+pub fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, entry) in map.iter_mut() {
+                if is_sensitive_key(key) {
+                    *entry = placeholder(entry);
+                } else {
+                    redact(entry);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                redact(item);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Renders `url` with sensitive query parameters (e.g. Gemini's `key`) masked.
+pub fn redact_url(url: &Url) -> String {
+    if !url.query_pairs().any(|(name, _)| is_sensitive_key(&name)) {
+        return url.to_string();
+    }
+
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(name, value)| {
+            let value = if is_sensitive_key(&name) {
+                "REDACTED".to_string()
+            } else {
+                value.into_owned()
+            };
+            (name.into_owned(), value)
+        })
+        .collect();
+
+    let mut redacted = url.clone();
+    redacted.query_pairs_mut().clear().extend_pairs(pairs);
+    redacted.to_string()
+}
+
+fn is_sensitive_key(key: &str) -> bool {
+    REDACTED_KEYS
+        .iter()
+        .any(|candidate| key.eq_ignore_ascii_case(candidate))
+}
+
+fn placeholder(value: &Value) -> Value {
+    match value {
+        Value::String(text) => Value::String(format!("<redacted {} bytes>", text.len())),
+        _ => Value::String("<redacted>".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_inline_audio_and_keys() {
+        let mut body = json!({
+            "contents": [{
+                "parts": [
+                    { "text": "transcribe" },
+                    { "inline_data": { "mime_type": "audio/flac", "data": "AAAA" } }
+                ]
+            }],
+            "api_key": "secret"
+        });
+
+        redact(&mut body);
+
+        assert_eq!(
+            body,
+            json!({
+                "contents": [{
+                    "parts": [
+                        { "text": "transcribe" },
+                        { "inline_data": { "mime_type": "audio/flac", "data": "<redacted 4 bytes>" } }
+                    ]
+                }],
+                "api_key": "<redacted 6 bytes>"
+            })
+        );
+    }
+
+    #[test]
+    fn masks_key_query_parameter() {
+        let url =
+            Url::parse("https://example.com/v1beta/models/x:generateContent?key=secret&alt=json")
+                .unwrap();
+
+        let redacted = redact_url(&url);
+
+        assert!(!redacted.contains("secret"));
+        assert!(redacted.contains("key=REDACTED"));
+        assert!(redacted.contains("alt=json"));
+    }
+}
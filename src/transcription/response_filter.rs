@@ -0,0 +1,132 @@
+//! Cleans up provider replies that aren't a bare transcript: an assistant
+//! preamble ("Sure! Here's the transcription:") is cut off, while commentary
+//! about the audio or text in an unexpected script is rejected.
+
+use crate::config::ResponseFilterConfig;
+use crate::transcription::writing_script::is_unexpected_script;
+use regex::Regex;
+use std::fmt;
+use std::sync::LazyLock;
+
+/// "Sure! Here's the transcription:" in front of the transcript. Only a
+/// preamble that names the transcription matches, so dictation opening with
+/// "Okay, here's the plan" is left alone.
+static PREAMBLE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?ix)^\W*
+            (?:(?:sure|certainly|okay|ok|of\ course|absolutely)\b[\s,.!]*)?
+            here(?:'s|\ is)\ (?:the|your|a)\ (?:\w+\ )?transcri(?:pt|ption|bed\ text)\b
+            (?:\ of\ (?:the|your)\ audio)?\s*[:.\-–—]*\s*",
+    )
+    .expect("valid preamble regex")
+});
+
+/// Replies that are about the audio rather than a transcript of it. Phrases
+/// people also dictate ("I'm sorry", "I can't hear you") are not included.
+static COMMENTARY_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?ix)^\W*(?:
+            (?:i|the\ model)\ (?:can(?:'t|not)|am\ unable\ to|couldn't|could\ not)\ transcribe\b
+            | as\ an\ (?:ai|language\ model)\b
+            | the\ (?:provided\ )?audio\ (?:contains\ no|does\ not\ contain|doesn't\ contain|is\ (?:silent|empty))
+            | there\ (?:is|was)\ no\ (?:speech|audible\ speech|discernible\ speech)\ in\ the\ audio
+        )",
+    )
+    .expect("valid commentary regex")
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rejection {
+    Commentary,
+    UnexpectedScript,
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Commentary => f.write_str("reply is commentary, not a transcript"),
+            Self::UnexpectedScript => f.write_str("reply is in an unexpected writing system"),
+        }
+    }
+}
+
+/// `text` without an assistant preamble, or why it should not be injected
+/// at all.
+pub fn check<'a>(text: &'a str, config: &ResponseFilterConfig) -> Result<&'a str, Rejection> {
+    if !config.enabled || text.trim().is_empty() {
+        return Ok(text);
+    }
+
+    let text = match PREAMBLE_REGEX.find(text) {
+        Some(preamble) => &text[preamble.end()..],
+        None => text,
+    };
+
+    if COMMENTARY_REGEX.is_match(text) {
+        return Err(Rejection::Commentary);
+    }
+
+    if !config.scripts.is_empty() && is_unexpected_script(text, &config.scripts) {
+        return Err(Rejection::UnexpectedScript);
+    }
+
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::WritingScript;
+
+    #[test]
+    fn strips_preambles_and_rejects_commentary() {
+        let config = ResponseFilterConfig::default();
+        assert_eq!(
+            check("Sure! Here's the transcription: hello world", &config),
+            Ok("hello world")
+        );
+        assert_eq!(
+            check("Here is the transcript of the audio:\nship it", &config),
+            Ok("ship it")
+        );
+        assert_eq!(
+            check("I cannot transcribe this audio.", &config),
+            Err(Rejection::Commentary)
+        );
+        assert_eq!(
+            check("The audio contains no speech.", &config),
+            Err(Rejection::Commentary)
+        );
+
+        for dictation in [
+            "Sure, I'll be there at noon.",
+            "I'm sorry, but I can't make it tomorrow.",
+            "Okay, here's the plan for Monday.",
+            "I can't hear you very well on this call.",
+        ] {
+            assert_eq!(check(dictation, &config), Ok(dictation));
+        }
+    }
+
+    #[test]
+    fn rejects_unexpected_scripts() {
+        let any = ResponseFilterConfig::default();
+        assert_eq!(
+            check("Это расшифровка аудио", &any),
+            Ok("Это расшифровка аудио")
+        );
+
+        let latin = ResponseFilterConfig {
+            scripts: vec![WritingScript::Latin],
+            ..ResponseFilterConfig::default()
+        };
+        assert_eq!(
+            check("Это расшифровка аудио", &latin),
+            Err(Rejection::UnexpectedScript)
+        );
+        assert_eq!(
+            check("Meet Dmitri (Дмитрий) at the café", &latin),
+            Ok("Meet Dmitri (Дмитрий) at the café")
+        );
+    }
+}
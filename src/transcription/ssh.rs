@@ -0,0 +1,210 @@
+use crate::config::{SshConfig, TranscriptionProvider};
+use crate::transcription::audio::encode_to_flac;
+use crate::transcription::postprocess::clean_transcription;
+use crate::transcription::prompt::PromptContext;
+use crate::transcription::{BackendMetrics, TranscriptionResult};
+use anyhow::{Context, Result};
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::process::Command;
+use tokio::time::timeout;
+use tokio::try_join;
+use tracing::{debug, info, warn};
+
+/// Runs whisper on a remote machine over `ssh`, streaming FLAC on stdin and
+/// reading the transcript from stdout.
+#[derive(Clone)]
+pub struct SshTranscriber {
+    host: String,
+    ssh_args: Vec<String>,
+    command_template: String,
+    prompt: String,
+    request_timeout: Duration,
+}
+
+impl SshTranscriber {
+    pub fn new(config: &SshConfig, request_timeout: Duration, prompt: String) -> Result<Self> {
+        Ok(Self {
+            host: config.host.trim().to_string(),
+            ssh_args: config.ssh_args.clone(),
+            command_template: config.command.clone(),
+            prompt,
+            request_timeout,
+        })
+    }
+
+    pub fn initialize(&self) -> Result<()> {
+        if self.host.is_empty() {
+            anyhow::bail!(
+                "transcription.ssh.host is required to use the SSH transcription backend"
+            );
+        }
+
+        info!(
+            "✅ SSH transcription ready (host: {}, timeout: {:?})",
+            self.host, self.request_timeout
+        );
+        Ok(())
+    }
+
+    pub async fn transcribe(
+        &self,
+        audio_data: Vec<f32>,
+        context: &PromptContext<'_>,
+    ) -> Result<TranscriptionResult> {
+        if audio_data.is_empty() {
+            return Ok(TranscriptionResult {
+                text: String::new(),
+                segments: Vec::new(),
+                metrics: BackendMetrics::default(),
+            });
+        }
+
+        let duration_secs = audio_data.len() as f32 / 16000.0;
+        info!(
+            "🧠 Transcribing {:.2}s of audio on {}",
+            duration_secs, self.host
+        );
+
+        let encode_start = Instant::now();
+        let encoded = encode_to_flac(&audio_data).await?;
+        let encode_duration = encode_start.elapsed();
+        let encoded_len = encoded.data.len();
+
+        let prompt = context.render(&self.prompt, TranscriptionProvider::Ssh);
+        let remote_command = render_command(&self.command_template, &prompt);
+
+        let transcribe_start = Instant::now();
+        let raw = timeout(
+            self.request_timeout,
+            self.run_remote(&encoded.data, &remote_command),
+        )
+        .await
+        .with_context(|| {
+            format!(
+                "SSH transcription on {} timed out after {:?}",
+                self.host, self.request_timeout
+            )
+        })??;
+        let transcription_duration = transcribe_start.elapsed();
+
+        let cleaned = clean_transcription(raw.trim(), &prompt);
+        if cleaned.is_empty() {
+            warn!("SSH host returned empty or non-speech transcription");
+        } else {
+            info!("✅ Transcription (SSH): {}", cleaned);
+        }
+
+        Ok(TranscriptionResult {
+            text: cleaned,
+            segments: Vec::new(),
+            metrics: BackendMetrics {
+                encode_duration: Some(encode_duration),
+                encoded_bytes: Some(encoded_len),
+                upload_duration: None,
+                response_duration: None,
+                transcription_duration,
+                fallback_used: false,
+            },
+        })
+    }
+
+    async fn run_remote(&self, audio: &[u8], remote_command: &str) -> Result<String> {
+        debug!(
+            host = self.host.as_str(),
+            command = remote_command,
+            "Running remote whisper"
+        );
+
+        let mut child = Command::new("ssh")
+            .arg("-o")
+            .arg("BatchMode=yes")
+            .args(&self.ssh_args)
+            .arg(&self.host)
+            .arg(remote_command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .context("Failed to spawn ssh. Ensure OpenSSH is installed")?;
+
+        let mut stdin = child.stdin.take().context("Failed to open ssh stdin")?;
+        let mut stdout = child.stdout.take().context("Failed to open ssh stdout")?;
+        let mut stderr = child.stderr.take().context("Failed to open ssh stderr")?;
+
+        let write_future = async move {
+            stdin
+                .write_all(audio)
+                .await
+                .context("Failed to stream FLAC audio over ssh")?;
+            stdin
+                .shutdown()
+                .await
+                .context("Failed to close ssh stdin")?;
+            Ok::<(), anyhow::Error>(())
+        };
+
+        let read_future = async move {
+            let mut buf = Vec::new();
+            stdout
+                .read_to_end(&mut buf)
+                .await
+                .context("Failed to read ssh stdout")?;
+            Ok::<Vec<u8>, anyhow::Error>(buf)
+        };
+
+        let stderr_future = async move {
+            let mut buf = Vec::new();
+            stderr
+                .read_to_end(&mut buf)
+                .await
+                .context("Failed to read ssh stderr")?;
+            Ok::<Vec<u8>, anyhow::Error>(buf)
+        };
+
+        let (_, output, stderr_bytes) = try_join!(write_future, read_future, stderr_future)?;
+        let status = child.wait().await.context("Failed to wait for ssh")?;
+
+        if !status.success() {
+            let stderr_text = String::from_utf8_lossy(&stderr_bytes);
+            anyhow::bail!(
+                "Remote whisper on {} failed ({}): {}",
+                self.host,
+                status,
+                stderr_text.trim()
+            );
+        }
+
+        Ok(String::from_utf8_lossy(&output).into_owned())
+    }
+}
+
+/// Substitutes `{prompt}` with the single-quoted prompt so the remote shell
+/// passes it through as one argument.
+fn render_command(template: &str, prompt: &str) -> String {
+    template.replace("{prompt}", &shell_quote(prompt))
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prompt_placeholder_is_shell_quoted() {
+        let command = render_command(
+            "whisper-cli -f - --prompt {prompt}",
+            "Don't add punctuation",
+        );
+
+        assert_eq!(
+            command,
+            r"whisper-cli -f - --prompt 'Don'\''t add punctuation'"
+        );
+    }
+}
@@ -0,0 +1,41 @@
+//! Tells which writing system a transcript is in, for the response filter
+//! and the injection post-check.
+
+use crate::config::WritingScript;
+
+/// Share of letters outside the allowed scripts above which text counts as
+/// foreign; tolerates the odd foreign name or symbol.
+const FOREIGN_LETTER_RATIO: f32 = 0.3;
+
+/// Whether `text` is mostly written in scripts outside `allowed`. Letters of
+/// scripts not covered by [`WritingScript`] are ignored.
+pub fn is_unexpected_script(text: &str, allowed: &[WritingScript]) -> bool {
+    let mut letters = 0usize;
+    let mut foreign = 0usize;
+    for script in text.chars().filter(|ch| ch.is_alphabetic()).map(script_of) {
+        letters += 1;
+        if script.is_some_and(|script| !allowed.contains(&script)) {
+            foreign += 1;
+        }
+    }
+
+    letters > 0 && foreign as f32 / letters as f32 > FOREIGN_LETTER_RATIO
+}
+
+/// Classifies a letter; `None` for scripts the filter doesn't know about.
+fn script_of(ch: char) -> Option<WritingScript> {
+    let script = match u32::from(ch) {
+        0x0041..=0x024F | 0x1E00..=0x1EFF => WritingScript::Latin,
+        0x0370..=0x03FF | 0x1F00..=0x1FFF => WritingScript::Greek,
+        0x0400..=0x052F => WritingScript::Cyrillic,
+        0x0590..=0x05FF => WritingScript::Hebrew,
+        0x0600..=0x06FF | 0x0750..=0x077F => WritingScript::Arabic,
+        0x0900..=0x097F => WritingScript::Devanagari,
+        0x0E00..=0x0E7F => WritingScript::Thai,
+        0x1100..=0x11FF | 0x3130..=0x318F | 0xAC00..=0xD7AF => WritingScript::Hangul,
+        0x3040..=0x30FF => WritingScript::Japanese,
+        0x4E00..=0x9FFF | 0x3400..=0x4DBF => WritingScript::Chinese,
+        _ => return None,
+    };
+    Some(script)
+}
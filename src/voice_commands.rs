@@ -0,0 +1,141 @@
+//! Utterances that are commands rather than dictation: a transcript that is
+//! only "undo", "stop dictation" or "switch to code mode" runs the action
+//! and nothing is typed. "Scratch that" inside a dictation is handled by
+//! the injector (see [`crate::input::scratch`]).
+
+use crate::config::{DictationMode, VoiceAction, VoiceCommandsConfig};
+use crate::session::{is_stats_phrase, normalize};
+use std::collections::HashMap;
+
+const BUILTIN_PHRASES: [(&str, VoiceAction); 6] = [
+    ("undo", VoiceAction::Undo),
+    ("undo that", VoiceAction::Undo),
+    ("stop dictation", VoiceAction::StopDictation),
+    ("stop listening", VoiceAction::StopDictation),
+    ("next mode", VoiceAction::CycleMode),
+    ("switch mode", VoiceAction::CycleMode),
+];
+
+#[derive(Debug, Clone)]
+pub struct VoiceCommands {
+    phrases: HashMap<String, VoiceAction>,
+}
+
+impl VoiceCommands {
+    /// The built-ins, including "<mode> mode" and "switch to <mode> mode"
+    /// for each dictation mode, with `config.commands` merged over them.
+    pub fn new(config: &VoiceCommandsConfig) -> Self {
+        let mut phrases = HashMap::new();
+        if !config.enabled {
+            return Self { phrases };
+        }
+
+        for (phrase, action) in BUILTIN_PHRASES {
+            phrases.insert(phrase.to_string(), action);
+        }
+        for mode in DictationMode::ALL {
+            let action = VoiceAction::for_mode(mode);
+            phrases.insert(format!("{} mode", mode.label()), action);
+            phrases.insert(format!("switch to {} mode", mode.label()), action);
+        }
+        for (phrase, &action) in &config.commands {
+            let phrase = normalize(phrase);
+            if phrase.is_empty() {
+                continue;
+            }
+            if action == VoiceAction::Off {
+                phrases.remove(&phrase);
+            } else {
+                phrases.insert(phrase, action);
+            }
+        }
+        Self { phrases }
+    }
+
+    /// The action when `transcript` is nothing but a command phrase,
+    /// ignoring case and the punctuation a backend adds.
+    pub fn parse(&self, transcript: &str) -> Option<VoiceAction> {
+        self.phrases.get(&normalize(transcript)).copied()
+    }
+
+    /// What `transcript` asks the daemon to do instead of being typed:
+    /// `stats_phrase` inserts the session statistics, a command phrase runs
+    /// its action. Only `live` dictation controls anything; a labelled
+    /// track (the other side of a call) or an utterance resumed from the
+    /// spool is always typed.
+    pub fn control(&self, transcript: &str, stats_phrase: &str, live: bool) -> Option<Control> {
+        if !live {
+            return None;
+        }
+        if is_stats_phrase(transcript, stats_phrase) {
+            return Some(Control::InsertStats);
+        }
+        self.parse(transcript).map(Control::Voice)
+    }
+}
+
+/// A transcript that controls the daemon; see [`VoiceCommands::control`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    InsertStats,
+    Voice(VoiceAction),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_whole_utterances_with_overrides() {
+        let config = VoiceCommandsConfig {
+            enabled: true,
+            commands: HashMap::from([
+                ("Undo".to_string(), VoiceAction::Off),
+                ("Go quiet!".to_string(), VoiceAction::StopDictation),
+            ]),
+        };
+        let commands = VoiceCommands::new(&config);
+
+        assert_eq!(
+            commands.parse("Switch to code mode."),
+            Some(VoiceAction::CodeMode)
+        );
+        assert_eq!(commands.parse("Prose mode"), Some(VoiceAction::ProseMode));
+        assert_eq!(
+            commands.parse("Stop dictation."),
+            Some(VoiceAction::StopDictation)
+        );
+        assert_eq!(commands.parse("go quiet"), Some(VoiceAction::StopDictation));
+        assert_eq!(commands.parse("Undo that"), Some(VoiceAction::Undo));
+        assert_eq!(commands.parse("Undo."), None);
+        assert_eq!(commands.parse("Please switch to code mode"), None);
+
+        let disabled = VoiceCommands::new(&VoiceCommandsConfig {
+            enabled: false,
+            ..config
+        });
+        assert_eq!(disabled.parse("stop dictation"), None);
+    }
+
+    #[test]
+    fn only_live_dictation_controls_the_daemon() {
+        let commands = VoiceCommands::new(&VoiceCommandsConfig {
+            enabled: true,
+            commands: HashMap::new(),
+        });
+        let control = |text, live| commands.control(text, "insert word count", live);
+
+        assert_eq!(
+            control("Insert word count.", true),
+            Some(Control::InsertStats)
+        );
+        assert_eq!(
+            control("Undo that.", true),
+            Some(Control::Voice(VoiceAction::Undo))
+        );
+        // Resumed from the spool, or the other side of a call.
+        assert_eq!(control("Insert word count.", false), None);
+        assert_eq!(control("Undo that.", false), None);
+        assert_eq!(control("Insert the word count", true), None);
+    }
+}
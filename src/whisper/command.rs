@@ -0,0 +1,232 @@
+//! `whisper-cli` argument assembly, kept free of I/O so each flag
+//! combination can be checked without a binary or model on disk.
+
+use super::WhisperVadOptions;
+use std::ffi::{OsStr, OsString};
+use std::path::Path;
+use std::process::Command;
+
+/// The arguments for one whisper-cli run: plain text written next to
+/// `audio` as `.txt`, no timestamps.
+pub struct WhisperCommandBuilder<'a> {
+    model: &'a Path,
+    audio: &'a Path,
+    language: &'a str,
+    threads: usize,
+    prompt: &'a str,
+    no_speech_threshold: Option<f32>,
+    vad: Option<&'a WhisperVadOptions>,
+    gpu: bool,
+}
+
+impl<'a> WhisperCommandBuilder<'a> {
+    pub fn new(model: &'a Path, audio: &'a Path) -> Self {
+        Self {
+            model,
+            audio,
+            language: "en",
+            threads: 4,
+            prompt: "",
+            no_speech_threshold: None,
+            vad: None,
+            gpu: true,
+        }
+    }
+
+    pub fn language(mut self, language: &'a str) -> Self {
+        self.language = language;
+        self
+    }
+
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    pub fn prompt(mut self, prompt: &'a str) -> Self {
+        self.prompt = prompt;
+        self
+    }
+
+    pub fn no_speech_threshold(mut self, threshold: f32) -> Self {
+        self.no_speech_threshold = Some(threshold);
+        self
+    }
+
+    /// Adds the `--vad` flags when `vad` is enabled and has a model.
+    pub fn vad(mut self, vad: &'a WhisperVadOptions) -> Self {
+        self.vad = Some(vad);
+        self
+    }
+
+    /// whisper-cli uses the GPU unless told `--no-gpu`.
+    pub fn gpu(mut self, enabled: bool) -> Self {
+        self.gpu = enabled;
+        self
+    }
+
+    pub fn args(&self) -> Vec<OsString> {
+        let mut args = Args::default();
+        args.pair("-m", self.model);
+        args.pair("-f", self.audio);
+        args.flag("--output-txt");
+        args.pair("--language", self.language);
+        args.pair("--threads", self.threads.to_string());
+        args.pair("--prompt", self.prompt);
+        args.flag("--no-timestamps");
+        if let Some(threshold) = self.no_speech_threshold {
+            args.pair("--no-speech-thold", threshold.to_string());
+        }
+
+        if let Some((vad, model_path)) = self
+            .vad
+            .filter(|vad| vad.is_active())
+            .and_then(|vad| Some((vad, vad.model_path.as_deref()?)))
+        {
+            args.flag("--vad");
+            args.pair("--vad-model", model_path);
+            args.pair("--vad-threshold", vad.threshold.to_string());
+            args.pair(
+                "--vad-min-speech-duration-ms",
+                vad.min_speech_ms.to_string(),
+            );
+            args.pair(
+                "--vad-min-silence-duration-ms",
+                vad.min_silence_ms.to_string(),
+            );
+            if vad.max_speech_s.is_finite() {
+                args.pair("--vad-max-speech-duration-s", vad.max_speech_s.to_string());
+            }
+            args.pair("--vad-speech-pad-ms", vad.speech_pad_ms.to_string());
+            args.pair("--vad-samples-overlap", vad.samples_overlap.to_string());
+        }
+
+        if !self.gpu {
+            args.flag("--no-gpu");
+        }
+        args.0
+    }
+
+    pub fn command(&self, binary: &Path) -> Command {
+        let mut command = Command::new(binary);
+        command.args(self.args());
+        command
+    }
+
+    /// The run as a line to paste into a shell, for reproducing a failure
+    /// by hand.
+    pub fn shell_line(&self, binary: &Path) -> String {
+        std::iter::once(binary.as_os_str().to_os_string())
+            .chain(self.args())
+            .map(|arg| shell_quote(&arg))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+#[derive(Default)]
+struct Args(Vec<OsString>);
+
+impl Args {
+    fn flag(&mut self, flag: &str) {
+        self.0.push(flag.into());
+    }
+
+    fn pair(&mut self, flag: &str, value: impl AsRef<OsStr>) {
+        self.0.push(flag.into());
+        self.0.push(value.as_ref().to_os_string());
+    }
+}
+
+fn shell_quote(arg: &OsStr) -> String {
+    let arg = arg.to_string_lossy();
+    let plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c));
+    if plain {
+        arg.into_owned()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn args(builder: &WhisperCommandBuilder) -> Vec<String> {
+        builder
+            .args()
+            .into_iter()
+            .map(|arg| arg.into_string().unwrap())
+            .collect()
+    }
+
+    fn value_of<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+        let index = args.iter().position(|arg| arg == flag)?;
+        args.get(index + 1).map(String::as_str)
+    }
+
+    #[test]
+    fn assembles_language_gpu_and_vad_flags() {
+        let (model, audio) = (Path::new("/m/ggml-base.en.bin"), Path::new("/tmp/a.wav"));
+        let plain = WhisperCommandBuilder::new(model, audio);
+        let base = args(&plain);
+        assert_eq!(
+            base[..5],
+            [
+                "-m",
+                "/m/ggml-base.en.bin",
+                "-f",
+                "/tmp/a.wav",
+                "--output-txt"
+            ]
+        );
+        assert_eq!(value_of(&base, "--language"), Some("en"));
+        assert!(!base.contains(&"--no-gpu".to_string()));
+        assert!(!base.contains(&"--vad".to_string()));
+
+        let mut vad = WhisperVadOptions::disabled();
+        vad.enabled = true;
+        let without_model = args(&WhisperCommandBuilder::new(model, audio).vad(&vad));
+        assert!(!without_model.contains(&"--vad".to_string()));
+
+        vad.model_path = Some(PathBuf::from("/m/silero.bin"));
+        vad.max_speech_s = 12.5;
+        let full = args(
+            &WhisperCommandBuilder::new(model, audio)
+                .language("de")
+                .threads(8)
+                .no_speech_threshold(0.6)
+                .vad(&vad)
+                .gpu(false),
+        );
+        assert_eq!(value_of(&full, "--language"), Some("de"));
+        assert_eq!(value_of(&full, "--threads"), Some("8"));
+        assert_eq!(value_of(&full, "--no-speech-thold"), Some("0.6"));
+        assert_eq!(value_of(&full, "--vad-model"), Some("/m/silero.bin"));
+        assert_eq!(value_of(&full, "--vad-max-speech-duration-s"), Some("12.5"));
+        assert_eq!(full.last().map(String::as_str), Some("--no-gpu"));
+
+        vad.max_speech_s = f32::INFINITY;
+        let unbounded = args(&WhisperCommandBuilder::new(model, audio).vad(&vad));
+        assert!(value_of(&unbounded, "--vad-max-speech-duration-s").is_none());
+    }
+
+    #[test]
+    fn passes_prompts_verbatim_and_quotes_them_for_the_shell() {
+        let prompt = r#"Don't say "um"; -- $HOME"#;
+        let builder = WhisperCommandBuilder::new(Path::new("/m/a b.bin"), Path::new("/tmp/a.wav"))
+            .prompt(prompt);
+        assert_eq!(value_of(&args(&builder), "--prompt"), Some(prompt));
+
+        let line = builder.shell_line(Path::new("/usr/bin/whisper-cli"));
+        assert!(line.starts_with("/usr/bin/whisper-cli -m '/m/a b.bin' -f /tmp/a.wav"));
+        assert!(line.contains(r#"--prompt 'Don'\''t say "um"; -- $HOME'"#));
+        assert!(WhisperCommandBuilder::new(Path::new("m"), Path::new("a"))
+            .shell_line(Path::new("w"))
+            .contains("--prompt ''"));
+    }
+}
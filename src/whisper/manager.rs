@@ -1,5 +1,9 @@
+use super::WhisperCommandBuilder;
+use crate::audio::recording::write_wav;
+use crate::config::{TranscriptionProvider, VadConfig};
 use crate::transcription::{
-    clean_transcription, contains_only_non_speech_markers, BackendMetrics, TranscriptionResult,
+    clean_transcription, contains_only_non_speech_markers, BackendMetrics, PromptContext,
+    TranscriptionResult,
 };
 use anyhow::{anyhow, Context, Result};
 use std::convert::TryFrom;
@@ -35,7 +39,27 @@ impl WhisperVadOptions {
         }
     }
 
-    fn is_active(&self) -> bool {
+    /// Options for `config`, repaired the same way as at config load so
+    /// programmatic configs can't produce empty transcripts either.
+    pub fn from_config(config: &VadConfig, model_path: Option<PathBuf>) -> Self {
+        let mut config = config.clone();
+        for fix in config.sanitize() {
+            warn!("Whisper VAD: {fix}");
+        }
+
+        Self {
+            enabled: config.enabled,
+            model_path,
+            threshold: config.threshold,
+            min_speech_ms: config.min_speech_ms,
+            min_silence_ms: config.min_silence_ms,
+            max_speech_s: config.max_speech_s,
+            speech_pad_ms: config.speech_pad_ms,
+            samples_overlap: config.samples_overlap,
+        }
+    }
+
+    pub(super) fn is_active(&self) -> bool {
         self.enabled && self.model_path.is_some()
     }
 }
@@ -82,7 +106,16 @@ impl WhisperManager {
 
     pub fn initialize(&self) -> Result<()> {
         if !self.model_path.exists() {
-            return Err(anyhow!("Whisper model not found at: {:?}", self.model_path));
+            let name = self
+                .model_path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            return Err(anyhow!(
+                "Whisper model not found at: {:?} (download it with `hyprwhspr-rs model pull {}`)",
+                self.model_path,
+                name
+            ));
         }
 
         let available_binary = self
@@ -134,7 +167,9 @@ impl WhisperManager {
             if let Some(path) = &self.vad.model_path {
                 info!("   VAD: enabled ({})", path.display());
             } else {
-                warn!("   VAD: enabled but model file not found (will run without VAD)");
+                warn!(
+                    "   VAD: enabled but model file not found (will run without VAD; `hyprwhspr-rs model pull silero` fetches it)"
+                );
             }
         } else {
             info!("   VAD: disabled");
@@ -144,8 +179,6 @@ impl WhisperManager {
     }
 
     fn detect_gpu() -> String {
-        use std::process::Command;
-
         // Check NVIDIA
         if Command::new("nvidia-smi").output().is_ok() {
             return "NVIDIA GPU detected".to_string();
@@ -164,10 +197,15 @@ impl WhisperManager {
         "CPU only (no GPU detected)".to_string()
     }
 
-    pub async fn transcribe(&self, audio_data: Vec<f32>) -> Result<TranscriptionResult> {
+    pub async fn transcribe(
+        &self,
+        audio_data: Vec<f32>,
+        context: &PromptContext<'_>,
+    ) -> Result<TranscriptionResult> {
         if audio_data.is_empty() {
             return Ok(TranscriptionResult {
                 text: String::new(),
+                segments: Vec::new(),
                 metrics: BackendMetrics::default(),
             });
         }
@@ -189,11 +227,12 @@ impl WhisperManager {
         debug!("Saved audio to: {:?}", temp_wav);
 
         // Run whisper.cpp CLI
+        let prompt = context.render(&self.whisper_prompt, TranscriptionProvider::WhisperCpp);
         let transcribe_start = Instant::now();
-        let transcription = self.run_whisper_cli(&temp_wav).await?;
+        let transcription = self.run_whisper_cli(&temp_wav, &prompt).await?;
         let transcription_duration = transcribe_start.elapsed();
         let trimmed = transcription.trim();
-        let cleaned_transcription = clean_transcription(trimmed, &self.whisper_prompt);
+        let cleaned_transcription = clean_transcription(trimmed, &prompt);
 
         // Always clean up after successful transcription pass
         let _ = fs::remove_file(&temp_wav);
@@ -204,6 +243,7 @@ impl WhisperManager {
             upload_duration: None,
             response_duration: None,
             transcription_duration,
+            fallback_used: false,
         };
 
         if cleaned_transcription.is_empty() {
@@ -219,6 +259,7 @@ impl WhisperManager {
             }
             return Ok(TranscriptionResult {
                 text: String::new(),
+                segments: Vec::new(),
                 metrics,
             });
         }
@@ -233,58 +274,18 @@ impl WhisperManager {
 
         Ok(TranscriptionResult {
             text: cleaned_transcription,
+            segments: Vec::new(),
             metrics,
         })
     }
 
     fn save_audio_as_wav(&self, audio_data: &[f32], path: &PathBuf) -> Result<()> {
-        use std::io::Write;
-
-        // Convert f32 samples to i16
-        let samples_i16: Vec<i16> = audio_data
-            .iter()
-            .map(|&sample| (sample * 32767.0).clamp(-32768.0, 32767.0) as i16)
-            .collect();
-
-        // WAV file header
-        let mut file = fs::File::create(path)?;
-
-        let channels: u16 = 1;
-        let sample_rate: u32 = 16000;
-        let bits_per_sample: u16 = 16;
-        let byte_rate = sample_rate * channels as u32 * bits_per_sample as u32 / 8;
-        let block_align = channels * bits_per_sample / 8;
-        let data_size = (samples_i16.len() * 2) as u32;
-
-        // RIFF header
-        file.write_all(b"RIFF")?;
-        file.write_all(&(36 + data_size).to_le_bytes())?;
-        file.write_all(b"WAVE")?;
-
-        // fmt chunk
-        file.write_all(b"fmt ")?;
-        file.write_all(&16u32.to_le_bytes())?; // Chunk size
-        file.write_all(&1u16.to_le_bytes())?; // Audio format (PCM)
-        file.write_all(&channels.to_le_bytes())?;
-        file.write_all(&sample_rate.to_le_bytes())?;
-        file.write_all(&byte_rate.to_le_bytes())?;
-        file.write_all(&block_align.to_le_bytes())?;
-        file.write_all(&bits_per_sample.to_le_bytes())?;
-
-        // data chunk
-        file.write_all(b"data")?;
-        file.write_all(&data_size.to_le_bytes())?;
-
-        // Write samples
-        for sample in samples_i16 {
-            file.write_all(&sample.to_le_bytes())?;
-        }
-
+        write_wav(path, audio_data, 16000)?;
         debug!("Saved audio to WAV: {:?}", path);
         Ok(())
     }
 
-    async fn run_whisper_cli(&self, audio_file: &PathBuf) -> Result<String> {
+    async fn run_whisper_cli(&self, audio_file: &PathBuf, prompt: &str) -> Result<String> {
         let mut last_error: Option<anyhow::Error> = None;
         let mut attempted: Vec<PathBuf> = Vec::new();
 
@@ -299,7 +300,7 @@ impl WhisperManager {
 
             attempted.push(binary.clone());
 
-            match self.invoke_whisper(binary, audio_file) {
+            match self.invoke_whisper(binary, audio_file, prompt) {
                 Ok(result) => {
                     if last_error.is_some() {
                         info!("Whisper succeeded using fallback binary: {:?}", binary);
@@ -327,68 +328,15 @@ impl WhisperManager {
         Err(last_error.unwrap_or_else(|| anyhow!("All whisper binaries failed. Tried: {}", tried)))
     }
 
-    fn invoke_whisper(&self, binary: &Path, audio_file: &PathBuf) -> Result<String> {
-        let mut cmd = Command::new(binary);
-
-        // Basic args
-        cmd.args(&[
-            "-m",
-            self.model_path
-                .to_str()
-                .ok_or_else(|| anyhow!("Model path contains invalid UTF-8"))?,
-            "-f",
-            audio_file
-                .to_str()
-                .ok_or_else(|| anyhow!("Audio path contains invalid UTF-8"))?,
-            "--output-txt",
-            "--language",
-            "en",
-            "--threads",
-            &self.threads.to_string(),
-            "--prompt",
-            &self.whisper_prompt,
-            "--no-timestamps", // Just plain text, no timestamps
-        ]);
-
-        cmd.arg("--no-speech-thold");
-        cmd.arg(format!("{}", self.no_speech_threshold));
-
-        if self.vad.is_active() {
-            if let Some(model_path) = &self.vad.model_path {
-                cmd.arg("--vad");
-                cmd.arg("--vad-model");
-                cmd.arg(model_path);
-
-                cmd.arg("--vad-threshold");
-                cmd.arg(format!("{}", self.vad.threshold));
-
-                cmd.arg("--vad-min-speech-duration-ms");
-                cmd.arg(format!("{}", self.vad.min_speech_ms));
-
-                cmd.arg("--vad-min-silence-duration-ms");
-                cmd.arg(format!("{}", self.vad.min_silence_ms));
-
-                if self.vad.max_speech_s.is_finite() {
-                    cmd.arg("--vad-max-speech-duration-s");
-                    cmd.arg(format!("{}", self.vad.max_speech_s));
-                }
-
-                cmd.arg("--vad-speech-pad-ms");
-                cmd.arg(format!("{}", self.vad.speech_pad_ms));
-
-                cmd.arg("--vad-samples-overlap");
-                cmd.arg(format!("{}", self.vad.samples_overlap));
-            }
-        }
-
-        // GPU control: AUR version uses --no-gpu flag (opposite logic)
-        // If gpu_layers == 0, disable GPU. Otherwise let it use GPU by default
-        if self.gpu_layers == 0 {
-            cmd.arg("--no-gpu");
-            debug!("GPU disabled (CPU only)");
-        } else {
-            debug!("GPU enabled (will use GPU if available)");
-        }
+    fn invoke_whisper(&self, binary: &Path, audio_file: &PathBuf, prompt: &str) -> Result<String> {
+        let builder = WhisperCommandBuilder::new(&self.model_path, audio_file)
+            .threads(self.threads)
+            .prompt(prompt)
+            .no_speech_threshold(self.no_speech_threshold)
+            .vad(&self.vad)
+            // If gpu_layers == 0, disable GPU. Otherwise let it use GPU by default
+            .gpu(self.gpu_layers != 0);
+        let mut cmd = builder.command(binary);
 
         debug!("Running whisper (binary: {:?}): {:?}", binary, cmd);
 
@@ -428,13 +376,7 @@ impl WhisperManager {
                     "Transcription file was empty. WAV file saved at: {:?}",
                     audio_file
                 );
-                info!(
-                    "You can test manually with: {} -m {} -f {:?} -ngl {}",
-                    binary.display(),
-                    self.model_path.display(),
-                    audio_file,
-                    self.gpu_layers
-                );
+                info!("You can test manually with: {}", builder.shell_line(binary));
             }
 
             Ok(transcription.trim().to_string())
@@ -448,3 +390,54 @@ impl WhisperManager {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vad_options_degrade_gracefully() {
+        let defaults = WhisperVadOptions::from_config(&VadConfig::default(), None);
+        assert_eq!(defaults.threshold, 0.5);
+        assert!(defaults.max_speech_s.is_infinite());
+        assert_eq!(defaults.speech_pad_ms, 30);
+
+        let broken = VadConfig {
+            threshold: 1.5,
+            min_silence_ms: 2000,
+            max_speech_s: 1.0,
+            samples_overlap: -0.2,
+            ..VadConfig::default()
+        };
+        let options = WhisperVadOptions::from_config(&broken, None);
+        assert_eq!(options.threshold, 0.5);
+        assert_eq!(options.samples_overlap, 0.10);
+        assert!(options.max_speech_s.is_infinite());
+
+        let padded = VadConfig {
+            max_speech_s: 0.5,
+            speech_pad_ms: 400,
+            ..VadConfig::default()
+        };
+        assert_eq!(
+            WhisperVadOptions::from_config(&padded, None).speech_pad_ms,
+            125
+        );
+
+        let unbounded = VadConfig {
+            speech_pad_ms: 5000,
+            ..VadConfig::default()
+        };
+        let options = WhisperVadOptions::from_config(&unbounded, None);
+        assert!(options.max_speech_s.is_infinite());
+        assert_eq!(options.speech_pad_ms, 1000);
+
+        let negative = VadConfig {
+            max_speech_s: -3.0,
+            ..VadConfig::default()
+        };
+        assert!(WhisperVadOptions::from_config(&negative, None)
+            .max_speech_s
+            .is_infinite());
+    }
+}
@@ -1,3 +1,5 @@
+pub mod command;
 pub mod manager;
 
+pub use command::WhisperCommandBuilder;
 pub use manager::{WhisperManager, WhisperVadOptions};
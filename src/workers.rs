@@ -0,0 +1,59 @@
+//! Dedicated threads for CPU-heavy audio work (resampling, VAD trimming) so
+//! long recordings don't stall the tokio runtime that serves shortcuts, IPC,
+//! and status updates.
+
+use anyhow::{anyhow, Context, Result};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::thread;
+use tokio::sync::oneshot;
+
+#[derive(Clone)]
+pub struct WorkerPool {
+    pool: Arc<rayon::ThreadPool>,
+}
+
+impl WorkerPool {
+    /// `threads == 0` sizes the pool to leave one core for the runtime.
+    pub fn new(threads: usize) -> Result<Self> {
+        let threads = if threads == 0 {
+            thread::available_parallelism()
+                .map(|cores| cores.get().saturating_sub(1))
+                .unwrap_or(1)
+                .max(1)
+        } else {
+            threads
+        };
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .thread_name(|index| format!("hyprwhspr-worker-{index}"))
+            .build()
+            .context("Failed to build worker thread pool")?;
+
+        Ok(Self {
+            pool: Arc::new(pool),
+        })
+    }
+
+    pub fn threads(&self) -> usize {
+        self.pool.current_num_threads()
+    }
+
+    /// Runs `job` on the pool and awaits its result without blocking the
+    /// runtime. A panicking job is reported as an error.
+    pub async fn run<T, F>(&self, job: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+        self.pool.spawn(move || {
+            let _ = tx.send(panic::catch_unwind(AssertUnwindSafe(job)));
+        });
+
+        rx.await
+            .context("Worker pool dropped the job")?
+            .map_err(|_| anyhow!("Worker thread panicked"))
+    }
+}
@@ -1,3 +1,5 @@
+#![cfg(feature = "vad")]
+
 use hyprwhspr_rs::audio::FastVad;
 use hyprwhspr_rs::config::FastVadConfig;
 use std::f32::consts::PI;
@@ -62,3 +64,36 @@ fn silence_short_circuits_transmission() {
     assert_eq!(outcome.segments, 0);
     assert_eq!(outcome.dropped_samples, audio.len());
 }
+
+#[test]
+fn reports_pauses_between_segments() {
+    let config = FastVadConfig {
+        enabled: true,
+        min_speech_ms: 90,
+        ..Default::default()
+    };
+
+    let mut vad = FastVad::maybe_new(&config, TEST_SAMPLE_RATE_HZ)
+        .expect("fast VAD initialization should succeed")
+        .expect("fast VAD should be enabled");
+
+    let mut audio = Vec::new();
+    audio.extend(silence_ms(300));
+    audio.extend(tone_ms(600));
+    audio.extend(silence_ms(1500));
+    audio.extend(tone_ms(600));
+    audio.extend(silence_ms(300));
+
+    let outcome = vad.trim(&audio).expect("fast VAD should process audio");
+
+    assert!(outcome.segments >= 2);
+    assert_eq!(outcome.breaks.len(), outcome.segments - 1);
+    assert!(outcome
+        .breaks
+        .iter()
+        .any(|segment| segment.pause_ms >= 1200));
+    assert!(outcome
+        .breaks
+        .iter()
+        .all(|segment| segment.offset < outcome.trimmed_audio.len()));
+}